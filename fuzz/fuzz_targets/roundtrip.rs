@@ -0,0 +1,30 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sie4::{writer::WriterProfile, Item, SliceReader, Writer};
+
+fuzz_target!(|data: &[u8]| {
+    // the items that can be read, up to the first error
+    let items = SliceReader::new(data)
+        .map_while(Result::ok)
+        .map(|item| item.into_owned())
+        .collect::<Vec<Item>>();
+
+    for profile in [WriterProfile::SpecStrict, WriterProfile::Readable] {
+        let mut writer = Writer::with_profile(Vec::new(), profile);
+        for item in &items {
+            // some text that can be read can't be written, e.g. line
+            // breaks in quotes
+            if writer.write_item(item).is_err() {
+                return;
+            }
+        }
+        let written = writer.into_inner();
+
+        let read = SliceReader::new(&written)
+            .map(|item| item.map(|item| item.into_owned()))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(read, items);
+    }
+});
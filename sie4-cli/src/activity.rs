@@ -0,0 +1,73 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use sie4::reports;
+use time::Date;
+
+use crate::filter::parse_date;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the SIE4 file to read.
+    sie4: PathBuf,
+    /// Only count vouchers registered on or after this date, e.g.
+    /// `2023-01-01`.
+    #[clap(long, value_parser = parse_date)]
+    from: Option<Date>,
+    /// Only count vouchers registered on or before this date.
+    #[clap(long, value_parser = parse_date)]
+    to: Option<Date>,
+    /// A signature that may register vouchers. Can be repeated; if given,
+    /// vouchers registered by any other signature are listed.
+    #[clap(long)]
+    allow: Vec<String>,
+    /// Print the report as JSON.
+    #[clap(long)]
+    json: bool,
+}
+
+/// Exits with status 1 if any voucher was registered by a signature that
+/// isn't allowed.
+pub fn run(args: &Args) -> anyhow::Result<ExitCode> {
+    let doc = crate::read_document(&args.sie4)?;
+    let period = args.from.unwrap_or(Date::MIN)..=args.to.unwrap_or(Date::MAX);
+    let activity = reports::activity_by_signature(&doc, period);
+    let unauthorized = if args.allow.is_empty() {
+        Vec::new()
+    } else {
+        let allowed = args.allow.iter().map(String::as_str).collect::<Vec<_>>();
+        activity.unauthorized(&allowed)
+    };
+
+    if args.json {
+        let json = serde_json::json!({
+            "rows": activity.rows,
+            "unauthorized": unauthorized,
+        });
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    } else {
+        for row in &activity.rows {
+            println!(
+                "{} {}: {} vouchers, {} transactions",
+                row.period,
+                row.signature.as_deref().unwrap_or("(unsigned)"),
+                row.vouchers,
+                row.transactions
+            );
+        }
+        for reg in &unauthorized {
+            println!(
+                "unauthorized: {} {} registered {} by {}",
+                reg.series,
+                reg.no,
+                reg.date,
+                reg.signature.as_deref().unwrap_or("(unsigned)")
+            );
+        }
+    }
+
+    Ok(if unauthorized.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
@@ -0,0 +1,71 @@
+use std::{
+    collections::hash_map::RandomState,
+    fs::File,
+    hash::{BuildHasher, Hasher},
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use rust_decimal::Decimal;
+use sie4::{anonymize::Anonymizer, Writer};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the SIE4 file to read.
+    sie4: PathBuf,
+    /// Path to the file to create. Written to stdout by default.
+    #[clap(long, short)]
+    output: Option<PathBuf>,
+    /// Keep the names of accounts.
+    #[clap(long)]
+    keep_accounts: bool,
+    /// Multiply all amounts by a factor, random between 0.5 and 2 unless
+    /// given.
+    #[clap(long, value_name = "FACTOR", num_args = 0..=1, default_missing_value = "random")]
+    scale_amounts: Option<String>,
+    /// Replace free text with hashes instead of removing it, so that
+    /// equal texts stay equal. The hashes are keyed by a number, random
+    /// unless given; give the same key to compare anonymized files.
+    #[clap(long, value_name = "KEY", num_args = 0..=1, default_missing_value = "random")]
+    hash_texts: Option<String>,
+}
+
+pub fn run(args: &Args) -> anyhow::Result<()> {
+    let scale_amounts = match args.scale_amounts.as_deref() {
+        None => None,
+        Some("random") => Some(random_factor()),
+        Some(factor) => Some(factor.parse()?),
+    };
+    let hash_texts = match args.hash_texts.as_deref() {
+        None => None,
+        Some("random") => Some(random()),
+        Some(key) => Some(key.parse()?),
+    };
+    let anonymizer = Anonymizer {
+        keep_accounts: args.keep_accounts,
+        scale_amounts,
+        hash_texts,
+    };
+    let doc = anonymizer.anonymize(crate::read_document(&args.sie4)?);
+
+    let w: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut writer = Writer::new(w);
+    for item in doc.items() {
+        writer.write_item(item)?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// A factor with three decimals in `0.500..2.000`.
+fn random_factor() -> Decimal {
+    Decimal::new(500 + i64::try_from(random() % 1500).unwrap_or_default(), 3)
+}
+
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
@@ -0,0 +1,117 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use sie4::{
+    duplicates::{self, Config},
+    Document,
+};
+use time::Date;
+
+use crate::filter::parse_date;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the SIE4 file to read.
+    sie4: PathBuf,
+    /// Print the report as JSON.
+    #[clap(long)]
+    json: bool,
+    /// List what each user registered, by the signatures of the vouchers
+    /// and transactions, instead of auditing the numbering.
+    #[clap(long)]
+    by_user: bool,
+    /// Only list what the user with this signature registered. Implies
+    /// `--by-user`.
+    #[clap(long)]
+    user: Option<String>,
+    /// Only list what was registered on or after this date, e.g.
+    /// `2023-03-01`.
+    #[clap(long, value_parser = parse_date)]
+    from: Option<Date>,
+    /// Only list what was registered on or before this date.
+    #[clap(long, value_parser = parse_date)]
+    to: Option<Date>,
+}
+
+/// Exits with status 1 if the numbering of any series has gaps or
+/// duplicates. Vouchers and transactions that were likely booked twice
+/// are listed for review, but don't fail the audit.
+pub fn run(args: &Args) -> anyhow::Result<ExitCode> {
+    let doc = crate::read_document(&args.sie4)?;
+    if args.by_user || args.user.is_some() {
+        by_user(args, &doc)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let series = doc.series();
+    let issues = doc.numbering_issues();
+    let duplicates = duplicates::find(&doc, &Config::default());
+
+    if args.json {
+        let json = serde_json::json!({
+            "series": series,
+            "issues": issues,
+            "duplicates": duplicates,
+        });
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    } else {
+        for series in &series {
+            if let (Some(first), Some(last)) = (series.first(), series.last()) {
+                println!(
+                    "{} in {}: {} vouchers numbered {first}-{last}",
+                    series.name,
+                    series.fiscal_year,
+                    series.numbers.values().sum::<usize>()
+                );
+            }
+        }
+        for issue in &issues {
+            println!("{issue}");
+        }
+        for duplicate in &duplicates {
+            println!("possible duplicate: {duplicate}");
+        }
+    }
+
+    Ok(if issues.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn by_user(args: &Args, doc: &Document) -> anyhow::Result<()> {
+    let period = args.from.unwrap_or(Date::MIN)..=args.to.unwrap_or(Date::MAX);
+    let mut signatures = doc.signatures();
+    if let Some(user) = &args.user {
+        signatures.retain(|signature, _| signature == user);
+    }
+    for entries in signatures.values_mut() {
+        entries.retain(|entry| period.contains(&entry.registered));
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&signatures)?);
+        return Ok(());
+    }
+    for (signature, entries) in &signatures {
+        let transactions = entries
+            .iter()
+            .map(|entry| entry.transactions.len())
+            .sum::<usize>();
+        println!(
+            "{signature}: {} vouchers, {transactions} transactions",
+            entries.iter().filter(|entry| entry.voucher).count()
+        );
+        for entry in entries {
+            let voucher = if entry.voucher { "voucher and " } else { "" };
+            println!(
+                "  {} ({}): {voucher}{} of its transactions",
+                entry.ver,
+                entry.registered,
+                entry.transactions.len()
+            );
+        }
+    }
+
+    Ok(())
+}
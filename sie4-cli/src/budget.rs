@@ -0,0 +1,154 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use clap::ValueEnum;
+use rust_decimal::Decimal;
+use sie4::{
+    budget::{self, Alert, Budget, Thresholds},
+    item::{AccountNo, Period},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// One line per alert, for reading.
+    Table,
+    /// One row per alert, with a header.
+    Csv,
+    Json,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the SIE4 file to read.
+    sie4: PathBuf,
+    /// Path to a CSV file with the budget, one `account,period,amount`
+    /// per line, e.g. `5010,202301,10000.00`. The budget of the SIE4 file
+    /// (#PBUDGET) by default.
+    budget: Option<PathBuf>,
+    /// The fiscal year of the #PBUDGET items to compare, where 0 is the
+    /// current one.
+    #[clap(long, default_value = "0", allow_hyphen_values = true)]
+    year: i32,
+    /// The first period to compare, e.g. `202301`.
+    #[clap(long)]
+    from: Option<Period>,
+    /// The last period to compare.
+    #[clap(long)]
+    to: Option<Period>,
+    /// Only report excesses larger than this amount.
+    #[clap(long, default_value = "0")]
+    amount: Decimal,
+    /// Only report excesses larger than this percentage of the budget.
+    #[clap(long, default_value = "0")]
+    percent: Decimal,
+    #[clap(long, short, value_enum, default_value = "table")]
+    format: Format,
+    /// Print the alerts as JSON, as `--format json`.
+    #[clap(long, hide = true)]
+    json: bool,
+}
+
+pub fn run(args: &Args) -> anyhow::Result<()> {
+    let doc = crate::read_document(&args.sie4)?;
+    let budget = match &args.budget {
+        Some(path) => read_budget(path)?,
+        None => Budget::from_document(&doc, args.year),
+    };
+    let budget = match (args.from, args.to) {
+        (Some(from), Some(to)) => budget.periods(from..=to),
+        (Some(from), None) => budget.periods(from..),
+        (None, Some(to)) => budget.periods(..=to),
+        (None, None) => budget,
+    };
+    let thresholds = Thresholds {
+        amount: args.amount,
+        percent: args.percent,
+    };
+    let alerts = budget::alerts(&doc, &budget, thresholds);
+    let name = |alert: &Alert| {
+        doc.account(alert.account)
+            .map(|konto| konto.name.as_str())
+            .unwrap_or_default()
+    };
+
+    match if args.json { Format::Json } else { args.format } {
+        Format::Table => {
+            for alert in &alerts {
+                let percent = alert
+                    .percent
+                    .map(|percent| format!(" ({percent}%)"))
+                    .unwrap_or_default();
+                println!(
+                    "{} {} {}: {} over the budget of {}{percent}",
+                    alert.period,
+                    alert.account,
+                    name(alert),
+                    alert.amount,
+                    alert.budget
+                );
+            }
+        }
+        Format::Csv => {
+            println!("period,account,name,budget,actual,amount,percent");
+            for alert in &alerts {
+                println!(
+                    "{},{},\"{}\",{},{},{},{}",
+                    alert.period,
+                    alert.account,
+                    name(alert).replace('"', "\"\""),
+                    alert.budget,
+                    alert.actual,
+                    alert.amount,
+                    alert
+                        .percent
+                        .map(|percent| percent.to_string())
+                        .unwrap_or_default()
+                );
+            }
+        }
+        Format::Json => println!("{}", serde_json::to_string_pretty(&alerts)?),
+    }
+
+    Ok(())
+}
+
+/// A budget with an `account,period,amount` line per account and period,
+/// optionally after a header.
+fn read_budget(path: &Path) -> anyhow::Result<Budget> {
+    let file = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let mut budget = Budget::default();
+
+    for (i, line) in file.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let [account, period, amount] = line.split(',').map(str::trim).collect::<Vec<_>>()[..]
+        else {
+            bail!(
+                "{}:{}: expected `account,period,amount`",
+                path.display(),
+                i + 1
+            );
+        };
+        let Ok(account) = account.parse::<AccountNo>() else {
+            if i == 0 {
+                // the header
+                continue;
+            }
+            bail!("{}:{}: invalid account", path.display(), i + 1);
+        };
+        budget.insert(
+            account,
+            period
+                .parse()
+                .with_context(|| format!("{}:{}: invalid period", path.display(), i + 1))?,
+            amount
+                .parse()
+                .with_context(|| format!("{}:{}: invalid amount", path.display(), i + 1))?,
+        );
+    }
+
+    Ok(budget)
+}
@@ -0,0 +1,222 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context};
+use clap::ValueEnum;
+use iso_currency::Currency;
+use sie4::{
+    currency::Rates,
+    export::csv::{self, Column, Columns},
+    Document,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// The whole document as JSON.
+    Json,
+    /// One row per transaction.
+    Csv,
+    /// Excel workbook with one sheet per account or voucher series.
+    #[cfg(feature = "xlsx")]
+    Xlsx,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            #[cfg(feature = "xlsx")]
+            "xlsx" => Some(Self::Xlsx),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the SIE4 file to read.
+    sie4: PathBuf,
+    /// Output format. Inferred from the output path by default, and
+    /// otherwise JSON.
+    #[clap(long, short, value_enum)]
+    format: Option<Format>,
+    /// Path to the file to create. If it exists, it will be overwritten.
+    /// JSON and CSV are written to stdout by default, and Excel workbooks
+    /// to the input path but with an .xlsx extension.
+    #[clap(long, short)]
+    output: Option<PathBuf>,
+    /// Comma-separated CSV columns, e.g. `date,account,amount,dim1`.
+    #[clap(long, value_delimiter = ',')]
+    columns: Vec<Column>,
+    /// Write CSV numbers with decimal commas, separated by semicolons.
+    #[clap(long)]
+    swedish_numbers: bool,
+    /// How to lay out the Excel workbook.
+    #[cfg(feature = "xlsx")]
+    #[clap(long, value_enum, default_value_t)]
+    layout: crate::xlsx::Layout,
+    /// The account number to have initially visible in a workbook laid out
+    /// per account.
+    #[cfg(feature = "xlsx")]
+    #[clap(long, default_value = "1930")]
+    active_sheet: u32,
+    /// CSV file of exchange rates into the `--base` currency, with one
+    /// `currency,date,rate` line per rate, e.g. `EUR,2023-01-01,11.10`.
+    /// Unless the books are in the base currency, the amounts are also
+    /// written converted, along with the rates.
+    #[clap(long)]
+    rates: Option<PathBuf>,
+    /// The currency to convert into with `--rates`.
+    #[clap(long, default_value = "SEK", value_parser = parse_currency)]
+    base: Currency,
+    /// Also write the removed transactions (`#BTRANS`) of the vouchers,
+    /// e.g. for an audit of everything that was booked.
+    #[clap(long)]
+    include_removed: bool,
+    /// Open the output file when done.
+    #[clap(long)]
+    open: bool,
+}
+
+pub fn run(args: Args) -> anyhow::Result<()> {
+    let format = args
+        .format
+        .or_else(|| args.output.as_deref().and_then(Format::from_path))
+        .unwrap_or(Format::Json);
+    #[cfg(feature = "xlsx")]
+    let output = args
+        .output
+        .or_else(|| (format == Format::Xlsx).then(|| args.sie4.with_extension("xlsx")));
+    #[cfg(not(feature = "xlsx"))]
+    let output = args.output;
+    let reader = sie4::Reader::new(File::open(&args.sie4)?);
+    let rates = args
+        .rates
+        .as_deref()
+        .map(|path| read_rates(path, args.base))
+        .transpose()?;
+
+    match format {
+        Format::Json => {
+            let mut doc = reader.collect::<Result<Document, _>>()?;
+            doc.set_include_removed(args.include_removed);
+            let mut json = serde_json::to_value(&doc)?;
+            if let Some(rates) = rates.filter(|rates| rates.base() != doc.currency()) {
+                json["conversions"] = conversions(&doc, &rates)?;
+            }
+            let mut w = writer(output.as_deref())?;
+            serde_json::to_writer_pretty(&mut w, &json)?;
+            writeln!(w)?;
+            w.flush()?;
+        }
+        Format::Csv => {
+            let mut doc = reader.collect::<Result<Document, _>>()?;
+            doc.set_include_removed(args.include_removed);
+            let columns = if args.columns.is_empty() {
+                Columns::default()
+            } else {
+                Columns::new(args.columns)
+            };
+            let mut columns = columns.swedish_numbers(args.swedish_numbers);
+            if let Some(rates) = rates {
+                columns = columns.convert(rates);
+            }
+            csv::transactions(&doc, &columns, writer(output.as_deref())?)?;
+        }
+        #[cfg(feature = "xlsx")]
+        Format::Xlsx => {
+            let output = output.as_deref().expect("xlsx output path");
+            crate::xlsx::write(
+                &std::fs::read(&args.sie4)?,
+                output,
+                args.layout,
+                args.active_sheet,
+                rates.as_ref(),
+                args.include_removed,
+            )?;
+        }
+    }
+
+    if args.open {
+        let output = output.context("cannot open stdout")?;
+        Command::new("open")
+            .arg(&output)
+            .spawn()
+            .with_context(|| format!("failed to open {output:?}"))?;
+    }
+
+    Ok(())
+}
+
+fn writer(path: Option<&Path>) -> io::Result<Box<dyn Write>> {
+    Ok(match path {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    })
+}
+
+fn parse_currency(s: &str) -> Result<Currency, String> {
+    Currency::from_code(s).ok_or_else(|| format!("unknown currency `{s}`"))
+}
+
+fn read_rates(path: &Path, base: Currency) -> anyhow::Result<Rates> {
+    let file = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let mut rates = Rates::new(base);
+
+    for (i, line) in file.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let [currency, date, rate] = line.split(',').collect::<Vec<_>>()[..] else {
+            bail!(
+                "{}:{}: expected `currency,date,rate`",
+                path.display(),
+                i + 1
+            );
+        };
+        let date = time::Date::parse(
+            date.trim(),
+            time::macros::format_description!("[year]-[month]-[day]"),
+        )
+        .with_context(|| format!("{}:{}: invalid date", path.display(), i + 1))?;
+        rates.insert(
+            parse_currency(currency.trim()).map_err(anyhow::Error::msg)?,
+            date,
+            rate.trim()
+                .parse()
+                .with_context(|| format!("{}:{}: invalid rate", path.display(), i + 1))?,
+        );
+    }
+
+    Ok(rates)
+}
+
+/// The transactions of the vouchers converted with `rates`, recording
+/// the rate that each was converted with.
+fn conversions(doc: &Document, rates: &Rates) -> anyhow::Result<serde_json::Value> {
+    let book = doc.currency();
+    let mut conversions = Vec::new();
+
+    for ver in doc.vouchers() {
+        for trans in ver.counted(doc.include_removed()) {
+            let date = doc.date_policy().date(ver, trans);
+            let conversion = rates.conversion(trans.amount.0, book, date)?;
+            conversions.push(serde_json::json!({
+                "series": ver.series,
+                "no": ver.no,
+                "account": trans.account,
+                "amount": trans.amount,
+                "conversion": conversion,
+            }));
+        }
+    }
+
+    Ok(conversions.into())
+}
@@ -0,0 +1,33 @@
+use std::{path::PathBuf, process::ExitCode};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the old SIE4 file.
+    old: PathBuf,
+    /// Path to the new SIE4 file.
+    new: PathBuf,
+    /// Print the changes as JSON.
+    #[clap(long)]
+    json: bool,
+}
+
+/// Exits with a failure if the files differ, like `diff(1)`.
+pub fn run(args: &Args) -> anyhow::Result<ExitCode> {
+    let old = crate::read_document(&args.old)?;
+    let new = crate::read_document(&args.new)?;
+    let changes = sie4::diff::diff(&old, &new);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&changes)?);
+    } else {
+        for change in &changes {
+            println!("{change}");
+        }
+    }
+
+    Ok(if changes.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
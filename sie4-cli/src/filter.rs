@@ -0,0 +1,56 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use sie4::{filter::Filter, item::AccountNo, Writer};
+use time::Date;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the SIE4 file to read.
+    sie4: PathBuf,
+    /// Path to the file to create. Written to stdout by default.
+    #[clap(long, short)]
+    output: Option<PathBuf>,
+    /// Keep the vouchers with a transaction on the account, and only its
+    /// balances. Can be repeated.
+    #[clap(long)]
+    account: Vec<AccountNo>,
+    /// Keep the vouchers dated on or after this date, e.g. `2023-01-01`.
+    #[clap(long, value_parser = parse_date)]
+    from: Option<Date>,
+    /// Keep the vouchers dated on or before this date.
+    #[clap(long, value_parser = parse_date)]
+    to: Option<Date>,
+    /// Keep the vouchers of the series. Can be repeated.
+    #[clap(long)]
+    series: Vec<String>,
+}
+
+pub fn parse_date(s: &str) -> Result<Date, time::error::Parse> {
+    Date::parse(s, time::macros::format_description!("[year]-[month]-[day]"))
+}
+
+pub fn run(args: &Args) -> anyhow::Result<()> {
+    let filter = Filter {
+        accounts: args.account.clone(),
+        from: args.from,
+        to: args.to,
+        series: args.series.clone(),
+    };
+    let doc = filter.filter(crate::read_document(&args.sie4)?);
+
+    let w: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut writer = Writer::new(w);
+    for item in doc.items() {
+        writer.write_item(item)?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
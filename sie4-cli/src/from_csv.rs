@@ -0,0 +1,59 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::Context;
+use sie4::{journal::csv::Converter, Writer};
+use time::{Date, OffsetDateTime};
+
+use crate::filter::parse_date;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the CSV file to read, with a header naming the columns,
+    /// e.g. `date,account,amount,text,series`.
+    csv: PathBuf,
+    /// The name of the company (#FNAMN).
+    #[clap(long)]
+    company: String,
+    /// Path to the file to create. Written to stdout by default.
+    #[clap(long, short)]
+    output: Option<PathBuf>,
+    /// The series of the rows without one.
+    #[clap(long, default_value = "A")]
+    series: String,
+    /// Read decimal commas and semicolon-separated fields, as spreadsheets
+    /// with a Swedish locale write them.
+    #[clap(long)]
+    swedish_numbers: bool,
+    /// The date that the file is generated (#GEN), today by default.
+    #[clap(long, value_parser = parse_date)]
+    date: Option<Date>,
+}
+
+pub fn run(args: &Args) -> anyhow::Result<()> {
+    let converter = Converter::new(
+        &args.company,
+        args.date
+            .unwrap_or_else(|| OffsetDateTime::now_utc().date()),
+    )
+    .series(&args.series)
+    .swedish_numbers(args.swedish_numbers);
+    let doc = converter
+        .document(File::open(&args.csv)?)
+        .with_context(|| format!("failed to convert {}", args.csv.display()))?;
+
+    let w: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut writer = Writer::new(w);
+    for item in doc.items() {
+        writer.write_item(item)?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
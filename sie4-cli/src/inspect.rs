@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use sie4::item::Item;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the SIE4 file to read.
+    sie4: PathBuf,
+}
+
+pub fn run(args: &Args) -> anyhow::Result<()> {
+    let doc = crate::read_document(&args.sie4)?;
+
+    for item in doc.items() {
+        match item {
+            Item::Program(program) => {
+                println!("Program:       {} {}", program.name, program.version)
+            }
+            Item::FNamn(fnamn) => println!("Company:       {}", fnamn.name),
+            Item::Fnr(fnr) => println!("Company id:    {}", fnr.id),
+            Item::Orgnr(orgnr) => println!("Org. no.:      {}", orgnr.org_no),
+            Item::Valuta(valuta) => println!("Currency:      {}", valuta.currency.code()),
+            _ => (),
+        }
+    }
+
+    for year in doc.fiscal_years().iter() {
+        println!(
+            "Fiscal year:   {:>2}: {} – {}",
+            year.no, year.start, year.end
+        );
+    }
+
+    println!("Accounts:      {}", doc.accounts().count());
+    println!("Verifications: {}", doc.vouchers().count());
+    println!(
+        "Transactions:  {}",
+        doc.vouchers()
+            .map(|ver| ver.counted(doc.include_removed()).count())
+            .sum::<usize>()
+    );
+
+    Ok(())
+}
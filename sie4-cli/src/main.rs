@@ -1,228 +1,103 @@
-use std::{
-    collections::BTreeMap,
-    fs::File,
-    path::{Path, PathBuf},
-    process::Command,
-};
+use std::{path::Path, process::ExitCode};
 
 use anyhow::Context;
-use clap::Parser;
-use rust_decimal::prelude::ToPrimitive;
-use sie4::item::{Item, Trans};
-use time::Date;
-use xlsxwriter::{prelude::*, worksheet::conditional_format::ConditionalFormat};
+use clap::{Parser, Subcommand};
+use sie4::{diagnostics::Source, Document};
+
+mod activity;
+mod anonymize;
+mod audit;
+mod budget;
+mod convert;
+mod diff;
+mod filter;
+mod from_csv;
+mod inspect;
+mod statements;
+mod validate;
+mod vat;
+#[cfg(feature = "xlsx")]
+mod xlsx;
 
 #[derive(Debug, Parser)]
+#[clap(name = "sie4", version, about)]
 struct Args {
-    /// Path to the SIE4 file to read.
-    sie4: PathBuf,
-    /// Path to the Excel workbook to create. If it exists, it will be
-    /// overwritten. Defaults to the input path but with an .xlsx extension.
-    #[clap(long, short)]
-    output: Option<String>,
-    /// The account number to have initially visible in the workbook.
-    #[clap(long, default_value = "1930")]
-    active_sheet: u32,
-
-    #[clap(long)]
-    open: bool,
-}
-
-const SERIES: u16 = 0;
-const VER_NO: u16 = SERIES + 1;
-const DATE: u16 = 2;
-const AMOUNT: u16 = 3;
-const AMOUNT_LETTER: char = ((AMOUNT + 65) as u8) as char;
-const BALANCE: u16 = 4;
-const ACCOUNT_NAME: u16 = 5;
-const ACCOUNT_NO: u16 = 6;
-const DESCRIPTION: u16 = 7;
-
-struct TransactionsSheet<'a> {
-    inner: Worksheet<'a>,
-    row: u32,
+    #[clap(subcommand)]
+    command: Command,
 }
 
-fn accounting_fmt(sheet: &mut Worksheet<'_>, col: u16) -> Result<(), XlsxError> {
-    sheet.set_column(
-        col,
-        col,
-        12.,
-        Some(&Format::new().set_num_format("_-* #,##0.00 kr")),
-    )?;
-    sheet.conditional_format_range(
-        1,
-        col,
-        1000,
-        col,
-        &ConditionalFormat::cell_greater_than(0., Format::new().set_font_color(FormatColor::Green)),
-    )?;
-    sheet.conditional_format_range(
-        1,
-        col,
-        1000,
-        col,
-        &ConditionalFormat::cell_less_than_or_equal_to(
-            0.,
-            Format::new().set_font_color(FormatColor::Red),
-        ),
-    )?;
-    Ok(())
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Count the vouchers registered by each signature, e.g. to check
+    /// them against the people who may register vouchers.
+    Activity(activity::Args),
+    /// Remove identifying information, e.g. to attach a file to a bug
+    /// report.
+    Anonymize(anonymize::Args),
+    /// Check that the vouchers of each series are numbered without gaps
+    /// or duplicates, and list the vouchers that were likely booked twice.
+    /// With `--by-user`, list what each user registered instead.
+    Audit(audit::Args),
+    /// List the accounts that exceeded their budgets, from #PBUDGET or a
+    /// CSV file.
+    Budget(budget::Args),
+    /// Convert a SIE4 file into another format.
+    Convert(convert::Args),
+    /// Show what changed between two SIE4 files.
+    Diff(diff::Args),
+    /// Extract the vouchers of some accounts, series or dates into a
+    /// SIE4 file of their own, with recomputed balances.
+    Filter(filter::Args),
+    /// Convert journal entries in a CSV file, e.g. of books kept in a
+    /// spreadsheet, into a SIE4 import file.
+    FromCsv(from_csv::Args),
+    /// Print a summary of a SIE4 file.
+    Inspect(inspect::Args),
+    /// Print the income statement and balance sheet, with the accounts
+    /// summed by the lines of an annual report.
+    Statements(statements::Args),
+    /// Check that a SIE4 file follows the specification.
+    Validate(validate::Args),
+    /// Sum the boxes of the VAT declaration from the transactions of a
+    /// period.
+    Vat(vat::Args),
 }
 
-impl<'a> TransactionsSheet<'a> {
-    const STARTING_ROW: u32 = 1;
-
-    fn new(mut sheet: Worksheet<'a>) -> Result<Self, XlsxError> {
-        sheet.merge_range(0, SERIES, 0, VER_NO, "Verifikation", None)?;
-        sheet.write_string(0, DATE, "Datum", None)?;
-        sheet.write_string(0, AMOUNT, "Belopp", None)?;
-        sheet.write_string(0, ACCOUNT_NAME, "Konto", None)?;
-        sheet.write_string(0, DESCRIPTION, "Beskrivning", None)?;
-        sheet.write_string(0, ACCOUNT_NO, "Konto#", None)?;
-        sheet.write_string(0, BALANCE, "Saldo", None)?;
-
-        sheet.set_column(DESCRIPTION, DESCRIPTION, 30., None)?;
-        sheet.set_column(
-            DATE,
-            DATE,
-            10.,
-            Some(&Format::new().set_num_format("yyyy-mm-dd")),
-        )?;
-        sheet.set_column(SERIES, SERIES, 1., None)?;
-        sheet.set_column(VER_NO, VER_NO, 4., None)?;
-        sheet.set_column(ACCOUNT_NAME, ACCOUNT_NAME, 30., None)?;
-
-        accounting_fmt(&mut sheet, AMOUNT)?;
-        accounting_fmt(&mut sheet, BALANCE)?;
-
-        Ok(Self {
-            inner: sheet,
-            row: Self::STARTING_ROW,
-        })
-    }
-
-    fn write(
-        &mut self,
-        trans: &Trans,
-        series: &str,
-        ver_no: u32,
-        date: Date,
-        account_name: &str,
-    ) -> Result<(), XlsxError> {
-        let sheet = &mut self.inner;
-        sheet.write_string(self.row, SERIES, series, None)?;
-        sheet.write_number(self.row, VER_NO, ver_no.into(), None)?;
-        sheet.write_datetime(
-            self.row,
-            DATE,
-            &DateTime {
-                year: date.year().try_into().unwrap(),
-                month: u8::from(date.month()).try_into().unwrap(),
-                day: date.day().try_into().unwrap(),
-                hour: 0,
-                min: 0,
-                second: 0.,
-            },
-            None,
-        )?;
-        sheet.write_number(self.row, AMOUNT, trans.amount.to_f64().unwrap(), None)?;
-        sheet.write_string(self.row, ACCOUNT_NAME, &account_name, None)?;
-        sheet.write_string(
-            self.row,
-            DESCRIPTION,
-            trans.text.as_deref().unwrap_or(""),
-            None,
-        )?;
-        sheet.write_number(self.row, ACCOUNT_NO, trans.account.into(), None)?;
-        sheet.write_formula(
-            self.row,
-            BALANCE,
-            &format!("=SUM({AMOUNT_LETTER}{}:{AMOUNT_LETTER}{})", 1, self.row + 1),
-            None,
-        )?;
-
-        self.row += 1;
-
-        Ok(())
+fn main() -> anyhow::Result<ExitCode> {
+    match Args::parse().command {
+        Command::Activity(args) => return activity::run(&args),
+        Command::Anonymize(args) => anonymize::run(&args)?,
+        Command::Audit(args) => return audit::run(&args),
+        Command::Budget(args) => budget::run(&args)?,
+        Command::Convert(args) => convert::run(args)?,
+        Command::Diff(args) => return diff::run(&args),
+        Command::Filter(args) => filter::run(&args)?,
+        Command::FromCsv(args) => from_csv::run(&args)?,
+        Command::Inspect(args) => inspect::run(&args)?,
+        Command::Statements(args) => statements::run(&args)?,
+        Command::Validate(args) => return validate::run(&args),
+        Command::Vat(args) => vat::run(&args)?,
     }
 
-    fn touched(&self) -> bool {
-        self.row > Self::STARTING_ROW
-    }
+    Ok(ExitCode::SUCCESS)
 }
 
-fn main() -> anyhow::Result<()> {
-    let args = Args::parse();
-    let sie4 = File::open(&args.sie4)?;
-    let reader = sie4::Reader::new(sie4);
-
-    let output = args.output.unwrap_or_else(|| {
-        let mut output = args.sie4.clone();
-        output.set_extension("xlsx");
-        output.to_str().unwrap().to_owned()
-    });
-    if Path::new(&output).try_exists()? {
-        std::fs::remove_file(&output)?;
-    }
-
-    let workbook = Workbook::new(&output)?;
-    let mut accounts = BTreeMap::new();
-
-    for res in reader {
-        match res? {
-            Item::Konto(account) => {
-                let name = account
-                    .name
-                    .chars()
-                    .take(24)
-                    .map(|c| match c {
-                        '/' => '-',
-                        c => c,
-                    })
-                    .collect::<String>();
-                let name = format!("{} ({})", name, account.no);
-                let sheet = TransactionsSheet::new(
-                    workbook
-                        .add_worksheet(Some(&name))
-                        .with_context(|| format!("failed to add worksheet named {name:?}"))?,
-                )?;
-                accounts.insert(account.no, (account.name, sheet));
-            }
-            Item::Ver(ver) => {
-                for trans in ver.transactions.0 {
-                    let (account_name, sheet) = accounts.get_mut(&trans.account).unwrap();
-                    sheet.write(
-                        &trans,
-                        &ver.series,
-                        ver.no,
-                        trans.date.unwrap_or(ver.date),
-                        account_name,
-                    )?;
-                }
-            }
-            _ => (),
-        }
-    }
-
-    for (no, (_, sheet)) in accounts.iter_mut() {
-        if !sheet.touched() {
-            sheet.inner.hide();
-        }
-
-        if *no == args.active_sheet {
-            sheet.inner.activate();
-        }
-    }
-
-    workbook.close()?;
-
-    if args.open {
-        Command::new("open")
-            .arg(&output)
-            .spawn()
-            .with_context(|| format!("failed to open {output:?}"))?;
-    }
+/// Read the SIE4 file at `path`, with errors on a line of the file
+/// rendered with the line.
+fn read_document(path: &Path) -> anyhow::Result<Document> {
+    let data = std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    Document::from_reader(&data[..]).map_err(|e| {
+        anyhow::anyhow!(render(&Source::new(&data), e).trim_end().to_owned())
+            .context(format!("failed to read {}", path.display()))
+    })
+}
 
-    Ok(())
+/// `diagnostic` rendered by miette, with the lines of `source` that it is
+/// about.
+fn render(source: &Source, diagnostic: impl miette::Diagnostic) -> String {
+    let mut s = String::new();
+    // writing to a string doesn't fail
+    let _ =
+        miette::GraphicalReportHandler::new().render_report(&mut s, &source.annotate(diagnostic));
+    s
 }
@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use sie4::reports::statements::{self, Mapping, StatementLine};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the SIE4 file to read.
+    sie4: PathBuf,
+    /// Path to a TOML file with the lines of the statements and their
+    /// accounts. The lines of K2 for the BAS chart of accounts by default.
+    #[clap(long)]
+    rules: Option<PathBuf>,
+    /// The fiscal year, where 0 is the current one.
+    #[clap(long, default_value = "0", allow_hyphen_values = true)]
+    year: i32,
+    /// Print the statements as JSON.
+    #[clap(long)]
+    json: bool,
+    /// Add the removed transactions (`#BTRANS`) of the year to the
+    /// accounts, as if they had never been removed.
+    #[clap(long)]
+    include_removed: bool,
+}
+
+pub fn run(args: &Args) -> anyhow::Result<()> {
+    let mut doc = crate::read_document(&args.sie4)?;
+    doc.set_include_removed(args.include_removed);
+    let mapping = match &args.rules {
+        Some(path) => {
+            let rules = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            Mapping::from_toml(&rules)
+                .with_context(|| format!("invalid rules in {}", path.display()))?
+        }
+        None => Mapping::bas(),
+    };
+    let statements = statements::statements(&doc, &mapping, args.year);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&statements)?);
+        return Ok(());
+    }
+
+    let print = |title: &str, lines: &[StatementLine]| {
+        println!("{title}");
+        for line in lines {
+            println!("  {}: {}", line.name, line.amount);
+        }
+    };
+    print("Resultaträkning", &statements.income);
+    println!("  Årets resultat: {}", -statements.result());
+    print("Balansräkning", &statements.balance);
+    for (account, statement, amount) in &statements.unmapped {
+        eprintln!("warning: account {account} ({statement:?}) of {amount} is on no line");
+    }
+
+    Ok(())
+}
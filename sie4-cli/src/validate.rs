@@ -0,0 +1,71 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use sie4::{
+    diagnostics::Source,
+    validate::{self, Config, Materiality, Severity, Threshold},
+};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the SIE4 file to validate.
+    sie4: PathBuf,
+    /// Print the report as JSON.
+    #[clap(long)]
+    json: bool,
+    /// Fail on warnings too.
+    #[clap(long)]
+    deny_warnings: bool,
+    /// Warn about transactions larger than this amount, or percentage of
+    /// the turnover such as `2%`.
+    #[clap(long)]
+    materiality: Option<Threshold>,
+    /// The materiality threshold of an account class, e.g. `1=50000` for
+    /// assets. Can be repeated.
+    #[clap(long, value_parser = parse_class_threshold)]
+    class_materiality: Vec<(u32, Threshold)>,
+}
+
+fn parse_class_threshold(s: &str) -> anyhow::Result<(u32, Threshold)> {
+    let (class, threshold) = s
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("expected CLASS=THRESHOLD"))?;
+    Ok((class.parse()?, threshold.parse()?))
+}
+
+/// Exits with status 1 if the file is invalid.
+pub fn run(args: &Args) -> anyhow::Result<ExitCode> {
+    let data = std::fs::read(&args.sie4)?;
+    let materiality =
+        (args.materiality.is_some() || !args.class_materiality.is_empty()).then(|| Materiality {
+            default: args.materiality,
+            classes: args.class_materiality.iter().copied().collect(),
+        });
+    let report = validate::validate_bytes_with(&data, &Config { materiality });
+    let valid = report.is_valid() && !(args.deny_warnings && report.count(Severity::Warning) > 0);
+
+    if args.json {
+        let json = serde_json::json!({
+            "valid": valid,
+            "findings": report.findings,
+        });
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    } else {
+        let source = Source::new(&data);
+        for finding in &report.findings {
+            println!("{}", crate::render(&source, finding.clone()));
+        }
+
+        println!(
+            "{}: {} errors, {} warnings",
+            args.sie4.display(),
+            report.count(Severity::Error),
+            report.count(Severity::Warning)
+        );
+    }
+
+    Ok(if valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
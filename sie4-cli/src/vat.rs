@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use sie4::vat::Report;
+use time::{Date, Month};
+
+use crate::filter::parse_date;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the SIE4 file to read.
+    sie4: PathBuf,
+    /// The first day of the period, e.g. `2023-01-01`. The start of the
+    /// current fiscal year by default.
+    #[clap(long, value_parser = parse_date)]
+    from: Option<Date>,
+    /// The last day of the period. The end of the current fiscal year by
+    /// default.
+    #[clap(long, value_parser = parse_date)]
+    to: Option<Date>,
+    /// Report each month of the period separately.
+    #[clap(long)]
+    monthly: bool,
+    /// Print the reports as JSON.
+    #[clap(long)]
+    json: bool,
+}
+
+pub fn run(args: &Args) -> anyhow::Result<()> {
+    let doc = crate::read_document(&args.sie4)?;
+    let years = doc.fiscal_years();
+    let current = years.current();
+    let from = args
+        .from
+        .or(current.map(|year| year.start))
+        .context("no --from and no current fiscal year (#RAR 0)")?;
+    let to = args
+        .to
+        .or(current.map(|year| year.end))
+        .context("no --to and no current fiscal year (#RAR 0)")?;
+
+    let periods = if args.monthly {
+        months(from, to)
+    } else {
+        vec![(from, to)]
+    };
+    let reports = periods
+        .into_iter()
+        .map(|(from, to)| Report::compute(&doc, from..=to))
+        .collect::<Vec<_>>();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+        return Ok(());
+    }
+
+    for report in &reports {
+        println!("{} - {}", report.period.start(), report.period.end());
+        for (no, amount) in &report.boxes {
+            println!("  {no:02}: {amount}");
+        }
+    }
+
+    Ok(())
+}
+
+/// The calendar months from `from` to `to`, cut at both ends.
+fn months(from: Date, to: Date) -> Vec<(Date, Date)> {
+    let mut months = Vec::new();
+    let mut start = from;
+    while start <= to {
+        let (year, month) = match start.month() {
+            Month::December => (start.year() + 1, Month::January),
+            month => (start.year(), month.next()),
+        };
+        let next = Date::from_calendar_date(year, month, 1).unwrap_or(Date::MAX);
+        let end = next.previous_day().unwrap_or(next).min(to);
+        months.push((start, end));
+        if next == Date::MAX {
+            break;
+        }
+        start = next;
+    }
+    months
+}
@@ -0,0 +1,540 @@
+//! Excel workbooks of transactions, with one sheet per account or per
+//! voucher series.
+
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::Context;
+use clap::ValueEnum;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use sie4::{
+    currency::Rates,
+    item::AccountNo,
+    resolve::{ResolvedTrans, ResolvedVer},
+    Document, Item, Reader,
+};
+use xlsxwriter::{prelude::*, worksheet::conditional_format::ConditionalFormat};
+
+const SERIES: u16 = 0;
+const VER_NO: u16 = SERIES + 1;
+const DATE: u16 = 2;
+const AMOUNT: u16 = 3;
+const AMOUNT_LETTER: char = ((AMOUNT + 65) as u8) as char;
+const BALANCE: u16 = 4;
+const ACCOUNT_NAME: u16 = 5;
+const ACCOUNT_NO: u16 = 6;
+const DESCRIPTION: u16 = 7;
+const CURRENCY: u16 = 8;
+const RATE: u16 = 9;
+const CONVERTED: u16 = 10;
+
+/// How the transactions are laid out in the workbook.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Layout {
+    /// One sheet per account, with a running balance, after a summary
+    /// of all accounts (huvudbok).
+    #[default]
+    Accounts,
+    /// One sheet per voucher series in chronological order (grundbok),
+    /// with each voucher followed by its transactions.
+    Series,
+}
+
+struct TransactionsSheet<'a> {
+    inner: Worksheet<'a>,
+    row: u32,
+    rates: Option<&'a Rates>,
+}
+
+fn accounting_fmt(sheet: &mut Worksheet<'_>, col: u16) -> Result<(), XlsxError> {
+    sheet.set_column(
+        col,
+        col,
+        12.,
+        Some(Format::new().set_num_format("_-* #,##0.00 kr")),
+    )?;
+    sheet.conditional_format_range(
+        1,
+        col,
+        1000,
+        col,
+        &ConditionalFormat::cell_greater_than(0., Format::new().set_font_color(FormatColor::Green)),
+    )?;
+    sheet.conditional_format_range(
+        1,
+        col,
+        1000,
+        col,
+        &ConditionalFormat::cell_less_than_or_equal_to(
+            0.,
+            Format::new().set_font_color(FormatColor::Red),
+        ),
+    )?;
+    Ok(())
+}
+
+fn datetime(date: time::Date) -> DateTime {
+    DateTime {
+        year: date.year().try_into().unwrap(),
+        month: u8::from(date.month()).try_into().unwrap(),
+        day: date.day().try_into().unwrap(),
+        hour: 0,
+        min: 0,
+        second: 0.,
+    }
+}
+
+/// Account numbers are written as numbers, unless they have letters or
+/// leading zeros, which a number would lose.
+fn write_account(
+    sheet: &mut Worksheet<'_>,
+    row: u32,
+    col: u16,
+    account: AccountNo,
+) -> Result<(), XlsxError> {
+    match account.number().filter(|_| account.is_canonical()) {
+        Some(no) => sheet.write_number(row, col, no.into(), None),
+        None => sheet.write_string(row, col, account.as_str(), None),
+    }
+}
+
+/// Sheet name without the characters that Excel doesn't allow.
+fn sheet_name(name: &str, max: usize) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, '\\' | '?' | '*' | '[' | ']' | ':'))
+        .take(max)
+        .map(|c| match c {
+            '/' => '-',
+            c => c,
+        })
+        .collect()
+}
+
+impl<'a> TransactionsSheet<'a> {
+    const STARTING_ROW: u32 = 1;
+
+    fn new(mut sheet: Worksheet<'a>, rates: Option<&'a Rates>) -> Result<Self, XlsxError> {
+        sheet.merge_range(0, SERIES, 0, VER_NO, "Verifikation", None)?;
+        sheet.write_string(0, DATE, "Datum", None)?;
+        sheet.write_string(0, AMOUNT, "Belopp", None)?;
+        sheet.write_string(0, ACCOUNT_NAME, "Konto", None)?;
+        sheet.write_string(0, DESCRIPTION, "Beskrivning", None)?;
+        sheet.write_string(0, ACCOUNT_NO, "Konto#", None)?;
+        sheet.write_string(0, BALANCE, "Saldo", None)?;
+
+        sheet.set_column(DESCRIPTION, DESCRIPTION, 30., None)?;
+        sheet.set_column(
+            DATE,
+            DATE,
+            10.,
+            Some(Format::new().set_num_format("yyyy-mm-dd")),
+        )?;
+        sheet.set_column(SERIES, SERIES, 1., None)?;
+        sheet.set_column(VER_NO, VER_NO, 4., None)?;
+        sheet.set_column(ACCOUNT_NAME, ACCOUNT_NAME, 30., None)?;
+
+        accounting_fmt(&mut sheet, AMOUNT)?;
+        accounting_fmt(&mut sheet, BALANCE)?;
+
+        if let Some(rates) = rates {
+            sheet.write_string(0, CURRENCY, "Valuta", None)?;
+            sheet.write_string(0, RATE, "Kurs", None)?;
+            sheet.write_string(
+                0,
+                CONVERTED,
+                &format!("Belopp {}", rates.base().code()),
+                None,
+            )?;
+            let amount = Format::new().set_num_format("#,##0.00").clone();
+            sheet.set_column(CONVERTED, CONVERTED, 12., Some(&amount))?;
+        }
+
+        Ok(Self {
+            inner: sheet,
+            row: Self::STARTING_ROW,
+            rates,
+        })
+    }
+
+    fn write(&mut self, ver: &ResolvedVer, trans: &ResolvedTrans) -> anyhow::Result<()> {
+        let date = trans.date;
+        let sheet = &mut self.inner;
+        sheet.write_string(self.row, SERIES, &ver.series, None)?;
+        sheet.write_number(self.row, VER_NO, ver.no.into(), None)?;
+        sheet.write_datetime(self.row, DATE, &datetime(date), None)?;
+        sheet.write_number(self.row, AMOUNT, trans.amount.to_f64().unwrap(), None)?;
+        sheet.write_string(
+            self.row,
+            ACCOUNT_NAME,
+            trans.account_name.as_deref().unwrap_or(""),
+            None,
+        )?;
+        sheet.write_string(
+            self.row,
+            DESCRIPTION,
+            trans.text.as_deref().unwrap_or(""),
+            None,
+        )?;
+        write_account(sheet, self.row, ACCOUNT_NO, trans.account)?;
+        sheet.write_formula(
+            self.row,
+            BALANCE,
+            &format!("=SUM({AMOUNT_LETTER}{}:{AMOUNT_LETTER}{})", 1, self.row + 1),
+            None,
+        )?;
+
+        if let Some(rates) = self.rates.filter(|rates| rates.base() != trans.currency) {
+            let conversion = rates.conversion(trans.amount, trans.currency, date)?;
+            sheet.write_string(self.row, CURRENCY, trans.currency.code(), None)?;
+            sheet.write_number(self.row, RATE, conversion.rate.to_f64().unwrap(), None)?;
+            sheet.write_number(
+                self.row,
+                CONVERTED,
+                conversion.amount.round_dp(2).to_f64().unwrap(),
+                None,
+            )?;
+        }
+
+        self.row += 1;
+
+        Ok(())
+    }
+}
+
+struct JournalSheet<'a> {
+    inner: Worksheet<'a>,
+    row: u32,
+}
+
+impl<'a> JournalSheet<'a> {
+    const VER: u16 = 0;
+    const DATE: u16 = 1;
+    const ACCOUNT_NO: u16 = 2;
+    const ACCOUNT_NAME: u16 = 3;
+    const DESCRIPTION: u16 = 4;
+    const DEBIT: u16 = 5;
+    const CREDIT: u16 = 6;
+
+    fn new(mut sheet: Worksheet<'a>) -> Result<Self, XlsxError> {
+        let bold = Format::new().set_bold().clone();
+        sheet.write_string(0, Self::VER, "Verifikation", Some(&bold))?;
+        sheet.write_string(0, Self::DATE, "Datum", Some(&bold))?;
+        sheet.merge_range(
+            0,
+            Self::ACCOUNT_NO,
+            0,
+            Self::ACCOUNT_NAME,
+            "Konto",
+            Some(&bold),
+        )?;
+        sheet.write_string(0, Self::DESCRIPTION, "Beskrivning", Some(&bold))?;
+        sheet.write_string(0, Self::DEBIT, "Debet", Some(&bold))?;
+        sheet.write_string(0, Self::CREDIT, "Kredit", Some(&bold))?;
+
+        sheet.set_column(Self::VER, Self::VER, 10., None)?;
+        sheet.set_column(
+            Self::DATE,
+            Self::DATE,
+            10.,
+            Some(Format::new().set_num_format("yyyy-mm-dd")),
+        )?;
+        sheet.set_column(
+            Self::ACCOUNT_NO,
+            Self::ACCOUNT_NO,
+            8.,
+            Some(Format::new().set_indent(1)),
+        )?;
+        sheet.set_column(Self::ACCOUNT_NAME, Self::ACCOUNT_NAME, 30., None)?;
+        sheet.set_column(Self::DESCRIPTION, Self::DESCRIPTION, 30., None)?;
+        let amount = Format::new().set_num_format("#,##0.00").clone();
+        sheet.set_column(Self::DEBIT, Self::CREDIT, 12., Some(&amount))?;
+        sheet.freeze_panes(1, 0);
+
+        Ok(Self {
+            inner: sheet,
+            row: 1,
+        })
+    }
+
+    fn write(&mut self, ver: &ResolvedVer) -> Result<(), XlsxError> {
+        let sheet = &mut self.inner;
+        let bold = Format::new().set_bold().clone();
+        let date = Format::new()
+            .set_bold()
+            .set_num_format("yyyy-mm-dd")
+            .clone();
+
+        let no = format!("{} {}", ver.series, ver.no);
+        sheet.write_string(self.row, Self::VER, &no, Some(&bold))?;
+        sheet.write_datetime(self.row, Self::DATE, &datetime(ver.date), Some(&date))?;
+        sheet.write_string(
+            self.row,
+            Self::DESCRIPTION,
+            ver.text.as_deref().unwrap_or(""),
+            Some(&bold),
+        )?;
+        self.row += 1;
+
+        for trans in &ver.transactions {
+            write_account(sheet, self.row, Self::ACCOUNT_NO, trans.account)?;
+            sheet.write_string(
+                self.row,
+                Self::ACCOUNT_NAME,
+                trans.account_name.as_deref().unwrap_or(""),
+                None,
+            )?;
+            sheet.write_string(
+                self.row,
+                Self::DESCRIPTION,
+                trans.text.as_deref().unwrap_or(""),
+                None,
+            )?;
+            let amount = trans.amount.to_f64().unwrap();
+            if amount < 0. {
+                sheet.write_number(self.row, Self::CREDIT, -amount, None)?;
+            } else {
+                sheet.write_number(self.row, Self::DEBIT, amount, None)?;
+            }
+            self.row += 1;
+        }
+
+        Ok(())
+    }
+}
+
+/// The opening balance, change and closing balance of every account in
+/// the current year, with links to the sheets of the accounts.
+struct SummarySheet<'a> {
+    inner: Worksheet<'a>,
+    row: u32,
+}
+
+impl<'a> SummarySheet<'a> {
+    const NAME: &'static str = "Huvudbok";
+
+    const ACCOUNT_NO: u16 = 0;
+    const ACCOUNT_NAME: u16 = 1;
+    const OPENING: u16 = 2;
+    const CHANGE: u16 = 3;
+    const CLOSING: u16 = 4;
+
+    fn new(mut sheet: Worksheet<'a>) -> Result<Self, XlsxError> {
+        let bold = Format::new().set_bold().clone();
+        sheet.write_string(0, Self::ACCOUNT_NO, "Konto#", Some(&bold))?;
+        sheet.write_string(0, Self::ACCOUNT_NAME, "Konto", Some(&bold))?;
+        sheet.write_string(0, Self::OPENING, "Ingående balans", Some(&bold))?;
+        sheet.write_string(0, Self::CHANGE, "Förändring", Some(&bold))?;
+        sheet.write_string(0, Self::CLOSING, "Utgående balans", Some(&bold))?;
+
+        sheet.set_column(Self::ACCOUNT_NAME, Self::ACCOUNT_NAME, 30., None)?;
+        let amount = Format::new().set_num_format("#,##0.00").clone();
+        sheet.set_column(Self::OPENING, Self::CLOSING, 15., Some(&amount))?;
+        sheet.freeze_panes(1, 0);
+
+        Ok(Self {
+            inner: sheet,
+            row: 1,
+        })
+    }
+
+    /// `account_sheet` is the name of the sheet of the account, if it has
+    /// one.
+    fn write(
+        &mut self,
+        no: AccountNo,
+        name: Option<&str>,
+        account_sheet: Option<&str>,
+        balances: &Balances,
+    ) -> Result<(), XlsxError> {
+        let sheet = &mut self.inner;
+        let closing = balances
+            .closing
+            .unwrap_or(balances.opening + balances.transactions);
+
+        write_account(sheet, self.row, Self::ACCOUNT_NO, no)?;
+        let name = name.unwrap_or("");
+        if let Some(account_sheet) = account_sheet {
+            let link = Format::new()
+                .set_underline(FormatUnderline::Single)
+                .set_font_color(FormatColor::Blue)
+                .clone();
+            let url = format!("internal:'{}'!A1", account_sheet.replace('\'', "''"));
+            sheet.write_url(self.row, Self::ACCOUNT_NAME, &url, Some(&link))?;
+            // keeps the link, but shows the name instead of the url
+            sheet.write_string(self.row, Self::ACCOUNT_NAME, name, Some(&link))?;
+        } else {
+            sheet.write_string(self.row, Self::ACCOUNT_NAME, name, None)?;
+        }
+        for (col, amount) in [
+            (Self::OPENING, balances.opening),
+            (Self::CHANGE, closing - balances.opening),
+            (Self::CLOSING, closing),
+        ] {
+            sheet.write_number(self.row, col, amount.to_f64().unwrap(), None)?;
+        }
+
+        self.row += 1;
+
+        Ok(())
+    }
+}
+
+/// The balances of an account in the current year.
+#[derive(Debug, Default)]
+struct Balances {
+    /// `#IB`
+    opening: Decimal,
+    /// `#UB`, or `#RES` of result accounts.
+    closing: Option<Decimal>,
+    /// The sum of the transactions.
+    transactions: Decimal,
+}
+
+/// With `rates`, the transactions of a workbook laid out per account are
+/// also converted into their base currency, unless already in it.
+pub fn write(
+    data: &[u8],
+    output: &Path,
+    layout: Layout,
+    active_sheet: u32,
+    rates: Option<&Rates>,
+    include_removed: bool,
+) -> anyhow::Result<()> {
+    if output.try_exists()? {
+        std::fs::remove_file(output)?;
+    }
+
+    let output = output.to_str().context("output path is not valid UTF-8")?;
+    let workbook = Workbook::new(output)?;
+
+    let vers = Reader::new(data)
+        .verifications()
+        .include_removed(include_removed)
+        .collect::<Result<Vec<_>, _>>()?;
+    match layout {
+        Layout::Accounts => {
+            let doc = Document::from_slice(data)?;
+            write_accounts(&workbook, &doc, &vers, active_sheet, rates)?;
+        }
+        Layout::Series => write_series(&workbook, &vers)?,
+    }
+
+    workbook.close()?;
+
+    Ok(())
+}
+
+fn write_series(workbook: &Workbook, vers: &[ResolvedVer]) -> anyhow::Result<()> {
+    let mut series = BTreeMap::<_, Vec<_>>::new();
+    for ver in vers {
+        series.entry(ver.series.as_str()).or_default().push(ver);
+    }
+
+    for (name, mut vers) in series {
+        vers.sort_by_key(|ver| (ver.date, ver.no));
+
+        let name = match sheet_name(name, 31) {
+            name if name.is_empty() => "-".to_owned(),
+            name => name,
+        };
+        let mut sheet = JournalSheet::new(
+            workbook
+                .add_worksheet(Some(&name))
+                .with_context(|| format!("failed to add worksheet named {name:?}"))?,
+        )?;
+
+        for ver in vers {
+            sheet.write(ver)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_accounts(
+    workbook: &Workbook,
+    doc: &Document,
+    vers: &[ResolvedVer],
+    active_sheet: u32,
+    rates: Option<&Rates>,
+) -> anyhow::Result<()> {
+    // transactions by account, so that the sheets are in account order
+    let mut accounts = BTreeMap::<_, Vec<_>>::new();
+    for ver in vers {
+        for trans in &ver.transactions {
+            accounts
+                .entry(trans.account)
+                .or_default()
+                .push((ver, trans));
+        }
+    }
+
+    let sheet_names = accounts
+        .iter()
+        .map(|(no, rows)| {
+            let name = match rows
+                .iter()
+                .find_map(|(_, trans)| trans.account_name.as_deref())
+            {
+                // within Excel's 31 characters with ` (` and `)` around
+                // the number, which can be up to `AccountNo::MAX_LEN` long
+                Some(name) => format!("{} ({no})", sheet_name(name, 31 - no.as_str().len() - 3)),
+                // not declared with #KONTO, as in many minimal files
+                None => format!("{no} (okänt)"),
+            };
+            (*no, name)
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let mut balances = BTreeMap::<AccountNo, Balances>::new();
+    for konto in doc.accounts() {
+        balances.entry(konto.no).or_default();
+    }
+    for item in doc.items() {
+        match item {
+            Item::Ib(ib) if ib.year == 0 => {
+                balances.entry(ib.account).or_default().opening = ib.balance.0;
+            }
+            Item::Ub(ub) if ub.year == 0 => {
+                balances.entry(ub.account).or_default().closing = Some(ub.balance.0);
+            }
+            Item::Res(res) if res.year == 0 => {
+                balances.entry(res.account).or_default().closing = Some(res.balance.0);
+            }
+            _ => (),
+        }
+    }
+    for (no, rows) in &accounts {
+        balances.entry(*no).or_default().transactions =
+            rows.iter().map(|(_, trans)| trans.amount).sum();
+    }
+
+    let mut summary = SummarySheet::new(workbook.add_worksheet(Some(SummarySheet::NAME))?)?;
+    for (no, balances) in &balances {
+        summary.write(
+            *no,
+            doc.account(*no).map(|konto| konto.name.as_str()),
+            sheet_names.get(no).map(String::as_str),
+            balances,
+        )?;
+    }
+
+    for (no, rows) in accounts {
+        let name = &sheet_names[&no];
+        let mut sheet = TransactionsSheet::new(
+            workbook
+                .add_worksheet(Some(name))
+                .with_context(|| format!("failed to add worksheet named {name:?}"))?,
+            rates,
+        )?;
+
+        for (ver, trans) in rows {
+            sheet.write(ver, trans)?;
+        }
+
+        if no == active_sheet {
+            sheet.inner.activate();
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,252 @@
+//! Python bindings, e.g. for loading SIE4 files into pandas.
+//!
+//! ```python
+//! import pandas as pd
+//! import sie4
+//!
+//! doc = sie4.read_file("export.se")
+//! df = pd.DataFrame(doc.transactions())
+//! ```
+//!
+//! Amounts are `decimal.Decimal` and dates `datetime.date`.
+
+use std::{collections::BTreeMap, fs::File, io::BufReader, path::PathBuf};
+
+use ::sie4::{accounts::Accounts, item::Trans, Item};
+use pyo3::{
+    create_exception,
+    exceptions::{PyOSError, PyValueError},
+    prelude::*,
+    types::{PyDate, PyDict},
+};
+use rust_decimal::Decimal;
+
+create_exception!(sie4, ParseError, PyValueError, "The file isn't valid SIE4.");
+
+/// A date, converted into a `datetime.date`.
+#[derive(Debug, Clone, Copy)]
+struct Day(time::Date);
+
+impl<'py> IntoPyObject<'py> for Day {
+    type Target = PyDate;
+    type Output = Bound<'py, PyDate>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> PyResult<Self::Output> {
+        PyDate::new(py, self.0.year(), self.0.month().into(), self.0.day())
+    }
+}
+
+/// A fiscal year (`#RAR`), where `no` is `0` for the current year and
+/// `-1` for the previous one.
+#[pyclass(module = "sie4", frozen, get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+struct FiscalYear {
+    no: i32,
+    start: Day,
+    end: Day,
+}
+
+/// A transaction (`#TRANS`) of a voucher, as corrected. `account` is
+/// the account number as written, e.g. `"1930"`, and `objects` are
+/// `(dimension, object)` pairs.
+#[pyclass(module = "sie4", frozen, get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+struct Transaction {
+    account: String,
+    objects: Vec<(u32, String)>,
+    amount: Decimal,
+    /// The date of the transaction, or else of the voucher.
+    date: Day,
+    text: Option<String>,
+    quantity: Option<Decimal>,
+    signature: Option<String>,
+}
+
+/// A voucher (`#VER`).
+#[pyclass(module = "sie4", frozen, get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+struct Voucher {
+    series: String,
+    no: u32,
+    date: Day,
+    text: Option<String>,
+    reg_date: Option<Day>,
+    sign: Option<String>,
+    transactions: Vec<Transaction>,
+}
+
+/// An opening (`IB`) or closing (`UB`) balance, or a result (`RES`).
+#[pyclass(module = "sie4", frozen, get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+struct Balance {
+    kind: &'static str,
+    year: i32,
+    account: String,
+    balance: Decimal,
+    quantity: Option<Decimal>,
+}
+
+/// The contents of a file.
+#[pyclass(module = "sie4", frozen, get_all, skip_from_py_object)]
+#[derive(Debug)]
+struct Document {
+    /// `#FNAMN`
+    company_name: Option<String>,
+    /// `#ORGNR`
+    org_no: Option<String>,
+    /// The currency of the books, e.g. `"SEK"`.
+    currency: &'static str,
+    /// The names of the accounts, by number as written.
+    accounts: BTreeMap<String, String>,
+    fiscal_years: Vec<FiscalYear>,
+    vouchers: Vec<Voucher>,
+    balances: Vec<Balance>,
+}
+
+#[pymethods]
+impl Document {
+    /// One dict per transaction, with the series, number and text of its
+    /// voucher and the name of its account, e.g. for `pandas.DataFrame`.
+    fn transactions<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let mut rows = Vec::new();
+
+        for ver in &self.vouchers {
+            for trans in &ver.transactions {
+                let row = PyDict::new(py);
+                row.set_item("series", &ver.series)?;
+                row.set_item("no", ver.no)?;
+                row.set_item("voucher_text", &ver.text)?;
+                row.set_item("date", trans.date)?;
+                row.set_item("account", &trans.account)?;
+                row.set_item("account_name", self.accounts.get(&trans.account))?;
+                row.set_item("amount", trans.amount)?;
+                row.set_item("quantity", trans.quantity)?;
+                row.set_item("text", &trans.text)?;
+                row.set_item("signature", &trans.signature)?;
+                rows.push(row);
+            }
+        }
+
+        Ok(rows)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<Document {:?} with {} accounts and {} vouchers>",
+            self.company_name.as_deref().unwrap_or_default(),
+            self.accounts.len(),
+            self.vouchers.len()
+        )
+    }
+}
+
+fn transaction(trans: &Trans, date: time::Date) -> Transaction {
+    Transaction {
+        account: trans.account.to_string(),
+        objects: trans.objects.0.clone(),
+        amount: trans.amount.0,
+        date: Day(trans.date.unwrap_or(date)),
+        text: trans.text.clone(),
+        quantity: trans.quantity,
+        signature: trans.signature.clone(),
+    }
+}
+
+impl From<::sie4::Document> for Document {
+    fn from(doc: ::sie4::Document) -> Self {
+        let company = doc.company();
+        let mut balances = Vec::new();
+
+        for item in doc.items() {
+            let (kind, year, account, balance, quantity) = match item {
+                Item::Ib(ib) => ("IB", ib.year, ib.account, ib.balance, ib.quantity),
+                Item::Ub(ub) => ("UB", ub.year, ub.account, ub.balance, ub.quantity),
+                Item::Res(res) => ("RES", res.year, res.account, res.balance, res.quantity),
+                _ => continue,
+            };
+            balances.push(Balance {
+                kind,
+                year,
+                account: account.to_string(),
+                balance: balance.0,
+                quantity,
+            });
+        }
+
+        Self {
+            company_name: company.name,
+            org_no: company.org_no.map(|org_no| org_no.to_string()),
+            currency: doc.currency().code(),
+            accounts: Accounts::from_document(&doc)
+                .iter()
+                .map(|(no, name)| (no.to_string(), name.to_owned()))
+                .collect(),
+            fiscal_years: doc
+                .fiscal_years()
+                .iter()
+                .map(|year| FiscalYear {
+                    no: year.no,
+                    start: Day(year.start),
+                    end: Day(year.end),
+                })
+                .collect(),
+            vouchers: doc
+                .vouchers()
+                .map(|ver| Voucher {
+                    series: ver.series.clone(),
+                    no: ver.no,
+                    date: Day(ver.date),
+                    text: ver.text.clone(),
+                    reg_date: ver.reg_date.map(Day),
+                    sign: ver.sign.clone(),
+                    transactions: ver
+                        .counted(doc.include_removed())
+                        .map(|trans| transaction(trans, ver.date))
+                        .collect(),
+                })
+                .collect(),
+            balances,
+        }
+    }
+}
+
+/// Read a SIE4 file.
+///
+/// Raises `OSError` if the file can't be read and `ParseError` if it
+/// isn't valid.
+#[pyfunction]
+fn read_file(py: Python<'_>, path: PathBuf) -> PyResult<Document> {
+    let file = File::open(path)?;
+    let doc = py
+        .detach(|| ::sie4::Document::from_reader(BufReader::new(file)))
+        .map_err(|e| match e {
+            ::sie4::reader::Error::Io(e) => PyOSError::new_err(e.to_string()),
+            e => ParseError::new_err(e.to_string()),
+        })?;
+
+    Ok(doc.into())
+}
+
+/// Parse the contents of a SIE4 file, see `read_file`.
+#[pyfunction]
+fn parse(py: Python<'_>, data: &[u8]) -> PyResult<Document> {
+    let doc = py
+        .detach(|| ::sie4::Document::from_slice(data))
+        .map_err(|e| ParseError::new_err(e.to_string()))?;
+
+    Ok(doc.into())
+}
+
+#[pymodule(name = "sie4")]
+fn sie4_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(read_file, m)?)?;
+    m.add_function(wrap_pyfunction!(parse, m)?)?;
+    m.add_class::<Document>()?;
+    m.add_class::<Voucher>()?;
+    m.add_class::<Transaction>()?;
+    m.add_class::<Balance>()?;
+    m.add_class::<FiscalYear>()?;
+    m.add("ParseError", m.py().get_type::<ParseError>())?;
+    Ok(())
+}
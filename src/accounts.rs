@@ -0,0 +1,194 @@
+//! Names of accounts, looked up by number or by name.
+//!
+//! ```
+//! use sie4::{accounts::Accounts, Document};
+//!
+//! let doc = Document::from_reader(&b"#KONTO 2440 \"Leverant\x94rsskulder\"
+//! #KONTO 1930 \"F\x94retagskonto\"
+//! #KONTO 1910 \"Kassa\"
+//! "[..])
+//! .unwrap();
+//!
+//! let accounts = Accounts::from_document(&doc);
+//! assert_eq!(accounts.get(1930), Some("Företagskonto"));
+//! assert_eq!(accounts.by_name("Kassa").unwrap(), 1910);
+//! assert_eq!(accounts.search("skuld")[0], (2440.into(), "Leverantörsskulder"));
+//! assert_eq!(accounts.class(1).map(|(no, _)| no).collect::<Vec<_>>(), [1910, 1930]);
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{
+    item::{AccountNo, Konto},
+    Document,
+};
+
+/// The names of accounts (`#KONTO`) by number. Iteration is in the order
+/// of the numbers, which is that of the BAS chart of accounts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Accounts(BTreeMap<AccountNo, String>);
+
+/// How well `name` matches the lowercase `query`, with lower being
+/// better: `0` for a prefix of the name, `1` for a prefix of a later
+/// word, `2` for a part of a word and `3` for the characters of the query
+/// in order, e.g. `lvskld` for `Leverantörsskulder`.
+fn rank(name: &str, query: &str) -> Option<u8> {
+    let name = name.to_lowercase();
+
+    if name.starts_with(query) {
+        return Some(0);
+    }
+    if name
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word.starts_with(query))
+    {
+        return Some(1);
+    }
+    if name.contains(query) {
+        return Some(2);
+    }
+
+    let mut chars = name.chars();
+    query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .all(|c| chars.any(|n| n == c))
+        .then_some(3)
+}
+
+impl Accounts {
+    /// The accounts of `doc`, including those of its shared chart. Later
+    /// declarations of the same account replace earlier ones.
+    #[must_use]
+    pub fn from_document(doc: &Document) -> Self {
+        doc.accounts().collect()
+    }
+
+    #[must_use]
+    pub fn get(&self, no: impl Into<AccountNo>) -> Option<&str> {
+        self.0.get(&no.into()).map(String::as_str)
+    }
+
+    /// The first account named exactly `name`.
+    #[must_use]
+    pub fn by_name(&self, name: &str) -> Option<AccountNo> {
+        self.iter().find(|&(_, n)| n == name).map(|(no, _)| no)
+    }
+
+    /// The accounts whose names match `query`, ignoring case, with the
+    /// best matches first: names that start with the query, then names
+    /// with a word that does, names that contain it and last names that
+    /// contain its characters in order. Matches that are equally good
+    /// are in account order.
+    #[must_use]
+    pub fn search(&self, query: &str) -> Vec<(AccountNo, &str)> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches = self
+            .iter()
+            .filter_map(|(no, name)| Some((rank(name, &query)?, no, name)))
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|&(rank, no, _)| (rank, no));
+        matches
+            .into_iter()
+            .map(|(_, no, name)| (no, name))
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (AccountNo, &str)> {
+        self.0.iter().map(|(&no, name)| (no, name.as_str()))
+    }
+
+    /// The accounts of a class, see [`Accounts::classes`].
+    pub fn class(&self, class: u32) -> impl Iterator<Item = (AccountNo, &str)> {
+        self.iter().filter(move |(no, _)| no.class() == Some(class))
+    }
+
+    /// The accounts grouped by class, i.e. the first digit of their
+    /// numbers. In BAS, `1` is assets, `2` equity and liabilities, `3`
+    /// revenue, `4` to `7` costs and `8` financial items. Accounts
+    /// without a class, see [`AccountNo::class`], are left out.
+    #[must_use]
+    pub fn classes(&self) -> BTreeMap<u32, Vec<(AccountNo, &str)>> {
+        let mut classes = BTreeMap::<_, Vec<_>>::new();
+        for (no, name) in self.iter() {
+            if let Some(class) = no.class() {
+                classes.entry(class).or_default().push((no, name));
+            }
+        }
+        classes
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<'a> FromIterator<&'a Konto> for Accounts {
+    fn from_iter<T: IntoIterator<Item = &'a Konto>>(iter: T) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|konto| (konto.no, konto.name.clone()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search() {
+        let accounts = [
+            (1930, "Företagskonto"),
+            (2440, "Leverantörsskulder"),
+            (2640, "Ingående moms"),
+            (2611, "Utgående moms på försäljning, 25 %"),
+            (3001, "Försäljning inom Sverige, 25 % moms"),
+            (10, "Annat"),
+        ]
+        .into_iter()
+        .map(|(no, name)| Konto {
+            no: AccountNo::from(no),
+            name: name.to_owned(),
+        })
+        .collect::<Vec<_>>();
+        let accounts = accounts.iter().collect::<Accounts>();
+
+        let search = |query| {
+            accounts
+                .search(query)
+                .into_iter()
+                .filter_map(|(no, _)| no.number())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(search("MOMS"), [2611, 2640, 3001]);
+        assert_eq!(search("förs"), [3001, 2611, 1930]);
+        assert_eq!(search("lev skuld"), [2440]);
+        assert_eq!(search("ftgsk"), [1930]);
+        assert_eq!(search(" "), Vec::<u32>::new());
+
+        assert_eq!(accounts.by_name("Annat"), Some(10.into()));
+        assert_eq!(accounts.by_name("annat"), None);
+        assert_eq!(
+            accounts
+                .classes()
+                .into_iter()
+                .map(|(class, accounts)| (class, accounts.len()))
+                .collect::<Vec<_>>(),
+            [(1, 2), (2, 3), (3, 1)]
+        );
+    }
+}
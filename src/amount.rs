@@ -0,0 +1,269 @@
+//! Amounts of money in the books, see [`Amount`].
+
+use std::{
+    fmt,
+    iter::Sum,
+    ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+/// An amount in the books, e.g. of a transaction (`#TRANS`) or a balance
+/// (`#IB`). Debits are positive and credits negative, as in SIE.
+///
+/// Amounts are displayed as in SIE, or with `{:#}` in the Swedish
+/// format, with spaces between the thousands and a decimal comma.
+///
+/// ```
+/// use rust_decimal_macros::dec;
+/// use sie4::amount::Amount;
+///
+/// let amount = Amount::from(dec!(-1234.56));
+/// assert!(amount.is_credit());
+/// assert_eq!(amount.abs(), dec!(1234.56));
+/// assert_eq!((amount.debit(), amount.credit()), (Amount::ZERO, dec!(1234.56).into()));
+/// assert_eq!(amount.to_string(), "-1234.56");
+/// assert_eq!(format!("{amount:#}"), "-1 234,56");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct Amount(pub Decimal);
+
+impl Amount {
+    pub const ZERO: Self = Self(Decimal::ZERO);
+
+    /// Whether the amount is debited, i.e. positive.
+    #[must_use]
+    pub fn is_debit(self) -> bool {
+        self.0.is_sign_positive() && !self.0.is_zero()
+    }
+
+    /// Whether the amount is credited, i.e. negative.
+    #[must_use]
+    pub fn is_credit(self) -> bool {
+        self.0.is_sign_negative() && !self.0.is_zero()
+    }
+
+    #[must_use]
+    pub fn is_zero(self) -> bool {
+        self.0.is_zero()
+    }
+
+    #[must_use]
+    pub fn abs(self) -> Self {
+        Self(self.0.abs())
+    }
+
+    /// The amount in a debit column: the amount if debited, otherwise
+    /// zero.
+    #[must_use]
+    pub fn debit(self) -> Self {
+        if self.is_debit() {
+            self
+        } else {
+            Self::ZERO
+        }
+    }
+
+    /// The amount in a credit column, which is positive: the negated
+    /// amount if credited, otherwise zero.
+    #[must_use]
+    pub fn credit(self) -> Self {
+        if self.is_credit() {
+            -self
+        } else {
+            Self::ZERO
+        }
+    }
+
+    /// The amount of a debit and a credit column, the inverse of
+    /// [`Amount::debit`] and [`Amount::credit`].
+    #[must_use]
+    pub fn from_columns(debit: Self, credit: Self) -> Self {
+        debit - credit
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !f.alternate() {
+            return fmt::Display::fmt(&self.0, f);
+        }
+
+        let digits = self.0.abs().to_string();
+        let (int, fraction) = digits.split_once('.').unwrap_or((&digits, ""));
+        let mut s = String::new();
+        if self.is_credit() {
+            s.push('-');
+        }
+        for (i, digit) in int.chars().enumerate() {
+            if i > 0 && (int.len() - i) % 3 == 0 {
+                s.push(' ');
+            }
+            s.push(digit);
+        }
+        if !fraction.is_empty() {
+            s.push(',');
+            s.push_str(fraction);
+        }
+        f.pad(&s)
+    }
+}
+
+/// As in SIE, e.g. `-1234.56`.
+impl FromStr for Amount {
+    type Err = rust_decimal::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+impl From<Decimal> for Amount {
+    fn from(amount: Decimal) -> Self {
+        Self(amount)
+    }
+}
+
+impl From<Amount> for Decimal {
+    fn from(amount: Amount) -> Self {
+        amount.0
+    }
+}
+
+impl PartialEq<Decimal> for Amount {
+    fn eq(&self, other: &Decimal) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<Amount> for Decimal {
+    fn eq(&self, other: &Amount) -> bool {
+        *self == other.0
+    }
+}
+
+impl Neg for Amount {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(-self.0)
+    }
+}
+
+impl Add for Amount {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Amount {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl AddAssign for Amount {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl SubAssign for Amount {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0;
+    }
+}
+
+/// Scaling, e.g. by an exchange rate.
+impl Mul<Decimal> for Amount {
+    type Output = Self;
+
+    fn mul(self, rhs: Decimal) -> Self {
+        Self(self.0 * rhs)
+    }
+}
+
+impl MulAssign<Decimal> for Amount {
+    fn mul_assign(&mut self, rhs: Decimal) {
+        self.0 *= rhs;
+    }
+}
+
+impl Sum for Amount {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        Self(iter.map(|amount| amount.0).sum())
+    }
+}
+
+impl<'a> Sum<&'a Amount> for Amount {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+/// Summing amounts into a decimal, e.g. of a report.
+impl AddAssign<Amount> for Decimal {
+    fn add_assign(&mut self, rhs: Amount) {
+        *self += rhs.0;
+    }
+}
+
+impl SubAssign<Amount> for Decimal {
+    fn sub_assign(&mut self, rhs: Amount) {
+        *self -= rhs.0;
+    }
+}
+
+impl Sum<Amount> for Decimal {
+    fn sum<I: Iterator<Item = Amount>>(iter: I) -> Self {
+        iter.map(|amount| amount.0).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn swedish() {
+        for (amount, formatted) in [
+            (dec!(0), "0"),
+            (dec!(-0.00), "0,00"),
+            (dec!(12.5), "12,5"),
+            (dec!(123.00), "123,00"),
+            (dec!(1234), "1 234"),
+            (dec!(-123456.78), "-123 456,78"),
+            (dec!(1234567.89), "1 234 567,89"),
+        ] {
+            assert_eq!(format!("{:#}", Amount(amount)), formatted);
+        }
+        assert_eq!(format!("{:>10}", Amount(dec!(1234))), "      1234");
+        assert_eq!(format!("{:>#10}", Amount(dec!(1234))), "     1 234");
+    }
+
+    #[test]
+    fn signs() {
+        let zero = Amount(dec!(-0.00));
+        assert!(!zero.is_debit() && !zero.is_credit());
+        assert_eq!((zero.debit(), zero.credit()), (Amount::ZERO, Amount::ZERO));
+
+        let debit = Amount(dec!(100));
+        assert_eq!((debit.debit(), debit.credit()), (debit, Amount::ZERO));
+        assert_eq!(Amount::from_columns(debit.debit(), debit.credit()), debit);
+        let credit = -debit;
+        assert_eq!(
+            Amount::from_columns(credit.debit(), credit.credit()),
+            credit
+        );
+        assert_eq!([debit, credit, debit].iter().sum::<Amount>(), debit);
+    }
+}
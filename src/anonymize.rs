@@ -0,0 +1,244 @@
+//! Removal of identifying information, e.g. to share files in bug reports.
+//!
+//! ```
+//! use rust_decimal_macros::dec;
+//! use sie4::{anonymize::Anonymizer, item::Item, Document};
+//!
+//! let doc = Document::from_reader(&b"#FNAMN \"R\x84ksm\x94rg\x86s AB\"
+//! #KONTO 1930 \"Nordea 1234 56 78901\"
+//! #VER A 1 20230102 \"Rent Storgatan 1\"
+//! {
+//!     #TRANS 1930 {} -12000.00
+//!     #TRANS 5010 {} 12000.00
+//! }
+//! "[..])
+//! .unwrap();
+//!
+//! let anonymizer = Anonymizer {
+//!     scale_amounts: Some(dec!(0.5)),
+//!     ..Anonymizer::default()
+//! };
+//! let doc = anonymizer.anonymize(doc);
+//! let Item::FNamn(fnamn) = &doc.items()[0] else { panic!() };
+//! assert_eq!(fnamn.name, "Company");
+//! assert_eq!(doc.account(1930).unwrap().name, "Account 1930");
+//!
+//! let ver = doc.vouchers().next().unwrap();
+//! assert_eq!(ver.text, None);
+//! assert_eq!(ver.transactions.0[0].trans().amount, dec!(-6000.0000));
+//! ```
+//!
+//! To compare anonymized files, texts can be hashed instead of removed:
+//!
+//! ```
+//! use sie4::{anonymize::Anonymizer, Document};
+//!
+//! let data = b"#VER A 1 20230102 \"Rent\" 20230103 \"AA\"
+//! {
+//!     #TRANS 1930 {} -12000.00 20230102 \"Rent\"
+//! }
+//! ";
+//! let anonymizer = Anonymizer {
+//!     hash_texts: Some(1234),
+//!     ..Anonymizer::default()
+//! };
+//! let doc = anonymizer.anonymize(Document::from_reader(&data[..]).unwrap());
+//!
+//! let ver = doc.vouchers().next().unwrap();
+//! assert_ne!(ver.text.as_deref(), Some("Rent"));
+//! assert_eq!(ver.text, ver.transactions.0[0].trans().text);
+//! ```
+
+use rust_decimal::Decimal;
+
+use crate::{
+    amount::Amount,
+    custom::Value,
+    item::{Trans, Ver},
+    tags::Tags,
+    Document, Item,
+};
+
+/// Replaces names, addresses and free text with placeholders and drops
+/// `#KSUMMA`, since the checksum would no longer be valid. Account
+/// numbers, dates, dimensions and the structure of the file are kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Anonymizer {
+    /// Keep the names of accounts, which usually come from a standard
+    /// chart of accounts.
+    pub keep_accounts: bool,
+    /// Multiply all amounts by a factor. The multiplication is exact, so
+    /// vouchers and balances still add up, but amounts may get more
+    /// decimals.
+    pub scale_amounts: Option<Decimal>,
+    /// Replace the texts and signatures of vouchers and transactions, and
+    /// the text fields of [custom items](crate::custom), with hashes keyed
+    /// by this number instead of removing them, so that equal texts stay
+    /// equal. A hash is the first 64 bits of the keyed BLAKE3 hash of the
+    /// text, so it is the same on all platforms and versions of the
+    /// library.
+    pub hash_texts: Option<u64>,
+}
+
+impl Anonymizer {
+    #[must_use]
+    pub fn anonymize(&self, doc: Document) -> Document {
+        doc.into_items()
+            .into_iter()
+            .filter_map(|item| self.anonymize_item(item))
+            .collect()
+    }
+
+    fn scale(&self, amount: &mut Amount) {
+        if let Some(factor) = self.scale_amounts {
+            *amount *= factor;
+        }
+    }
+
+    /// The hash of `text`, or `None` if texts are removed.
+    fn text(&self, text: &str) -> Option<String> {
+        let key = blake3::derive_key("sie4 anonymize text", &self.hash_texts?.to_le_bytes());
+        let hash = blake3::keyed_hash(&key, text.as_bytes());
+        let mut prefix = [0; 8];
+        prefix.copy_from_slice(&hash.as_bytes()[..8]);
+        Some(format!("{:016x}", u64::from_be_bytes(prefix)))
+    }
+
+    fn anonymize_text(&self, text: &mut Option<String>) {
+        *text = text.as_deref().and_then(|text| self.text(text));
+    }
+
+    fn anonymize_item(&self, mut item: Item) -> Option<Item> {
+        match &mut item {
+            Item::Adress(adress) => {
+                "Contact".clone_into(&mut adress.contact);
+                "Street 1".clone_into(&mut adress.distribution_address);
+                "123 45 City".clone_into(&mut adress.postal_address);
+                "000-000 00 00".clone_into(&mut adress.phone);
+            }
+            Item::FNamn(fnamn) => "Company".clone_into(&mut fnamn.name),
+            Item::Custom(custom) => {
+                for value in &mut custom.fields {
+                    match value {
+                        Value::Text(text) => *text = self.text(text).unwrap_or_default(),
+                        Value::List(values) => {
+                            for text in values {
+                                *text = self.text(text).unwrap_or_default();
+                            }
+                        }
+                    }
+                }
+            }
+            Item::Gen(gen) => self.anonymize_text(&mut gen.signature),
+            Item::Ib(ib) => self.scale(&mut ib.balance),
+            Item::Konto(konto) if !self.keep_accounts => {
+                konto.name = format!("Account {}", konto.no);
+            }
+            Item::Ksumma(_) | Item::Prosa(_) => return None,
+            Item::Oib(oib) => self.scale(&mut oib.balance),
+            Item::Oub(oub) => self.scale(&mut oub.balance),
+            Item::Orgnr(orgnr) => orgnr.org_no = "555555-5555".parse().expect("valid"),
+            Item::PBudget(pbudget) => self.scale(&mut pbudget.balance),
+            Item::PSaldo(psaldo) => self.scale(&mut psaldo.balance),
+            Item::Res(res) => self.scale(&mut res.balance),
+            Item::Trans(trans) => self.anonymize_trans(trans),
+            Item::Ub(ub) => self.scale(&mut ub.balance),
+            Item::Ver(ver) => self.anonymize_ver(ver),
+            _ => (),
+        }
+
+        Some(item)
+    }
+
+    fn anonymize_ver(&self, ver: &mut Ver) {
+        self.anonymize_text(&mut ver.text);
+        self.anonymize_text(&mut ver.sign);
+        ver.tags = Tags::default();
+
+        for line in &mut ver.transactions.0 {
+            self.anonymize_trans(line.trans_mut());
+        }
+    }
+
+    fn anonymize_trans(&self, trans: &mut Trans) {
+        self.scale(&mut trans.amount);
+        self.anonymize_text(&mut trans.text);
+        self.anonymize_text(&mut trans.signature);
+        trans.tags = Tags::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::validate::validate;
+
+    #[test]
+    fn still_valid() {
+        let data = b"#FLAGGA 0
+#KSUMMA
+#PROGRAM \"Vi iMproved\" 9.0
+#FORMAT PC8
+#GEN 20230102 \"Anna Andersson\"
+#SIETYP 4
+#FNAMN \"R\x84ksm\x94rg\x86s AB\"
+#ORGNR 556677-8899
+#ADRESS \"Anna\" \"Storgatan 1\" \"123 45 Stockholm\" \"08-123 456\"
+#RAR 0 20230101 20231231
+#KONTO 1930 \"Bank\"
+#KONTO 5010 \"Lokalhyra\"
+#IB 0 1930 15000.00
+#UB 0 1930 3000.00
+#VER A 1 20230102 \"Rent\" 20230103 \"AA\"
+{
+    #TRANS 1930 {} -12000.00 20230102 \"Storgatan\"
+    #TRANS 5010 {} 12000.00
+}
+#KSUMMA 1234567890
+";
+        let doc = || Document::from_reader(&data[..]).unwrap();
+        let anonymized = Anonymizer {
+            keep_accounts: true,
+            scale_amounts: Some(Decimal::new(137, 2)),
+            hash_texts: None,
+        }
+        .anonymize(doc());
+
+        assert!(validate(&anonymized).is_valid());
+        assert_eq!(anonymized.account(5010).unwrap().name, "Lokalhyra");
+        assert!(!anonymized
+            .items()
+            .iter()
+            .any(|item| matches!(item, Item::Ksumma(_))));
+
+        let debug = format!("{anonymized:?}");
+        for secret in ["Anna", "Storgatan", "Stockholm", "556677", "AA"] {
+            assert!(!debug.contains(secret), "{secret} was not removed");
+        }
+
+        let hashed = Anonymizer {
+            hash_texts: Some(1),
+            ..Anonymizer::default()
+        };
+        let anonymized = hashed.anonymize(doc());
+        assert!(validate(&anonymized).is_valid());
+        assert_eq!(anonymized, hashed.anonymize(doc()));
+        let debug = format!("{anonymized:?}");
+        for secret in ["Anna", "Storgatan", "Stockholm", "556677", "AA"] {
+            assert!(!debug.contains(secret), "{secret} was not hashed");
+        }
+
+        let ver = anonymized.vouchers().next().unwrap();
+        let rekeyed = Anonymizer {
+            hash_texts: Some(2),
+            ..Anonymizer::default()
+        }
+        .anonymize(doc());
+        assert!(ver.text.is_some());
+        assert_ne!(ver.text, rekeyed.vouchers().next().unwrap().text);
+
+        // the hashes don't change between versions
+        assert_eq!(hashed.text("Rent").unwrap(), "0ab2d58343e1323a");
+    }
+}
@@ -0,0 +1,265 @@
+//! Budgets (`#PBUDGET`) compared with the outcome.
+//!
+//! ```
+//! use rust_decimal_macros::dec;
+//! use sie4::{
+//!     budget::{alerts, Budget, Thresholds},
+//!     Document,
+//! };
+//!
+//! let doc = Document::from_reader(&b"#PBUDGET 0 202301 5010 {} 10000.00
+//! #PBUDGET 0 202302 5010 {} 10000.00
+//! #VER A 1 20230102 \"Rent\"
+//! {
+//!     #TRANS 1930 {} -12000.00
+//!     #TRANS 5010 {} 12000.00
+//! }
+//! "[..])
+//! .unwrap();
+//!
+//! let budget = Budget::from_document(&doc, 0);
+//! let alerts = alerts(&doc, &budget, Thresholds::default());
+//! assert_eq!(alerts.len(), 1);
+//! assert_eq!(alerts[0].account, 5010);
+//! assert_eq!(alerts[0].amount, dec!(2000.00));
+//! assert_eq!(alerts[0].percent, Some(dec!(20)));
+//! ```
+
+use std::{collections::BTreeMap, ops::RangeBounds};
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::{
+    item::{AccountNo, Period},
+    Document,
+};
+
+/// The budget of each account and period, for a single fiscal year.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Budget {
+    amounts: BTreeMap<(AccountNo, Period), Decimal>,
+}
+
+impl Budget {
+    /// The budgets of whole accounts in the fiscal year `year`, where `0`
+    /// is the current year. Budgets of objects are left out.
+    #[must_use]
+    pub fn from_document(doc: &Document, year: i32) -> Self {
+        let mut budget = Self::default();
+
+        for pbudget in doc.period_budgets() {
+            if pbudget.year == year && pbudget.objects.0.is_empty() {
+                budget.insert(pbudget.account, pbudget.period, pbudget.balance.0);
+            }
+        }
+
+        budget
+    }
+
+    /// Add `amount` to the budget of `account` for `period`, e.g. to
+    /// read budgets kept outside of the file.
+    pub fn insert(&mut self, account: impl Into<AccountNo>, period: Period, amount: Decimal) {
+        *self.amounts.entry((account.into(), period)).or_default() += amount;
+    }
+
+    /// Only the budgets of the periods in `periods`.
+    #[must_use]
+    pub fn periods(&self, periods: impl RangeBounds<Period>) -> Self {
+        Self {
+            amounts: self
+                .amounts
+                .iter()
+                .filter(|((_, period), _)| periods.contains(period))
+                .map(|(&key, &amount)| (key, amount))
+                .collect(),
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, account: impl Into<AccountNo>, period: Period) -> Option<Decimal> {
+        self.amounts.get(&(account.into(), period)).copied()
+    }
+
+    /// The budgets by account and period, in that order.
+    pub fn iter(&self) -> impl Iterator<Item = (AccountNo, Period, Decimal)> + '_ {
+        self.amounts
+            .iter()
+            .map(|(&(account, period), &amount)| (account, period, amount))
+    }
+}
+
+/// How much the outcome may exceed the budget without an [`Alert`]. Both
+/// thresholds have to be exceeded; the default alerts on any excess.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Thresholds {
+    pub amount: Decimal,
+    /// In percent of the budget. Ignored if the budget is zero.
+    pub percent: Decimal,
+}
+
+/// An account whose outcome exceeded its budget for a period.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Alert {
+    pub account: AccountNo,
+    pub period: Period,
+    pub budget: Decimal,
+    pub actual: Decimal,
+    /// How much the outcome exceeded the budget, always positive.
+    pub amount: Decimal,
+    /// [`Alert::amount`] in percent of the budget, rounded to two
+    /// decimals, unless the budget is zero.
+    pub percent: Option<Decimal>,
+}
+
+/// Compare the budget with the transactions of each account and period,
+/// as corrected (see [`crate::item::Ver::corrected`]). This is the
+/// outcome that budgets of result accounts are written for.
+///
+/// The outcome exceeds the budget if it is further from zero in the
+/// direction of the budget, so larger costs as well as larger revenues
+/// (which are negative) are reported. A budget of zero is exceeded in
+/// either direction.
+#[must_use]
+pub fn alerts(doc: &Document, budget: &Budget, thresholds: Thresholds) -> Vec<Alert> {
+    let mut actual = BTreeMap::<(AccountNo, Period), Decimal>::new();
+
+    for ver in doc.vouchers() {
+        for trans in ver.counted(doc.include_removed()) {
+            let date = doc.date_policy().date(ver, trans);
+            let period = Period {
+                year: date.year(),
+                month: date.month(),
+            };
+            if budget.get(trans.account, period).is_some() {
+                *actual.entry((trans.account, period)).or_default() += trans.amount;
+            }
+        }
+    }
+
+    budget
+        .iter()
+        .filter_map(|(account, period, budget)| {
+            let actual = actual.get(&(account, period)).copied().unwrap_or_default();
+            let amount = if budget.is_sign_negative() {
+                budget - actual
+            } else if budget.is_zero() {
+                actual.abs()
+            } else {
+                actual - budget
+            };
+            let percent = (!budget.is_zero())
+                .then(|| (amount / budget.abs() * Decimal::ONE_HUNDRED).round_dp(2));
+
+            let exceeded = amount > Decimal::ZERO
+                && amount > thresholds.amount
+                && percent.is_none_or(|percent| percent > thresholds.percent);
+
+            exceeded.then_some(Alert {
+                account,
+                period,
+                budget,
+                actual,
+                amount,
+                percent,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+    use time::Month;
+
+    use super::*;
+
+    #[test]
+    fn thresholds() {
+        let doc = Document::from_reader(
+            &b"#PBUDGET 0 202301 3010 {} -1000.00
+#PBUDGET 0 202301 5010 {} 100.00
+#PBUDGET 0 202301 5010 {1 Z1} 1.00
+#PBUDGET 0 202301 6110 {} 0
+#PBUDGET -1 202201 5010 {} 1.00
+#VER A 1 20230102
+{
+    #TRANS 1930 {} 1095.00
+    #TRANS 3010 {} -1200.00
+    #TRANS 5010 {} 105.00 20230131
+}
+#VER A 2 20230201
+{
+    #TRANS 1930 {} -100.00
+    #TRANS 6110 {} 100.00
+    #TRANS 5010 {} 100.00 20230201
+    #TRANS 5010 {} -100.00 20230201
+}
+"[..],
+        )
+        .unwrap();
+        let budget = Budget::from_document(&doc, 0);
+        assert_eq!(budget.iter().count(), 3);
+
+        let summary = |thresholds| {
+            alerts(&doc, &budget, thresholds)
+                .into_iter()
+                .map(|alert| (alert.account.to_string(), alert.amount, alert.percent))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            summary(Thresholds::default()),
+            [
+                ("3010".to_owned(), dec!(200.00), Some(dec!(20))),
+                ("5010".to_owned(), dec!(5.00), Some(dec!(5))),
+            ]
+        );
+        assert_eq!(
+            summary(Thresholds {
+                amount: dec!(10),
+                percent: Decimal::ZERO,
+            }),
+            [("3010".to_owned(), dec!(200.00), Some(dec!(20)))]
+        );
+        assert_eq!(
+            summary(Thresholds {
+                amount: Decimal::ZERO,
+                percent: dec!(20),
+            }),
+            []
+        );
+
+        let alert = &alerts(&doc, &budget, Thresholds::default())[0];
+        assert_eq!(
+            alert.period,
+            Period {
+                year: 2023,
+                month: Month::January
+            }
+        );
+        assert_eq!(alert.actual, dec!(-1200.00));
+    }
+
+    #[test]
+    fn periods() {
+        let period = |s: &str| s.parse::<Period>().unwrap();
+        let mut budget = Budget::default();
+        budget.insert(5010, period("202301"), dec!(100));
+        budget.insert(5010, period("202301"), dec!(50));
+        budget.insert(5010, period("202302"), dec!(100));
+        budget.insert(6110, period("202303"), dec!(10));
+
+        assert_eq!(budget.get(5010, period("202301")), Some(dec!(150)));
+        let later = budget.periods(period("202302")..);
+        assert_eq!(
+            later.iter().collect::<Vec<_>>(),
+            [
+                (5010.into(), period("202302"), dec!(100)),
+                (6110.into(), period("202303"), dec!(10))
+            ]
+        );
+        assert_eq!(budget.periods(..period("202302")).iter().count(), 1);
+        assert!("2023-01".parse::<Period>().is_err());
+    }
+}
@@ -0,0 +1,201 @@
+//! Accounts and dimensions shared between documents.
+//!
+//! The yearly files of a company usually declare the same accounts. A
+//! [`Chart`] collects the declarations once, and documents that share it
+//! drop their own copies.
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use sie4::{chart::Chart, Document};
+//!
+//! let mut y2022 = Document::from_reader(&b"#KONTO 1930 \"Bank\"\n"[..]).unwrap();
+//! let mut y2023 = Document::from_reader(&b"#KONTO 1930 \"Bank\"\n#KONTO 2440 \"Skulder\"\n"[..]).unwrap();
+//!
+//! let mut chart = Chart::default();
+//! chart.extend(&y2022).unwrap();
+//! chart.extend(&y2023).unwrap();
+//!
+//! let chart = Arc::new(chart);
+//! y2022.share_chart(Arc::clone(&chart)).unwrap();
+//! y2023.share_chart(Arc::clone(&chart)).unwrap();
+//! assert_eq!(y2022.account(2440).unwrap().name, "Skulder");
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{
+    item::{AccountNo, Dim, Konto},
+    Document, Item,
+};
+
+/// Accounts (`#KONTO`) and dimensions (`#DIM`) by number.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Chart {
+    accounts: BTreeMap<AccountNo, Konto>,
+    dimensions: BTreeMap<u32, Dim>,
+}
+
+/// A declaration of a document that doesn't agree with a [`Chart`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Conflict {
+    #[error("account {no} is named {chart:?} in the chart but {document:?} in the document")]
+    Account {
+        no: AccountNo,
+        chart: String,
+        document: String,
+    },
+    #[error("dimension {no} is named {chart:?} in the chart but {document:?} in the document")]
+    Dimension {
+        no: u32,
+        chart: String,
+        document: String,
+    },
+    #[error("account {0} is not in the chart")]
+    MissingAccount(AccountNo),
+    #[error("dimension {0} is not in the chart")]
+    MissingDimension(u32),
+}
+
+impl Chart {
+    /// Add the declarations of `doc` that aren't in the chart yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, without adding anything, if `doc` declares an
+    /// account or dimension with another name than the chart.
+    pub fn extend(&mut self, doc: &Document) -> Result<(), Conflict> {
+        self.check(doc, false)?;
+
+        for item in doc.items() {
+            match item {
+                Item::Konto(konto) => {
+                    self.accounts.entry(konto.no).or_insert_with(|| Konto {
+                        no: konto.no,
+                        name: konto.name.clone(),
+                    });
+                }
+                Item::Dim(dim) => {
+                    self.dimensions.entry(dim.no).or_insert_with(|| Dim {
+                        no: dim.no,
+                        name: dim.name.clone(),
+                    });
+                }
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that every declaration of `doc` is in the chart.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first declaration that is missing or named
+    /// differently.
+    pub fn verify(&self, doc: &Document) -> Result<(), Conflict> {
+        self.check(doc, true)
+    }
+
+    fn check(&self, doc: &Document, complete: bool) -> Result<(), Conflict> {
+        for item in doc.items() {
+            match item {
+                Item::Konto(konto) => match self.accounts.get(&konto.no) {
+                    Some(chart) if chart.name != konto.name => {
+                        return Err(Conflict::Account {
+                            no: konto.no,
+                            chart: chart.name.clone(),
+                            document: konto.name.clone(),
+                        });
+                    }
+                    None if complete => return Err(Conflict::MissingAccount(konto.no)),
+                    _ => (),
+                },
+                Item::Dim(dim) => match self.dimensions.get(&dim.no) {
+                    Some(chart) if chart.name != dim.name => {
+                        return Err(Conflict::Dimension {
+                            no: dim.no,
+                            chart: chart.name.clone(),
+                            document: dim.name.clone(),
+                        });
+                    }
+                    None if complete => return Err(Conflict::MissingDimension(dim.no)),
+                    _ => (),
+                },
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn accounts(&self) -> impl Iterator<Item = &Konto> {
+        self.accounts.values()
+    }
+
+    #[must_use]
+    pub fn account(&self, no: impl Into<AccountNo>) -> Option<&Konto> {
+        self.accounts.get(&no.into())
+    }
+
+    pub fn dimensions(&self) -> impl Iterator<Item = &Dim> {
+        self.dimensions.values()
+    }
+
+    #[must_use]
+    pub fn dimension(&self, no: u32) -> Option<&Dim> {
+        self.dimensions.get(&no)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn doc(data: &[u8]) -> Document {
+        Document::from_reader(data).unwrap()
+    }
+
+    #[test]
+    fn conflicts() {
+        let mut chart = Chart::default();
+        chart
+            .extend(&doc(
+                b"#KONTO 1930 \"Bank\"\n#DIM 1 \"Kostnadsst\x84lle\"\n",
+            ))
+            .unwrap();
+
+        assert_eq!(
+            chart.extend(&doc(b"#KONTO 1510 \"Kunder\"\n#KONTO 1930 \"Kassa\"\n")),
+            Err(Conflict::Account {
+                no: 1930.into(),
+                chart: "Bank".to_owned(),
+                document: "Kassa".to_owned(),
+            })
+        );
+        // nothing is added on conflicts
+        assert!(chart.account(1510).is_none());
+
+        let mut other = doc(b"#DIM 1 \"Kostnadsst\x84lle\"\n#DIM 6 \"Projekt\"\n");
+        assert_eq!(chart.verify(&other), Err(Conflict::MissingDimension(6)));
+        assert!(other.share_chart(Arc::new(chart)).is_err());
+        assert_eq!(other.dimensions().count(), 2);
+    }
+
+    #[test]
+    fn shared() {
+        let mut doc = doc(b"#FLAGGA 0\n#KONTO 1930 \"Bank\"\n#DIM 1 \"Kostnadsst\x84lle\"\n");
+        let mut chart = Chart::default();
+        chart.extend(&doc).unwrap();
+        doc.share_chart(Arc::new(chart)).unwrap();
+
+        assert_eq!(doc.items().len(), 1);
+        assert_eq!(doc.account(1930).unwrap().name, "Bank");
+        assert_eq!(doc.dimensions().next().unwrap().name, "Kostnadsställe");
+    }
+}
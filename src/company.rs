@@ -0,0 +1,342 @@
+//! The company whose books a file contains.
+//!
+//! ```
+//! use sie4::Document;
+//!
+//! let doc = Document::from_reader(&b"#FNAMN \"R\x84ksm\x94rg\x86s AB\"
+//! #ORGNR 5566778899
+//! #FTYP AB
+//! #BKOD 62010
+//! "[..])
+//! .unwrap();
+//!
+//! let company = doc.company();
+//! assert_eq!(company.name.as_deref(), Some("Räksmörgås AB"));
+//! assert_eq!(company.org_no.unwrap().to_string(), "556677-8899");
+//! assert_eq!(company.to_string(), "Räksmörgås AB (AB), 556677-8899, SNI 62010");
+//! ```
+
+use std::{collections::BTreeMap, fmt, io::Read, str::FromStr};
+
+use serde::Serialize;
+
+use crate::{item::Adress, reader, sni::SniCode, Document, Item, Reader};
+
+/// A Swedish organisationsnummer, or the personnummer of a sole trader,
+/// with a valid check digit. It is written as `NNNNNN-NNNN`, optionally
+/// preceded by the century as in `16556677-8899`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OrgNr {
+    century: Option<u8>,
+    /// The ten digits.
+    digits: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid organisation number `{0}`")]
+pub struct InvalidOrgNr(String);
+
+/// The check digit (Luhn algorithm) of the nine digits before it.
+fn check_digit(digits: u64) -> u64 {
+    let sum = (0..9).fold(0, |sum, i| {
+        let digit = digits / 10u64.pow(8 - i) % 10;
+        let product = if i % 2 == 0 { digit * 2 } else { digit };
+        sum + product / 10 + product % 10
+    });
+    (10 - sum % 10) % 10
+}
+
+impl OrgNr {
+    /// The century of the personnummer of a sole trader, e.g. `19`, or
+    /// `16` for organisations, if it was written.
+    #[must_use]
+    pub fn century(self) -> Option<u8> {
+        self.century
+    }
+
+    /// Whether this is the personnummer of a sole trader rather than the
+    /// number of a legal entity, whose third digit is at least `2`.
+    #[must_use]
+    pub fn is_personal(self) -> bool {
+        self.digits / 10u64.pow(7) % 10 < 2
+    }
+
+    /// The first digit, which tells the kind of legal entity, e.g. `5`
+    /// for limited companies (aktiebolag) and `9` for partnerships.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn group(self) -> u8 {
+        // a single digit
+        (self.digits / 10u64.pow(9)) as u8
+    }
+}
+
+/// Ten digits, optionally with a hyphen before the last four and with
+/// the century first.
+impl FromStr for OrgNr {
+    type Err = InvalidOrgNr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidOrgNr(s.to_owned());
+        // the hyphen is found by byte index
+        if !s.is_ascii() {
+            return Err(invalid());
+        }
+
+        let digits = match s.len().checked_sub(5).map(|i| s.split_at(i)) {
+            Some((first, last)) if last.starts_with('-') => format!("{first}{}", &last[1..]),
+            _ => s.to_owned(),
+        };
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let (century, digits) = match digits.len() {
+            10 => (None, digits.as_str()),
+            12 => {
+                let (century, digits) = digits.split_at(2);
+                (Some(century.parse().map_err(|_| invalid())?), digits)
+            }
+            _ => return Err(invalid()),
+        };
+
+        let digits = digits.parse().map_err(|_| invalid())?;
+        if check_digit(digits / 10) != digits % 10 {
+            return Err(invalid());
+        }
+        Ok(Self { century, digits })
+    }
+}
+
+impl fmt::Display for OrgNr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(century) = self.century {
+            write!(f, "{century:02}")?;
+        }
+        write!(f, "{:06}-{:04}", self.digits / 10_000, self.digits % 10_000)
+    }
+}
+
+impl Serialize for OrgNr {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// What the identification items say about the company, see
+/// [`Document::company`].
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CompanyInfo {
+    /// `#FNAMN`
+    pub name: Option<String>,
+    /// `#ORGNR`
+    pub org_no: Option<OrgNr>,
+    /// `#ADRESS`
+    pub address: Option<Adress>,
+    /// `#BKOD`
+    pub sni: Option<SniCode>,
+    /// The type of company (`#FTYP`), e.g. `AB` for a limited company.
+    pub kind: Option<String>,
+    /// `#FNR`
+    pub id: Option<String>,
+}
+
+impl CompanyInfo {
+    /// What tells the company apart from others: the `#FNR`, or else the
+    /// organisation number or the name. Empty if none of them is known.
+    #[must_use]
+    pub fn key(&self) -> String {
+        self.id
+            .clone()
+            .or_else(|| self.org_no.map(|org_no| org_no.to_string()))
+            .or_else(|| self.name.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// The name, kind, number and industry, as far as they are known, e.g.
+/// `Räksmörgås AB (AB), 556677-8899, SNI 62010`.
+impl fmt::Display for CompanyInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        match (&self.name, &self.kind) {
+            (Some(name), Some(kind)) => parts.push(format!("{name} ({kind})")),
+            (Some(name), None) => parts.push(name.clone()),
+            (None, Some(kind)) => parts.push(format!("({kind})")),
+            (None, None) => (),
+        }
+        parts.extend(self.org_no.map(|org_no| org_no.to_string()));
+        parts.extend(self.sni.map(|sni| format!("SNI {sni}")));
+
+        f.write_str(&parts.join(", "))
+    }
+}
+
+impl Document {
+    /// The company, from the first of each of the identification items.
+    #[must_use]
+    pub fn company(&self) -> CompanyInfo {
+        let mut company = CompanyInfo::default();
+
+        for item in self.items() {
+            match item {
+                Item::FNamn(fnamn) => {
+                    company.name.get_or_insert_with(|| fnamn.name.clone());
+                }
+                Item::Orgnr(orgnr) => {
+                    company.org_no.get_or_insert(orgnr.org_no);
+                }
+                Item::Adress(adress) => {
+                    company.address.get_or_insert_with(|| Adress {
+                        contact: adress.contact.clone(),
+                        distribution_address: adress.distribution_address.clone(),
+                        postal_address: adress.postal_address.clone(),
+                        phone: adress.phone.clone(),
+                    });
+                }
+                Item::BKod(bkod) => {
+                    company.sni.get_or_insert(bkod.sni);
+                }
+                Item::FTyp(ftyp) => {
+                    company.kind.get_or_insert_with(|| ftyp.kind.clone());
+                }
+                Item::Fnr(fnr) => {
+                    company.id.get_or_insert_with(|| fnr.id.clone());
+                }
+                _ => (),
+            }
+        }
+
+        company
+    }
+
+    /// Read a file with the books of several companies in sequence, each
+    /// starting with `#FLAGGA` or, after the balances of the previous
+    /// one, with `#FNR`. The documents are keyed by
+    /// [`CompanyInfo::key`], and those of the same company are joined.
+    ///
+    /// ```
+    /// use sie4::Document;
+    ///
+    /// let data = b"#FLAGGA 0
+    /// #FNR 1
+    /// #FNAMN \"First AB\"
+    /// #UB 0 1930 100.00
+    /// #FNR 2
+    /// #FNAMN \"Second AB\"
+    /// #UB 0 1930 200.00
+    /// ";
+    /// let companies = Document::by_company(&data[..]).unwrap();
+    ///
+    /// assert_eq!(companies.len(), 2);
+    /// let second = &companies["2"];
+    /// assert_eq!(second.company().name.as_deref(), Some("Second AB"));
+    /// assert_eq!(second.items().len(), 3);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error of the reader, see
+    /// [`Reader::next_document`].
+    pub fn by_company(reader: impl Read) -> Result<BTreeMap<String, Self>, reader::Error> {
+        let mut reader = Reader::new(reader);
+        let mut companies = BTreeMap::<_, Self>::new();
+
+        while let Some(doc) = reader.next_document() {
+            let doc = doc?;
+            let key = doc.company().key();
+            let doc = match companies.remove(&key) {
+                Some(previous) => previous
+                    .into_items()
+                    .into_iter()
+                    .chain(doc.into_items())
+                    .collect(),
+                None => doc,
+            };
+            companies.insert(key, doc);
+        }
+
+        Ok(companies)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let org_no = "556677-8899".parse::<OrgNr>().unwrap();
+        assert_eq!(org_no, "5566778899".parse().unwrap());
+        assert_eq!(org_no.group(), 5);
+        assert!(!org_no.is_personal());
+        assert_eq!(org_no.century(), None);
+
+        let sole_trader = "19811218-9876".parse::<OrgNr>().unwrap();
+        assert_eq!(sole_trader.to_string(), "19811218-9876");
+        assert_eq!(sole_trader.century(), Some(19));
+        assert!(sole_trader.is_personal());
+        assert_eq!(
+            "165566778899".parse::<OrgNr>().unwrap().to_string(),
+            "16556677-8899"
+        );
+
+        for invalid in [
+            "556677-8898",
+            "55667-78899",
+            "556677-889",
+            "5566778899-",
+            "556677+8899",
+            "556677 8899",
+            "55667\u{e9}7899",
+            "",
+        ] {
+            assert_eq!(
+                invalid.parse::<OrgNr>(),
+                Err(InvalidOrgNr(invalid.to_owned())),
+                "{invalid}"
+            );
+        }
+
+        // `\x82` is decoded as `é`
+        let data = b"#ORGNR \"55667\x827899\"\n";
+        assert!(crate::Reader::new(&data[..]).next().unwrap().is_err());
+        assert!(crate::Document::from_reader(&data[..]).is_err());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(CompanyInfo::default().to_string(), "");
+        assert_eq!(
+            CompanyInfo {
+                kind: Some("HB".to_owned()),
+                sni: "01110".parse().ok(),
+                ..CompanyInfo::default()
+            }
+            .to_string(),
+            "(HB), SNI 01110"
+        );
+    }
+
+    #[test]
+    fn by_company() {
+        let data = b"#FLAGGA 0
+#FNR \"A\"
+#KONTO 1930 Bank
+#UB 0 1930 100.00
+#FLAGGA 0
+#FNAMN \"No id AB\"
+#UB 0 1930 50.00
+#FNR \"B\"
+#UB 0 1930 200.00
+#FLAGGA 0
+#FNR \"A\"
+#UB -1 1930 80.00
+";
+        let companies = Document::by_company(&data[..]).unwrap();
+        assert_eq!(companies.keys().collect::<Vec<_>>(), ["A", "B", "No id AB"]);
+        assert_eq!(companies["A"].items().len(), 7);
+        assert_eq!(companies["A"].account(1930).unwrap().name, "Bank");
+        assert_eq!(companies["B"].company().id.as_deref(), Some("B"));
+        assert_eq!(companies["No id AB"].items().len(), 3);
+    }
+}
@@ -0,0 +1,53 @@
+//! The English names of earlier versions, for code that hasn't migrated
+//! to the names of the specification yet.
+//!
+//! ```
+//! #![allow(deprecated)]
+//! use sie4::{compat::Account, Item};
+//!
+//! let item = Item::parse(sie4::Span::new(b"#KONTO 1930 \"Bank\"\n")).unwrap().1;
+//! let account: &Account = item.as_account().unwrap();
+//! assert_eq!(account.account_no(), 1930);
+//! ```
+
+use crate::{
+    item::{AccountNo, Konto, Trans, Ver},
+    Item,
+};
+
+#[deprecated(note = "use `sie4::item::Konto`")]
+pub type Account = Konto;
+
+#[deprecated(note = "use `sie4::item::Ver`")]
+pub type Verification = Ver;
+
+#[deprecated(note = "use `sie4::item::Trans`")]
+pub type Transaction = Trans;
+
+impl Konto {
+    #[deprecated(note = "use `Konto::no`")]
+    #[must_use]
+    pub fn account_no(&self) -> AccountNo {
+        self.no
+    }
+}
+
+impl Item {
+    #[deprecated(note = "match `Item::Konto`")]
+    #[must_use]
+    pub fn as_account(&self) -> Option<&Konto> {
+        match self {
+            Self::Konto(konto) => Some(konto),
+            _ => None,
+        }
+    }
+
+    #[deprecated(note = "match `Item::Ver`")]
+    #[must_use]
+    pub fn as_verification(&self) -> Option<&Ver> {
+        match self {
+            Self::Ver(ver) => Some(ver),
+            _ => None,
+        }
+    }
+}
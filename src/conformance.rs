@@ -0,0 +1,309 @@
+//! Test vectors from the SIE 4B specification, to tell how much of it an
+//! implementation of the format reads and writes.
+//!
+//! Every [`Vector`] is an input that must either be rejected or be read
+//! and written back in the canonical form of
+//! [`WriterProfile::SpecStrict`]: quoted text, CRLF line breaks and no
+//! indentation. The vectors are grouped by [`Level`], and an
+//! implementation conforms to a level if it passes every vector of that
+//! level and of those below it.
+//!
+//! ```
+//! use sie4::conformance::{run, Level, ThisCrate};
+//!
+//! let report = run(&ThisCrate);
+//! assert_eq!(report.level(), Some(Level::Encoding));
+//! ```
+
+use std::error::Error;
+
+use crate::{
+    writer::{Writer, WriterProfile},
+    ItemRef, SliceReader,
+};
+
+/// How much of the specification a vector tests, from the least to the
+/// most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// Every label with each combination of its optional fields, and
+    /// the invalid input that must be rejected.
+    Basic,
+    /// Code page 437, escapes, unquoted text, line breaks and
+    /// whitespace.
+    Encoding,
+    /// The rest of the specification: `#FNR`, `#ENHET`, the later charts
+    /// of accounts, vouchers without a number and labels that must be
+    /// ignored.
+    Complete,
+}
+
+/// What an implementation must make of the input of a [`Vector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expected {
+    /// The input written back in the canonical form, which must in turn
+    /// be written back unchanged.
+    Canonical(&'static [u8]),
+    /// The input is invalid.
+    Rejected,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Vector {
+    /// Unique, e.g. `trans_quantity`.
+    pub name: &'static str,
+    pub level: Level,
+    /// A whole file, in code page 437.
+    pub input: &'static [u8],
+    pub expected: Expected,
+}
+
+macro_rules! vectors {
+    ($($level:ident $name:ident: $input:literal => $expected:tt,)*) => {
+        /// All the test vectors, by level.
+        pub const VECTORS: &[Vector] = &[$(Vector {
+            name: stringify!($name),
+            level: Level::$level,
+            input: $input,
+            expected: vectors!(@expected $expected),
+        },)*];
+    };
+    (@expected rejected) => {
+        Expected::Rejected
+    };
+    (@expected $canonical:literal) => {
+        Expected::Canonical($canonical)
+    };
+}
+
+vectors! {
+    Basic flagga: b"#FLAGGA 0\r\n" => b"#FLAGGA 0\r\n",
+    Basic program: b"#PROGRAM \"Prog\" \"1.0\"\r\n" => b"#PROGRAM \"Prog\" \"1.0\"\r\n",
+    Basic format: b"#FORMAT PC8\r\n" => b"#FORMAT PC8\r\n",
+    Basic gen: b"#GEN 20230101\r\n" => b"#GEN 20230101\r\n",
+    Basic gen_signature: b"#GEN 20230101 \"AK\"\r\n" => b"#GEN 20230101 \"AK\"\r\n",
+    Basic sietyp: b"#SIETYP 4\r\n" => b"#SIETYP 4\r\n",
+    Basic prosa: b"#PROSA \"Annual accounts\"\r\n" => b"#PROSA \"Annual accounts\"\r\n",
+    Basic ftyp: b"#FTYP \"AB\"\r\n" => b"#FTYP \"AB\"\r\n",
+    Basic orgnr: b"#ORGNR \"556677-8899\"\r\n" => b"#ORGNR \"556677-8899\"\r\n",
+    Basic bkod: b"#BKOD 62010\r\n" => b"#BKOD 62010\r\n",
+    Basic adress: b"#ADRESS \"A\" \"B\" \"C\" \"D\"\r\n" => b"#ADRESS \"A\" \"B\" \"C\" \"D\"\r\n",
+    Basic fnamn: b"#FNAMN \"Company AB\"\r\n" => b"#FNAMN \"Company AB\"\r\n",
+    Basic rar: b"#RAR 0 20230101 20231231\r\n#RAR -1 20220101 20221231\r\n"
+        => b"#RAR 0 20230101 20231231\r\n#RAR -1 20220101 20221231\r\n",
+    Basic taxar: b"#TAXAR 2024\r\n" => b"#TAXAR 2024\r\n",
+    Basic omfattn: b"#OMFATTN 20230630\r\n" => b"#OMFATTN 20230630\r\n",
+    Basic kptyp: b"#KPTYP BAS96\r\n" => b"#KPTYP BAS96\r\n",
+    Basic valuta: b"#VALUTA EUR\r\n" => b"#VALUTA EUR\r\n",
+    Basic konto: b"#KONTO 1930 \"Bank\"\r\n" => b"#KONTO 1930 \"Bank\"\r\n",
+    Basic ktyp: b"#KTYP 1930 T\r\n" => b"#KTYP 1930 T\r\n",
+    Basic sru: b"#SRU 1930 7281\r\n" => b"#SRU 1930 7281\r\n",
+    Basic dim: b"#DIM 1 \"Cost centre\"\r\n" => b"#DIM 1 \"Cost centre\"\r\n",
+    Basic underdim: b"#UNDERDIM 21 \"Team\" 1\r\n" => b"#UNDERDIM 21 \"Team\" 1\r\n",
+    Basic objekt: b"#OBJEKT 1 \"1\" \"Nord\"\r\n" => b"#OBJEKT 1 \"1\" \"Nord\"\r\n",
+    Basic ib: b"#IB 0 1930 100.00\r\n" => b"#IB 0 1930 100.00\r\n",
+    Basic ib_quantity: b"#IB -1 1460 100.00 2\r\n" => b"#IB -1 1460 100.00 2\r\n",
+    Basic ub: b"#UB 0 1930 100.00\r\n" => b"#UB 0 1930 100.00\r\n",
+    Basic oib: b"#OIB 0 1930 {1 \"1\"} 100.00\r\n" => b"#OIB 0 1930 {1 \"1\"} 100.00\r\n",
+    Basic oub: b"#OUB 0 1930 {1 \"1\"} 100.00 2\r\n" => b"#OUB 0 1930 {1 \"1\"} 100.00 2\r\n",
+    Basic res: b"#RES 0 3010 -100.00\r\n" => b"#RES 0 3010 -100.00\r\n",
+    Basic psaldo: b"#PSALDO 0 202301 1930 {} 100.00\r\n" => b"#PSALDO 0 202301 1930 {} 100.00\r\n",
+    Basic psaldo_object: b"#PSALDO 0 202301 1930 {\"1\" \"1\"} 100.00 2\r\n"
+        => b"#PSALDO 0 202301 1930 {\"1\" \"1\"} 100.00 2\r\n",
+    Basic pbudget: b"#PBUDGET 0 202301 1930 {} 100.00\r\n" => b"#PBUDGET 0 202301 1930 {} 100.00\r\n",
+    Basic ver: b"#VER \"A\" 1 20230101\r\n{\r\n}\r\n" => b"#VER \"A\" 1 20230101\r\n{\r\n}\r\n",
+    Basic ver_text_reg_date: b"#VER \"A\" 1 20230101 \"Rent\" 20230102\r\n{\r\n}\r\n"
+        => b"#VER \"A\" 1 20230101 \"Rent\" 20230102\r\n{\r\n}\r\n",
+    Basic ver_sign: b"#VER \"A\" 1 20230101 \"\" \"\" \"AK\"\r\n{\r\n}\r\n"
+        => b"#VER \"A\" 1 20230101 \"\" \"\" \"AK\"\r\n{\r\n}\r\n",
+    Basic trans: b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1930 {} -100.00\r\n#TRANS 5010 {} 100.00\r\n}\r\n"
+        => b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1930 {} -100.00\r\n#TRANS 5010 {} 100.00\r\n}\r\n",
+    Basic trans_objects: b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 5010 {\"1\" \"456\" \"7\" \"47\"} 100.00\r\n}\r\n"
+        => b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 5010 {\"1\" \"456\" \"7\" \"47\"} 100.00\r\n}\r\n",
+    Basic trans_date: b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1930 {} 100.00 20230102\r\n}\r\n"
+        => b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1930 {} 100.00 20230102\r\n}\r\n",
+    Basic trans_text: b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1930 {} 100.00 \"\" \"Rent\"\r\n}\r\n"
+        => b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1930 {} 100.00 \"\" \"Rent\"\r\n}\r\n",
+    Basic trans_quantity: b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1460 {} 0 \"\" \"\" 2\r\n}\r\n"
+        => b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1460 {} 0 \"\" \"\" 2\r\n}\r\n",
+    Basic trans_signature: b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1930 {} 100.00 \"\" \"\" \"\" \"AK\"\r\n}\r\n"
+        => b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1930 {} 100.00 \"\" \"\" \"\" \"AK\"\r\n}\r\n",
+    Basic trans_all: b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1930 {\"1\" \"1\"} -1.00 20230102 \"t\" 2 \"AK\"\r\n}\r\n"
+        => b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1930 {\"1\" \"1\"} -1.00 20230102 \"t\" 2 \"AK\"\r\n}\r\n",
+    Basic corrections: b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1930 {} 1.00\r\n#RTRANS 1931 {} 1.00\r\n#TRANS 1931 {} 1.00\r\n#BTRANS 1930 {} 1.00\r\n}\r\n"
+        => b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1930 {} 1.00\r\n#RTRANS 1931 {} 1.00\r\n#TRANS 1931 {} 1.00\r\n#BTRANS 1930 {} 1.00\r\n}\r\n",
+    Basic order: b"#FLAGGA 0\r\n#FNAMN \"Company AB\"\r\n#KONTO 1930 \"Bank\"\r\n#IB 0 1930 100.00\r\n"
+        => b"#FLAGGA 0\r\n#FNAMN \"Company AB\"\r\n#KONTO 1930 \"Bank\"\r\n#IB 0 1930 100.00\r\n",
+    Basic invalid_flag: b"#FLAGGA 2\r\n" => rejected,
+    Basic invalid_sietyp: b"#SIETYP 5\r\n" => rejected,
+    Basic invalid_date: b"#VER \"A\" 1 20231301\r\n{\r\n}\r\n" => rejected,
+    Basic invalid_period: b"#PSALDO 0 202313 1930 {} 100.00\r\n" => rejected,
+    Basic decimal_comma: b"#IB 0 1930 100,00\r\n" => rejected,
+    Basic missing_field: b"#KONTO 1930\r\n" => rejected,
+    Basic unclosed_block: b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1930 {} 100.00\r\n" => rejected,
+    Basic out_of_order: b"#KONTO 1930 \"Bank\"\r\n#FNAMN \"Company AB\"\r\n" => rejected,
+    Encoding cp437: b"#FNAMN \"\x86\x84\x94 \x8f\x8e\x99 \x82\x81\"\r\n" => b"#FNAMN \"\x86\x84\x94 \x8f\x8e\x99 \x82\x81\"\r\n",
+    Encoding escaped_quote: b"#FNAMN \"Say \\\"hi\\\"\"\r\n" => b"#FNAMN \"Say \\\"hi\\\"\"\r\n",
+    Encoding escaped_backslash: b"#FNAMN \"C:\\\\dir\"\r\n" => b"#FNAMN \"C:\\\\dir\"\r\n",
+    Encoding backslash: b"#FNAMN \"C:\\dir\"\r\n" => b"#FNAMN \"C:\\\\dir\"\r\n",
+    Encoding braces_in_text: b"#KONTO 1930 \"{Bank}\"\r\n" => b"#KONTO 1930 \"{Bank}\"\r\n",
+    Encoding empty_text: b"#KONTO 1930 \"\"\r\n" => b"#KONTO 1930 \"\"\r\n",
+    Encoding unquoted: b"#PROGRAM Prog 1.0\r\n#KONTO 1930 Bank\r\n#OBJEKT 1 1 Nord\r\n"
+        => b"#PROGRAM \"Prog\" \"1.0\"\r\n#KONTO 1930 \"Bank\"\r\n#OBJEKT 1 \"1\" \"Nord\"\r\n",
+    Encoding unquoted_objects: b"#OIB 0 1930 {1 1} 100.00\r\n#PSALDO 0 202301 1930 {1 1} 100.00\r\n"
+        => b"#OIB 0 1930 {1 \"1\"} 100.00\r\n#PSALDO 0 202301 1930 {\"1\" \"1\"} 100.00\r\n",
+    Encoding amounts: b"#IB 0 1930 1.5\r\n#IB 0 1931 -0.50\r\n#IB 0 1932 100\r\n"
+        => b"#IB 0 1930 1.5\r\n#IB 0 1931 -0.50\r\n#IB 0 1932 100\r\n",
+    Encoding lf: b"#FLAGGA 0\n#VER A 1 20230101\n{\n#TRANS 1930 {} 1.00\n}\n"
+        => b"#FLAGGA 0\r\n#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1930 {} 1.00\r\n}\r\n",
+    Encoding tabs: b"#KONTO\t1930\t\"Bank\"\r\n" => b"#KONTO 1930 \"Bank\"\r\n",
+    Encoding whitespace: b"  #KONTO   1930   \"Bank\"   \r\n" => b"#KONTO 1930 \"Bank\"\r\n",
+    Encoding indented_block: b"#VER A 1 20230101\r\n{\r\n    #TRANS 1930 {} 1.00\r\n}\r\n"
+        => b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1930 {} 1.00\r\n}\r\n",
+    Encoding brace_on_item_line: b"#VER A 1 20230101 {\r\n#TRANS 1930 {} 1.00\r\n}\r\n"
+        => b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1930 {} 1.00\r\n}\r\n",
+    Encoding entries_on_brace_lines: b"#VER A 1 20230101\r\n{#TRANS 1930 {} 1.00\r\n#TRANS 1931 {} -1.00}\r\n"
+        => b"#VER \"A\" 1 20230101\r\n{\r\n#TRANS 1930 {} 1.00\r\n#TRANS 1931 {} -1.00\r\n}\r\n",
+    Complete fnr: b"#FNR \"123\"\r\n" => b"#FNR \"123\"\r\n",
+    Complete enhet: b"#ENHET 1460 \"st\"\r\n" => b"#ENHET 1460 \"st\"\r\n",
+    Complete kptyp_bas2014: b"#KPTYP BAS2014\r\n" => b"#KPTYP BAS2014\r\n",
+    Complete ver_without_number: b"#VER \"A\" \"\" 20230101\r\n{\r\n}\r\n" => b"#VER \"A\" \"\" 20230101\r\n{\r\n}\r\n",
+    Complete unknown_label: b"#XYZ 1 2\r\n#KONTO 1930 \"Bank\"\r\n" => b"#KONTO 1930 \"Bank\"\r\n",
+}
+
+/// A reader and writer of SIE4 to run the vectors against.
+pub trait Implementation {
+    /// Read `input` as a whole file and write what was read in the
+    /// canonical form, see [`Expected::Canonical`].
+    ///
+    /// # Errors
+    ///
+    /// If the input is rejected, for whatever reason.
+    fn rewrite(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn Error>>;
+}
+
+/// The [`SliceReader`] and [`Writer`] of this crate, with their default
+/// settings.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThisCrate;
+
+impl Implementation for ThisCrate {
+    fn rewrite(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut writer = Writer::with_profile(Vec::new(), WriterProfile::SpecStrict);
+        for item in SliceReader::new(input) {
+            writer.write_item(&item.map(ItemRef::into_owned)?)?;
+        }
+        writer.flush()?;
+        Ok(writer.into_inner())
+    }
+}
+
+/// What an implementation made of a vector.
+#[derive(Debug)]
+pub struct Outcome {
+    pub vector: &'static Vector,
+    /// The output or the error message.
+    pub output: Result<Vec<u8>, String>,
+    pub passed: bool,
+}
+
+/// See [`run`].
+#[derive(Debug)]
+pub struct Report {
+    /// In the order of [`VECTORS`].
+    pub outcomes: Vec<Outcome>,
+}
+
+impl Report {
+    pub fn failures(&self) -> impl Iterator<Item = &Outcome> {
+        self.outcomes.iter().filter(|outcome| !outcome.passed)
+    }
+
+    /// The highest level whose vectors, and those of the levels below
+    /// it, all passed.
+    #[must_use]
+    pub fn level(&self) -> Option<Level> {
+        let lowest_failed = self.failures().map(|outcome| outcome.vector.level).min();
+        [Level::Basic, Level::Encoding, Level::Complete]
+            .into_iter()
+            .take_while(|level| lowest_failed.is_none_or(|failed| level < &failed))
+            .last()
+    }
+}
+
+/// Run every vector against the implementation.
+pub fn run(implementation: &impl Implementation) -> Report {
+    let outcomes = VECTORS
+        .iter()
+        .map(|vector| {
+            let output = implementation
+                .rewrite(vector.input)
+                .map_err(|e| e.to_string());
+            let passed = match (vector.expected, &output) {
+                (Expected::Canonical(canonical), Ok(output)) => {
+                    output == canonical
+                        && implementation
+                            .rewrite(canonical)
+                            .is_ok_and(|again| again == canonical)
+                }
+                (Expected::Rejected, Err(_)) => true,
+                _ => false,
+            };
+
+            Outcome {
+                vector,
+                output,
+                passed,
+            }
+        })
+        .collect();
+
+    Report { outcomes }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn this_crate() {
+        let names = VECTORS.iter().map(|v| v.name).collect::<HashSet<_>>();
+        assert_eq!(names.len(), VECTORS.len());
+
+        let report = run(&ThisCrate);
+        let failures = report
+            .failures()
+            .map(|outcome| outcome.vector.name)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            failures,
+            [
+                "enhet",
+                "kptyp_bas2014",
+                "ver_without_number",
+                "unknown_label"
+            ]
+        );
+        assert_eq!(report.level(), Some(Level::Encoding));
+    }
+
+    #[test]
+    fn levels() {
+        struct Echo;
+
+        impl Implementation for Echo {
+            fn rewrite(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+                Ok(input.to_vec())
+            }
+        }
+
+        let report = run(&Echo);
+        assert_eq!(report.level(), None);
+        assert!(report
+            .failures()
+            .all(|outcome| outcome.vector.expected == Expected::Rejected
+                || outcome.vector.level > Level::Basic));
+    }
+}
@@ -0,0 +1,422 @@
+//! Group-level reports of several companies.
+//!
+//! ```
+//! use rust_decimal_macros::dec;
+//! use sie4::{
+//!     consolidate::{consolidate, EliminationRules},
+//!     Document,
+//! };
+//!
+//! let parent = Document::from_reader(&b"#FNAMN \"Parent AB\"
+//! #IB 0 1660 500.00
+//! #RES 0 3010 -800.00
+//! #RES 0 5010 300.00
+//! "[..])
+//! .unwrap();
+//! let subsidiary = Document::from_reader(&b"#FNAMN \"Subsidiary AB\"
+//! #IB 0 2460 -500.00
+//! #RES 0 3010 -400.00
+//! "[..])
+//! .unwrap();
+//!
+//! let rules = EliminationRules {
+//!     accounts: vec![1660.into(), 2460.into()],
+//!     ..EliminationRules::default()
+//! };
+//! let group = consolidate(&[parent, subsidiary], &rules).unwrap();
+//!
+//! assert_eq!(group.companies[1].name.as_deref(), Some("Subsidiary AB"));
+//! let receivables = group.line(1660).unwrap();
+//! assert_eq!(receivables.companies, [dec!(500.00), dec!(0)]);
+//! assert_eq!(receivables.closing, dec!(0));
+//! assert_eq!(group.income_statement().count(), 2);
+//! assert_eq!(group.result(), dec!(-900.00));
+//! assert_eq!(group.unmatched(), dec!(0));
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use iso_currency::Currency;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::{
+    company::OrgNr,
+    item::{AccountKind, AccountNo},
+    Document, Item,
+};
+
+/// What is internal to the group and therefore eliminated.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EliminationRules {
+    /// Accounts that only hold amounts between the companies of the
+    /// group, e.g. receivables from and liabilities to group companies
+    /// (1660 and 2460 in BAS). Their balances are eliminated entirely.
+    pub accounts: Vec<AccountNo>,
+    /// A dimension whose objects are the counterparties within the
+    /// group. Transactions with an object of the dimension are
+    /// eliminated from the closing balances and results of their
+    /// accounts.
+    pub counterparty_dimension: Option<u32>,
+}
+
+/// The consolidated companies failed to share a currency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("company {company} has its books in {found} rather than {expected}")]
+pub struct MixedCurrencies {
+    /// Index of the company in the consolidated documents.
+    pub company: usize,
+    pub expected: Currency,
+    pub found: Currency,
+}
+
+/// A company of the group (`#FNAMN` and `#ORGNR`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Company {
+    pub name: Option<String>,
+    pub org_no: Option<OrgNr>,
+}
+
+/// An account of the group trial balance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Line {
+    pub account: AccountNo,
+    /// Name of the account in the first company that declares it.
+    pub name: Option<String>,
+    pub kind: Option<AccountKind>,
+    /// The opening balance of the group, after eliminations. Always zero
+    /// for result accounts.
+    pub opening: Decimal,
+    /// The closing balance or result of the group, after eliminations.
+    pub closing: Decimal,
+    /// The closing balance or result of each company, before
+    /// eliminations.
+    pub companies: Vec<Decimal>,
+    /// How much of the sum of [`Line::companies`] was eliminated.
+    pub eliminated: Decimal,
+}
+
+impl Line {
+    fn is_balance(&self) -> bool {
+        self.kind.is_none_or(AccountKind::is_balance)
+    }
+}
+
+/// An amount that was eliminated from the closing balance or result of
+/// an account of a company.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Elimination {
+    /// Index of the company.
+    pub company: usize,
+    pub account: AccountNo,
+    pub amount: Decimal,
+}
+
+/// The group trial balance and income statement from [`consolidate`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Consolidation {
+    pub companies: Vec<Company>,
+    pub currency: Currency,
+    lines: Vec<Line>,
+    pub eliminations: Vec<Elimination>,
+}
+
+impl Consolidation {
+    /// Every account of the group, in order.
+    #[must_use]
+    pub fn trial_balance(&self) -> &[Line] {
+        &self.lines
+    }
+
+    #[must_use]
+    pub fn line(&self, account: impl Into<AccountNo>) -> Option<&Line> {
+        self.lines
+            .binary_search_by_key(&account.into(), |line| line.account)
+            .ok()
+            .map(|i| &self.lines[i])
+    }
+
+    /// The result accounts, i.e. revenue and costs.
+    pub fn income_statement(&self) -> impl Iterator<Item = &Line> {
+        self.lines.iter().filter(|line| !line.is_balance())
+    }
+
+    /// The result of the group, which is negative for a profit since
+    /// revenue is credited.
+    #[must_use]
+    pub fn result(&self) -> Decimal {
+        self.income_statement().map(|line| line.closing).sum()
+    }
+
+    /// The sum of all eliminations, which is zero if the amounts between
+    /// the companies match.
+    #[must_use]
+    pub fn unmatched(&self) -> Decimal {
+        self.eliminations.iter().map(|e| e.amount).sum()
+    }
+}
+
+/// The balances of a company in the current fiscal year.
+#[derive(Default)]
+struct Balances {
+    opening: BTreeMap<AccountNo, Decimal>,
+    closing: BTreeMap<AccountNo, Decimal>,
+    results: BTreeMap<AccountNo, Decimal>,
+    transactions: BTreeMap<AccountNo, Decimal>,
+    counterparties: BTreeMap<AccountNo, Decimal>,
+}
+
+impl Balances {
+    fn new(doc: &Document, rules: &EliminationRules) -> Self {
+        let mut balances = Self::default();
+        let year = doc.fiscal_years().get(0).copied();
+
+        for item in doc.items() {
+            match item {
+                Item::Ib(ib) if ib.year == 0 => {
+                    *balances.opening.entry(ib.account).or_default() += ib.balance;
+                }
+                Item::Ub(ub) if ub.year == 0 => {
+                    *balances.closing.entry(ub.account).or_default() += ub.balance;
+                }
+                Item::Res(res) if res.year == 0 => {
+                    *balances.results.entry(res.account).or_default() += res.balance;
+                }
+                _ => (),
+            }
+        }
+
+        for ver in doc.vouchers() {
+            for trans in ver.counted(doc.include_removed()) {
+                let date = doc.date_policy().date(ver, trans);
+                if year
+                    .as_ref()
+                    .is_some_and(|year| !(year.start..=year.end).contains(&date))
+                {
+                    continue;
+                }
+
+                *balances.transactions.entry(trans.account).or_default() += trans.amount;
+                let counterparty = rules
+                    .counterparty_dimension
+                    .is_some_and(|dimension| trans.objects.0.iter().any(|(d, _)| *d == dimension));
+                if counterparty {
+                    *balances.counterparties.entry(trans.account).or_default() += trans.amount;
+                }
+            }
+        }
+
+        balances
+    }
+
+    fn accounts(&self) -> impl Iterator<Item = AccountNo> + '_ {
+        self.opening
+            .keys()
+            .chain(self.closing.keys())
+            .chain(self.results.keys())
+            .chain(self.transactions.keys())
+            .copied()
+    }
+
+    /// The opening and closing balances of a balance account, or else
+    /// zero and the result. Missing `#UB` and `#RES` are computed from
+    /// the transactions.
+    fn get(&self, account: AccountNo, balance: bool) -> (Decimal, Decimal) {
+        let opening = self.opening.get(&account).copied().unwrap_or_default();
+        let transactions = self.transactions.get(&account).copied().unwrap_or_default();
+
+        if balance {
+            let closing = self.closing.get(&account).copied();
+            (opening, closing.unwrap_or(opening + transactions))
+        } else {
+            let result = self.results.get(&account).copied();
+            (Decimal::ZERO, result.unwrap_or(transactions))
+        }
+    }
+}
+
+/// Add up the balances of the current fiscal year (`0`) of several
+/// companies, eliminating what is internal to the group according to
+/// `rules`.
+///
+/// The closing balances and results are those of `#UB` and `#RES`, or
+/// else computed from the opening balances and the transactions of the
+/// year, as corrected.
+///
+/// # Errors
+///
+/// Returns an error unless all companies have the same currency (see
+/// [`Document::currency`]).
+pub fn consolidate(
+    docs: &[Document],
+    rules: &EliminationRules,
+) -> Result<Consolidation, MixedCurrencies> {
+    let currency = docs.first().map_or(Currency::SEK, Document::currency);
+    if let Some((company, doc)) = docs
+        .iter()
+        .enumerate()
+        .find(|(_, doc)| doc.currency() != currency)
+    {
+        return Err(MixedCurrencies {
+            company,
+            expected: currency,
+            found: doc.currency(),
+        });
+    }
+
+    let balances = docs
+        .iter()
+        .map(|doc| Balances::new(doc, rules))
+        .collect::<Vec<_>>();
+    let accounts = balances
+        .iter()
+        .flat_map(Balances::accounts)
+        .collect::<BTreeSet<_>>();
+
+    let mut lines = Vec::with_capacity(accounts.len());
+    let mut eliminations = Vec::new();
+    for account in accounts {
+        let name = docs
+            .iter()
+            .find_map(|doc| doc.account(account))
+            .map(|konto| konto.name.clone());
+        let kind = docs.iter().find_map(|doc| doc.account_kind(account));
+        let mut line = Line {
+            account,
+            name,
+            kind,
+            opening: Decimal::ZERO,
+            closing: Decimal::ZERO,
+            companies: Vec::with_capacity(docs.len()),
+            eliminated: Decimal::ZERO,
+        };
+        let internal = rules.accounts.contains(&account);
+
+        for (company, balances) in balances.iter().enumerate() {
+            let (opening, closing) = balances.get(account, line.is_balance());
+            line.companies.push(closing);
+
+            let eliminated = if internal {
+                closing
+            } else {
+                balances
+                    .counterparties
+                    .get(&account)
+                    .copied()
+                    .unwrap_or_default()
+            };
+            if !internal {
+                line.opening += opening;
+            }
+            line.closing += closing - eliminated;
+            if !eliminated.is_zero() {
+                line.eliminated += eliminated;
+                eliminations.push(Elimination {
+                    company,
+                    account,
+                    amount: eliminated,
+                });
+            }
+        }
+
+        lines.push(line);
+    }
+
+    Ok(Consolidation {
+        companies: docs
+            .iter()
+            .map(|doc| {
+                let company = doc.company();
+                Company {
+                    name: company.name,
+                    org_no: company.org_no,
+                }
+            })
+            .collect(),
+        currency,
+        lines,
+        eliminations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn counterparties() {
+        let parent = Document::from_reader(
+            &b"#RAR 0 20230101 20231231
+#KONTO 3010 \"F\x94rs\x84ljning\"
+#IB 0 1930 100.00
+#VER A 1 20230102
+{
+    #TRANS 1510 {9 \"S\"} 250.00
+    #TRANS 3010 {9 \"S\"} -250.00
+}
+#VER A 2 20230103
+{
+    #TRANS 1930 {} 1000.00
+    #TRANS 3010 {} -1000.00
+}
+#VER A 3 20221231
+{
+    #TRANS 1930 {} 1.00
+    #TRANS 3010 {} -1.00
+}
+"[..],
+        )
+        .unwrap();
+        let subsidiary = Document::from_reader(
+            &b"#RAR 0 20230101 20231231
+#VALUTA SEK
+#VER A 1 20230102
+{
+    #TRANS 2440 {9 \"P\"} -240.00
+    #TRANS 4010 {9 \"P\"} 240.00
+}
+"[..],
+        )
+        .unwrap();
+
+        let rules = EliminationRules {
+            counterparty_dimension: Some(9),
+            ..EliminationRules::default()
+        };
+        let group = consolidate(&[parent, subsidiary], &rules).unwrap();
+
+        let summary = group
+            .trial_balance()
+            .iter()
+            .map(|line| (line.account, line.opening, line.closing, line.eliminated))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                (1510.into(), dec!(0), dec!(0), dec!(250.00)),
+                (1930.into(), dec!(100.00), dec!(1100.00), dec!(0)),
+                (2440.into(), dec!(0), dec!(0), dec!(-240.00)),
+                (3010.into(), dec!(0), dec!(-1000.00), dec!(-250.00)),
+                (4010.into(), dec!(0), dec!(0), dec!(240.00)),
+            ]
+        );
+        assert_eq!(
+            group.line(3010).unwrap().name.as_deref(),
+            Some("Försäljning")
+        );
+        assert_eq!(group.result(), dec!(-1000.00));
+        assert_eq!(group.unmatched(), dec!(0));
+
+        let euro = Document::from_reader(&b"#VALUTA EUR\n"[..]).unwrap();
+        assert_eq!(
+            consolidate(&[Document::default(), euro], &EliminationRules::default()),
+            Err(MixedCurrencies {
+                company: 1,
+                expected: Currency::SEK,
+                found: Currency::EUR,
+            })
+        );
+    }
+}
@@ -0,0 +1,619 @@
+//! Amounts in other currencies than that of the books.
+//!
+//! The amounts of a file are in the currency of the books, declared with
+//! `#VALUTA` (see [`Document::currency`]). SIE4 has no field for the
+//! foreign amount of a transaction, so programs that export it use
+//! extensions of their own, e.g. a dimension of the object list. Such
+//! amounts can be attached to the transactions with
+//! [`Document::set_foreign_amounts`]. Whether the amounts are in the
+//! currency of the books is checked by
+//! [`Rule::Currency`](crate::validate::Rule::Currency), and
+//! [`Document::restate`] converts all of them into another currency.
+//!
+//! ```
+//! use iso_currency::Currency;
+//! use rust_decimal_macros::dec;
+//! use sie4::{
+//!     currency::{ForeignAmount, Rates},
+//!     Document,
+//! };
+//! use time::macros::date;
+//!
+//! let mut doc = Document::from_reader(&b"#VALUTA SEK
+//! #VER A 1 20230102 \"Invoice\"
+//! {
+//!     #TRANS 1510 {99 \"EUR 100.00\"} 1100.00
+//!     #TRANS 3010 {} -1100.00
+//! }
+//! "[..])
+//! .unwrap();
+//!
+//! // this exporter writes the foreign amount as an object of dimension 99
+//! doc.set_foreign_amounts(|_, trans| {
+//!     let (_, object) = trans.objects.0.iter().find(|(dimension, _)| *dimension == 99)?;
+//!     let (currency, amount) = object.split_once(' ')?;
+//!     Some(ForeignAmount {
+//!         currency: Currency::from_code(currency)?,
+//!         amount: amount.parse().ok()?,
+//!     })
+//! });
+//!
+//! let mut rates = Rates::new(Currency::EUR);
+//! rates.insert(Currency::SEK, date!(2023 - 01 - 01), dec!(0.09));
+//!
+//! let ver = doc.vouchers().next().unwrap();
+//! let [receivable, revenue] = &ver.transactions.0[..] else { panic!() };
+//! let book = doc.currency();
+//! assert_eq!(receivable.trans().currency(book), Currency::EUR);
+//! let dates = doc.date_policy();
+//! assert_eq!(rates.convert_trans(receivable.trans(), ver, book, dates), Ok(dec!(100.00)));
+//! assert_eq!(rates.convert_trans(revenue.trans(), ver, book, dates), Ok(dec!(-99.0000)));
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+
+use iso_currency::Currency;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::Serialize;
+use time::Date;
+
+use crate::{
+    amount::Amount,
+    document::DatePolicy,
+    fiscal::FiscalYears,
+    item::{Group, Period, Trans, TransLine, Valuta, Ver},
+    Document, Item,
+};
+
+/// The amount of a transaction in a foreign currency, see
+/// [`Trans::foreign`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ForeignAmount {
+    pub currency: Currency,
+    pub amount: Decimal,
+}
+
+impl Trans {
+    /// The currency of [`Trans::currency_amount`], given the currency of
+    /// the books.
+    #[must_use]
+    pub fn currency(&self, book: Currency) -> Currency {
+        self.foreign.map_or(book, |foreign| foreign.currency)
+    }
+
+    /// The foreign amount, if any, or else the amount.
+    #[must_use]
+    pub fn currency_amount(&self) -> Decimal {
+        self.foreign.map_or(self.amount.0, |foreign| foreign.amount)
+    }
+}
+
+impl Document {
+    /// The currency of the books (`#VALUTA`), which is SEK unless
+    /// declared otherwise.
+    #[must_use]
+    pub fn currency(&self) -> Currency {
+        self.items()
+            .iter()
+            .find_map(|item| match item {
+                Item::Valuta(valuta) => Some(valuta.currency),
+                _ => None,
+            })
+            .unwrap_or(Currency::SEK)
+    }
+
+    /// Set [`Trans::foreign`] of every transaction to what `f` returns,
+    /// e.g. from the extension fields used by the exporting program.
+    pub fn set_foreign_amounts(
+        &mut self,
+        mut f: impl FnMut(&Ver, &Trans) -> Option<ForeignAmount>,
+    ) {
+        for ver in self.vouchers_mut() {
+            let foreign = ver
+                .transactions
+                .0
+                .iter()
+                .map(|line| f(ver, line.trans()))
+                .collect::<Vec<_>>();
+
+            for (line, foreign) in ver.transactions.0.iter_mut().zip(foreign) {
+                line.trans_mut().foreign = foreign;
+            }
+        }
+    }
+}
+
+/// No rate for the currency on the date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("no rate for {currency} on {date}")]
+pub struct MissingRate {
+    pub currency: Currency,
+    pub date: Date,
+}
+
+/// An amount converted with [`Rates::conversion`], with the rate that
+/// was used, e.g. to record alongside the converted amounts of an export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Conversion {
+    /// The currency of the original amount.
+    pub from: Currency,
+    /// The base currency of the rates.
+    pub to: Currency,
+    /// The date that the rate is for.
+    pub date: Date,
+    pub rate: Decimal,
+    /// The converted amount, unrounded.
+    pub amount: Decimal,
+}
+
+/// Exchange rates into a base currency, each valid from a date until the
+/// next rate of the same currency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rates {
+    base: Currency,
+    rates: HashMap<Currency, BTreeMap<Date, Decimal>>,
+}
+
+impl Rates {
+    #[must_use]
+    pub fn new(base: Currency) -> Self {
+        Self {
+            base,
+            rates: HashMap::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn base(&self) -> Currency {
+        self.base
+    }
+
+    /// Set the value of one unit of `currency` in the base currency from
+    /// `date` on.
+    pub fn insert(&mut self, currency: Currency, date: Date, rate: Decimal) {
+        self.rates.entry(currency).or_default().insert(date, rate);
+    }
+
+    /// The value of one unit of `currency` in the base currency on
+    /// `date`, which is always one for the base currency itself.
+    #[must_use]
+    pub fn rate(&self, currency: Currency, date: Date) -> Option<Decimal> {
+        if currency == self.base {
+            return Some(Decimal::ONE);
+        }
+
+        self.rates
+            .get(&currency)?
+            .range(..=date)
+            .next_back()
+            .map(|(_, &rate)| rate)
+    }
+
+    /// Convert `amount` of `currency` into the base currency.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no rate for the currency on the date.
+    pub fn convert(
+        &self,
+        amount: Decimal,
+        currency: Currency,
+        date: Date,
+    ) -> Result<Decimal, MissingRate> {
+        self.conversion(amount, currency, date)
+            .map(|conversion| conversion.amount)
+    }
+
+    /// Like [`Rates::convert`], but with the rate that was used.
+    ///
+    /// # Errors
+    ///
+    /// See [`Rates::convert`].
+    pub fn conversion(
+        &self,
+        amount: Decimal,
+        currency: Currency,
+        date: Date,
+    ) -> Result<Conversion, MissingRate> {
+        let rate = self
+            .rate(currency, date)
+            .ok_or(MissingRate { currency, date })?;
+
+        Ok(Conversion {
+            from: currency,
+            to: self.base,
+            date,
+            rate,
+            amount: amount * rate,
+        })
+    }
+
+    /// Convert the [currency amount](Trans::currency_amount) of a
+    /// transaction of `ver` on its date according to `dates`, given the
+    /// currency of the books.
+    ///
+    /// # Errors
+    ///
+    /// See [`Rates::convert`].
+    pub fn convert_trans(
+        &self,
+        trans: &Trans,
+        ver: &Ver,
+        book: Currency,
+        dates: DatePolicy,
+    ) -> Result<Decimal, MissingRate> {
+        self.convert(
+            trans.currency_amount(),
+            trans.currency(book),
+            dates.date(ver, trans),
+        )
+    }
+}
+
+/// Why a document couldn't be [restated](Document::restate).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RestateError {
+    #[error(transparent)]
+    MissingRate(#[from] MissingRate),
+    /// A balance of a fiscal year without a `#RAR`, so that the date of
+    /// its rate is unknown.
+    #[error("no #RAR for the fiscal year {0}")]
+    MissingYear(i32),
+    /// A `#PSALDO` or `#PBUDGET` of a period that doesn't exist.
+    #[error("invalid period {0}")]
+    InvalidPeriod(Period),
+    /// A `#TRANS` outside of a verification without a date of its own.
+    #[error("transaction on account {0} without a date")]
+    UndatedTransaction(crate::item::AccountNo),
+}
+
+impl Document {
+    /// The document with its amounts restated in the base currency of
+    /// `rates`, e.g. to consolidate the books of a Swedish subsidiary
+    /// into those of a group that reports in EUR. `#VALUTA` is set to the
+    /// base currency.
+    ///
+    /// Transactions are converted at the rate of their date according to
+    /// [`Document::date_policy`], opening balances at the rate of the
+    /// first day of their fiscal year, and closing balances and results
+    /// at that of the last, so they no longer add up: the difference is
+    /// the translation difference. Period balances and budgets are
+    /// converted at the rate of the last day of their period.
+    ///
+    /// The amounts are rounded to the decimals of the base currency. The
+    /// rounding difference of a balanced verification is put on its
+    /// largest transaction, to keep it balanced. The original amounts
+    /// are kept as [foreign amounts](Trans::foreign), unless there
+    /// already is one, and those in the base currency become the amounts.
+    /// `#KSUMMA` is removed, since the checksum no longer matches.
+    ///
+    /// ```
+    /// use iso_currency::Currency;
+    /// use rust_decimal_macros::dec;
+    /// use sie4::{currency::Rates, item::Item, Document};
+    /// use time::macros::date;
+    ///
+    /// let doc = Document::from_reader(&b"#RAR 0 20230101 20231231
+    /// #UB 0 1930 1000.00
+    /// #VER A 1 20230102
+    /// {
+    ///     #TRANS 1930 {} 100.00
+    ///     #TRANS 3010 {} -100.00
+    /// }
+    /// "[..])
+    /// .unwrap();
+    ///
+    /// let mut rates = Rates::new(Currency::EUR);
+    /// rates.insert(Currency::SEK, date!(2023 - 01 - 01), dec!(0.09));
+    /// rates.insert(Currency::SEK, date!(2023 - 12 - 01), dec!(0.08));
+    ///
+    /// let group = doc.restate(&rates).unwrap();
+    /// assert_eq!(group.currency(), Currency::EUR);
+    /// let Item::Ub(ub) = &group.items()[2] else { panic!() };
+    /// assert_eq!(ub.balance, dec!(80.00));
+    /// let ver = group.vouchers().next().unwrap();
+    /// assert_eq!(ver.transactions.0[0].trans().amount, dec!(9.00));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If there is no rate for an amount, or its date is unknown.
+    pub fn restate(self, rates: &Rates) -> Result<Self, RestateError> {
+        let restatement = Restatement {
+            rates,
+            book: self.currency(),
+            years: self.fiscal_years(),
+            dates: self.date_policy(),
+            removed: self.include_removed(),
+            decimals: rates.base().exponent().map_or(2, u32::from),
+        };
+
+        let mut items = Vec::with_capacity(self.items().len() + 1);
+        for mut item in self.into_items() {
+            match &mut item {
+                Item::Ksumma(_) => continue,
+                Item::Valuta(valuta) => valuta.currency = rates.base(),
+                item => restatement.item(item)?,
+            }
+            items.push(item);
+        }
+        if !items.iter().any(|item| matches!(item, Item::Valuta(_))) {
+            let at = items
+                .iter()
+                .rposition(|item| item.group() <= Group::Identification)
+                .map_or(0, |i| i + 1);
+            let valuta = Valuta {
+                currency: rates.base(),
+            };
+            items.insert(at, Item::Valuta(valuta));
+        }
+
+        Ok(items.into_iter().collect())
+    }
+}
+
+/// The conversions of [`Document::restate`].
+struct Restatement<'a> {
+    rates: &'a Rates,
+    /// The currency of the books before.
+    book: Currency,
+    years: FiscalYears,
+    dates: DatePolicy,
+    /// See [`Document::include_removed`].
+    removed: bool,
+    /// The decimals of the base currency.
+    decimals: u32,
+}
+
+impl Restatement<'_> {
+    fn item(&self, item: &mut Item) -> Result<(), RestateError> {
+        match item {
+            Item::Ib(ib) => self.convert(&mut ib.balance, self.year(ib.year, false)?),
+            Item::Oib(oib) => self.convert(&mut oib.balance, self.year(oib.year, false)?),
+            Item::Ub(ub) => self.convert(&mut ub.balance, self.year(ub.year, true)?),
+            Item::Oub(oub) => self.convert(&mut oub.balance, self.year(oub.year, true)?),
+            Item::Res(res) => self.convert(&mut res.balance, self.year(res.year, true)?),
+            Item::PSaldo(psaldo) => {
+                self.convert(&mut psaldo.balance, Self::period_end(psaldo.period)?)
+            }
+            Item::PBudget(pbudget) => {
+                self.convert(&mut pbudget.balance, Self::period_end(pbudget.period)?)
+            }
+            Item::Trans(trans) => {
+                let date = trans
+                    .date
+                    .ok_or(RestateError::UndatedTransaction(trans.account))?;
+                self.trans(trans, date)
+            }
+            Item::Ver(ver) => self.ver(ver),
+            _ => Ok(()),
+        }
+    }
+
+    /// The first day of the fiscal year `no`, or the last if `end`.
+    fn year(&self, no: i32, end: bool) -> Result<Date, RestateError> {
+        let year = self.years.get(no).ok_or(RestateError::MissingYear(no))?;
+        Ok(if end { year.end } else { year.start })
+    }
+
+    fn period_end(period: Period) -> Result<Date, RestateError> {
+        Date::from_calendar_date(period.year, period.month, period.month.length(period.year))
+            .map_err(|_| RestateError::InvalidPeriod(period))
+    }
+
+    fn convert(&self, amount: &mut Amount, date: Date) -> Result<(), RestateError> {
+        let converted = self.rates.convert(amount.0, self.book, date)?;
+        *amount = self.round(converted);
+        Ok(())
+    }
+
+    fn round(&self, amount: Decimal) -> Amount {
+        Amount(amount.round_dp_with_strategy(self.decimals, RoundingStrategy::MidpointAwayFromZero))
+    }
+
+    /// Convert `trans`, keeping the original amount as the foreign one.
+    fn trans(&self, trans: &mut Trans, date: Date) -> Result<(), RestateError> {
+        let original = trans.amount;
+        match trans.foreign {
+            Some(foreign) if foreign.currency == self.rates.base() => {
+                trans.amount = foreign.amount.into();
+                trans.foreign = None;
+            }
+            foreign => {
+                self.convert(&mut trans.amount, date)?;
+                trans.foreign = foreign.or(Some(ForeignAmount {
+                    currency: self.book,
+                    amount: original.0,
+                }));
+            }
+        }
+        Ok(())
+    }
+
+    fn ver(&self, ver: &mut Ver) -> Result<(), RestateError> {
+        let balanced = ver
+            .counted(self.removed)
+            .map(|trans| trans.amount)
+            .sum::<Amount>()
+            .is_zero();
+        let base = self.rates.base();
+        // amounts in the base currency are exact, so the others are rounded
+        let rounded = |trans: &Trans| trans.foreign.is_none_or(|foreign| foreign.currency != base);
+        let mut largest = None::<(usize, Amount)>;
+
+        for i in 0..ver.transactions.0.len() {
+            let date = self.dates.date(ver, ver.transactions.0[i].trans());
+            let line = &mut ver.transactions.0[i];
+            let adjustable = !matches!(line, TransLine::Removed(_)) && rounded(line.trans());
+            let trans = line.trans_mut();
+            self.trans(trans, date)?;
+            if adjustable && largest.is_none_or(|(_, amount)| trans.amount.abs() > amount) {
+                largest = Some((i, trans.amount.abs()));
+            }
+        }
+
+        let difference = ver
+            .counted(self.removed)
+            .map(|trans| trans.amount)
+            .sum::<Amount>();
+        if let (true, Some((i, _))) = (balanced, largest) {
+            ver.transactions.0[i].trans_mut().amount -= difference;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+    use time::macros::date;
+
+    use super::*;
+
+    #[test]
+    fn rates() {
+        let mut rates = Rates::new(Currency::SEK);
+        rates.insert(Currency::EUR, date!(2023 - 01 - 01), dec!(11.10));
+        rates.insert(Currency::EUR, date!(2023 - 02 - 01), dec!(11.20));
+
+        assert_eq!(
+            rates.rate(Currency::SEK, date!(1999 - 01 - 01)),
+            Some(Decimal::ONE)
+        );
+        assert_eq!(rates.rate(Currency::EUR, date!(2022 - 12 - 31)), None);
+        assert_eq!(
+            rates.rate(Currency::EUR, date!(2023 - 01 - 31)),
+            Some(dec!(11.10))
+        );
+        assert_eq!(
+            rates.rate(Currency::EUR, date!(2023 - 02 - 01)),
+            Some(dec!(11.20))
+        );
+        assert_eq!(
+            rates.convert(dec!(2), Currency::USD, date!(2023 - 03 - 01)),
+            Err(MissingRate {
+                currency: Currency::USD,
+                date: date!(2023 - 03 - 01)
+            })
+        );
+
+        assert_eq!(
+            rates.conversion(dec!(2), Currency::EUR, date!(2023 - 02 - 02)),
+            Ok(Conversion {
+                from: Currency::EUR,
+                to: Currency::SEK,
+                date: date!(2023 - 02 - 02),
+                rate: dec!(11.20),
+                amount: dec!(22.40),
+            })
+        );
+
+        let doc = Document::from_reader(&b"#FLAGGA 0\n"[..]).unwrap();
+        assert_eq!(doc.currency(), Currency::SEK);
+    }
+
+    #[test]
+    fn restate() {
+        let doc = || {
+            let mut doc = Document::from_reader(
+                &b"#FLAGGA 0
+#KSUMMA
+#VALUTA SEK
+#RAR 0 20230101 20231231
+#IB 0 1930 1000.00
+#UB 0 1930 1100.00
+#RES 0 3010 -100.00
+#VER A 1 20230102
+{
+    #TRANS 1930 {} 33.33
+    #TRANS 1510 {} 33.33
+    #TRANS 3010 {} -66.66
+}
+#VER A 2 20230601
+{
+    #TRANS 1510 {} 34.00
+    #TRANS 3010 {} -34.00
+}
+#KSUMMA 1234
+"[..],
+            )
+            .unwrap();
+            doc.set_foreign_amounts(|ver, trans| {
+                (ver.no == 2 && trans.account == 1510).then_some(ForeignAmount {
+                    currency: Currency::EUR,
+                    amount: dec!(3.00),
+                })
+            });
+            doc
+        };
+
+        let mut rates = Rates::new(Currency::EUR);
+        rates.insert(Currency::SEK, date!(2023 - 01 - 01), dec!(0.1));
+        rates.insert(Currency::SEK, date!(2023 - 06 - 01), dec!(0.09));
+        let group = doc().restate(&rates).unwrap();
+
+        assert_eq!(group.currency(), Currency::EUR);
+        assert!(!group
+            .items()
+            .iter()
+            .any(|item| matches!(item, Item::Ksumma(_))));
+        let balances = group
+            .items()
+            .iter()
+            .filter_map(|item| match item {
+                Item::Ib(ib) => Some(ib.balance.0),
+                Item::Ub(ub) => Some(ub.balance.0),
+                Item::Res(res) => Some(res.balance.0),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(balances, [dec!(100.00), dec!(99.00), dec!(-9.00)]);
+
+        let amounts = group
+            .vouchers()
+            .map(|ver| {
+                ver.transactions
+                    .0
+                    .iter()
+                    .map(|line| (line.trans().amount.0, line.trans().foreign))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let sek = |amount| {
+            Some(ForeignAmount {
+                currency: Currency::SEK,
+                amount,
+            })
+        };
+        assert_eq!(
+            amounts,
+            [
+                // -6.666 is rounded to -6.67, and then adjusted to balance
+                vec![
+                    (dec!(3.33), sek(dec!(33.33))),
+                    (dec!(3.33), sek(dec!(33.33))),
+                    (dec!(-6.66), sek(dec!(-66.66)))
+                ],
+                // the foreign amount is exact, so the other one is adjusted
+                vec![(dec!(3.00), None), (dec!(-3.00), sek(dec!(-34.00)))],
+            ]
+        );
+        assert!(crate::validate::validate(&group)
+            .findings
+            .iter()
+            .all(|finding| finding.rule != crate::validate::Rule::Currency));
+
+        let mut rates = Rates::new(Currency::EUR);
+        rates.insert(Currency::SEK, date!(2023 - 03 - 01), dec!(0.09));
+        assert_eq!(
+            doc().restate(&rates),
+            Err(RestateError::MissingRate(MissingRate {
+                currency: Currency::SEK,
+                date: date!(2023 - 01 - 01)
+            }))
+        );
+
+        let doc = Document::from_reader(&b"#UB -1 1930 1.00\n"[..]).unwrap();
+        assert_eq!(doc.restate(&rates), Err(RestateError::MissingYear(-1)));
+    }
+}
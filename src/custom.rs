@@ -0,0 +1,259 @@
+//! Items that aren't part of SIE4, such as the extensions of some
+//! programs, read into types of your own.
+//!
+//! Items with unknown labels fail to parse. To read them instead, add
+//! their labels to a [`Registry`] and give it to the reader (see
+//! [`Reader::custom_items`]). They are then read as [`Custom`] items,
+//! with their fields as text, and written back as they were. Types that
+//! implement [`CustomItem`] can be read from and converted into them.
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use sie4::{
+//!     custom::{Custom, CustomItem, Registry, Value},
+//!     item::{Group, Item},
+//!     Reader,
+//! };
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Project {
+//!     code: String,
+//!     name: String,
+//! }
+//!
+//! impl CustomItem for Project {
+//!     const LABEL: &'static str = "FORTNOXPROJ";
+//!     const GROUP: Group = Group::Account;
+//!     type Error = &'static str;
+//!
+//!     fn from_fields(fields: &[Value]) -> Result<Self, Self::Error> {
+//!         match fields {
+//!             [Value::Text(code), Value::Text(name)] => Ok(Self {
+//!                 code: code.clone(),
+//!                 name: name.clone(),
+//!             }),
+//!             _ => Err("expected a code and a name"),
+//!         }
+//!     }
+//!
+//!     fn to_fields(&self) -> Vec<Value> {
+//!         vec![Value::Text(self.code.clone()), Value::Text(self.name.clone())]
+//!     }
+//! }
+//!
+//! let data = b"#FLAGGA 0\n#FORTNOXPROJ P1 \"New office\"\n#KONTO 1930 Bank\n";
+//! let registry = Arc::new(Registry::new().register::<Project>());
+//! let items = Reader::new(&data[..])
+//!     .custom_items(registry)
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//!
+//! let Item::Custom(custom) = &items[1] else {
+//!     panic!("expected a custom item");
+//! };
+//! let project = custom.get::<Project>().unwrap().unwrap();
+//! assert_eq!(project.name, "New office");
+//! assert_eq!(Custom::new(&project).to_string(), "#FORTNOXPROJ \"P1\" \"New office\"");
+//! ```
+//!
+//! [`Reader::custom_items`]: crate::Reader::custom_items
+
+use std::{collections::HashMap, fmt};
+
+use serde::Serialize;
+
+use crate::{
+    item::{Fields, Group},
+    tokens::Record,
+    writer,
+};
+
+/// A field of a [`Custom`] item.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(untagged)]
+pub enum Value {
+    /// Text, with or without quotes.
+    Text(String),
+    /// A list in curly braces, e.g. an object list (`{1 "456"}`).
+    List(Vec<String>),
+}
+
+/// An item with a label from a [`Registry`]. Sub-entries (in curly
+/// braces on the following lines) aren't supported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Custom {
+    /// Without the `#`.
+    pub label: &'static str,
+    #[serde(skip)]
+    pub group: Group,
+    pub fields: Vec<Value>,
+}
+
+impl Custom {
+    /// The item of `item`, to write it.
+    #[must_use]
+    pub fn new<T: CustomItem>(item: &T) -> Self {
+        Self {
+            label: T::LABEL,
+            group: T::GROUP,
+            fields: item.to_fields(),
+        }
+    }
+
+    /// Read the item as a `T`, unless it has another label.
+    #[must_use]
+    pub fn get<T: CustomItem>(&self) -> Option<Result<T, T::Error>> {
+        (self.label == T::LABEL).then(|| T::from_fields(&self.fields))
+    }
+}
+
+/// See [`Item`](crate::Item#impl-Display-for-Item).
+impl fmt::Display for Custom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writer::display(self.label, self, f)
+    }
+}
+
+/// A type read from [`Custom`] items, see [`Custom::get`].
+pub trait CustomItem: Sized {
+    /// The label, without the `#`, e.g. `FORTNOXPROJ`.
+    const LABEL: &'static str;
+
+    /// The group that the item is in, see [`Group`].
+    const GROUP: Group;
+
+    type Error;
+
+    /// Read the item from its fields.
+    ///
+    /// # Errors
+    ///
+    /// If the fields aren't of this item.
+    fn from_fields(fields: &[Value]) -> Result<Self, Self::Error>;
+
+    /// The fields to write.
+    fn to_fields(&self) -> Vec<Value>;
+}
+
+/// The labels of the custom items to read, see the [module](self).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Registry {
+    labels: HashMap<&'static str, Group>,
+}
+
+impl Registry {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read the items of `T`.
+    #[must_use]
+    pub fn register<T: CustomItem>(self) -> Self {
+        self.label(T::LABEL, T::GROUP)
+    }
+
+    /// Read the items labeled `label` (without the `#`), in `group`,
+    /// without a type of their own.
+    ///
+    /// # Panics
+    ///
+    /// If the label is of an item of SIE4.
+    #[must_use]
+    pub fn label(mut self, label: &'static str, group: Group) -> Self {
+        assert!(
+            Group::of_label(label.as_bytes()).is_none(),
+            "#{label} is an item of SIE4"
+        );
+        self.labels.insert(label, group);
+        self
+    }
+
+    /// The label and group of the items labeled `label`, if registered.
+    fn get(&self, label: &str) -> Option<(&'static str, Group)> {
+        self.labels
+            .get_key_value(label)
+            .map(|(&label, &group)| (label, group))
+    }
+}
+
+/// Decode `record`, with the `fields` left of it, if its label is in
+/// `registry`.
+pub(crate) fn decode(record: &Record, registry: &Registry, fields: &mut Fields) -> Option<Custom> {
+    let (label, group) = registry.get(record.label)?;
+    let values = fields.values();
+    fields.end();
+    Some(Custom {
+        label,
+        group,
+        fields: values,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{reader, Item, ItemRef, Reader, SliceReader, Writer};
+
+    #[test]
+    fn read_and_write() {
+        let data = b"#FLAGGA 0
+#PROGRAM Fortnox 3.0
+#FORTNOXEXT 1 {1 \"Sales dept\"} \"\"
+#KONTO 1930 Bank
+";
+        let registry = Arc::new(Registry::new().label("FORTNOXEXT", Group::Identification));
+
+        let items = Reader::new(&data[..])
+            .custom_items(Arc::clone(&registry))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let Item::Custom(custom) = &items[2] else {
+            panic!("expected a custom item");
+        };
+        assert_eq!(
+            custom.fields,
+            [
+                Value::Text("1".to_owned()),
+                Value::List(vec!["1".to_owned(), "Sales dept".to_owned()]),
+                Value::Text(String::new()),
+            ]
+        );
+
+        let mut writer = Writer::new(Vec::new());
+        for item in &items {
+            writer.write_item(item).unwrap();
+        }
+        let written = writer.into_inner();
+        let read = Reader::new(&written[..])
+            .custom_items(Arc::clone(&registry))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(read, items);
+        assert_eq!(
+            custom.to_string(),
+            "#FORTNOXEXT \"1\" {\"1\" \"Sales dept\"} \"\""
+        );
+
+        let items = SliceReader::new(data)
+            .custom_items(registry)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(matches!(&items[2], ItemRef::Custom(item) if item == custom));
+
+        // the labels must be registered
+        assert!(matches!(
+            Reader::new(&data[..]).nth(2),
+            Some(Err(reader::Error::Parse(_)))
+        ));
+        // and in order
+        let registry = Arc::new(Registry::new().label("FORTNOXEXT", Group::Balance));
+        assert!(Reader::new(&data[..])
+            .custom_items(registry)
+            .strict(true)
+            .any(|item| item.is_err()));
+    }
+}
@@ -0,0 +1,462 @@
+//! Errors and findings rendered with the line of the input that they
+//! are about, with a caret under the offending field, for people to read
+//! rather than programs.
+//!
+//! ```
+//! use sie4::{diagnostics::Diagnostic, validate};
+//!
+//! let data = b"#FLAGGA 0\n#KONTO 19-30 \"Bank\"\n";
+//! let report = validate::validate_bytes(data);
+//! let diagnostic = Diagnostic::from_finding(&report.findings[0]).unwrap();
+//! assert_eq!(
+//!     diagnostic.render(data),
+//!     "error[parse]: invalid or incomplete item `#KONTO`
+//!  --> line 2, column 1
+//!   |
+//! 2 | #KONTO 19-30 \"Bank\"
+//!   | ^^^^^^
+//! "
+//! );
+//! ```
+//!
+//! [`reader::Error`] and [`Finding`] are also [`miette::Diagnostic`]s, with
+//! a label at the field or item that they are about. Miette renders input
+//! as UTF-8, so it's given the input through a [`Source`], which decodes it.
+
+use std::{
+    borrow::Cow,
+    fmt::{self, Write},
+};
+
+use codepage_437::{BorrowFromCp437, CP437_CONTROL};
+use miette::LabeledSpan;
+
+use crate::{
+    parsers::{is_line_break, is_whitespace},
+    reader::{self, Truncation},
+    validate::{Finding, Severity},
+};
+
+/// A message about a line of the input, see the [module](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// What kind of problem it is, e.g. the [id](crate::validate::Rule::id)
+    /// of a rule.
+    pub code: Option<&'static str>,
+    pub message: String,
+    /// Counted from 1.
+    pub line: u32,
+    /// The start of the field to point at, in bytes counted from 1, or
+    /// else the start of the line.
+    pub column: Option<usize>,
+}
+
+impl Diagnostic {
+    /// The diagnostic of a finding, unless it isn't about a line, e.g. a
+    /// missing item.
+    #[must_use]
+    pub fn from_finding(finding: &Finding) -> Option<Self> {
+        Some(Self {
+            severity: finding.severity,
+            code: Some(finding.rule.id()),
+            message: finding.message.clone(),
+            line: finding.line?,
+            column: finding.column,
+        })
+    }
+
+    /// The diagnostic of an error of a reader, unless it isn't about a
+    /// line, e.g. an I/O error.
+    #[must_use]
+    pub fn from_error(e: &reader::Error) -> Option<Self> {
+        let (line, column) = match e {
+            reader::Error::Io(_) | reader::Error::Checksum { .. } => return None,
+            reader::Error::Parse(e) => (e.line, Some(e.column)),
+            reader::Error::InvalidAmount { line, column, .. } => (*line, Some(*column)),
+            reader::Error::InvalidSubEntry { line, .. }
+            | reader::Error::ExtraFields { line, .. }
+            | reader::Error::Truncated(
+                Truncation::Item { line, .. } | Truncation::Ksumma { line, .. },
+            )
+            | reader::Error::ItemTooLarge { line, .. }
+            | reader::Error::OutOfOrder { line, .. } => (*line, None),
+        };
+        let message = match e {
+            reader::Error::Parse(e) => format!("{:?}: `{}`", e.kind, e.snippet),
+            e => e.to_string(),
+        };
+
+        Some(Self {
+            severity: Severity::Error,
+            code: Some(error_code(e)),
+            message,
+            line,
+            column,
+        })
+    }
+
+    /// The diagnostic of a warning of a reader.
+    #[must_use]
+    pub fn from_warning(warning: &reader::Warning) -> Self {
+        let (code, line) = match warning {
+            reader::Warning::OutOfOrder { line, .. } => ("order", *line),
+            reader::Warning::LenientAmount { line, .. } => ("amount", *line),
+            reader::Warning::DroppedQuantity { line, .. } => ("quantity", *line),
+            reader::Warning::ByteOrderMark => ("preamble", 1),
+            reader::Warning::Preamble { line, .. } => ("preamble", *line),
+        };
+
+        Self {
+            severity: Severity::Warning,
+            code: Some(code),
+            message: warning.to_string(),
+            line,
+            column: None,
+        }
+    }
+
+    /// The diagnostic with the line of `source`, the input that it is
+    /// about, and a caret under the field at the column, or the label of
+    /// the item. The line is decoded from code page 437. If it's beyond
+    /// the end of the input, only the message is rendered.
+    #[must_use]
+    pub fn render(&self, source: &[u8]) -> String {
+        let mut s = match self.severity {
+            Severity::Warning => "warning".to_owned(),
+            Severity::Error => "error".to_owned(),
+        };
+        if let Some(code) = self.code {
+            let _ = write!(s, "[{code}]");
+        }
+        let _ = writeln!(s, ": {}", self.message);
+
+        let Some(line) = nth_line(source, self.line) else {
+            return s;
+        };
+        let start = match self.column {
+            Some(column) => column.saturating_sub(1).min(line.len()),
+            None => line.iter().take_while(|&&c| is_whitespace(c)).count(),
+        };
+        let len = field_len(&line[start..]).max(1);
+
+        let no = self.line.to_string();
+        let gutter = " ".repeat(no.len());
+        let _ = writeln!(s, "{gutter}--> line {}, column {}", self.line, start + 1);
+        let _ = writeln!(s, "{gutter} |");
+        let _ = writeln!(s, "{no} | {}", Cow::borrow_from_cp437(line, &CP437_CONTROL));
+        // tabs are kept for the caret to line up
+        let indent = line[..start]
+            .iter()
+            .map(|&c| if c == b'\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        let _ = writeln!(s, "{gutter} | {indent}{}", "^".repeat(len));
+        s
+    }
+}
+
+/// What kind of error `e` is, the code of its [`Diagnostic`].
+fn error_code(e: &reader::Error) -> &'static str {
+    match e {
+        reader::Error::Io(_) => "io",
+        reader::Error::Parse(_) => "parse",
+        reader::Error::InvalidSubEntry { .. } => "sub-entry",
+        reader::Error::InvalidAmount { .. } => "amount",
+        reader::Error::ExtraFields { .. } => "extra-fields",
+        reader::Error::Truncated(_) => "truncated",
+        reader::Error::Checksum { .. } => "checksum",
+        reader::Error::ItemTooLarge { .. } => "too-large",
+        reader::Error::OutOfOrder { .. } => "order",
+    }
+}
+
+/// A label at `offset` in the input, without a length, i.e. at the field
+/// or item there once [widened](Source::annotate).
+fn label_at(offset: u64, label: Option<String>) -> Option<Box<dyn Iterator<Item = LabeledSpan>>> {
+    let offset = usize::try_from(offset).ok()?;
+    Some(Box::new(std::iter::once(LabeledSpan::new(
+        label, offset, 0,
+    ))))
+}
+
+impl miette::Diagnostic for reader::Error {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(error_code(self)))
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(miette::Severity::Error)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let (offset, label) = match self {
+            Self::Io(_) | Self::Checksum { .. } => return None,
+            Self::Parse(e) => (e.offset, format!("{:?}", e.kind)),
+            Self::InvalidSubEntry { offset, .. } => (*offset, "invalid sub-entry".to_owned()),
+            Self::InvalidAmount { offset, .. } => (*offset, "not an amount".to_owned()),
+            Self::ExtraFields { offset, .. } => (*offset, "extra field".to_owned()),
+            Self::Truncated(Truncation::Item { offset, .. }) => (*offset, "not ended".to_owned()),
+            Self::Truncated(Truncation::Ksumma { offset, .. }) => {
+                (*offset, "without a checksum".to_owned())
+            }
+            Self::ItemTooLarge { offset, max, .. } => (*offset, format!("larger than {max} bytes")),
+            Self::OutOfOrder { offset, before, .. } => {
+                (*offset, format!("must appear before `#{before}`"))
+            }
+        };
+        label_at(offset, Some(label))
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Finding {}
+
+impl miette::Diagnostic for Finding {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(self.rule.id()))
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(match self.severity {
+            Severity::Warning => miette::Severity::Warning,
+            Severity::Error => miette::Severity::Error,
+        })
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(self.citation))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        label_at(self.offset?, None)
+    }
+}
+
+/// The input that [`miette::Diagnostic`]s are about, decoded from code
+/// page 437 for miette to render them with.
+#[derive(Debug)]
+pub struct Source<'a> {
+    raw: &'a [u8],
+    text: Cow<'a, str>,
+}
+
+impl<'a> Source<'a> {
+    #[must_use]
+    pub fn new(raw: &'a [u8]) -> Self {
+        Self {
+            raw,
+            text: Cow::borrow_from_cp437(raw, &CP437_CONTROL),
+        }
+    }
+
+    /// `diagnostic` with this source. Its labels are moved from the
+    /// offsets of the input to those of the decoded text, and labels
+    /// without a length are widened to the field at their offset.
+    #[must_use]
+    pub fn annotate<D: miette::Diagnostic>(&self, diagnostic: D) -> Annotated<'_, D> {
+        let labels = diagnostic
+            .labels()
+            .into_iter()
+            .flatten()
+            .map(|label| {
+                let start = label.offset().min(self.raw.len());
+                let len = match label.len() {
+                    0 => field_len(&self.raw[start..]),
+                    len => len,
+                };
+                let end = (start + len).min(self.raw.len());
+                LabeledSpan::new(
+                    label.label().map(str::to_owned),
+                    decoded_len(&self.raw[..start]),
+                    decoded_len(&self.raw[start..end]),
+                )
+            })
+            .collect();
+
+        Annotated {
+            diagnostic,
+            text: &self.text,
+            labels,
+        }
+    }
+}
+
+/// The length of `raw` decoded from code page 437, in bytes of UTF-8.
+fn decoded_len(raw: &[u8]) -> usize {
+    raw.iter()
+        .map(|&c| CP437_CONTROL.decode(c).len_utf8())
+        .sum()
+}
+
+/// A [`miette::Diagnostic`] with its [`Source`], from [`Source::annotate`].
+#[derive(Debug)]
+pub struct Annotated<'a, D> {
+    diagnostic: D,
+    text: &'a Cow<'a, str>,
+    labels: Vec<LabeledSpan>,
+}
+
+impl<D: fmt::Display> fmt::Display for Annotated<'_, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.diagnostic.fmt(f)
+    }
+}
+
+impl<D: std::error::Error> std::error::Error for Annotated<'_, D> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.diagnostic.source()
+    }
+}
+
+impl<D: miette::Diagnostic> miette::Diagnostic for Annotated<'_, D> {
+    fn code<'b>(&'b self) -> Option<Box<dyn fmt::Display + 'b>> {
+        self.diagnostic.code()
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        self.diagnostic.severity()
+    }
+
+    fn help<'b>(&'b self) -> Option<Box<dyn fmt::Display + 'b>> {
+        self.diagnostic.help()
+    }
+
+    fn url<'b>(&'b self) -> Option<Box<dyn fmt::Display + 'b>> {
+        self.diagnostic.url()
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(self.text)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(self.labels.iter().cloned()))
+    }
+}
+
+/// Line `n` of `source`, counted from 1, without its line break.
+fn nth_line(source: &[u8], n: u32) -> Option<&[u8]> {
+    let mut lines = source.split(|&c| c == b'\n');
+    let line = lines.nth(usize::try_from(n).ok()?.checked_sub(1)?)?;
+    // the last line is empty if the input ends with a line break
+    if line.is_empty() && lines.next().is_none() {
+        return None;
+    }
+    Some(line.strip_suffix(b"\r").unwrap_or(line))
+}
+
+/// The length of the field at the start of `s`, including the quotes or
+/// braces around it.
+fn field_len(s: &[u8]) -> usize {
+    let close = match s.first() {
+        Some(b'"') => b'"',
+        Some(b'{') => b'}',
+        _ => {
+            return s
+                .iter()
+                .take_while(|&&c| !is_whitespace(c) && !is_line_break(c))
+                .count()
+        }
+    };
+    s.iter()
+        .skip(1)
+        .position(|&c| c == close)
+        .map_or(s.len(), |end| end + 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Document, Reader};
+
+    #[test]
+    fn fields() {
+        let data = b"#FLAGGA 0\r\n#VER A 1 20230101\r\n{\r\n\t#TRANS 1930 {1 \"Nord\"} 1.0x \"\xe5\"\r\n}\r\n";
+        let e = Document::from_reader(&data[..]).unwrap_err();
+        let diagnostic = Diagnostic::from_error(&e).unwrap();
+        assert_eq!(diagnostic.line, 4);
+        assert_eq!(
+            diagnostic.render(data),
+            "error[amount]: invalid amount `1.0x` on line 4, column 25
+ --> line 4, column 25
+  |
+4 | \t#TRANS 1930 {1 \"Nord\"} 1.0x \"σ\"
+  | \t                       ^^^^
+"
+        );
+
+        let pointed = |column| {
+            Diagnostic {
+                column: Some(column),
+                ..diagnostic.clone()
+            }
+            .render(data)
+            .lines()
+            .last()
+            .unwrap()
+            .to_owned()
+        };
+        assert_eq!(pointed(14), "  | \t            ^^^^^^^^^^");
+        assert_eq!(pointed(30), "  | \t                            ^^^");
+
+        let mut reader = Reader::new(&b"#FLAGGA 0\n#UB 0 1930 1000,00\n"[..]).lenient_amounts(true);
+        reader.by_ref().for_each(drop);
+        let warning = Diagnostic::from_warning(&reader.warnings()[0]).render(b"");
+        assert_eq!(
+            warning,
+            "warning[amount]: amount `1000,00` on line 2 isn't in the standard format\n"
+        );
+    }
+
+    #[test]
+    fn miette() {
+        let render = |diagnostic: &dyn miette::Diagnostic| {
+            let mut s = String::new();
+            miette::GraphicalReportHandler::new_themed(miette::GraphicalTheme::none())
+                .render_report(&mut s, diagnostic)
+                .unwrap();
+            s
+        };
+
+        let data =
+            b"#FLAGGA 0\n#VER A 1 20230101 \"\x84ta\"\n{\n\t#TRANS 1930 {1 \"\x84\"} 1.0x\n}\n";
+        let e = Document::from_reader(&data[..]).unwrap_err();
+        let source = Source::new(data);
+        // the tab is expanded, and the label is after `ä` in the decoded text
+        assert_eq!(
+            render(&source.annotate(e)),
+            "amount
+
+  x invalid amount `1.0x` on line 4, column 22
+   ,-[4:23]
+ 3 | {
+ 4 |     #TRANS 1930 {1 \"ä\"} 1.0x
+   :                         ^^|^
+   :                           `-- not an amount
+ 5 | }
+   `----
+"
+        );
+
+        let report = crate::validate::validate_bytes(data);
+        assert_eq!(
+            render(&source.annotate(report.findings[0].clone())),
+            "parse
+
+  x invalid amount `1.0x`
+   ,-[4:23]
+ 3 | {
+ 4 |     #TRANS 1930 {1 \"ä\"} 1.0x
+   :                         ^^^^
+ 5 | }
+   `----
+  help: SIE 4B, File format: items, fields and character set
+"
+        );
+    }
+}
@@ -0,0 +1,383 @@
+//! Semantic comparison of two documents.
+//!
+//! ```
+//! use sie4::{diff::diff, Document};
+//!
+//! let old = Document::from_reader(&b"#KONTO 1930 \"Bank\"\n#UB 0 1930 100.00\n"[..]).unwrap();
+//! let new = Document::from_reader(&b"#KONTO 1930 \"Bankkonto\"\n#UB 0 1930 250.00\n"[..]).unwrap();
+//!
+//! let changes = diff(&old, &new);
+//! assert_eq!(changes[0].to_string(), "~ account 1930: \"Bank\" -> \"Bankkonto\"");
+//! assert_eq!(changes[1].to_string(), "~ UB 0 1930: 100.00 -> 250.00");
+//! ```
+
+use std::{collections::BTreeMap, fmt};
+
+use serde::Serialize;
+
+use crate::{
+    amount::Amount,
+    fiscal::FiscalYears,
+    item::{AccountNo, Konto, Ver},
+    Document, Item,
+};
+
+/// The kind of a balance item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum BalanceKind {
+    /// `#IB`
+    Ib,
+    /// `#UB`
+    Ub,
+    /// `#RES`
+    Res,
+}
+
+impl fmt::Display for BalanceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Ib => "IB",
+            Self::Ub => "UB",
+            Self::Res => "RES",
+        })
+    }
+}
+
+/// The fiscal year of a balance. The relative year numbers of two files
+/// needn't refer to the same year, so years are compared by when they
+/// start where the files declare them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BalanceYear {
+    /// The calendar year in which the fiscal year starts, as in
+    /// [`VerId`](crate::series::VerId), from its `#RAR`.
+    Starting(i32),
+    /// The year number of the balance, e.g. `-1`, since the file declares
+    /// no `#RAR` for it.
+    Relative(i32),
+}
+
+impl BalanceYear {
+    fn new(year: i32, years: &FiscalYears) -> Self {
+        years.get(year).map_or(Self::Relative(year), |year| {
+            Self::Starting(year.start.year())
+        })
+    }
+}
+
+impl fmt::Display for BalanceYear {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Starting(year) | Self::Relative(year) => write!(f, "{year}"),
+        }
+    }
+}
+
+/// A difference between two documents.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "change", rename_all = "kebab-case")]
+pub enum Change<'a> {
+    AccountAdded(&'a Konto),
+    AccountRemoved(&'a Konto),
+    AccountRenamed {
+        no: AccountNo,
+        old: &'a str,
+        new: &'a str,
+    },
+    VoucherAdded(&'a Ver),
+    VoucherRemoved(&'a Ver),
+    /// A verification with the same [`VerId`](crate::series::VerId)
+    /// differs in any other way, e.g. its transactions.
+    VoucherChanged {
+        old: &'a Ver,
+        new: &'a Ver,
+    },
+    /// A balance was added (`old` is `None`), removed (`new` is `None`)
+    /// or changed.
+    BalanceChanged {
+        kind: BalanceKind,
+        year: BalanceYear,
+        account: AccountNo,
+        old: Option<Amount>,
+        new: Option<Amount>,
+    },
+}
+
+impl fmt::Display for Change<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn voucher(f: &mut fmt::Formatter<'_>, ver: &Ver) -> fmt::Result {
+            write!(f, "voucher {} {} {}", ver.series, ver.no, ver.date)?;
+            if let Some(text) = &ver.text {
+                write!(f, " {text:?}")?;
+            }
+            Ok(())
+        }
+
+        fn balance(b: Option<Amount>) -> String {
+            b.map_or_else(|| "none".to_owned(), |b| b.to_string())
+        }
+
+        match self {
+            Self::AccountAdded(konto) => write!(f, "+ account {} {:?}", konto.no, konto.name),
+            Self::AccountRemoved(konto) => write!(f, "- account {} {:?}", konto.no, konto.name),
+            Self::AccountRenamed { no, old, new } => {
+                write!(f, "~ account {no}: {old:?} -> {new:?}")
+            }
+            Self::VoucherAdded(ver) => {
+                f.write_str("+ ")?;
+                voucher(f, ver)
+            }
+            Self::VoucherRemoved(ver) => {
+                f.write_str("- ")?;
+                voucher(f, ver)
+            }
+            Self::VoucherChanged { new, .. } => {
+                f.write_str("~ ")?;
+                voucher(f, new)
+            }
+            Self::BalanceChanged {
+                kind,
+                year,
+                account,
+                old,
+                new,
+            } => write!(
+                f,
+                "~ {kind} {year} {account}: {} -> {}",
+                balance(*old),
+                balance(*new)
+            ),
+        }
+    }
+}
+
+/// Compare two documents, returning the changes from `old` to `new`.
+///
+/// Accounts are matched by number, verifications by their
+/// [`VerId`](crate::series::VerId), so that vouchers that reuse a series
+/// and number in another fiscal year aren't compared, and balances by
+/// kind, fiscal year (see [`BalanceYear`]) and account. Vouchers that
+/// share an identifier, and balances that are repeated, are matched in
+/// order, so that a duplicate one is added or removed; see
+/// [`Document::dedup_balances`] to merge balances first. The changes are
+/// ordered by accounts, then verifications and finally balances.
+#[must_use]
+pub fn diff<'a>(old: &'a Document, new: &'a Document) -> Vec<Change<'a>> {
+    let mut changes = Vec::new();
+
+    let accounts = |doc: &'a Document| {
+        doc.accounts()
+            .map(|konto| (konto.no, konto))
+            .collect::<BTreeMap<_, _>>()
+    };
+    for (_, pair) in join(accounts(old), accounts(new)) {
+        match pair {
+            (Some(old), None) => changes.push(Change::AccountRemoved(old)),
+            (None, Some(new)) => changes.push(Change::AccountAdded(new)),
+            (Some(old), Some(new)) if old.name != new.name => {
+                changes.push(Change::AccountRenamed {
+                    no: new.no,
+                    old: &old.name,
+                    new: &new.name,
+                });
+            }
+            _ => (),
+        }
+    }
+
+    // duplicates are told apart by their order
+    let vouchers = |doc: &'a Document| {
+        doc.vouchers_by_id()
+            .into_iter()
+            .flat_map(|(id, vers)| {
+                vers.into_iter()
+                    .enumerate()
+                    .map(move |(i, ver)| ((id.clone(), i), ver))
+            })
+            .collect::<BTreeMap<_, _>>()
+    };
+    for (_, pair) in join(vouchers(old), vouchers(new)) {
+        match pair {
+            (Some(old), None) => changes.push(Change::VoucherRemoved(old)),
+            (None, Some(new)) => changes.push(Change::VoucherAdded(new)),
+            (Some(old), Some(new)) if old != new => {
+                changes.push(Change::VoucherChanged { old, new });
+            }
+            _ => (),
+        }
+    }
+
+    for ((kind, year, account, _), (old, new)) in join(balances(old), balances(new)) {
+        if old != new {
+            changes.push(Change::BalanceChanged {
+                kind,
+                year,
+                account,
+                old,
+                new,
+            });
+        }
+    }
+
+    changes
+}
+
+/// The balances of `doc`, with the number of earlier balances of the
+/// same kind, year and account.
+fn balances(doc: &Document) -> BTreeMap<(BalanceKind, BalanceYear, AccountNo, usize), Amount> {
+    let years = doc.fiscal_years();
+    let mut balances = BTreeMap::new();
+    let mut counts = BTreeMap::<_, usize>::new();
+    for item in doc.items() {
+        let (kind, year, account, balance) = match item {
+            Item::Ib(ib) => (BalanceKind::Ib, ib.year, ib.account, ib.balance),
+            Item::Ub(ub) => (BalanceKind::Ub, ub.year, ub.account, ub.balance),
+            Item::Res(res) => (BalanceKind::Res, res.year, res.account, res.balance),
+            _ => continue,
+        };
+        let key = (kind, BalanceYear::new(year, &years), account);
+        let count = counts.entry(key).or_default();
+        balances.insert((key.0, key.1, key.2, *count), balance);
+        *count += 1;
+    }
+    balances
+}
+
+/// Full outer join of two maps.
+fn join<K: Ord, V>(
+    mut old: BTreeMap<K, V>,
+    new: BTreeMap<K, V>,
+) -> BTreeMap<K, (Option<V>, Option<V>)> {
+    let mut joined = BTreeMap::new();
+    for (k, new) in new {
+        let old = old.remove(&k);
+        joined.insert(k, (old, Some(new)));
+    }
+    for (k, old) in old {
+        joined.insert(k, (Some(old), None));
+    }
+    joined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rust_decimal_macros::dec;
+
+    fn doc(data: &[u8]) -> Document {
+        Document::from_reader(data).unwrap()
+    }
+
+    #[test]
+    fn unchanged() {
+        let data = b"#KONTO 1930 \"Bank\"\n#IB 0 1930 1.00\n#VER A 1 20230101\n{\n#TRANS 1930 {} 1.00\n}\n";
+        assert_eq!(diff(&doc(data), &doc(data)), []);
+    }
+
+    #[test]
+    fn duplicate_vouchers() {
+        let old = doc(b"#VER A 1 20230101 \"Sale\"\n{\n}\n");
+        let new = doc(b"#VER A 1 20230101 \"Sale\"\n{\n}\n#VER A 1 20230105 \"Copy\"\n{\n}\n");
+
+        let changes = diff(&old, &new)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(changes, ["+ voucher A 1 2023-01-05 \"Copy\""]);
+        assert!(
+            matches!(&diff(&new, &old)[..], [Change::VoucherRemoved(ver)] if ver.text.as_deref() == Some("Copy"))
+        );
+    }
+
+    #[test]
+    fn balance_years() {
+        // the same year, numbered from another current year
+        let old = doc(b"#RAR 0 20230101 20231231\n#RAR -1 20220101 20221231\n#UB 0 1930 100.00\n#UB -1 1930 50.00\n");
+        let new = doc(b"#RAR 0 20240101 20241231\n#RAR -1 20230101 20231231\n#UB 0 1930 200.00\n#UB -1 1930 100.00\n");
+        let changes = diff(&old, &new)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                "~ UB 2022 1930: 50.00 -> none",
+                "~ UB 2024 1930: none -> 200.00"
+            ]
+        );
+
+        let old = doc(b"#UB 0 1930 100.00\n");
+        let new = doc(b"#UB 0 1930 100.00\n#UB 0 1930 50.00\n");
+        assert!(matches!(
+            &diff(&old, &new)[..],
+            [Change::BalanceChanged {
+                year: BalanceYear::Relative(0),
+                old: None,
+                new: Some(b),
+                ..
+            }] if *b == dec!(50.00)
+        ));
+    }
+
+    #[test]
+    fn changes() {
+        let old = doc(b"#KONTO 1510 \"Kundfordringar\"
+#KONTO 1930 \"Bank\"
+#IB 0 1930 100.00
+#RES 0 3001 -50.00
+#VER A 1 20230101 \"Sale\"
+{
+#TRANS 1930 {} 50.00
+#TRANS 3001 {} -50.00
+}
+#VER A 2 20230102
+{
+}
+");
+        let new = doc(b"#KONTO 1930 \"Bank\"
+#KONTO 3001 \"F\x94rs\x84ljning\"
+#IB 0 1930 100.00
+#UB 0 1930 150.00
+#VER A 1 20230101 \"Sale\"
+{
+#TRANS 1930 {} 60.00
+#TRANS 3001 {} -60.00
+}
+#VER B 1 20230103
+{
+}
+#VER A 2 20240102
+{
+}
+");
+        let changes = diff(&old, &new)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            changes,
+            [
+                "- account 1510 \"Kundfordringar\"",
+                "+ account 3001 \"Försäljning\"",
+                "~ voucher A 1 2023-01-01 \"Sale\"",
+                "- voucher A 2 2023-01-02",
+                "+ voucher B 1 2023-01-03",
+                "+ voucher A 2 2024-01-02",
+                "~ UB 0 1930: none -> 150.00",
+                "~ RES 0 3001: -50.00 -> none",
+            ]
+        );
+
+        assert!(matches!(
+            diff(&old, &new)[6],
+            Change::BalanceChanged {
+                kind: BalanceKind::Ub,
+                old: None,
+                new: Some(b),
+                ..
+            } if b == dec!(150.00)
+        ));
+    }
+}
@@ -0,0 +1,384 @@
+//! The hierarchy of dimensions.
+//!
+//! Objects, e.g. cost centres or projects, belong to dimensions declared
+//! with `#DIM`. A dimension declared with `#UNDERDIM` is part of another
+//! dimension, which may in turn be part of a third.
+//!
+//! ```
+//! use sie4::Document;
+//!
+//! let doc = Document::from_reader(&b"#DIM 1 \"Kostnadsst\x84lle\"
+//! #UNDERDIM 2 \"Kostnadsb\x84rare\" 1
+//! #UNDERDIM 21 \"Produkt\" 2
+//! "[..])
+//! .unwrap();
+//!
+//! let dimensions = doc.dimension_tree().unwrap();
+//! assert_eq!(dimensions.ancestors(21).collect::<Vec<_>>(), [2, 1]);
+//! assert_eq!(dimensions.roots().collect::<Vec<_>>(), [1]);
+//! ```
+//!
+//! The dimensions numbered below 20 are reserved by the specification,
+//! some of them for the uses in [`STANDARD`], which files often leave
+//! undeclared.
+//!
+//! ```
+//! use sie4::Document;
+//!
+//! let doc = Document::from_reader(&b"#OBJEKT 6 \"P1\" \"Nybygge\"\n"[..]).unwrap();
+//!
+//! let dimensions = doc.dimension_tree().unwrap().with_standard();
+//! assert_eq!(dimensions.get(6).unwrap().name, "Projekt");
+//! assert_eq!(dimensions.object(6, "P1"), Some("Nybygge"));
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::item::{Dim, Objekt, UnderDim};
+
+/// The dimensions reserved by the specification for a particular use,
+/// with their names and the dimensions that they are part of. The rest of
+/// the dimensions below 20 are reserved for future use.
+pub const STANDARD: [(u32, &str, Option<u32>); 7] = [
+    (1, "Kostnadsställe/resultatenhet", None),
+    (2, "Kostnadsbärare", Some(1)),
+    (6, "Projekt", None),
+    (7, "Anställd", None),
+    (8, "Kund", None),
+    (9, "Leverantör", None),
+    (10, "Faktura", None),
+];
+
+/// The first dimension that isn't reserved.
+pub const FIRST_FREE: u32 = 20;
+
+/// A dimension in [`Dimensions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dimension {
+    pub no: u32,
+    pub name: String,
+    /// The dimension that this is part of, if it's declared with
+    /// `#UNDERDIM`.
+    pub parent: Option<u32>,
+}
+
+impl Dimension {
+    /// The dimension `no` as reserved by the specification, see
+    /// [`STANDARD`].
+    #[must_use]
+    pub fn standard(no: u32) -> Option<Self> {
+        STANDARD
+            .iter()
+            .find(|(standard, ..)| *standard == no)
+            .map(|&(no, name, parent)| Self {
+                no,
+                name: name.to_owned(),
+                parent,
+            })
+    }
+}
+
+/// Dimensions by number, as a forest, and the objects (`#OBJEKT`) of
+/// each. Every parent is declared and there are no cycles.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Dimensions {
+    dimensions: BTreeMap<u32, Dimension>,
+    /// Names by dimension and number.
+    objects: BTreeMap<u32, BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    #[error("dimension {0} is declared more than once")]
+    Redeclared(u32),
+    #[error("dimension {no} is part of dimension {parent}, which isn't declared")]
+    UnknownParent { no: u32, parent: u32 },
+    /// The dimensions, starting with the lowest number, each part of the
+    /// next and the last part of the first.
+    #[error("dimensions {} are part of each other", display_cycle(.0))]
+    Cycle(Vec<u32>),
+}
+
+fn display_cycle(cycle: &[u32]) -> String {
+    cycle
+        .iter()
+        .chain(cycle.first())
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+impl Dimensions {
+    /// Build the hierarchy of `#DIM` and `#UNDERDIM` declarations.
+    /// Identical declarations are only counted once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a dimension is declared twice with different
+    /// names or parents, is part of an undeclared dimension or is
+    /// (indirectly) part of itself.
+    pub fn new<'a>(
+        dims: impl IntoIterator<Item = &'a Dim>,
+        sub_dims: impl IntoIterator<Item = &'a UnderDim>,
+    ) -> Result<Self, Error> {
+        let mut dimensions = BTreeMap::new();
+
+        let all = dims
+            .into_iter()
+            .map(|dim| Dimension {
+                no: dim.no,
+                name: dim.name.clone(),
+                parent: None,
+            })
+            .chain(sub_dims.into_iter().map(|dim| Dimension {
+                no: dim.no,
+                name: dim.name.clone(),
+                parent: Some(dim.parent),
+            }));
+
+        for dimension in all {
+            match dimensions.get(&dimension.no) {
+                Some(existing) if *existing != dimension => {
+                    return Err(Error::Redeclared(dimension.no));
+                }
+                Some(_) => (),
+                None => {
+                    dimensions.insert(dimension.no, dimension);
+                }
+            }
+        }
+
+        let dimensions = Self {
+            dimensions,
+            objects: BTreeMap::new(),
+        };
+        dimensions.check()?;
+        Ok(dimensions)
+    }
+
+    /// The dimensions reserved by the specification, see [`STANDARD`].
+    #[must_use]
+    pub fn standard() -> Self {
+        Self::default().with_standard()
+    }
+
+    /// Add the [`STANDARD`] dimensions that aren't declared, e.g. to name
+    /// the projects (6) of a file that doesn't declare `#DIM 6`.
+    #[must_use]
+    pub fn with_standard(mut self) -> Self {
+        for &(no, ..) in &STANDARD {
+            // an undeclared dimension can't be the ancestor of another, so
+            // this doesn't make a cycle
+            if let Some(dimension) = Dimension::standard(no) {
+                self.dimensions.entry(no).or_insert(dimension);
+            }
+        }
+        self
+    }
+
+    /// Add the objects, which may belong to dimensions that aren't
+    /// declared. The last name of an object declared more than once is
+    /// kept.
+    #[must_use]
+    pub fn with_objects<'a>(mut self, objects: impl IntoIterator<Item = &'a Objekt>) -> Self {
+        for objekt in objects {
+            self.objects
+                .entry(objekt.dimension)
+                .or_default()
+                .insert(objekt.no.clone(), objekt.name.clone());
+        }
+        self
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        for dimension in self.dimensions.values() {
+            let mut path = vec![dimension.no];
+            let mut current = dimension;
+
+            while let Some(parent) = current.parent {
+                if let Some(start) = path.iter().position(|&no| no == parent) {
+                    let mut cycle = path.split_off(start);
+                    let min = cycle.iter().enumerate().min_by_key(|(_, no)| **no);
+                    let min = min.map_or(0, |(i, _)| i);
+                    cycle.rotate_left(min);
+                    return Err(Error::Cycle(cycle));
+                }
+
+                current = self.dimensions.get(&parent).ok_or(Error::UnknownParent {
+                    no: current.no,
+                    parent,
+                })?;
+                path.push(parent);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn get(&self, no: u32) -> Option<&Dimension> {
+        self.dimensions.get(&no)
+    }
+
+    #[must_use]
+    pub fn contains(&self, no: u32) -> bool {
+        self.dimensions.contains_key(&no)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Dimension> {
+        self.dimensions.values()
+    }
+
+    /// Dimensions that aren't part of another.
+    pub fn roots(&self) -> impl Iterator<Item = u32> + '_ {
+        self.iter()
+            .filter(|dimension| dimension.parent.is_none())
+            .map(|dimension| dimension.no)
+    }
+
+    /// Dimensions that are directly part of `no`.
+    pub fn children(&self, no: u32) -> impl Iterator<Item = u32> + '_ {
+        self.iter()
+            .filter(move |dimension| dimension.parent == Some(no))
+            .map(|dimension| dimension.no)
+    }
+
+    /// The dimensions that are part of `no`, directly or not, depth
+    /// first.
+    pub fn descendants(&self, no: u32) -> impl Iterator<Item = u32> + '_ {
+        let mut stack = self.children(no).collect::<Vec<_>>();
+        stack.reverse();
+        std::iter::from_fn(move || {
+            let next = stack.pop()?;
+            let len = stack.len();
+            stack.extend(self.children(next));
+            stack[len..].reverse();
+            Some(next)
+        })
+    }
+
+    /// The name of the object `no` of `dimension`.
+    #[must_use]
+    pub fn object(&self, dimension: u32, no: &str) -> Option<&str> {
+        self.objects.get(&dimension)?.get(no).map(String::as_str)
+    }
+
+    /// The numbers and names of the objects of `dimension`, by number.
+    pub fn objects(&self, dimension: u32) -> impl Iterator<Item = (&str, &str)> {
+        self.objects
+            .get(&dimension)
+            .into_iter()
+            .flatten()
+            .map(|(no, name)| (no.as_str(), name.as_str()))
+    }
+
+    /// The parent of `no`, its parent and so on, up to a root.
+    pub fn ancestors(&self, no: u32) -> impl Iterator<Item = u32> + '_ {
+        std::iter::successors(self.get(no).and_then(|d| d.parent), |&no| {
+            self.get(no).and_then(|d| d.parent)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dim(no: u32) -> Dim {
+        Dim {
+            no,
+            name: format!("Dim {no}"),
+        }
+    }
+
+    fn sub(no: u32, parent: u32) -> UnderDim {
+        UnderDim {
+            no,
+            name: format!("Dim {no}"),
+            parent,
+        }
+    }
+
+    #[test]
+    fn hierarchy() {
+        let dims = [dim(1), dim(6), dim(1)];
+        let subs = [sub(2, 1), sub(21, 2), sub(22, 2)];
+        let dimensions = Dimensions::new(&dims, &subs).unwrap();
+
+        assert_eq!(dimensions.roots().collect::<Vec<_>>(), [1, 6]);
+        assert_eq!(dimensions.children(2).collect::<Vec<_>>(), [21, 22]);
+        assert_eq!(dimensions.ancestors(22).collect::<Vec<_>>(), [2, 1]);
+        assert_eq!(dimensions.ancestors(6).count(), 0);
+        assert!(!dimensions.contains(7));
+
+        assert_eq!(
+            Dimensions::new(&dims, &[sub(1, 6)]),
+            Err(Error::Redeclared(1))
+        );
+        assert_eq!(
+            Dimensions::new(&dims, &[sub(2, 3)]),
+            Err(Error::UnknownParent { no: 2, parent: 3 })
+        );
+    }
+
+    #[test]
+    fn standard() {
+        let dimensions = Dimensions::new(&[dim(6)], &[sub(21, 6)])
+            .unwrap()
+            .with_standard();
+        assert_eq!(dimensions.get(6).unwrap().name, "Dim 6");
+        assert_eq!(dimensions.get(7).unwrap().name, "Anställd");
+        assert_eq!(dimensions.ancestors(2).collect::<Vec<_>>(), [1]);
+        assert!(!dimensions.contains(3));
+        assert_eq!(dimensions.roots().collect::<Vec<_>>(), [1, 6, 7, 8, 9, 10]);
+
+        let dimensions = Dimensions::new(&[dim(1)], &[sub(20, 1), sub(22, 21), sub(21, 20)])
+            .unwrap()
+            .with_standard();
+        assert_eq!(
+            dimensions.descendants(1).collect::<Vec<_>>(),
+            [2, 20, 21, 22]
+        );
+        assert_eq!(Dimensions::standard().iter().count(), STANDARD.len());
+    }
+
+    #[test]
+    fn objects() {
+        let objekt = |dimension, no: &str, name: &str| Objekt {
+            dimension,
+            no: no.to_owned(),
+            name: name.to_owned(),
+        };
+        let dimensions = Dimensions::default().with_objects(&[
+            objekt(1, "10", "Stockholm"),
+            objekt(1, "02", "Göteborg"),
+            objekt(6, "10", "Nybygge"),
+            objekt(1, "10", "Solna"),
+        ]);
+
+        assert_eq!(dimensions.object(1, "10"), Some("Solna"));
+        assert_eq!(dimensions.object(6, "10"), Some("Nybygge"));
+        assert_eq!(dimensions.object(7, "10"), None);
+        assert_eq!(
+            dimensions.objects(1).collect::<Vec<_>>(),
+            [("02", "Göteborg"), ("10", "Solna")]
+        );
+        assert_eq!(dimensions.objects(2).count(), 0);
+    }
+
+    #[test]
+    fn cycles() {
+        let err =
+            Dimensions::new(&[dim(1)], &[sub(2, 1), sub(5, 3), sub(3, 4), sub(4, 5)]).unwrap_err();
+        assert_eq!(err, Error::Cycle(vec![3, 4, 5]));
+        assert_eq!(
+            err.to_string(),
+            "dimensions 3 -> 4 -> 5 -> 3 are part of each other"
+        );
+
+        assert_eq!(
+            Dimensions::new(&[], &[sub(7, 7)]),
+            Err(Error::Cycle(vec![7]))
+        );
+    }
+}
@@ -0,0 +1,1335 @@
+//! An entire SIE4 file read into memory.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    io::Read,
+    sync::Arc,
+};
+
+use rust_decimal::Decimal;
+
+use serde::Serialize;
+use time::Date;
+
+use crate::{
+    amount::Amount,
+    chart::{Chart, Conflict},
+    dimension::{self, Dimensions},
+    filter::{add, Sum},
+    fiscal::{FiscalYear, FiscalYears},
+    item::{
+        AccountKind, AccountNo, Dim, Ib, ItemType, Konto, Ktyp, Objekt, Oib, Oub, PBudget, PSaldo,
+        Res, Trans, Ub, UnderDim, Ver,
+    },
+    reader, Item, ItemRef, Reader, SliceReader,
+};
+
+/// All items of a file, in the order they were read.
+///
+/// ```
+/// use sie4::Document;
+///
+/// let data = b"#FLAGGA 0
+/// #KONTO 1930 \"Bank\"
+/// #KONTO 2440 \"Leverant\x94rsskulder\"
+/// #VER A 1 20230102 \"Rent\"
+/// {
+///     #TRANS 1930 {} -12000.00
+///     #TRANS 2440 {} 12000.00
+/// }
+/// ";
+///
+/// let mut doc = Document::from_reader(&data[..]).unwrap();
+/// assert_eq!(doc.accounts().count(), 2);
+///
+/// // the account is still referenced by the verification
+/// assert!(doc.remove_account(2440).is_err());
+///
+/// doc.retain_vouchers(|ver| ver.text.as_deref() != Some("Rent"));
+/// assert_eq!(doc.remove_account(2440).unwrap().name, "Leverantörsskulder");
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Document {
+    items: Vec<Item>,
+    #[serde(skip)]
+    chart: Option<Arc<Chart>>,
+    #[serde(skip)]
+    dates: DatePolicy,
+    #[serde(skip)]
+    removed: bool,
+}
+
+/// Which date of a transaction is authoritative for reporting, e.g. what
+/// period or fiscal year it belongs to. The policy of a document (see
+/// [`Document::set_date_policy`]) is used by [`Document::balance_as_of`],
+/// the budget alerts, validation and the exports.
+///
+/// ```
+/// use sie4::{document::DatePolicy, Document};
+/// use time::macros::date;
+///
+/// let doc = Document::from_reader(&b"#VER A 1 20230131 \"\" 20230201
+/// {
+///     #TRANS 1930 {} 100.00 20230130
+/// }
+/// "[..])
+/// .unwrap();
+/// let ver = doc.vouchers().next().unwrap();
+/// let trans = ver.transactions.0[0].trans();
+///
+/// assert_eq!(DatePolicy::Transaction.date(ver, trans), date!(2023 - 01 - 30));
+/// assert_eq!(DatePolicy::Voucher.date(ver, trans), date!(2023 - 01 - 31));
+/// assert_eq!(DatePolicy::Registration.date(ver, trans), date!(2023 - 02 - 01));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DatePolicy {
+    /// The date of the transaction, if it has one, or else that of the
+    /// verification.
+    #[default]
+    Transaction,
+    /// The date of the verification.
+    Voucher,
+    /// The registration date of the verification, if it has one, or else
+    /// its date.
+    Registration,
+}
+
+impl DatePolicy {
+    /// The date of `trans`, which is a transaction of `ver`.
+    #[must_use]
+    pub fn date(self, ver: &Ver, trans: &Trans) -> Date {
+        match self {
+            Self::Transaction => trans.date.unwrap_or(ver.date),
+            Self::Voucher => ver.date,
+            Self::Registration => ver.reg_date.unwrap_or(ver.date),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("account {0} does not exist")]
+    AccountNotFound(AccountNo),
+    /// Removing the account would leave dangling references to it.
+    #[error("account {account} is referenced by {references} items")]
+    AccountReferenced {
+        account: AccountNo,
+        references: usize,
+    },
+    #[error("{0}")]
+    DuplicateBalance(DuplicateBalance),
+    /// No fiscal year (`#RAR`) contains the date.
+    #[error("{0} is not in any fiscal year")]
+    OutsideFiscalYears(Date),
+}
+
+/// What [`Document::dedup_balances`] does with balances that are repeated
+/// for the same account and year.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateBalances {
+    /// Fail with [`Error::DuplicateBalance`].
+    #[default]
+    Error,
+    /// Add the duplicates to the first balance, e.g. if a program writes
+    /// the balance of each object as a balance of the whole account.
+    Sum,
+    /// Replace the first balance with the last duplicate.
+    LastWins,
+}
+
+/// A balance (`#IB`, `#UB` or `#RES`) that appears more than once for
+/// the same account and year.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateBalance {
+    pub label: &'static str,
+    pub year: i32,
+    pub account: AccountNo,
+    /// How many times the balance appears.
+    pub count: usize,
+}
+
+impl fmt::Display for DuplicateBalance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} of account {} for year {} appears {} times",
+            self.label, self.account, self.year, self.count
+        )
+    }
+}
+
+/// The types of the accounts of a document, from
+/// [`Document::account_kinds`], to look up many accounts without scanning
+/// the items for each of them.
+#[derive(Debug, Clone, Default)]
+pub struct AccountKinds(HashMap<AccountNo, AccountKind>);
+
+impl AccountKinds {
+    /// See [`Document::account_kind`].
+    #[must_use]
+    pub fn get(&self, no: impl Into<AccountNo>) -> Option<AccountKind> {
+        let no = no.into();
+        self.0
+            .get(&no)
+            .copied()
+            .or_else(|| no.number().and_then(AccountKind::from_bas))
+    }
+}
+
+impl Document {
+    /// Read all items from `reader`. Repeated balances are kept as they
+    /// are, see [`Document::dedup_balances`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error encountered by the [`Reader`].
+    pub fn from_reader(reader: impl Read) -> Result<Self, reader::Error> {
+        Reader::new(reader).collect()
+    }
+
+    /// Read all items from an in-memory file with a [`SliceReader`],
+    /// which doesn't need [`std::io`], e.g. for a file that a browser
+    /// has already loaded.
+    ///
+    /// ```
+    /// use sie4::Document;
+    ///
+    /// let doc = Document::from_slice(b"#FLAGGA 0\n#KONTO 1930 \"F\x94retagskonto\"\n").unwrap();
+    /// assert_eq!(doc.account(1930).unwrap().name, "F\u{f6}retagskonto");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error encountered by the [`SliceReader`].
+    pub fn from_slice(buf: &[u8]) -> Result<Self, reader::Error> {
+        SliceReader::new(buf)
+            .map(|res| res.map(ItemRef::into_owned))
+            .collect()
+    }
+
+    /// Read the documents of files concatenated in one stream, see
+    /// [`Reader::next_document`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error encountered by the [`Reader`].
+    pub fn from_concatenated(reader: impl Read) -> Result<Vec<Self>, reader::Error> {
+        let mut reader = Reader::new(reader);
+        std::iter::from_fn(|| reader.next_document()).collect()
+    }
+
+    #[must_use]
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    #[must_use]
+    pub fn into_items(self) -> Vec<Item> {
+        self.items
+    }
+
+    /// The items of type `T`, e.g. `doc.items_of::<Sru>()`. Unlike
+    /// [`Document::accounts`] and [`Document::dimensions`], this leaves
+    /// out the shared chart.
+    pub fn items_of<'a, T>(&'a self) -> impl Iterator<Item = &'a T>
+    where
+        T: ItemType + 'a,
+        &'a T: TryFrom<&'a Item>,
+    {
+        self.items.iter().filter_map(|item| item.try_into().ok())
+    }
+
+    /// All accounts (`#KONTO`), including those of the shared chart.
+    pub fn accounts(&self) -> impl Iterator<Item = &Konto> {
+        let items = self.items_of::<Konto>();
+        self.chart
+            .iter()
+            .flat_map(|chart| chart.accounts())
+            .chain(items)
+    }
+
+    #[must_use]
+    pub fn account(&self, no: impl Into<AccountNo>) -> Option<&Konto> {
+        let no = no.into();
+        self.accounts().find(|konto| konto.no == no)
+    }
+
+    /// The type of an account, as declared with `#KTYP` or else by its
+    /// number in the BAS chart of accounts (see [`AccountKind::from_bas`]).
+    #[must_use]
+    pub fn account_kind(&self, no: impl Into<AccountNo>) -> Option<AccountKind> {
+        let no = no.into();
+        self.items
+            .iter()
+            .find_map(|item| match item {
+                Item::Ktyp(ktyp) if ktyp.account == no => Some(ktyp.kind),
+                _ => None,
+            })
+            .or_else(|| no.number().and_then(AccountKind::from_bas))
+    }
+
+    /// The types of all accounts, as [`Document::account_kind`] would
+    /// return them.
+    #[must_use]
+    pub fn account_kinds(&self) -> AccountKinds {
+        let mut kinds = HashMap::new();
+        for ktyp in self.items_of::<Ktyp>() {
+            // the first one wins, as in `account_kind`
+            kinds.entry(ktyp.account).or_insert(ktyp.kind);
+        }
+        AccountKinds(kinds)
+    }
+
+    /// All dimensions (`#DIM`), including those of the shared chart.
+    pub fn dimensions(&self) -> impl Iterator<Item = &Dim> {
+        let items = self.items_of::<Dim>();
+        self.chart
+            .iter()
+            .flat_map(|chart| chart.dimensions())
+            .chain(items)
+    }
+
+    /// The hierarchy of the dimensions, including those of the shared
+    /// chart, and the sub-dimensions (`#UNDERDIM`), with their objects.
+    /// The [standard](Dimensions::with_standard) dimensions that aren't
+    /// declared are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the declarations don't form a hierarchy (see
+    /// [`Dimensions::new`]).
+    pub fn dimension_tree(&self) -> Result<Dimensions, dimension::Error> {
+        let dimensions = Dimensions::new(self.dimensions(), self.items_of::<UnderDim>())?;
+
+        Ok(dimensions.with_objects(self.items_of::<Objekt>()))
+    }
+
+    /// Use a [`Chart`] shared with other documents for the accounts and
+    /// dimensions. The `#KONTO` and `#DIM` items of the document are
+    /// removed, so [`Document::items`] no longer includes them; the chart
+    /// may also contain accounts that this document doesn't declare.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving the document unchanged, if any of its
+    /// declarations isn't in the chart (see [`Chart::verify`]).
+    pub fn share_chart(&mut self, chart: Arc<Chart>) -> Result<(), Conflict> {
+        chart.verify(self)?;
+        self.items
+            .retain(|item| !matches!(item, Item::Konto(_) | Item::Dim(_)));
+        self.chart = Some(chart);
+        Ok(())
+    }
+
+    /// See [`DatePolicy`].
+    #[must_use]
+    pub fn date_policy(&self) -> DatePolicy {
+        self.dates
+    }
+
+    pub fn set_date_policy(&mut self, policy: DatePolicy) {
+        self.dates = policy;
+    }
+
+    /// Whether the removed transactions (`#BTRANS`) of the vouchers count
+    /// as if they had never been removed, see [`Ver::counted`]. They
+    /// don't by default. The setting is used, like the
+    /// [date policy](DatePolicy), wherever the transactions of the
+    /// vouchers are summed or listed: the recomputed balances,
+    /// [validation](crate::validate::validate), the reports and the
+    /// exports.
+    #[must_use]
+    pub fn include_removed(&self) -> bool {
+        self.removed
+    }
+
+    /// See [`Document::include_removed`].
+    pub fn set_include_removed(&mut self, include: bool) {
+        self.removed = include;
+    }
+
+    /// The chart shared with [`Document::share_chart`], if any.
+    #[must_use]
+    pub fn chart(&self) -> Option<&Arc<Chart>> {
+        self.chart.as_ref()
+    }
+
+    /// The tax year that the file is meant for (`#TAXAR`).
+    #[must_use]
+    pub fn tax_year(&self) -> Option<i32> {
+        self.items.iter().find_map(|item| match item {
+            Item::Taxar(taxar) => Some(taxar.year),
+            _ => None,
+        })
+    }
+
+    /// The date that the balances and results are calculated up to
+    /// (`#OMFATTN`), if they don't cover the whole fiscal year.
+    #[must_use]
+    pub fn balances_until(&self) -> Option<Date> {
+        self.items.iter().find_map(|item| match item {
+            Item::Omfattn(omfattn) => Some(omfattn.date),
+            _ => None,
+        })
+    }
+
+    /// The comment on the file (`#PROSA`).
+    #[must_use]
+    pub fn comment(&self) -> Option<&str> {
+        self.items.iter().find_map(|item| match item {
+            Item::Prosa(prosa) => Some(prosa.text.as_str()),
+            _ => None,
+        })
+    }
+
+    /// The fiscal years declared with `#RAR`.
+    #[must_use]
+    pub fn fiscal_years(&self) -> FiscalYears {
+        self.items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Rar(rar) => Some(FiscalYear::from(rar)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Balances of accounts per period (`#PSALDO`).
+    pub fn period_balances(&self) -> impl Iterator<Item = &PSaldo> {
+        self.items_of::<PSaldo>()
+    }
+
+    /// Budgets of accounts per period (`#PBUDGET`).
+    pub fn period_budgets(&self) -> impl Iterator<Item = &PBudget> {
+        self.items_of::<PBudget>()
+    }
+
+    /// Balance of `account` at the end of `date`: the opening balance
+    /// (`#IB`) of the fiscal year that contains the date, plus the
+    /// transactions of the year up to and including the date.
+    ///
+    /// A missing opening balance counts as zero, as is always the case for
+    /// result accounts. Transactions are dated by their own date, or else
+    /// by the date of their verification, and corrected verifications
+    /// count as corrected (see [`Ver::corrected`]), unless removed
+    /// transactions are [included](Document::include_removed).
+    ///
+    /// ```
+    /// use rust_decimal_macros::dec;
+    /// use sie4::Document;
+    /// use time::macros::date;
+    ///
+    /// let doc = Document::from_reader(&b"#RAR 0 20230101 20231231
+    /// #IB 0 1930 1000.00
+    /// #VER A 1 20230110
+    /// {
+    ///     #TRANS 1930 {} -200.00
+    ///     #TRANS 5010 {} 200.00
+    /// }
+    /// "[..])
+    /// .unwrap();
+    ///
+    /// assert_eq!(doc.balance_as_of(1930, date!(2023 - 01 - 09)).unwrap(), dec!(1000.00));
+    /// assert_eq!(doc.balance_as_of(1930, date!(2023 - 01 - 10)).unwrap(), dec!(800.00));
+    /// assert_eq!(doc.balance_as_of(5010, date!(2023 - 12 - 31)).unwrap(), dec!(200.00));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::OutsideFiscalYears`] if no fiscal year contains
+    /// the date, since the balance would depend on transactions that
+    /// aren't in the document.
+    pub fn balance_as_of(
+        &self,
+        account: impl Into<AccountNo>,
+        date: Date,
+    ) -> Result<Decimal, Error> {
+        let account = account.into();
+        let years = self.fiscal_years();
+        let year = years
+            .containing(date)
+            .ok_or(Error::OutsideFiscalYears(date))?;
+
+        let opening = self
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Ib(ib) if ib.year == year.no && ib.account == account => Some(ib.balance),
+                _ => None,
+            })
+            .sum::<Decimal>();
+        let transactions = self
+            .vouchers()
+            .flat_map(|ver| {
+                ver.counted(self.removed).filter(move |trans| {
+                    trans.account == account
+                        && (year.start..=date).contains(&self.dates.date(ver, trans))
+                })
+            })
+            .map(|trans| trans.amount)
+            .sum::<Decimal>();
+
+        Ok(opening + transactions)
+    }
+
+    /// All verifications (`#VER`).
+    pub fn vouchers(&self) -> impl Iterator<Item = &Ver> {
+        self.items_of::<Ver>()
+    }
+
+    pub fn vouchers_mut(&mut self) -> impl Iterator<Item = &mut Ver> {
+        self.items
+            .iter_mut()
+            .filter_map(|item| item.try_into().ok())
+    }
+
+    /// Add a verification after the last one, or at the end. The
+    /// balances are not updated, see [`Document::recompute_balances`].
+    pub fn add_voucher(&mut self, ver: Ver) {
+        let index = self
+            .items
+            .iter()
+            .rposition(|item| matches!(item, Item::Ver(_)))
+            .map_or(self.items.len(), |index| index + 1);
+        self.items.insert(index, Item::Ver(ver));
+    }
+
+    /// Keep only the verifications for which `f` returns `true`.
+    pub fn retain_vouchers(&mut self, mut f: impl FnMut(&Ver) -> bool) {
+        self.items.retain(|item| match item {
+            Item::Ver(ver) => f(ver),
+            _ => true,
+        });
+    }
+
+    /// Remove the verification with the given series and number.
+    pub fn remove_voucher(&mut self, series: &str, no: u32) -> Option<Ver> {
+        let index = self.items.iter().position(
+            |item| matches!(item, Item::Ver(ver) if ver.series == series && ver.no == no),
+        )?;
+
+        self.items.remove(index).try_into().ok()
+    }
+
+    /// Merge balances (`#IB`, `#UB` and `#RES`) that appear more than
+    /// once for the same account and year into the first of them.
+    /// Returns the duplicates that were merged, e.g. to warn about them.
+    ///
+    /// ```
+    /// use rust_decimal_macros::dec;
+    /// use sie4::{document::DuplicateBalances, item::Item, Document};
+    ///
+    /// let mut doc = Document::from_reader(&b"#UB 0 1930 100.00\n#UB 0 1930 50.00\n"[..]).unwrap();
+    /// let duplicates = doc.dedup_balances(DuplicateBalances::Sum).unwrap();
+    /// assert_eq!(duplicates[0].to_string(), "#UB of account 1930 for year 0 appears 2 times");
+    /// assert!(matches!(doc.items(), [Item::Ub(ub)] if ub.balance == dec!(150.00)));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// With [`DuplicateBalances::Error`], returns the first duplicate as
+    /// [`Error::DuplicateBalance`] and leaves the document unchanged.
+    pub fn dedup_balances(
+        &mut self,
+        policy: DuplicateBalances,
+    ) -> Result<Vec<DuplicateBalance>, Error> {
+        // index of the first balance and the number of duplicates
+        let mut first = HashMap::new();
+        let mut duplicates = Vec::new();
+        for (index, item) in self.items.iter_mut().enumerate() {
+            if let Some(balance) = balance_mut(item) {
+                let (first_index, count) = first.entry(balance.key).or_insert((index, 0));
+                *count += 1;
+                if *first_index != index {
+                    duplicates.push((*first_index, index));
+                }
+            }
+        }
+
+        let mut report = first
+            .into_iter()
+            .filter(|(_, (_, count))| *count > 1)
+            .map(|((label, year, account), (index, count))| {
+                (
+                    index,
+                    DuplicateBalance {
+                        label,
+                        year,
+                        account,
+                        count,
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        report.sort_by_key(|(index, _)| *index);
+        let report = report.into_iter().map(|(_, d)| d).collect::<Vec<_>>();
+
+        if policy == DuplicateBalances::Error {
+            if let Some(duplicate) = report.first() {
+                return Err(Error::DuplicateBalance(duplicate.clone()));
+            }
+        }
+
+        for &(first, duplicate) in &duplicates {
+            let Some(BalanceMut {
+                balance, quantity, ..
+            }) = balance_mut(&mut self.items[duplicate])
+            else {
+                continue;
+            };
+            let (balance, quantity) = (*balance, *quantity);
+            let Some(first) = balance_mut(&mut self.items[first]) else {
+                continue;
+            };
+
+            if policy == DuplicateBalances::Sum {
+                *first.balance += balance;
+                *first.quantity = match (*first.quantity, quantity) {
+                    (Some(a), Some(b)) => Some(a + b),
+                    (a, b) => a.or(b),
+                };
+            } else {
+                *first.balance = balance;
+                *first.quantity = quantity;
+            }
+        }
+
+        let mut index = 0;
+        let mut removed = duplicates
+            .iter()
+            .map(|&(_, duplicate)| duplicate)
+            .peekable();
+        self.items.retain(|_| {
+            let keep = removed.peek() != Some(&index);
+            if !keep {
+                removed.next();
+            }
+            index += 1;
+            keep
+        });
+
+        Ok(report)
+    }
+
+    /// Recompute the balances (`#IB`, `#UB` and `#RES`) from the
+    /// verifications, e.g. after adding or removing some, so that the
+    /// document adds up again:
+    ///
+    /// - the closing balance of each fiscal year is its opening balance
+    ///   plus the transactions of the year, and its result the
+    ///   transactions on result accounts;
+    /// - the opening balance of each year changes as much as the closing
+    ///   balance of the year before, which keeps any difference between
+    ///   them, e.g. the result of the previous year booked to equity.
+    ///
+    /// Balances are added for accounts that have transactions but no
+    /// balances yet, and results without any transactions left are set
+    /// to zero rather than removed. Only the fiscal years declared with
+    /// `#RAR` are recomputed, and transactions are dated by the
+    /// [date policy](Document::date_policy). Accounts are told apart as
+    /// in [`Filter`](crate::filter::Filter). Period balances (`#PSALDO`)
+    /// are left as they are.
+    ///
+    /// Balances that appear more than once are merged into the first,
+    /// see [`Document::dedup_balances`].
+    ///
+    /// ```
+    /// use rust_decimal_macros::dec;
+    /// use sie4::{item::Item, Document};
+    ///
+    /// let mut doc = Document::from_reader(&b"#RAR 0 20230101 20231231
+    /// #RAR -1 20220101 20221231
+    /// #IB 0 1930 1000.00
+    /// #UB 0 1930 1000.00
+    /// #IB -1 1930 1000.00
+    /// #UB -1 1930 1000.00
+    /// "[..])
+    /// .unwrap();
+    ///
+    /// doc.add_voucher(
+    ///     "#VER A 1 20221231\n{\n#TRANS 1930 {} -100.00\n#TRANS 5010 {} 100.00\n}\n"
+    ///         .parse()
+    ///         .unwrap(),
+    /// );
+    /// doc.add_voucher(
+    ///     "#VER A 1 20230110\n{\n#TRANS 1930 {} -200.00\n#TRANS 5010 {} 200.00\n}\n"
+    ///         .parse()
+    ///         .unwrap(),
+    /// );
+    /// doc.recompute_balances();
+    ///
+    /// let balances = doc
+    ///     .items()
+    ///     .iter()
+    ///     .filter_map(|item| match item {
+    ///         Item::Ib(ib) => Some(("IB", ib.year, ib.balance.0)),
+    ///         Item::Ub(ub) => Some(("UB", ub.year, ub.balance.0)),
+    ///         Item::Res(res) => Some(("RES", res.year, res.balance.0)),
+    ///         _ => None,
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     balances,
+    ///     [
+    ///         ("IB", 0, dec!(900.00)),
+    ///         ("UB", 0, dec!(700.00)),
+    ///         ("IB", -1, dec!(1000.00)),
+    ///         ("UB", -1, dec!(900.00)),
+    ///         ("RES", 0, dec!(200.00)),
+    ///         ("RES", -1, dec!(100.00)),
+    ///     ]
+    /// );
+    /// ```
+    pub fn recompute_balances(&mut self) {
+        let mut years = self.fiscal_years().iter().copied().collect::<Vec<_>>();
+        // oldest first, so that changes carry over to the next year
+        years.sort_by_key(|year| year.no);
+
+        let mut computed = self.computed_balances(&years);
+
+        // update the balances in place, dropping duplicates
+        self.items.retain_mut(|item| {
+            let Some(balance) = balance_mut(item) else {
+                return true;
+            };
+            if !years.iter().any(|year| year.no == balance.key.1) {
+                return true;
+            }
+            let Some((amount, quantity)) = computed.remove(&balance.key) else {
+                return false;
+            };
+            *balance.balance = amount;
+            *balance.quantity = quantity;
+            true
+        });
+
+        self.add_balances(&computed);
+    }
+
+    /// The balances of `years`, oldest first, see
+    /// [`Document::recompute_balances`].
+    fn computed_balances(
+        &self,
+        years: &[FiscalYear],
+    ) -> BTreeMap<(&'static str, i32, AccountNo), Sum> {
+        let mut computed = BTreeMap::<(&'static str, i32, AccountNo), Sum>::new();
+        // how much the closing balances of the previous year changed
+        let mut changes = BTreeMap::<AccountNo, Sum>::new();
+        let mut previous = None;
+        let kinds = self.account_kinds();
+
+        for year in years {
+            let mut old = BTreeMap::<(&'static str, AccountNo), Sum>::new();
+            for item in &self.items {
+                let (year_no, account, balance, quantity) = match item {
+                    Item::Ib(ib) => (ib.year, ib.account, ib.balance, ib.quantity),
+                    Item::Ub(ub) => (ub.year, ub.account, ub.balance, ub.quantity),
+                    Item::Res(res) => (res.year, res.account, res.balance, res.quantity),
+                    _ => continue,
+                };
+                if year_no == year.no {
+                    add(
+                        old.entry((item.label(), account)).or_default(),
+                        balance,
+                        quantity,
+                    );
+                }
+            }
+            let has_balance = |account| {
+                old.contains_key(&(Ib::LABEL, account)) || old.contains_key(&(Ub::LABEL, account))
+            };
+
+            let mut opening = BTreeMap::<AccountNo, Sum>::new();
+            let mut closing = BTreeMap::<AccountNo, Sum>::new();
+            let mut results = BTreeMap::<AccountNo, Sum>::new();
+            for (&(label, account), &sum) in &old {
+                match label {
+                    Ib::LABEL => {
+                        opening.insert(account, sum);
+                        closing.entry(account).or_default();
+                    }
+                    Ub::LABEL => {
+                        closing.entry(account).or_default();
+                    }
+                    _ => {
+                        results.entry(account).or_default();
+                    }
+                }
+            }
+            if previous == Some(year.no - 1) {
+                for (&account, &(amount, quantity)) in &changes {
+                    add(opening.entry(account).or_default(), amount, quantity);
+                    closing.entry(account).or_default();
+                }
+            }
+
+            for ver in self.vouchers() {
+                for trans in ver.counted(self.removed) {
+                    if !year.contains(self.dates.date(ver, trans)) {
+                        continue;
+                    }
+                    let balance = kinds
+                        .get(trans.account)
+                        .map_or(has_balance(trans.account), AccountKind::is_balance);
+                    let sums = if balance { &mut closing } else { &mut results };
+                    add(
+                        sums.entry(trans.account).or_default(),
+                        trans.amount,
+                        trans.quantity,
+                    );
+                }
+            }
+
+            changes.clear();
+            for (account, mut sum) in closing {
+                let (amount, quantity) = opening.get(&account).copied().unwrap_or_default();
+                add(&mut sum, amount, quantity);
+
+                let (old_amount, old_quantity) =
+                    old.get(&(Ub::LABEL, account)).copied().unwrap_or_default();
+                let change = (
+                    sum.0 - old_amount,
+                    match (sum.1, old_quantity) {
+                        (Some(a), Some(b)) => Some(a - b),
+                        (a, b) => a.or(b.map(|b| -b)),
+                    },
+                );
+                if change != (Amount::ZERO, None) && change != (Amount::ZERO, Some(Decimal::ZERO)) {
+                    changes.insert(account, change);
+                }
+
+                // a missing opening balance counts as zero
+                if opening.contains_key(&account) {
+                    computed.insert((Ib::LABEL, year.no, account), (amount, quantity));
+                }
+                computed.insert((Ub::LABEL, year.no, account), sum);
+            }
+            for (account, sum) in results {
+                computed.insert((Res::LABEL, year.no, account), sum);
+            }
+            previous = Some(year.no);
+        }
+
+        computed
+    }
+
+    /// Add the balances of `computed` that are missing, in the order that
+    /// they are written.
+    fn add_balances(&mut self, computed: &BTreeMap<(&'static str, i32, AccountNo), Sum>) {
+        for label in [Ib::LABEL, Ub::LABEL, Res::LABEL] {
+            let rank = balance_rank(label);
+            let index = self
+                .items
+                .iter()
+                .rposition(|item| balance_rank(item.label()) <= rank)
+                .map_or_else(
+                    || {
+                        self.items
+                            .iter()
+                            .position(|item| balance_rank(item.label()) < u8::MAX)
+                            .unwrap_or(self.items.len())
+                    },
+                    |index| index + 1,
+                );
+
+            let mut added = computed
+                .iter()
+                .filter(|((l, ..), _)| *l == label)
+                .map(|(&(_, year, account), &(balance, quantity))| {
+                    (
+                        -year,
+                        account,
+                        match label {
+                            Ib::LABEL => Item::Ib(Ib {
+                                year,
+                                account,
+                                balance,
+                                quantity,
+                            }),
+                            Ub::LABEL => Item::Ub(Ub {
+                                year,
+                                account,
+                                balance,
+                                quantity,
+                            }),
+                            _ => Item::Res(Res {
+                                year,
+                                account,
+                                balance,
+                                quantity,
+                            }),
+                        },
+                    )
+                })
+                .collect::<Vec<_>>();
+            // the current year first, as written by most programs
+            added.sort_by_key(|&(year, account, _)| (year, account));
+            self.items
+                .splice(index..index, added.into_iter().map(|(.., item)| item));
+        }
+    }
+
+    /// Remove an account (`#KONTO`), along with its type (`#KTYP`).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AccountReferenced`] if any balance or transaction
+    /// still refers to the account; remove those first.
+    pub fn remove_account(&mut self, no: impl Into<AccountNo>) -> Result<Konto, Error> {
+        let no = no.into();
+        let references = self
+            .items
+            .iter()
+            .map(|item| references_account(item, no))
+            .sum::<usize>();
+
+        if references > 0 {
+            return Err(Error::AccountReferenced {
+                account: no,
+                references,
+            });
+        }
+
+        let index = self
+            .items
+            .iter()
+            .position(|item| matches!(item, Item::Konto(konto) if konto.no == no))
+            .ok_or(Error::AccountNotFound(no))?;
+
+        let Item::Konto(konto) = self.items.remove(index) else {
+            unreachable!()
+        };
+        self.items
+            .retain(|item| !matches!(item, Item::Ktyp(ktyp) if ktyp.account == no));
+        Ok(konto)
+    }
+}
+
+/// A balance item, identified by its label, year and account.
+struct BalanceMut<'a> {
+    key: (&'static str, i32, AccountNo),
+    balance: &'a mut Amount,
+    quantity: &'a mut Option<Decimal>,
+}
+
+fn balance_mut(item: &mut Item) -> Option<BalanceMut<'_>> {
+    let label = item.label();
+    let (year, account, balance, quantity) = match item {
+        Item::Ib(ib) => (ib.year, ib.account, &mut ib.balance, &mut ib.quantity),
+        Item::Ub(ub) => (ub.year, ub.account, &mut ub.balance, &mut ub.quantity),
+        Item::Res(res) => (res.year, res.account, &mut res.balance, &mut res.quantity),
+        _ => return None,
+    };
+    Some(BalanceMut {
+        key: (label, year, account),
+        balance,
+        quantity,
+    })
+}
+
+/// Where the balance items are written relative to each other, or
+/// [`u8::MAX`] for other items, see [`Document::recompute_balances`].
+fn balance_rank(label: &str) -> u8 {
+    match label {
+        Ib::LABEL => 0,
+        Ub::LABEL => 1,
+        Oib::LABEL | Oub::LABEL => 2,
+        Res::LABEL => 3,
+        PSaldo::LABEL => 4,
+        PBudget::LABEL => 5,
+        Ver::LABEL => 6,
+        _ => u8::MAX,
+    }
+}
+
+/// Number of references to `account` in `item`, not counting its
+/// definition.
+fn references_account(item: &Item, account: AccountNo) -> usize {
+    match item {
+        Item::Ib(ib) => usize::from(ib.account == account),
+        Item::Oib(oib) => usize::from(oib.account == account),
+        Item::Oub(oub) => usize::from(oub.account == account),
+        Item::PBudget(pbudget) => usize::from(pbudget.account == account),
+        Item::PSaldo(psaldo) => usize::from(psaldo.account == account),
+        Item::Ub(ub) => usize::from(ub.account == account),
+        Item::Res(res) => usize::from(res.account == account),
+        Item::Trans(trans) => usize::from(trans.account == account),
+        Item::Ver(ver) => ver
+            .transactions
+            .0
+            .iter()
+            .filter(|line| line.trans().account == account)
+            .count(),
+        _ => 0,
+    }
+}
+
+impl FromIterator<Item> for Document {
+    fn from_iter<T: IntoIterator<Item = Item>>(iter: T) -> Self {
+        Self {
+            items: iter.into_iter().collect(),
+            chart: None,
+            dates: DatePolicy::default(),
+            removed: false,
+        }
+    }
+}
+
+impl From<Vec<Item>> for Document {
+    fn from(items: Vec<Item>) -> Self {
+        Self {
+            items,
+            chart: None,
+            dates: DatePolicy::default(),
+            removed: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+    use time::macros::date;
+
+    use super::*;
+
+    const DATA: &[u8] = b"#FLAGGA 0
+#KONTO 1930 \"Bank\"
+#KONTO 2440 \"Leverant\x94rsskulder\"
+#KONTO 3010 \"F\x94rs\x84ljning\"
+#IB 0 1930 10000.00
+#VER A 1 20230102 \"Rent\"
+{
+    #TRANS 1930 {} -12000.00
+    #TRANS 2440 {} 12000.00
+}
+#VER A 2 20230103 \"Refund\"
+{
+    #TRANS 1930 {} 100.00
+    #TRANS 2440 {} -100.00
+}
+";
+
+    #[test]
+    fn identification() {
+        let doc = Document::from_reader(
+            &b"#FLAGGA 0
+#PROSA \"Bokslut {prelimin\x84rt}\"
+#FTYP AB
+#OMFATTN 20230630
+#TAXAR 2024
+"[..],
+        )
+        .unwrap();
+
+        assert_eq!(doc.comment(), Some("Bokslut {preliminärt}"));
+        assert_eq!(doc.company().kind.as_deref(), Some("AB"));
+        assert_eq!(doc.balances_until(), Some(date!(2023 - 06 - 30)));
+        assert_eq!(doc.tax_year(), Some(2024));
+    }
+
+    #[test]
+    fn dedup_balances() {
+        let data = b"#IB 0 1930 100.00
+#IB -1 1930 80.00
+#UB 0 1930 40.00 2
+#IB 0 1930 -10.00
+#UB 0 1930 60.00
+#RES 0 3010 -5.00
+#IB 0 1930 1.00 3
+";
+        let balances = |doc: &Document| {
+            doc.items()
+                .iter()
+                .map(|item| match item {
+                    Item::Ib(ib) => (ib.year, ib.balance.0, ib.quantity),
+                    Item::Ub(ub) => (ub.year, ub.balance.0, ub.quantity),
+                    Item::Res(res) => (res.year, res.balance.0, res.quantity),
+                    _ => unreachable!(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut doc = Document::from_reader(&data[..]).unwrap();
+        assert!(matches!(
+            doc.dedup_balances(DuplicateBalances::Error),
+            Err(Error::DuplicateBalance(DuplicateBalance {
+                label: "IB",
+                year: 0,
+                account,
+                count: 3,
+            })) if account == 1930
+        ));
+        assert_eq!(doc.items().len(), 7);
+
+        let duplicates = doc.dedup_balances(DuplicateBalances::Sum).unwrap();
+        assert_eq!(
+            duplicates.iter().map(|d| d.label).collect::<Vec<_>>(),
+            ["IB", "UB"]
+        );
+        assert_eq!(
+            balances(&doc),
+            [
+                (0, dec!(91.00), Some(dec!(3))),
+                (-1, dec!(80.00), None),
+                (0, dec!(100.00), Some(dec!(2))),
+                (0, dec!(-5.00), None),
+            ]
+        );
+        assert_eq!(doc.dedup_balances(DuplicateBalances::Error).unwrap(), []);
+
+        let mut doc = Document::from_reader(&data[..]).unwrap();
+        doc.dedup_balances(DuplicateBalances::LastWins).unwrap();
+        assert_eq!(
+            balances(&doc),
+            [
+                (0, dec!(1.00), Some(dec!(3))),
+                (-1, dec!(80.00), None),
+                (0, dec!(60.00), None),
+                (0, dec!(-5.00), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn balance_as_of() {
+        let mut doc = Document::from_reader(
+            &b"#RAR 0 20230101 20231231
+#RAR -1 20220101 20221231
+#IB 0 1930 1000.00
+#IB -1 1930 400.00
+#VER A 1 20221231
+{
+    #TRANS 1930 {} 600.00
+}
+#VER A 2 20230110
+{
+    #TRANS 1930 {} -200.00 20230201
+    #BTRANS 1930 {} -50.00
+}
+"[..],
+        )
+        .unwrap();
+        let balance = |doc: &Document, account, date| doc.balance_as_of(account, date);
+
+        assert_eq!(
+            balance(&doc, 1930, date!(2022 - 12 - 30)).unwrap(),
+            dec!(400.00)
+        );
+        assert_eq!(
+            balance(&doc, 1930, date!(2022 - 12 - 31)).unwrap(),
+            dec!(1000.00)
+        );
+        assert_eq!(
+            balance(&doc, 1930, date!(2023 - 01 - 31)).unwrap(),
+            dec!(1000.00)
+        );
+        assert_eq!(
+            balance(&doc, 1930, date!(2023 - 02 - 01)).unwrap(),
+            dec!(800.00)
+        );
+        assert_eq!(balance(&doc, 2440, date!(2023 - 02 - 01)).unwrap(), dec!(0));
+        assert!(matches!(
+            balance(&doc, 1930, date!(2024 - 01 - 01)),
+            Err(Error::OutsideFiscalYears(_))
+        ));
+
+        // the transaction counts from the date of the verification
+        doc.set_date_policy(DatePolicy::Voucher);
+        assert_eq!(
+            balance(&doc, 1930, date!(2023 - 01 - 10)).unwrap(),
+            dec!(800.00)
+        );
+
+        // as if the `#BTRANS` had never been removed
+        doc.set_include_removed(true);
+        assert_eq!(
+            balance(&doc, 1930, date!(2023 - 01 - 10)).unwrap(),
+            dec!(750.00)
+        );
+    }
+
+    #[test]
+    fn include_removed() {
+        let mut doc = Document::from_reader(
+            &b"#RAR 0 20230101 20231231
+#IB 0 1930 1000.00
+#UB 0 1930 0.00
+#VER A 1 20230110
+{
+    #TRANS 1930 {} -200.00
+    #TRANS 5010 {} 200.00
+    #BTRANS 1930 {} -50.00
+    #BTRANS 5010 {} 50.00
+}
+"[..],
+        )
+        .unwrap();
+
+        for (include, closing) in [(false, dec!(800.00)), (true, dec!(750.00))] {
+            doc.set_include_removed(include);
+            doc.recompute_balances();
+            let ub = doc.items().iter().find_map(|item| match item {
+                Item::Ub(ub) if ub.year == 0 => Some(ub.balance),
+                _ => None,
+            });
+            assert_eq!(ub, Some(Amount(closing)));
+            assert_eq!(
+                doc.balance_as_of(1930, date!(2023 - 12 - 31)).unwrap(),
+                closing
+            );
+
+            assert!(crate::validate::validate(&doc)
+                .findings
+                .iter()
+                .all(|finding| finding.rule != crate::validate::Rule::ClosingBalance));
+        }
+    }
+
+    #[test]
+    fn account_kind() {
+        let mut doc = Document::from_reader(
+            &b"#KONTO 2010 \"Eget kapital\"
+#KONTO 3740 \"\x99resutj\x84mning\"
+#KTYP 3740 K
+"[..],
+        )
+        .unwrap();
+
+        assert_eq!(doc.account_kind(3740), Some(AccountKind::Cost));
+        assert_eq!(doc.account_kind(3010), Some(AccountKind::Income));
+        assert_eq!(doc.account_kind(8310), Some(AccountKind::Income));
+        assert_eq!(doc.account_kind(8410), Some(AccountKind::Cost));
+        assert_eq!(doc.account_kind(9000), None);
+        assert!(doc.account_kind(2010).unwrap().is_credit());
+        assert!(doc.account_kind(2010).unwrap().is_balance());
+        let kinds = doc.account_kinds();
+        for no in [3740, 3010, 8410, 9000, 2010] {
+            assert_eq!(kinds.get(no), doc.account_kind(no));
+        }
+
+        doc.remove_account(3740).unwrap();
+        assert_eq!(doc.account_kind(3740), Some(AccountKind::Income));
+    }
+
+    #[test]
+    fn retain_vouchers() {
+        let mut doc = Document::from_reader(DATA).unwrap();
+        doc.retain_vouchers(|ver| ver.no != 1);
+        assert_eq!(doc.vouchers().map(|ver| ver.no).collect::<Vec<_>>(), [2]);
+        assert_eq!(doc.items().len(), 6);
+
+        assert_eq!(doc.remove_voucher("A", 2).unwrap().no, 2);
+        assert!(doc.remove_voucher("A", 2).is_none());
+        assert_eq!(doc.vouchers().count(), 0);
+    }
+
+    #[test]
+    fn recompute_balances() {
+        let mut doc = Document::from_reader(
+            &b"#RAR 0 20230101 20231231
+#RAR -1 20220101 20221231
+#KONTO 1930 \"Bank\"
+#KONTO 1460 \"Lager\"
+#KONTO 2099 \"\xc5rets resultat\"
+#IB 0 1930 500.00
+#IB 0 2099 -400.00
+#IB 0 1460 1000.00 10
+#UB 0 1930 0.00
+#UB 0 1930 0.00
+#IB -1 1930 0.00
+#UB -1 1930 500.00
+#UB -1 2099 0.00
+#UB -2 1930 123.00
+#RES 0 6110 50.00
+#RES -1 3010 -400.00
+#VER A 1 20220601
+{
+    #TRANS 1930 {} 500.00
+    #TRANS 3010 {} -500.00
+}
+#VER A 2 20230110
+{
+    #TRANS 1930 {} -100.00
+    #TRANS 1460 {} 100.00 \"\" \"\" 1
+}
+"[..],
+        )
+        .unwrap();
+        doc.add_voucher(
+            "#VER A 3 20230120\n{\n#TRANS 1930 {} -20.00\n#TRANS 5010 {} 20.00\n}\n"
+                .parse()
+                .unwrap(),
+        );
+        doc.recompute_balances();
+
+        let balances = doc
+            .items()
+            .iter()
+            .filter_map(|item| match item {
+                Item::Ib(ib) => Some(("IB", ib.year, ib.account, ib.balance.0, ib.quantity)),
+                Item::Ub(ub) => Some(("UB", ub.year, ub.account, ub.balance.0, ub.quantity)),
+                Item::Res(res) => Some(("RES", res.year, res.account, res.balance.0, res.quantity)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            balances,
+            [
+                // the result booked to equity is kept
+                ("IB", 0, 1930.into(), dec!(500.00), None),
+                ("IB", 0, 2099.into(), dec!(-400.00), None),
+                ("IB", 0, 1460.into(), dec!(1000.00), Some(dec!(10))),
+                ("UB", 0, 1930.into(), dec!(380.00), None),
+                ("IB", -1, 1930.into(), dec!(0.00), None),
+                ("UB", -1, 1930.into(), dec!(500.00), None),
+                ("UB", -1, 2099.into(), dec!(0.00), None),
+                // not a declared year
+                ("UB", -2, 1930.into(), dec!(123.00), None),
+                ("UB", 0, 1460.into(), dec!(1100.00), Some(dec!(11))),
+                ("UB", 0, 2099.into(), dec!(-400.00), None),
+                ("RES", 0, 6110.into(), dec!(0), None),
+                ("RES", -1, 3010.into(), dec!(-500.00), None),
+                ("RES", 0, 5010.into(), dec!(20.00), None),
+            ]
+        );
+        assert!(crate::validate::validate(&doc)
+            .findings
+            .iter()
+            .all(|finding| finding.rule != crate::validate::Rule::ClosingBalance));
+
+        // removing a voucher of the previous year carries over
+        doc.retain_vouchers(|ver| ver.no != 1);
+        doc.recompute_balances();
+        assert_eq!(
+            doc.balance_as_of(1930, date!(2023 - 01 - 01)).unwrap(),
+            dec!(0.00)
+        );
+        assert!(matches!(
+            &doc.items()[5..7],
+            [Item::Ib(ib), Item::Ib(_)] if ib.account == 1930 && ib.balance == dec!(0.00)
+        ));
+    }
+
+    #[test]
+    fn remove_account() {
+        let mut doc = Document::from_reader(DATA).unwrap();
+
+        assert!(matches!(
+            doc.remove_account(1930),
+            Err(Error::AccountReferenced {
+                account,
+                references: 3
+            }) if account == 1930
+        ));
+        assert!(matches!(
+            doc.remove_account(1234),
+            Err(Error::AccountNotFound(account)) if account == 1234
+        ));
+
+        assert_eq!(doc.remove_account(3010).unwrap().name, "Försäljning");
+        assert!(doc.account(3010).is_none());
+        assert_eq!(doc.accounts().count(), 2);
+    }
+}
@@ -0,0 +1,308 @@
+//! Vouchers and transactions that were likely booked twice, for review.
+//!
+//! Nothing here is certain: the same invoice may legitimately be paid in
+//! two equal instalments. Each [`Duplicate`] therefore has a confidence
+//! between 0 and 1.
+//!
+//! ```
+//! use sie4::{
+//!     duplicates::{self, Config, Duplicate},
+//!     Document,
+//! };
+//!
+//! let doc = Document::from_reader(&b"#VER A 1 20230110 \"Rent January\"
+//! {
+//!     #TRANS 1930 {} -12000.00
+//!     #TRANS 5010 {} 12000.00
+//! }
+//! #VER A 2 20230110 \"Rent, January\"
+//! {
+//!     #TRANS 5010 {} 12000.00
+//!     #TRANS 1930 {} -12000.00
+//! }
+//! "[..])
+//! .unwrap();
+//!
+//! let found = duplicates::find(&doc, &Config::default());
+//! assert_eq!(found.len(), 1);
+//! let Duplicate::Voucher { first, second, confidence } = &found[0] else {
+//!     panic!("expected a voucher");
+//! };
+//! assert_eq!((first.no, second.no), (1, 2));
+//! assert!(*confidence > 0.9);
+//! ```
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::{
+    amount::Amount,
+    item::{AccountNo, TransLine, Ver},
+    series::VerId,
+    Document,
+};
+
+/// What is compared, see [`find`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Config {
+    /// How many days apart the vouchers with the same transactions may
+    /// be dated. Defaults to 3.
+    pub max_days: u32,
+    /// The lowest confidence reported. Defaults to 0.5.
+    pub min_confidence: f64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_days: 3,
+            min_confidence: 0.5,
+        }
+    }
+}
+
+/// Something that was likely booked twice.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Duplicate {
+    /// `second` has the same transactions (accounts and amounts) as
+    /// `first`, which precedes it in the file.
+    Voucher {
+        first: VerId,
+        second: VerId,
+        confidence: f64,
+    },
+    /// The transactions `first` and `second` of `voucher`, indexed as in
+    /// [`Ver::transactions`], are identical.
+    Transaction {
+        voucher: VerId,
+        first: usize,
+        second: usize,
+        confidence: f64,
+    },
+}
+
+impl Duplicate {
+    #[must_use]
+    pub fn confidence(&self) -> f64 {
+        match self {
+            Self::Voucher { confidence, .. } | Self::Transaction { confidence, .. } => *confidence,
+        }
+    }
+}
+
+impl fmt::Display for Duplicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Voucher {
+                first,
+                second,
+                confidence,
+            } => write!(
+                f,
+                "{second} books the same as {first} ({:.0}%)",
+                confidence * 100.0
+            ),
+            Self::Transaction {
+                voucher,
+                first,
+                second,
+                confidence,
+            } => write!(
+                f,
+                "transactions {} and {} of {voucher} are identical ({:.0}%)",
+                first + 1,
+                second + 1,
+                confidence * 100.0
+            ),
+        }
+    }
+}
+
+/// The likely duplicates in `doc`, most likely first.
+///
+/// Vouchers are compared if their transactions, after corrections, have
+/// the same accounts and amounts. Their confidence is 0.5 for that, up
+/// to 0.3 more the closer they are dated and up to 0.2 more the more
+/// words their texts have in common.
+///
+/// Identical transactions of a voucher have a confidence of 0.6, or
+/// 0.95 if the voucher would balance without one of them.
+#[must_use]
+pub fn find(doc: &Document, config: &Config) -> Vec<Duplicate> {
+    let years = doc.fiscal_years();
+    let id = |ver: &Ver| VerId::new(ver, &years);
+
+    let mut found = Vec::new();
+    let mut by_amounts = BTreeMap::<Vec<(AccountNo, Amount)>, Vec<&Ver>>::new();
+
+    for ver in doc.vouchers() {
+        let mut amounts = ver
+            .counted(doc.include_removed())
+            .map(|trans| (trans.account, trans.amount))
+            .collect::<Vec<_>>();
+        if amounts.is_empty() {
+            continue;
+        }
+        amounts.sort();
+        by_amounts.entry(amounts).or_default().push(ver);
+
+        found.extend(
+            identical_transactions(ver).map(|(first, second, confidence)| Duplicate::Transaction {
+                voucher: id(ver),
+                first,
+                second,
+                confidence,
+            }),
+        );
+    }
+
+    for vers in by_amounts.values() {
+        for (i, first) in vers.iter().enumerate() {
+            for second in &vers[i + 1..] {
+                let days = (second.date - first.date).whole_days().unsigned_abs();
+                if days > u64::from(config.max_days) {
+                    continue;
+                }
+                #[allow(clippy::cast_precision_loss)]
+                let closeness = 1.0 - days as f64 / f64::from(config.max_days + 1);
+                let confidence = 0.5
+                    + 0.3 * closeness
+                    + 0.2 * similarity(first.text.as_deref(), second.text.as_deref());
+
+                found.push(Duplicate::Voucher {
+                    first: id(first),
+                    second: id(second),
+                    confidence,
+                });
+            }
+        }
+    }
+
+    found.retain(|duplicate| duplicate.confidence() >= config.min_confidence);
+    // stable, so that equally likely duplicates are in the order found
+    found.sort_by(|a, b| b.confidence().total_cmp(&a.confidence()));
+    found
+}
+
+/// The pairs of identical transactions of `ver`, with their confidence.
+fn identical_transactions(ver: &Ver) -> impl Iterator<Item = (usize, usize, f64)> + '_ {
+    let lines = ver
+        .transactions
+        .0
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, TransLine::Removed(_)))
+        .map(|(i, line)| (i, line.trans()))
+        .collect::<Vec<_>>();
+    let sum = lines.iter().map(|(_, trans)| trans.amount).sum::<Decimal>();
+
+    let mut pairs = Vec::new();
+    for (i, (first, a)) in lines.iter().enumerate() {
+        if let Some((second, b)) = lines[i + 1..].iter().find(|(_, b)| a == b) {
+            let balances = !sum.is_zero() && sum == b.amount;
+            pairs.push((*first, *second, if balances { 0.95 } else { 0.6 }));
+        }
+    }
+    pairs.into_iter()
+}
+
+/// The share of words of `a` and `b` that they have in common, ignoring
+/// case and punctuation. Texts without any words are alike.
+fn similarity(a: Option<&str>, b: Option<&str>) -> f64 {
+    fn words(text: Option<&str>) -> BTreeSet<String> {
+        text.unwrap_or_default()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    let (a, b) = (words(a), words(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let similarity = a.intersection(&b).count() as f64 / union as f64;
+    similarity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vouchers_and_transactions() {
+        let doc = Document::from_reader(
+            &b"#VER A 1 20230110 \"Office supplies\"
+{
+    #TRANS 1930 {} -500.00
+    #TRANS 6110 {} 500.00
+}
+#VER A 2 20230112 \"Coffee\"
+{
+    #TRANS 6110 {} 500.00
+    #TRANS 1930 {} -500.00
+}
+#VER A 3 20230301 \"Office supplies\"
+{
+    #TRANS 1930 {} -500.00
+    #TRANS 6110 {} 500.00
+}
+#VER A 4 20230302
+{
+    #TRANS 1930 {} -300.00
+    #TRANS 5010 {} 300.00
+    #TRANS 5010 {} 300.00
+}
+#VER A 5 20230303
+{
+    #TRANS 1930 {} -600.00
+    #TRANS 4010 {} 300.00
+    #TRANS 4010 {} 300.00
+}
+"[..],
+        )
+        .unwrap();
+
+        let found = find(&doc, &Config::default())
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            [
+                "transactions 2 and 3 of 2023/A/4 are identical (95%)",
+                "2023/A/2 books the same as 2023/A/1 (65%)",
+                "transactions 2 and 3 of 2023/A/5 are identical (60%)",
+            ]
+        );
+
+        let all = find(
+            &doc,
+            &Config {
+                max_days: 60,
+                min_confidence: 0.0,
+            },
+        );
+        assert_eq!(all.len(), 5);
+        assert!(all.iter().any(|duplicate| matches!(
+            duplicate,
+            Duplicate::Voucher { first, second, .. } if first.no == 1 && second.no == 3
+        )));
+    }
+
+    #[test]
+    fn text_similarity() {
+        assert!((similarity(Some("Rent, January"), Some("rent january")) - 1.0).abs() < 1e-9);
+        assert!((similarity(Some("Rent January"), Some("Rent February")) - 1.0 / 3.0).abs() < 1e-9);
+        assert!((similarity(None, Some("")) - 1.0).abs() < 1e-9);
+        assert!(similarity(Some("Rent"), None).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,4 @@
+//! Conversion of documents into other formats.
+
+#[cfg(feature = "csv")]
+pub mod csv;
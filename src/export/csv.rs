@@ -0,0 +1,407 @@
+//! Transactions as CSV, one row per transaction.
+//!
+//! ```
+//! use sie4::{
+//!     export::csv::{transactions, Column, Columns},
+//!     Document,
+//! };
+//!
+//! let doc = Document::from_reader(&b"#KONTO 1930 \"Bank\"
+//! #VER A 1 20230102 \"Rent\"
+//! {
+//!     #TRANS 1930 {} -12000.50
+//!     #TRANS 5010 {} 12000.50
+//! }
+//! "[..])
+//! .unwrap();
+//!
+//! let columns = Columns::new([Column::Account, Column::AccountName, Column::Amount])
+//!     .swedish_numbers(true);
+//! let mut csv = Vec::new();
+//! transactions(&doc, &columns, &mut csv).unwrap();
+//! assert_eq!(
+//!     String::from_utf8(csv).unwrap(),
+//!     "\"account\";\"account_name\";\"amount\"
+//! \"1930\";\"Bank\";\"-12000,50\"
+//! \"5010\";\"\";\"12000,50\"
+//! "
+//! );
+//! ```
+//!
+//! If the books are in another currency than SEK, [`Columns::convert`]
+//! adds the currency, the rate and the amount in SEK (or whichever the
+//! base currency of the rates is) to every row, to consolidate with
+//! books in other currencies:
+//!
+//! ```
+//! use iso_currency::Currency;
+//! use rust_decimal_macros::dec;
+//! use sie4::{
+//!     currency::Rates,
+//!     export::csv::{transactions, Column, Columns},
+//!     Document,
+//! };
+//! use time::macros::date;
+//!
+//! let doc = Document::from_reader(&b"#VALUTA EUR
+//! #VER A 1 20230102
+//! {
+//!     #TRANS 1930 {} -100.00
+//!     #TRANS 5010 {} 100.00
+//! }
+//! "[..])
+//! .unwrap();
+//!
+//! let mut rates = Rates::new(Currency::SEK);
+//! rates.insert(Currency::EUR, date!(2023 - 01 - 01), dec!(11.1234));
+//!
+//! let columns = Columns::new([Column::Account, Column::Amount]).convert(rates);
+//! let mut csv = Vec::new();
+//! transactions(&doc, &columns, &mut csv).unwrap();
+//! assert_eq!(
+//!     String::from_utf8(csv).unwrap(),
+//!     "\"account\",\"amount\",\"currency\",\"rate\",\"amount_sek\"
+//! \"1930\",\"-100.00\",\"EUR\",\"11.1234\",\"-1112.34\"
+//! \"5010\",\"100.00\",\"EUR\",\"11.1234\",\"1112.34\"
+//! "
+//! );
+//! ```
+
+use std::{fmt, io::Write, str::FromStr};
+
+use rust_decimal::Decimal;
+
+use crate::{
+    accounts::Accounts,
+    currency::{MissingRate, Rates},
+    document::DatePolicy,
+    item::{Trans, Ver},
+    Document,
+};
+
+/// A column of [`transactions`]. The header of the column is its
+/// [`Display`](fmt::Display) representation, which can also be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    /// Series of the verification.
+    Series,
+    /// Number of the verification.
+    No,
+    /// Date of the transaction according to the
+    /// [policy](Document::date_policy) of the document.
+    Date,
+    /// Registration date of the verification.
+    RegDate,
+    Account,
+    /// Name of the account, if it's declared.
+    AccountName,
+    Amount,
+    Quantity,
+    /// Text of the transaction, or else of the verification.
+    Text,
+    Signature,
+    /// The object of a dimension, e.g. `dim1` for the cost centre.
+    Object(u32),
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Series => "series",
+            Self::No => "no",
+            Self::Date => "date",
+            Self::RegDate => "reg_date",
+            Self::Account => "account",
+            Self::AccountName => "account_name",
+            Self::Amount => "amount",
+            Self::Quantity => "quantity",
+            Self::Text => "text",
+            Self::Signature => "signature",
+            Self::Object(dimension) => return write!(f, "dim{dimension}"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown column `{0}`")]
+pub struct UnknownColumn(String);
+
+impl FromStr for Column {
+    type Err = UnknownColumn;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "series" => Self::Series,
+            "no" => Self::No,
+            "date" => Self::Date,
+            "reg_date" => Self::RegDate,
+            "account" => Self::Account,
+            "account_name" => Self::AccountName,
+            "amount" => Self::Amount,
+            "quantity" => Self::Quantity,
+            "text" => Self::Text,
+            "signature" => Self::Signature,
+            _ => s
+                .strip_prefix("dim")
+                .and_then(|dimension| dimension.parse().ok())
+                .map(Self::Object)
+                .ok_or_else(|| UnknownColumn(s.to_owned()))?,
+        })
+    }
+}
+
+/// The columns of [`transactions`], in order, and how to format them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Columns {
+    list: Vec<Column>,
+    swedish_numbers: bool,
+    rates: Option<Rates>,
+}
+
+impl Columns {
+    pub fn new(columns: impl IntoIterator<Item = Column>) -> Self {
+        Self {
+            list: columns.into_iter().collect(),
+            swedish_numbers: false,
+            rates: None,
+        }
+    }
+
+    /// Unless the books are in the base currency of `rates`, end every
+    /// row with the currency of the books, the rate on the date of the
+    /// transaction and the amount converted into the base currency
+    /// (rounded to two decimals), in columns named e.g. `currency`,
+    /// `rate` and `amount_sek`.
+    #[must_use]
+    pub fn convert(mut self, rates: Rates) -> Self {
+        self.rates = Some(rates);
+        self
+    }
+
+    /// Use decimal commas, and semicolons to separate the fields, as
+    /// spreadsheets with a Swedish locale expect.
+    #[must_use]
+    pub fn swedish_numbers(mut self, swedish: bool) -> Self {
+        self.swedish_numbers = swedish;
+        self
+    }
+
+    fn number(&self, n: Decimal) -> String {
+        let n = n.to_string();
+        if self.swedish_numbers {
+            n.replace('.', ",")
+        } else {
+            n
+        }
+    }
+
+    fn value(
+        &self,
+        column: Column,
+        ver: &Ver,
+        trans: &Trans,
+        account_name: &str,
+        dates: DatePolicy,
+    ) -> String {
+        match column {
+            Column::Series => ver.series.clone(),
+            Column::No => ver.no.to_string(),
+            Column::Date => dates.date(ver, trans).to_string(),
+            Column::RegDate => ver
+                .reg_date
+                .map(|date| date.to_string())
+                .unwrap_or_default(),
+            Column::Account => trans.account.to_string(),
+            Column::AccountName => account_name.to_owned(),
+            Column::Amount => self.number(trans.amount.0),
+            Column::Quantity => trans
+                .quantity
+                .map(|quantity| self.number(quantity))
+                .unwrap_or_default(),
+            Column::Text => trans
+                .text
+                .as_deref()
+                .or(ver.text.as_deref())
+                .unwrap_or_default()
+                .to_owned(),
+            Column::Signature => trans.signature.clone().unwrap_or_default(),
+            Column::Object(dimension) => trans
+                .objects
+                .0
+                .iter()
+                .find(|(d, _)| *d == dimension)
+                .map(|(_, object)| object.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The columns that `sie4 convert` has always written.
+impl Default for Columns {
+    fn default() -> Self {
+        Self::new([
+            Column::Series,
+            Column::No,
+            Column::Date,
+            Column::Account,
+            Column::AccountName,
+            Column::Amount,
+            Column::Text,
+        ])
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+    #[error(transparent)]
+    MissingRate(#[from] MissingRate),
+}
+
+/// Write the transactions of all verifications, as corrected (see
+/// [`Ver::corrected`]) unless removed transactions are
+/// [included](Document::include_removed), with a header row. Every field
+/// is quoted.
+///
+/// # Errors
+///
+/// Returns an error if writing fails or if a rate is missing for
+/// [`Columns::convert`].
+pub fn transactions(doc: &Document, columns: &Columns, w: impl Write) -> Result<(), Error> {
+    let mut w = csv::WriterBuilder::new()
+        .delimiter(if columns.swedish_numbers { b';' } else { b',' })
+        .quote_style(csv::QuoteStyle::Always)
+        .from_writer(w);
+    let accounts = Accounts::from_document(doc);
+
+    let book = doc.currency();
+    let rates = columns.rates.as_ref().filter(|rates| rates.base() != book);
+
+    let mut header = columns
+        .list
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    if let Some(rates) = rates {
+        header.push("currency".to_owned());
+        header.push("rate".to_owned());
+        header.push(format!("amount_{}", rates.base().code().to_lowercase()));
+    }
+    w.write_record(header)?;
+
+    for ver in doc.vouchers() {
+        for trans in ver.counted(doc.include_removed()) {
+            let account_name = accounts.get(trans.account).unwrap_or_default();
+            let mut record = columns
+                .list
+                .iter()
+                .map(|&column| columns.value(column, ver, trans, account_name, doc.date_policy()))
+                .collect::<Vec<_>>();
+            if let Some(rates) = rates {
+                let date = doc.date_policy().date(ver, trans);
+                let conversion = rates.conversion(trans.amount.0, book, date)?;
+                record.push(book.code().to_owned());
+                record.push(columns.number(conversion.rate));
+                record.push(columns.number(conversion.amount.round_dp(2)));
+            }
+            w.write_record(record)?;
+        }
+    }
+
+    w.flush().map_err(csv::Error::from)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns() {
+        let doc = Document::from_reader(
+            &b"#VER A 1 20230102 \"Rent\" 20230105
+{
+    #TRANS 1930 {} -100.00 20230103
+    #TRANS 5010 {1 \"Z 1\" 6 P1} 100.00 \"\" \"Hyra, jan\" 2 AA
+}
+"[..],
+        )
+        .unwrap();
+        let columns = "series,no,date,reg_date,amount,quantity,text,signature,dim1,dim6,dim2"
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<Column>, _>>()
+            .unwrap();
+
+        let mut csv = Vec::new();
+        transactions(&doc, &Columns::new(columns), &mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "\"series\",\"no\",\"date\",\"reg_date\",\"amount\",\"quantity\",\"text\",\"signature\",\"dim1\",\"dim6\",\"dim2\"
+\"A\",\"1\",\"2023-01-03\",\"2023-01-05\",\"-100.00\",\"\",\"Rent\",\"\",\"\",\"\",\"\"
+\"A\",\"1\",\"2023-01-02\",\"2023-01-05\",\"100.00\",\"2\",\"Hyra, jan\",\"AA\",\"Z 1\",\"P1\",\"\"
+"
+        );
+
+        assert_eq!(
+            "dim".parse::<Column>(),
+            Err(UnknownColumn("dim".to_owned()))
+        );
+        assert_eq!(Column::Object(6).to_string().parse(), Ok(Column::Object(6)));
+    }
+
+    #[test]
+    fn removed() {
+        let mut doc = Document::from_reader(
+            &b"#VER A 1 20230102\n{\n#BTRANS 1930 {} -50.00\n#TRANS 1930 {} -100.00\n}\n"[..],
+        )
+        .unwrap();
+        let columns = Columns::new([Column::Account, Column::Amount]);
+        let csv = |doc: &Document| {
+            let mut csv = Vec::new();
+            transactions(doc, &columns, &mut csv).unwrap();
+            String::from_utf8(csv).unwrap()
+        };
+
+        assert_eq!(csv(&doc), "\"account\",\"amount\"\n\"1930\",\"-100.00\"\n");
+        doc.set_include_removed(true);
+        assert_eq!(
+            csv(&doc),
+            "\"account\",\"amount\"\n\"1930\",\"-50.00\"\n\"1930\",\"-100.00\"\n"
+        );
+    }
+
+    #[test]
+    fn convert() {
+        use iso_currency::Currency;
+        use rust_decimal_macros::dec;
+        use time::macros::date;
+
+        let data = b"#VER A 1 20230102
+{
+    #TRANS 1930 {} -100.00
+    #TRANS 5010 {} 100.00
+}
+";
+        let mut rates = Rates::new(Currency::SEK);
+        rates.insert(Currency::EUR, date!(2023 - 01 - 03), dec!(11));
+        let columns = Columns::new([Column::Amount]).convert(rates);
+
+        // already in SEK
+        let doc = Document::from_reader(&data[..]).unwrap();
+        let mut csv = Vec::new();
+        transactions(&doc, &columns, &mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "\"amount\"\n\"-100.00\"\n\"100.00\"\n"
+        );
+
+        let doc = Document::from_reader(&[&b"#VALUTA EUR\n"[..], data].concat()[..]).unwrap();
+        let Err(Error::MissingRate(missing)) = transactions(&doc, &columns, Vec::new()) else {
+            panic!("expected a missing rate");
+        };
+        assert_eq!(missing.date, date!(2023 - 01 - 02));
+    }
+}
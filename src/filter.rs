@@ -0,0 +1,330 @@
+//! Subsets of documents, e.g. to hand over only part of the books to an
+//! auditor.
+//!
+//! ```
+//! use rust_decimal_macros::dec;
+//! use sie4::{filter::Filter, item::Item, Document};
+//! use time::macros::date;
+//!
+//! let doc = Document::from_reader(&b"#RAR 0 20230101 20231231
+//! #IB 0 1930 1000.00
+//! #UB 0 1930 700.00
+//! #RES 0 5010 300.00
+//! #VER A 1 20230110
+//! {
+//!     #TRANS 1930 {} -100.00
+//!     #TRANS 5010 {} 100.00
+//! }
+//! #VER A 2 20230210
+//! {
+//!     #TRANS 1930 {} -200.00
+//!     #TRANS 5010 {} 200.00
+//! }
+//! "[..])
+//! .unwrap();
+//!
+//! let filter = Filter {
+//!     from: Some(date!(2023 - 02 - 01)),
+//!     ..Filter::default()
+//! };
+//! let doc = filter.filter(doc);
+//! assert_eq!(doc.vouchers().count(), 1);
+//! assert!(matches!(&doc.items()[1], Item::Ib(ib) if ib.balance == dec!(900.00)));
+//! assert!(matches!(&doc.items()[2], Item::Ub(ub) if ub.balance == dec!(700.00)));
+//! assert!(matches!(&doc.items()[3], Item::Res(res) if res.balance == dec!(200.00)));
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use rust_decimal::Decimal;
+use time::Date;
+
+use crate::{
+    amount::Amount,
+    fiscal::FiscalYear,
+    item::{AccountNo, Ib, Res, Ub, Ver},
+    Document, Item,
+};
+
+/// Which vouchers to keep. The balances (`#IB`, `#UB` and `#RES`) are
+/// recomputed so that the subset adds up: the opening balances are those
+/// at the start of the period, and the closing balances and results
+/// those of the opening balances and the kept vouchers.
+///
+/// Balances of fiscal years outside the period are dropped, as are
+/// `#PSALDO`, `#OIB`, `#OUB` and `#KSUMMA`, which would no longer be
+/// valid.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    /// Keep the vouchers with a transaction on any of these accounts,
+    /// and only the balances of them. All accounts if empty.
+    pub accounts: Vec<AccountNo>,
+    /// Keep the vouchers dated on or after this date.
+    pub from: Option<Date>,
+    /// Keep the vouchers dated on or before this date.
+    pub to: Option<Date>,
+    /// Keep the vouchers of these series. All series if empty.
+    pub series: Vec<String>,
+}
+
+/// A recomputed balance: the amount and the quantity, if any.
+pub(crate) type Sum = (Amount, Option<Decimal>);
+
+pub(crate) fn add(sum: &mut Sum, amount: Amount, quantity: Option<Decimal>) {
+    sum.0 += amount;
+    sum.1 = match (sum.1, quantity) {
+        (Some(a), Some(b)) => Some(a + b),
+        (a, b) => a.or(b),
+    };
+}
+
+impl Filter {
+    fn account(&self, account: AccountNo) -> bool {
+        self.accounts.is_empty() || self.accounts.contains(&account)
+    }
+
+    fn keep(&self, ver: &Ver, include_removed: bool) -> bool {
+        (self.series.is_empty() || self.series.contains(&ver.series))
+            && self.from.is_none_or(|from| ver.date >= from)
+            && self.to.is_none_or(|to| ver.date <= to)
+            && (self.accounts.is_empty()
+                || ver
+                    .counted(include_removed)
+                    .any(|trans| self.accounts.contains(&trans.account)))
+    }
+
+    /// The part of `year` within the period, if any.
+    fn period(&self, year: &FiscalYear) -> Option<(Date, Date)> {
+        let start = self.from.map_or(year.start, |from| from.max(year.start));
+        let end = self.to.map_or(year.end, |to| to.min(year.end));
+        (start <= end).then_some((start, end))
+    }
+
+    #[must_use]
+    pub fn filter(&self, doc: Document) -> Document {
+        let dates = doc.date_policy();
+        let removed = doc.include_removed();
+        let balances = self.balances(&doc);
+
+        let mut items = Vec::with_capacity(doc.items().len());
+        let mut balances = Some(balances);
+        for item in doc.into_items() {
+            match item {
+                Item::Ib(_) | Item::Ub(_) | Item::Res(_) => {
+                    items.extend(balances.take().into_iter().flatten());
+                }
+                Item::PSaldo(_) | Item::Oib(_) | Item::Oub(_) | Item::Ksumma(_) => (),
+                Item::Ver(ver) if !self.keep(&ver, removed) => (),
+                item => {
+                    if matches!(item, Item::PBudget(_) | Item::Ver(_)) {
+                        items.extend(balances.take().into_iter().flatten());
+                    }
+                    items.push(item);
+                }
+            }
+        }
+        items.extend(balances.into_iter().flatten());
+
+        let mut doc = Document::from(items);
+        doc.set_date_policy(dates);
+        doc
+    }
+
+    /// The recomputed balances of every fiscal year within the period.
+    fn balances(&self, doc: &Document) -> Vec<Item> {
+        let dates = doc.date_policy();
+        let kinds = doc.account_kinds();
+        let mut items = Vec::new();
+
+        for year in doc.fiscal_years().iter() {
+            let Some((start, end)) = self.period(year) else {
+                continue;
+            };
+
+            let mut opening = BTreeMap::<AccountNo, Sum>::new();
+            let mut closing = BTreeMap::<AccountNo, Sum>::new();
+            let mut results = BTreeMap::<AccountNo, Sum>::new();
+            let mut balance_accounts = BTreeSet::new();
+
+            for item in doc.items() {
+                match item {
+                    Item::Ib(ib) if ib.year == year.no && self.account(ib.account) => {
+                        balance_accounts.insert(ib.account);
+                        add(
+                            opening.entry(ib.account).or_default(),
+                            ib.balance,
+                            ib.quantity,
+                        );
+                    }
+                    Item::Ub(ub) if ub.year == year.no && self.account(ub.account) => {
+                        balance_accounts.insert(ub.account);
+                    }
+                    Item::Res(res) if res.year == year.no && self.account(res.account) => {
+                        results.entry(res.account).or_default();
+                    }
+                    _ => (),
+                }
+            }
+
+            for ver in doc.vouchers() {
+                let kept = self.keep(ver, doc.include_removed());
+                for trans in ver.counted(doc.include_removed()) {
+                    let date = dates.date(ver, trans);
+                    if !(year.start..=end).contains(&date) || !self.account(trans.account) {
+                        continue;
+                    }
+
+                    let balance = kinds
+                        .get(trans.account)
+                        .map_or(balance_accounts.contains(&trans.account), |kind| {
+                            kind.is_balance()
+                        });
+                    if !balance {
+                        if kept && date >= start {
+                            add(
+                                results.entry(trans.account).or_default(),
+                                trans.amount,
+                                trans.quantity,
+                            );
+                        }
+                    } else if date < start {
+                        // everything before the period, kept or not
+                        balance_accounts.insert(trans.account);
+                        add(
+                            opening.entry(trans.account).or_default(),
+                            trans.amount,
+                            trans.quantity,
+                        );
+                    } else if kept {
+                        balance_accounts.insert(trans.account);
+                        add(
+                            closing.entry(trans.account).or_default(),
+                            trans.amount,
+                            trans.quantity,
+                        );
+                    }
+                }
+            }
+
+            for &account in &balance_accounts {
+                let (balance, quantity) = opening.get(&account).copied().unwrap_or_default();
+                items.push(Item::Ib(Ib {
+                    year: year.no,
+                    account,
+                    balance,
+                    quantity,
+                }));
+            }
+            for &account in &balance_accounts {
+                let mut sum = opening.get(&account).copied().unwrap_or_default();
+                if let Some(&(amount, quantity)) = closing.get(&account) {
+                    add(&mut sum, amount, quantity);
+                }
+                items.push(Item::Ub(Ub {
+                    year: year.no,
+                    account,
+                    balance: sum.0,
+                    quantity: sum.1,
+                }));
+            }
+            for (account, (balance, quantity)) in results {
+                items.push(Item::Res(Res {
+                    year: year.no,
+                    account,
+                    balance,
+                    quantity,
+                }));
+            }
+        }
+
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+    use time::macros::date;
+
+    use super::*;
+
+    #[test]
+    fn series_and_accounts() {
+        let doc = Document::from_reader(
+            &b"#RAR 0 20230101 20231231
+#RAR -1 20220101 20221231
+#KONTO 1930 \"Bank\"
+#IB 0 1930 1000.00
+#IB 0 1510 50.00
+#UB 0 1930 600.00
+#UB 0 1510 150.00
+#IB -1 1930 900.00
+#PSALDO 0 202301 3010 {} -100.00
+#VER A 1 20230110
+{
+    #TRANS 1930 {} -300.00
+    #TRANS 5010 {} 300.00
+}
+#VER B 1 20230111
+{
+    #TRANS 1510 {} 100.00
+    #TRANS 3010 {} -100.00
+}
+#VER A 2 20230701
+{
+    #TRANS 1930 {} -100.00
+    #TRANS 5010 {} 100.00
+}
+"[..],
+        )
+        .unwrap();
+
+        let filter = Filter {
+            accounts: vec![1930.into(), 5010.into()],
+            to: Some(date!(2023 - 06 - 30)),
+            series: vec!["A".to_owned()],
+            ..Filter::default()
+        };
+        let doc = filter.filter(doc);
+
+        let vers = doc
+            .vouchers()
+            .map(|ver| (ver.series.as_str(), ver.no))
+            .collect::<Vec<_>>();
+        assert_eq!(vers, [("A", 1)]);
+        assert_eq!(
+            doc.items()
+                .iter()
+                .filter_map(|item| match item {
+                    Item::Ib(Ib {
+                        year,
+                        account,
+                        balance,
+                        ..
+                    }) => Some(("IB", *year, *account, balance.0)),
+                    Item::Ub(Ub {
+                        year,
+                        account,
+                        balance,
+                        ..
+                    }) => Some(("UB", *year, *account, balance.0)),
+                    Item::Res(Res {
+                        year,
+                        account,
+                        balance,
+                        ..
+                    }) => Some(("RES", *year, *account, balance.0)),
+                    Item::PSaldo(_) => Some(("PSALDO", 0, 0.into(), Decimal::ZERO)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+            [
+                ("IB", 0, 1930.into(), dec!(1000.00)),
+                ("UB", 0, 1930.into(), dec!(700.00)),
+                ("RES", 0, 5010.into(), dec!(300.00)),
+                ("IB", -1, 1930.into(), dec!(900.00)),
+                ("UB", -1, 1930.into(), dec!(900.00)),
+            ]
+        );
+    }
+}
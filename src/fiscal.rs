@@ -0,0 +1,174 @@
+//! Fiscal years (`#RAR`).
+//!
+//! Balances refer to fiscal years by their number relative to the
+//! current year: `0` is the current year, `-1` the previous one and so
+//! on.
+
+use time::Date;
+
+use crate::item::Rar;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FiscalYear {
+    /// `0` for the current year, `-1` for the previous year etc.
+    pub no: i32,
+    /// First day of the year.
+    pub start: Date,
+    /// Last day of the year (inclusive).
+    pub end: Date,
+}
+
+impl FiscalYear {
+    /// Whether `date` is within this fiscal year.
+    #[must_use]
+    pub fn contains(&self, date: Date) -> bool {
+        (self.start..=self.end).contains(&date)
+    }
+
+    /// The year before this one, assuming that it was a regular
+    /// twelve-month year. Fiscal years may be shortened or extended, so
+    /// prefer [`FiscalYears::get`] if the file declares the year.
+    ///
+    /// ```
+    /// use sie4::fiscal::FiscalYear;
+    /// use time::macros::date;
+    ///
+    /// let year = FiscalYear {
+    ///     no: 0,
+    ///     start: date!(2023 - 05 - 01),
+    ///     end: date!(2024 - 04 - 30),
+    /// };
+    /// let previous = year.previous().unwrap();
+    /// assert_eq!(previous.no, -1);
+    /// assert_eq!(previous.start, date!(2022 - 05 - 01));
+    /// assert_eq!(previous.end, date!(2023 - 04 - 30));
+    /// ```
+    #[must_use]
+    pub fn previous(&self) -> Option<Self> {
+        let end = self.start.previous_day()?;
+        let start = self
+            .start
+            .replace_year(self.start.year() - 1)
+            // february 29th
+            .or_else(|_| end.replace_year(self.start.year() - 1))
+            .ok()?;
+
+        Some(Self {
+            no: self.no - 1,
+            start,
+            end,
+        })
+    }
+}
+
+impl From<&Rar> for FiscalYear {
+    fn from(rar: &Rar) -> Self {
+        Self {
+            no: rar.no,
+            start: rar.start,
+            end: rar.end,
+        }
+    }
+}
+
+/// The fiscal years declared in a file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FiscalYears(Vec<FiscalYear>);
+
+impl FiscalYears {
+    /// The fiscal year with the given number.
+    #[must_use]
+    pub fn get(&self, no: i32) -> Option<&FiscalYear> {
+        self.0.iter().find(|year| year.no == no)
+    }
+
+    /// The current fiscal year (number `0`).
+    #[must_use]
+    pub fn current(&self) -> Option<&FiscalYear> {
+        self.get(0)
+    }
+
+    /// The fiscal year that `date` belongs to.
+    ///
+    /// ```
+    /// use sie4::Document;
+    /// use time::macros::date;
+    ///
+    /// let doc = Document::from_reader(
+    ///     &b"#RAR 0 20230101 20231231\n#RAR -1 20220101 20221231\n"[..],
+    /// )
+    /// .unwrap();
+    /// let years = doc.fiscal_years();
+    /// assert_eq!(years.containing(date!(2022 - 03 - 14)).unwrap().no, -1);
+    /// assert!(years.containing(date!(2021 - 03 - 14)).is_none());
+    /// ```
+    #[must_use]
+    pub fn containing(&self, date: Date) -> Option<&FiscalYear> {
+        self.0.iter().find(|year| year.contains(date))
+    }
+
+    /// The fiscal year before `year`, if declared.
+    #[must_use]
+    pub fn previous(&self, year: &FiscalYear) -> Option<&FiscalYear> {
+        self.get(year.no - 1)
+    }
+
+    /// Iterate over the years, most recent first.
+    pub fn iter(&self) -> impl Iterator<Item = &FiscalYear> {
+        self.0.iter()
+    }
+}
+
+impl FromIterator<FiscalYear> for FiscalYears {
+    fn from_iter<T: IntoIterator<Item = FiscalYear>>(iter: T) -> Self {
+        let mut years = iter.into_iter().collect::<Vec<_>>();
+        years.sort_by_key(|year| std::cmp::Reverse(year.no));
+        Self(years)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+
+    use super::*;
+
+    #[test]
+    fn previous() {
+        let year = FiscalYear {
+            no: 0,
+            start: date!(2020 - 02 - 29),
+            end: date!(2021 - 02 - 27),
+        };
+        assert_eq!(
+            year.previous(),
+            Some(FiscalYear {
+                no: -1,
+                start: date!(2019 - 02 - 28),
+                end: date!(2020 - 02 - 28),
+            })
+        );
+
+        // shortened year
+        let years = [
+            FiscalYear {
+                no: 0,
+                start: date!(2023 - 01 - 01),
+                end: date!(2023 - 12 - 31),
+            },
+            FiscalYear {
+                no: -1,
+                start: date!(2022 - 07 - 01),
+                end: date!(2022 - 12 - 31),
+            },
+        ]
+        .into_iter()
+        .collect::<FiscalYears>();
+        let current = years.current().unwrap();
+        assert_eq!(years.previous(current), years.get(-1));
+        assert!(years.previous(years.get(-1).unwrap()).is_none());
+        assert_eq!(years.containing(date!(2022 - 06 - 30)), None);
+        assert_eq!(years.containing(date!(2022 - 07 - 01)).unwrap().no, -1);
+        assert_eq!(years.containing(date!(2023 - 12 - 31)).unwrap().no, 0);
+    }
+}
@@ -0,0 +1,404 @@
+//! Loading files into a database in batches, see [`Import`].
+//!
+//! The items are read one at a time and given to a [`Sink`], e.g. of an
+//! SQL connection, in batches of a bounded number of items, which it
+//! commits in a transaction. Each commit comes with the
+//! [`Position`] of the last item of the batch, which can be stored with
+//! it: if the import fails, it can be resumed from there with
+//! [`Import::resume`], without importing anything twice.
+//!
+//! ```
+//! use std::convert::Infallible;
+//!
+//! use sie4::{
+//!     import::{Import, Sink},
+//!     item::{Konto, Ver},
+//!     reader::Position,
+//!     Reader,
+//! };
+//!
+//! /// A database that can't fail.
+//! #[derive(Default)]
+//! struct Db {
+//!     accounts: Vec<String>,
+//!     vouchers: Vec<u32>,
+//!     checkpoint: Option<Position>,
+//! }
+//!
+//! impl Sink for Db {
+//!     type Error = Infallible;
+//!
+//!     fn account(&mut self, account: Konto) -> Result<(), Infallible> {
+//!         self.accounts.push(account.name);
+//!         Ok(())
+//!     }
+//!
+//!     fn verification(&mut self, ver: Ver) -> Result<(), Infallible> {
+//!         self.vouchers.push(ver.no);
+//!         Ok(())
+//!     }
+//!
+//!     fn commit_batch(&mut self, checkpoint: Position) -> Result<(), Infallible> {
+//!         self.checkpoint = Some(checkpoint);
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let data = b"#FLAGGA 0
+//! #KONTO 1930 \"Bank\"
+//! #VER A 1 20230102
+//! {
+//! #TRANS 1930 {} -100.00
+//! #TRANS 3001 {} 100.00
+//! }
+//! #VER A 2 20230103
+//! {
+//! }
+//! ";
+//! let mut db = Db::default();
+//! let summary = Import::new(Reader::new(&data[..]))
+//!     .batch_size(2)
+//!     .run(&mut db)
+//!     .unwrap();
+//! // including the flag
+//! assert_eq!((summary.batches, summary.items), (2, 4));
+//! assert_eq!(db.accounts, ["Bank"]);
+//! assert_eq!(db.vouchers, [1, 2]);
+//! assert_eq!(db.checkpoint.unwrap().line, 8);
+//! ```
+
+use std::io::Read;
+
+use crate::{
+    item::{Konto, Ver},
+    reader::{self, Position},
+    Item, Reader,
+};
+
+/// Where an [`Import`] puts the items. Only the accounts (`#KONTO`) and
+/// verifications (`#VER`) are required, other items are ignored by
+/// default.
+///
+/// The items are given between [`Sink::begin_batch`] and
+/// [`Sink::commit_batch`], which are only called for batches with at
+/// least one item. If the import fails during a batch, it ends with
+/// [`Sink::abort_batch`] instead.
+pub trait Sink {
+    type Error;
+
+    /// Start a batch, e.g. by beginning a transaction.
+    ///
+    /// # Errors
+    ///
+    /// If the batch can't be started, which stops the import.
+    fn begin_batch(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Import an account.
+    ///
+    /// # Errors
+    ///
+    /// If the account can't be imported, which stops the import.
+    fn account(&mut self, account: Konto) -> Result<(), Self::Error>;
+
+    /// Import a verification, with its transactions.
+    ///
+    /// # Errors
+    ///
+    /// If the verification can't be imported, which stops the import.
+    fn verification(&mut self, ver: Ver) -> Result<(), Self::Error>;
+
+    /// Import an item that is neither an account nor a verification,
+    /// e.g. a balance. Ignored by default.
+    ///
+    /// # Errors
+    ///
+    /// If the item can't be imported, which stops the import.
+    fn item(&mut self, item: Item) -> Result<(), Self::Error> {
+        let _ = item;
+        Ok(())
+    }
+
+    /// Commit the batch, which ends with the item at `checkpoint`. The
+    /// checkpoint should be committed with it, to later
+    /// [resume](Import::resume) from.
+    ///
+    /// # Errors
+    ///
+    /// If the batch can't be committed, which stops the import.
+    fn commit_batch(&mut self, checkpoint: Position) -> Result<(), Self::Error>;
+
+    /// The import failed during the batch, which won't be committed, e.g.
+    /// to roll back its transaction.
+    fn abort_batch(&mut self) {}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<E> {
+    #[error("failed to read the input")]
+    Read(#[source] reader::Error),
+    #[error("failed to import the item on line {line}")]
+    Sink {
+        line: u32,
+        #[source]
+        source: E,
+    },
+}
+
+/// What an [`Import`] did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Summary {
+    /// The number of committed batches.
+    pub batches: usize,
+    /// The number of items given to the sink, excluding those of a
+    /// resumed import that had already been committed.
+    pub items: usize,
+    /// Where the last batch ended, or where the import was resumed from
+    /// if no batch was committed.
+    pub checkpoint: Option<Position>,
+}
+
+/// Drives a [`Sink`] with the items of a reader, see the
+/// [module](self).
+pub struct Import<R: Read> {
+    reader: Reader<R>,
+    batch_size: usize,
+    resume: Option<Position>,
+}
+
+impl<R: Read> Import<R> {
+    /// An import of the items of `reader`, in batches of 1000 items.
+    pub fn new(reader: Reader<R>) -> Self {
+        Self {
+            reader,
+            batch_size: 1000,
+            resume: None,
+        }
+    }
+
+    /// The maximum number of items in a batch, at least 1.
+    #[must_use]
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// Skip the items up to and including the one at `checkpoint`, from a
+    /// previous import of the same input (see [`Sink::commit_batch`]).
+    /// The items are still read, to check them, but not imported.
+    #[must_use]
+    pub fn resume(mut self, checkpoint: Position) -> Self {
+        self.resume = Some(checkpoint);
+        self
+    }
+
+    /// Import every item into `sink`.
+    ///
+    /// # Errors
+    ///
+    /// If the input can't be read, or the sink fails. The batches before
+    /// have been committed, and the import can be resumed from the
+    /// checkpoint of the last of them.
+    pub fn run<S: Sink>(mut self, sink: &mut S) -> Result<Summary, Error<S::Error>> {
+        let mut summary = Summary {
+            checkpoint: self.resume,
+            ..Summary::default()
+        };
+        let mut batch = None;
+
+        let res = self.import(sink, &mut summary, &mut batch);
+        if res.is_err() && batch.is_some() {
+            sink.abort_batch();
+        }
+        res.map(|()| summary)
+    }
+
+    /// Import the items into `sink`, with `batch` as the position of the
+    /// last item of the open batch, if any, and the number of items
+    /// imported in it.
+    fn import<S: Sink>(
+        &mut self,
+        sink: &mut S,
+        summary: &mut Summary,
+        batch: &mut Option<(Position, usize)>,
+    ) -> Result<(), Error<S::Error>> {
+        while let Some(item) = self.reader.next() {
+            let item = item.map_err(Error::Read)?;
+            // set by every item read
+            let Some(position) = self.reader.position() else {
+                continue;
+            };
+            if self
+                .resume
+                .is_some_and(|checkpoint| position.offset <= checkpoint.offset)
+            {
+                continue;
+            }
+
+            let sink_error = |source| Error::Sink {
+                line: position.line,
+                source,
+            };
+            let len = if let Some((_, len)) = *batch {
+                len
+            } else {
+                *batch = Some((position, 0));
+                sink.begin_batch().map_err(sink_error)?;
+                0
+            };
+            match item {
+                Item::Konto(konto) => sink.account(konto),
+                Item::Ver(ver) => sink.verification(ver),
+                item => sink.item(item),
+            }
+            .map_err(sink_error)?;
+            summary.items += 1;
+
+            let len = len + 1;
+            *batch = Some((position, len));
+            if len == self.batch_size {
+                Self::commit(sink, summary, batch)?;
+            }
+        }
+
+        Self::commit(sink, summary, batch)
+    }
+
+    /// Commit the open batch, if any.
+    fn commit<S: Sink>(
+        sink: &mut S,
+        summary: &mut Summary,
+        batch: &mut Option<(Position, usize)>,
+    ) -> Result<(), Error<S::Error>> {
+        let Some((position, _)) = *batch else {
+            return Ok(());
+        };
+        sink.commit_batch(position).map_err(|source| Error::Sink {
+            line: position.line,
+            source,
+        })?;
+        *batch = None;
+        summary.batches += 1;
+        summary.checkpoint = Some(position);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records the calls, and fails on the verification numbered `fail`.
+    #[derive(Default)]
+    struct Log {
+        calls: Vec<String>,
+        fail: Option<u32>,
+        checkpoint: Option<Position>,
+    }
+
+    impl Sink for Log {
+        type Error = String;
+
+        fn begin_batch(&mut self) -> Result<(), String> {
+            self.calls.push("begin".to_owned());
+            Ok(())
+        }
+
+        fn account(&mut self, account: Konto) -> Result<(), String> {
+            self.calls.push(format!("account {}", account.no));
+            Ok(())
+        }
+
+        fn verification(&mut self, ver: Ver) -> Result<(), String> {
+            if self.fail == Some(ver.no) {
+                return Err("full".to_owned());
+            }
+            self.calls.push(format!("ver {}", ver.no));
+            Ok(())
+        }
+
+        fn item(&mut self, item: Item) -> Result<(), String> {
+            self.calls.push(item.label().to_owned());
+            Ok(())
+        }
+
+        fn commit_batch(&mut self, checkpoint: Position) -> Result<(), String> {
+            self.calls.push(format!("commit {}", checkpoint.line));
+            self.checkpoint = Some(checkpoint);
+            Ok(())
+        }
+
+        fn abort_batch(&mut self) {
+            self.calls.push("abort".to_owned());
+        }
+    }
+
+    const DATA: &[u8] = b"#FLAGGA 0
+#KONTO 1930 \"Bank\"
+#VER A 1 20230102
+{
+}
+#VER A 2 20230103
+{
+}
+#VER A 3 20230104
+{
+}
+";
+
+    #[test]
+    fn resume() {
+        let mut log = Log {
+            fail: Some(3),
+            ..Log::default()
+        };
+        let err = Import::new(Reader::new(DATA))
+            .batch_size(2)
+            .run(&mut log)
+            .unwrap_err();
+        assert!(matches!(err, Error::Sink { line: 9, ref source } if source == "full"));
+        assert_eq!(
+            log.calls,
+            [
+                "begin",
+                "FLAGGA",
+                "account 1930",
+                "commit 2",
+                "begin",
+                "ver 1",
+                "ver 2",
+                "commit 6",
+                "begin",
+                "abort"
+            ]
+        );
+
+        let mut log = Log {
+            checkpoint: log.checkpoint,
+            ..Log::default()
+        };
+        let summary = Import::new(Reader::new(DATA))
+            .batch_size(2)
+            .resume(log.checkpoint.unwrap())
+            .run(&mut log)
+            .unwrap();
+        assert_eq!(log.calls, ["begin", "ver 3", "commit 9"]);
+        assert_eq!(summary.batches, 1);
+        assert_eq!(summary.items, 1);
+
+        let mut log = Log::default();
+        let summary = Import::new(Reader::new(DATA)).run(&mut log).unwrap();
+        assert_eq!(summary.batches, 1);
+        assert_eq!(summary.checkpoint.unwrap().line, 9);
+
+        // nothing left
+        let mut log = Log::default();
+        let summary = Import::new(Reader::new(DATA))
+            .resume(summary.checkpoint.unwrap())
+            .run(&mut log)
+            .unwrap();
+        assert_eq!(summary.batches, 0);
+        assert!(log.calls.is_empty());
+    }
+}
@@ -1,30 +1,44 @@
 //! Each file consists of a number of items.
 
-use std::fmt::Debug;
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug},
+    str::FromStr,
+};
 
-use codepage_437::{BorrowFromCp437, CP437_CONTROL};
+use codepage_437::{ToCp437, CP437_CONTROL};
 use iso_currency::Currency;
-use nom::{
-    branch::alt,
-    bytes::streaming::{tag, take_while},
-    character::streaming::{char, digit1},
-    combinator::{complete, cut, map, map_res, opt, recognize},
-    error::context,
-    multi::many0,
-    sequence::preceded,
-    IResult,
-};
+use nom::{combinator::map, error::ErrorKind, IResult, Slice};
 use rust_decimal::Decimal;
-use time::Date;
+use serde::Serialize;
+use time::{Date, Month};
 
 use crate::{
-    parsers::{self, date, in_curly_braces, is_line_break, is_whitespace, text, unquoted_text},
+    amount::Amount,
+    company::OrgNr,
+    currency::ForeignAmount,
+    custom::{self, Custom, Value},
+    parsers::{self, is_line_break, is_whitespace, Options},
+    reader,
+    sni::SniCode,
+    tags::Tags,
+    tokens::{self, LexError, Mode, Record, Text, Token},
+    writer::{self, Line, WriteField, WriteFields},
     Span,
 };
 
 /// Items are grouped into four groups, that must not appear out of order
-/// in the file.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+/// in the file. They follow the chapters of the specification that
+/// describe the items.
+///
+/// ```
+/// use sie4::item::Group;
+///
+/// assert!(Group::Account < Group::Balance);
+/// assert!(Group::Account.labels().any(|label| label == "KONTO"));
+/// assert_eq!(Group::Balance.to_string(), "balances and verifications");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub enum Group {
     /// The first group is constituted by [`Flagga`] only.
     Flag,
@@ -32,16 +46,257 @@ pub enum Group {
     /// which program generated the file ([`Program`]) and the company
     /// name ([`FNamn`]).
     Identification,
+    /// The chart of accounts ([`Konto`]) and the dimensions and objects
+    /// ([`Dim`] and [`Objekt`]).
     Account,
+    /// The balances ([`Ib`], [`Ub`] and [`Res`] among others) and the
+    /// verifications ([`Ver`]).
     Balance,
 }
 
-type Amount = Decimal;
+impl Group {
+    /// Every group, in the order that they appear in a file.
+    pub const ALL: [Self; 4] = [
+        Self::Flag,
+        Self::Identification,
+        Self::Account,
+        Self::Balance,
+    ];
+}
+
+impl fmt::Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Flag => "flag",
+            Self::Identification => "identification",
+            Self::Account => "chart of accounts",
+            Self::Balance => "balances and verifications",
+        })
+    }
+}
 
-trait ParseField {
-    fn parse_field(i: Span) -> IResult<Span, Self>
-    where
-        Self: Sized;
+/// Why a [`Record`] isn't an item, or what was skipped to read it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Problem {
+    /// A field isn't of the type of the item's field, or is missing.
+    Field(ErrorKind),
+    /// An amount of this many bytes isn't a number.
+    Amount(usize),
+    /// A sub-entry isn't an item of the block.
+    SubEntry,
+    /// The item, or a sub-entry, has more fields than it should.
+    ExtraFields,
+}
+
+/// A [`Problem`] and where it is in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Invalid<'a> {
+    pub problem: Problem,
+    /// The input from the problem.
+    pub at: &'a [u8],
+    pub line: u32,
+    /// Counted in bytes, starting at 1.
+    pub column: usize,
+    /// Of `at` in the input that the item was read from.
+    pub offset: usize,
+    /// Whether the field is invalid rather than of another type, which
+    /// fails optional fields instead of leaving them out.
+    hard: bool,
+}
+
+impl<'a> Invalid<'a> {
+    /// A problem in the sub-entry `entry`, which fails the entry rather
+    /// than the item unless it's an amount or extra fields, which are
+    /// reported as they are.
+    fn in_entry(self, entry: &Record<'a>) -> Self {
+        if matches!(self.problem, Problem::Amount(_) | Problem::ExtraFields) {
+            return self;
+        }
+        let indent = entry.raw.iter().take_while(|&&c| is_whitespace(c)).count();
+        let offset = entry.offset(entry.raw) + indent;
+        let (at, line, column) = entry.locate(offset);
+        Self {
+            problem: Problem::SubEntry,
+            at,
+            line,
+            column,
+            offset,
+            hard: true,
+        }
+    }
+
+    /// The problem as the error of a nom parser of `i`, which `at` is
+    /// part of.
+    fn to_nom(&self, i: Span<'a>) -> nom::Err<nom::error::Error<Span<'a>>> {
+        let at = i.slice(self.at.as_ptr() as usize - i.fragment().as_ptr() as usize..);
+        let e = |input, code| nom::error::Error::new(input, code);
+        match self.problem {
+            Problem::Field(kind) if !self.hard => nom::Err::Error(e(at, kind)),
+            Problem::Field(kind) => nom::Err::Failure(e(at, kind)),
+            Problem::Amount(len) => nom::Err::Failure(e(at.slice(..len), INVALID_AMOUNT)),
+            Problem::SubEntry => nom::Err::Failure(e(at, INVALID_SUB_ENTRY)),
+            Problem::ExtraFields => nom::Err::Failure(e(at, EXTRA_FIELDS)),
+        }
+    }
+}
+
+/// What was found while decoding an item, besides the item.
+#[derive(Debug, Default)]
+pub(crate) struct Found<'a> {
+    /// The invalid sub-entries and extra fields, which were skipped.
+    pub skipped: Vec<Invalid<'a>>,
+    /// What was read only leniently, e.g. the amounts read because of
+    /// [`Options::lenient_amounts`].
+    pub warnings: Vec<reader::Warning>,
+}
+
+/// The fields of a [`Record`] that are left to decode.
+pub(crate) struct Fields<'a, 'r> {
+    record: &'r Record<'a>,
+    rest: &'r [Token<'a>],
+    options: Options,
+    found: &'r mut Found<'a>,
+    /// Whether the sub-entries have been decoded.
+    block_read: bool,
+}
+
+impl<'a, 'r> Fields<'a, 'r> {
+    pub(crate) fn new(record: &'r Record<'a>, options: Options, found: &'r mut Found<'a>) -> Self {
+        Self {
+            record,
+            rest: &record.fields,
+            options,
+            found,
+            block_read: false,
+        }
+    }
+
+    fn peek(&self) -> Option<&'r Token<'a>> {
+        self.rest.first()
+    }
+
+    fn advance(&mut self) {
+        self.rest = &self.rest[1..];
+    }
+
+    fn invalid(&self, problem: Problem, offset: usize, hard: bool) -> Invalid<'a> {
+        let (at, line, column) = self.record.locate(offset);
+        Invalid {
+            problem,
+            at,
+            line,
+            column,
+            offset,
+            hard,
+        }
+    }
+
+    /// The field at hand, or where it's missing, is of another type.
+    fn error(&self, kind: ErrorKind, hard: bool) -> Invalid<'a> {
+        let offset = self
+            .peek()
+            .map_or(self.record.end(), |token| self.record.offset(token.raw()));
+        self.invalid(Problem::Field(kind), offset, hard)
+    }
+
+    /// `text`, which has been moved past, can't be converted to the field.
+    fn invalid_text(&self, text: Text<'a>, hard: bool) -> Invalid<'a> {
+        let offset = self.record.offset(text.raw);
+        self.invalid(Problem::Field(ErrorKind::MapRes), offset, hard)
+    }
+
+    /// The next field, which isn't a list.
+    fn text(&mut self, kind: ErrorKind) -> Result<Text<'a>, Invalid<'a>> {
+        match self.peek() {
+            Some(Token::Text(text)) => {
+                self.advance();
+                Ok(*text)
+            }
+            _ => Err(self.error(kind, false)),
+        }
+    }
+
+    /// The index of the tag that the next field is, exactly.
+    fn tag(&mut self, tags: &[&str]) -> Result<usize, Invalid<'a>> {
+        let tag = match self.peek() {
+            Some(Token::Text(text)) => tags.iter().position(|tag| tag.as_bytes() == text.raw),
+            _ => None,
+        };
+        let tag = tag.ok_or_else(|| self.error(ErrorKind::Tag, false))?;
+        self.advance();
+        Ok(tag)
+    }
+
+    /// Skip the fields left, as extra fields.
+    fn skip_extra(&mut self) {
+        if let Some(token) = self.peek() {
+            let extra = self.invalid(Problem::ExtraFields, self.record.offset(token.raw()), true);
+            self.found.skipped.push(extra);
+            self.rest = &[];
+        }
+    }
+
+    /// The fields left of a [`Custom`] item.
+    pub(crate) fn values(&mut self) -> Vec<Value> {
+        let values = self.rest.iter().map(Token::to_value).collect();
+        self.rest = &[];
+        values
+    }
+
+    /// Done with the item: what's left is extra fields, including any
+    /// sub-entries and fields after them.
+    pub(crate) fn end(&mut self) {
+        self.skip_extra();
+        if let Some(block) = self.record.block().filter(|_| !self.block_read) {
+            let extra = self.invalid(Problem::ExtraFields, block, true);
+            self.found.skipped.push(extra);
+        }
+        if let Some(trailing) = self.record.trailing() {
+            let extra = self.invalid(Problem::ExtraFields, trailing, true);
+            self.found.skipped.push(extra);
+        }
+    }
+}
+
+/// Field types, decoded from the fields of a [`Record`].
+pub(crate) trait Decode<'a>: Sized {
+    /// Decode the next field, or fields, moving past them. Fields of
+    /// another type are a soft error, which optional fields are left out
+    /// for, unless they are invalid as any field (see [`Invalid::hard`]).
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>>;
+}
+
+/// A field of an item, e.g. [`Konto::name`].
+///
+/// Every field type has a borrowed counterpart ([`Field::Ref`]) that is
+/// used by the borrowed item types (e.g. [`KontoRef`]). Text is borrowed
+/// as long as it doesn't need to be re-encoded from CP437; other types
+/// are usually their own borrowed counterpart.
+pub trait Field {
+    /// Borrowed representation of this field.
+    type Ref<'a>;
+
+    /// Convert the borrowed representation into the owned one.
+    fn from_ref(r: Self::Ref<'_>) -> Self;
+
+    /// Convert into a borrowed representation that owns its text.
+    fn into_ref<'a>(self) -> Self::Ref<'a>;
+}
+
+/// Borrowed items that can appear in [`SubEntries`].
+pub(crate) trait DecodeEntry<'a>: Sized {
+    /// Decode a sub-entry, or return `None` if it's another kind of
+    /// sub-entry.
+    fn decode_entry(
+        entry: &Record<'a>,
+        options: Options,
+        found: &mut Found<'a>,
+    ) -> Option<Result<Self, Invalid<'a>>>;
+
+    /// Post-process the sub-entries of a block.
+    fn collect(entries: Vec<Self>) -> Vec<Self> {
+        entries
+    }
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -64,66 +319,212 @@ pub trait ItemType {
         Self: Sized;
 }
 
-impl ParseField for String {
-    fn parse_field(i: Span) -> IResult<Span, Self>
-    where
-        Self: Sized,
-    {
-        map(text, |s| s.to_string())(i)
+impl<'a> Decode<'a> for Cow<'a, str> {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        fields.text(ErrorKind::TakeWhile1).map(|text| text.decode())
     }
 }
 
-impl ParseField for bool {
-    fn parse_field(i: Span) -> IResult<Span, Self>
-    where
-        Self: Sized,
-    {
-        alt((map(tag("0"), |_| false), map(tag("1"), |_| true)))(i)
+impl Field for String {
+    type Ref<'a> = Cow<'a, str>;
+
+    fn from_ref(r: Self::Ref<'_>) -> Self {
+        r.into_owned()
+    }
+
+    fn into_ref<'a>(self) -> Self::Ref<'a> {
+        Cow::Owned(self)
     }
 }
 
-impl ParseField for Date {
-    fn parse_field(i: Span) -> IResult<Span, Self>
-    where
-        Self: Sized,
-    {
-        let (i, s) = unquoted_text(i)?;
-        let (_, date) = cut(date)(s)?;
-        Ok((i, date))
+impl<'a> Decode<'a> for bool {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        Ok(fields.tag(&["0", "1"])? == 1)
     }
 }
 
-impl ParseField for Currency {
-    fn parse_field(i: Span) -> IResult<Span, Self>
-    where
-        Self: Sized,
-    {
-        let (i, s) = unquoted_text(i)?;
-        let (_, currency) = cut(parsers::from_str)(s)?;
-        Ok((i, currency))
+/// Dates are unquoted, see [`parsers::date`].
+impl<'a> Decode<'a> for Date {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        let options = fields.options;
+        let century_pivot = options.century_pivot.filter(|_| !options.strict);
+        let text = fields.text(ErrorKind::TakeWhile1)?;
+        parsers::parse_date(text.raw, century_pivot).ok_or_else(|| fields.invalid_text(text, true))
     }
 }
 
-impl ParseField for Decimal {
-    fn parse_field(i: Span) -> IResult<Span, Self>
-    where
-        Self: Sized,
-    {
-        let (i, s) = unquoted_text(i)?;
-        let (_, value) = cut(parsers::from_str)(s)?;
-        Ok((i, value))
+impl<'a> Decode<'a> for Currency {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        let text = fields.text(ErrorKind::TakeWhile1)?;
+        parsers::decode(text.raw)
+            .parse()
+            .map_err(|_| fields.invalid_text(text, true))
     }
 }
 
-impl<T: ParseField> ParseField for Option<T> {
-    fn parse_field(i: Span) -> IResult<Span, Self>
-    where
-        Self: Sized,
-    {
-        opt(T::parse_field)(i)
+impl<'a> Decode<'a> for SniCode {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        let text = fields.text(ErrorKind::TakeWhile1)?;
+        text.decode()
+            .parse()
+            .map_err(|_| fields.invalid_text(text, true))
+    }
+}
+
+impl<'a> Decode<'a> for OrgNr {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        let text = fields.text(ErrorKind::TakeWhile1)?;
+        text.decode()
+            .parse()
+            .map_err(|_| fields.invalid_text(text, true))
+    }
+}
+
+/// Amounts are written with `.` as the decimal separator and no
+/// thousands separators, unless read leniently (see
+/// [`crate::Reader::lenient_amounts`]).
+impl<'a> Decode<'a> for Decimal {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        let text = fields.text(ErrorKind::TakeWhile1)?;
+        if !text.quoted {
+            if let Some(value) = parsers::parse_amount(text.raw) {
+                return Ok(value);
+            }
+        }
+
+        let offset = fields.record.offset(text.raw);
+        let options = fields.options;
+        if options.lenient_amounts && !options.strict {
+            let decoded = text.decode();
+            if let Some(value) = normalize_amount(&decoded) {
+                let (_, line, _) = fields.record.locate(offset);
+                fields.found.warnings.push(reader::Warning::LenientAmount {
+                    text: decoded.into_owned(),
+                    line,
+                });
+                return Ok(value);
+            }
+        }
+
+        Err(fields.invalid(Problem::Amount(text.raw.len()), offset, true))
+    }
+}
+
+impl<'a> Decode<'a> for Amount {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        Decimal::decode(fields).map(Self)
+    }
+}
+
+/// An amount such as `1 234,56` or `1.234,56`, with a decimal comma and
+/// spaces or periods between the thousands, as `1234.56`.
+fn normalize_amount(s: &str) -> Option<Decimal> {
+    let s = s
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '\u{a0}'))
+        .collect::<String>();
+    let s = match (s.rfind(','), s.rfind('.')) {
+        // `1.234,56`
+        (Some(comma), Some(period)) if period < comma => s.replace('.', "").replace(',', "."),
+        // `1,234.56`
+        (Some(_), Some(_)) => s.replace(',', ""),
+        (Some(_), None) if s.matches(',').count() == 1 => s.replace(',', "."),
+        _ => s,
+    };
+    s.parse().ok()
+}
+
+/// Quantities are numeric, but some programs write them as (possibly
+/// empty) quoted text. The whole field is always consumed so that the
+/// following fields stay aligned; anything that isn't a number, with
+/// either `.` or `,` as the decimal separator, is read as `None` and
+/// reported as a [`Warning::DroppedQuantity`](reader::Warning). Unless
+/// [strict](crate::Reader::strict), where it has to be a number, as
+/// specified, or empty.
+fn quantity<'a>(fields: &mut Fields<'a, '_>) -> Result<Option<Decimal>, Invalid<'a>> {
+    let Some(Token::Text(text)) = fields.peek() else {
+        return Ok(None);
+    };
+    let text = *text;
+    let s = text.decode();
+    let s = s.trim();
+
+    if !fields.options.strict {
+        fields.advance();
+        let quantity = s.parse().or_else(|_| s.replace(',', ".").parse()).ok();
+        if quantity.is_none() && !s.is_empty() {
+            let (_, line, _) = fields.record.locate(fields.record.offset(text.raw));
+            fields
+                .found
+                .warnings
+                .push(reader::Warning::DroppedQuantity {
+                    text: s.to_owned(),
+                    line,
+                });
+        }
+        return Ok(quantity);
+    }
+    let quantity = match s.parse() {
+        Ok(quantity) => Some(quantity),
+        Err(_) if s.is_empty() => None,
+        Err(_) => return Err(fields.error(ErrorKind::MapRes, true)),
+    };
+    fields.advance();
+    Ok(quantity)
+}
+
+/// Missing optional fields are either left out or, if followed by other
+/// fields, written as `""`.
+impl<'a, T: Decode<'a>> Decode<'a> for Option<T> {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        match fields.peek() {
+            // nothing follows at the end of the line
+            None => return Ok(None),
+            Some(token) if token.raw() == b"\"\"" => {
+                fields.advance();
+                return Ok(None);
+            }
+            Some(_) => {}
+        }
+
+        let rest = fields.rest;
+        match T::decode(fields) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if !e.hard => {
+                fields.rest = rest;
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<T: Field> Field for Option<T> {
+    type Ref<'a> = Option<T::Ref<'a>>;
+
+    fn from_ref(r: Self::Ref<'_>) -> Self {
+        r.map(T::from_ref)
+    }
+
+    fn into_ref<'a>(self) -> Self::Ref<'a> {
+        self.map(T::into_ref)
     }
 }
 
+/// Error kind of the [`nom::Err::Failure`] returned when a sub-entry
+/// can't be parsed. The error's input starts at the sub-entry.
+pub(crate) const INVALID_SUB_ENTRY: nom::error::ErrorKind = nom::error::ErrorKind::Verify;
+
+/// Error kind of the [`nom::Err::Failure`] returned when an amount isn't
+/// a number. The error's input is the amount.
+pub(crate) const INVALID_AMOUNT: nom::error::ErrorKind = nom::error::ErrorKind::Float;
+
+/// Error kind of the [`nom::Err::Failure`] returned when an item has more
+/// fields than it should. The error's input starts at the first extra
+/// field.
+pub(crate) const EXTRA_FIELDS: nom::error::ErrorKind = nom::error::ErrorKind::NonEmpty;
+
 /// Sub-entries are enclosed in curly braces that should be free-standing
 /// on their own lines:
 ///
@@ -134,40 +535,110 @@ impl<T: ParseField> ParseField for Option<T> {
 ///     #TRANS 1910 {} -13200.00
 /// }
 /// ```
-#[derive(Debug, PartialEq, Eq)]
+///
+/// Unknown kinds of entries are skipped, but an invalid entry fails the
+/// whole item.
+#[derive(Debug, PartialEq, Eq, Serialize)]
 pub struct SubEntries<T>(pub Vec<T>);
 
-impl<T: ItemType> ParseField for SubEntries<T> {
-    fn parse_field(i: Span) -> IResult<Span, Self>
-    where
-        Self: Sized,
-    {
-        let (i, _) = take_while(|c| is_whitespace(c) || is_line_break(c))(i)?;
-        let (i, o) = in_curly_braces(i)?;
-        let (_, items) = many0(complete(|i| {
-            let (i, _) = take_while(|c| c != b'#')(i)?;
-            let (i, _) = preceded(char('#'), tag(T::LABEL))(i)?;
-            T::parse(i)
-        }))(o)?;
+impl<'a, T: DecodeEntry<'a>> Decode<'a> for SubEntries<T> {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        let record = fields.record;
+        if record.block().is_none() {
+            // an empty block on the line of the item reads as a list
+            return match fields.peek() {
+                Some(Token::List(list)) if list.items.is_empty() => {
+                    fields.advance();
+                    Ok(Self(Vec::new()))
+                }
+                _ => Err(fields.error(ErrorKind::Char, false)),
+            };
+        }
+        fields.skip_extra();
+        fields.block_read = true;
+
+        // The block is complete, so an entry that can't be read is
+        // skipped rather than a reason to fail the item.
+        let mut entries = Vec::with_capacity(record.sub_entries.len());
+        let mut errors = record.errors().iter().peekable();
+        for (i, entry) in record.sub_entries.iter().enumerate() {
+            while let Some(&(_, _, at)) = errors.next_if(|&&(before, ..)| before <= i) {
+                let invalid = fields.invalid(Problem::SubEntry, at, true);
+                fields.found.skipped.push(invalid);
+            }
+            // other kinds of sub-entries are skipped
+            match T::decode_entry(entry, fields.options, fields.found) {
+                Some(Ok(entry)) => entries.push(entry),
+                Some(Err(e)) => fields.found.skipped.push(e.in_entry(entry)),
+                None => {}
+            }
+        }
+        for &(_, _, at) in errors {
+            let invalid = fields.invalid(Problem::SubEntry, at, true);
+            fields.found.skipped.push(invalid);
+        }
+
+        Ok(Self(T::collect(entries)))
+    }
+}
+
+impl<T: Field> Field for SubEntries<T> {
+    type Ref<'a> = SubEntries<T::Ref<'a>>;
+
+    fn from_ref(r: Self::Ref<'_>) -> Self {
+        Self(r.0.into_iter().map(T::from_ref).collect())
+    }
 
-        Ok((i, Self(items)))
+    fn into_ref<'a>(self) -> Self::Ref<'a> {
+        SubEntries(self.0.into_iter().map(T::into_ref).collect())
     }
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
 pub struct List<T>(pub Vec<T>);
 
-impl<T: ParseField> ParseField for List<T> {
-    fn parse_field(i: Span) -> IResult<Span, Self>
-    where
-        Self: Sized,
-    {
-        let (i, o) = in_curly_braces(i)?;
-        // many0(T::parse_field("")) will return an `Incomplete` error, but
-        // we know that o is complete.
-        let (_, o) = many0(complete(T::parse_field))(o)?;
+/// Lists may be wrapped over several lines.
+impl<'a, T: DecodeElement<'a>> Decode<'a> for List<T> {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        let Some(Token::List(list)) = fields.peek() else {
+            return Err(fields.error(ErrorKind::Char, false));
+        };
+
+        let mut items = &list.items[..];
+        let mut elements = Vec::new();
+        while !items.is_empty() {
+            let element = T::decode_element(&mut items)
+                .ok_or_else(|| fields.error(ErrorKind::Char, false))?;
+            elements.push(element);
+        }
+        fields.advance();
+        Ok(Self(elements))
+    }
+}
+
+/// The elements of a [`List`].
+pub(crate) trait DecodeElement<'a>: Sized {
+    /// Decode the element at the start of `items`, moving past it.
+    fn decode_element(items: &mut &[Text<'a>]) -> Option<Self>;
+}
 
-        Ok((i, Self(o)))
+impl<'a> DecodeElement<'a> for Cow<'a, str> {
+    fn decode_element(items: &mut &[Text<'a>]) -> Option<Self> {
+        let (text, rest) = items.split_first()?;
+        *items = rest;
+        Some(text.decode())
+    }
+}
+
+impl<T: Field> Field for List<T> {
+    type Ref<'a> = List<T::Ref<'a>>;
+
+    fn from_ref(r: Self::Ref<'_>) -> Self {
+        Self(r.0.into_iter().map(T::from_ref).collect())
+    }
+
+    fn into_ref<'a>(self) -> Self::Ref<'a> {
+        List(self.0.into_iter().map(T::into_ref).collect())
     }
 }
 
@@ -177,34 +648,575 @@ impl<T> From<Vec<T>> for List<T> {
     }
 }
 
-macro_rules! parse_num_impl {
+/// A dimension and an object of it in an object list, e.g. `1 "456"` in
+/// `{1 "456" 7 "47"}`. Some programs quote the dimension as well.
+impl<'a> DecodeElement<'a> for (u32, Cow<'a, str>) {
+    fn decode_element(items: &mut &[Text<'a>]) -> Option<Self> {
+        let [dimension, object, rest @ ..] = *items else {
+            return None;
+        };
+        let dimension = dimension.decode().parse().ok()?;
+        *items = rest;
+        Some((dimension, object.decode()))
+    }
+}
+
+impl Field for (u32, String) {
+    type Ref<'a> = (u32, Cow<'a, str>);
+
+    fn from_ref((dimension, object): Self::Ref<'_>) -> Self {
+        (dimension, object.into_owned())
+    }
+
+    fn into_ref<'a>(self) -> Self::Ref<'a> {
+        (self.0, Cow::Owned(self.1))
+    }
+}
+
+/// An object of a dimension, e.g. a cost centre, written as
+/// `{dimension "object"}`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct Object {
+    pub dimension: u32,
+    pub object: String,
+}
+
+/// Borrowed variant of [`Object`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct ObjectRef<'a> {
+    pub dimension: u32,
+    pub object: Cow<'a, str>,
+}
+
+/// The dimension is an unquoted number.
+impl<'a> Decode<'a> for ObjectRef<'a> {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        let object = match fields.peek() {
+            Some(Token::List(list)) => match list.items[..] {
+                [dimension, object] if !dimension.quoted => std::str::from_utf8(dimension.raw)
+                    .ok()
+                    .filter(|s| s.bytes().all(|c| c.is_ascii_digit()))
+                    .and_then(|s| s.parse().ok())
+                    .map(|dimension| Self {
+                        dimension,
+                        object: object.decode(),
+                    }),
+                _ => None,
+            },
+            _ => None,
+        };
+        let object = object.ok_or_else(|| fields.error(ErrorKind::Char, false))?;
+        fields.advance();
+        Ok(object)
+    }
+}
+
+impl Field for Object {
+    type Ref<'a> = ObjectRef<'a>;
+
+    fn from_ref(r: Self::Ref<'_>) -> Self {
+        Self {
+            dimension: r.dimension,
+            object: r.object.into_owned(),
+        }
+    }
+
+    fn into_ref<'a>(self) -> Self::Ref<'a> {
+        ObjectRef {
+            dimension: self.dimension,
+            object: Cow::Owned(self.object),
+        }
+    }
+}
+
+/// An account number, e.g. `1930`.
+///
+/// Account numbers are usually four digits, but some programs write them
+/// with leading zeros (`01930`) or letters (`1930A`), which are kept as
+/// they are. They are compared by their numeric value first, so that
+/// `900` precedes `1930` but differs from `0900`, and accounts that
+/// aren't numbers come last.
+///
+/// ```
+/// use sie4::item::AccountNo;
+///
+/// let no: AccountNo = "01930".parse().unwrap();
+/// assert_eq!(no.to_string(), "01930");
+/// assert_eq!(no.number(), Some(1930));
+/// assert_eq!(no, 1930);
+/// assert_ne!(no, AccountNo::from(1930));
+/// assert!(AccountNo::from(900) < no);
+/// assert!("1930A".parse::<AccountNo>().unwrap().number().is_none());
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AccountNo {
+    len: u8,
+    bytes: [u8; AccountNo::MAX_LEN],
+}
+
+impl AccountNo {
+    /// The longest account number, in characters, which fits any
+    /// [`u32`].
+    pub const MAX_LEN: usize = 10;
+
+    /// The account number as written.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        // only ASCII alphanumerics
+        std::str::from_utf8(&self.bytes[..usize::from(self.len)]).unwrap_or_default()
+    }
+
+    /// The numeric value, unless the account number has letters (or is
+    /// too large for a [`u32`]).
+    #[must_use]
+    pub fn number(&self) -> Option<u32> {
+        self.as_str().parse().ok()
+    }
+
+    /// Whether the account number is written as [`AccountNo::from`] would
+    /// write its numeric value, i.e. without letters or leading zeros.
+    #[must_use]
+    pub fn is_canonical(&self) -> bool {
+        self.number().is_some_and(|no| *self == Self::from(no))
+    }
+
+    /// The class of the account: the first digit of its number, e.g. `1`
+    /// for the assets of BAS, ignoring leading zeros. Accounts that don't
+    /// start with a digit have none.
+    #[must_use]
+    pub fn class(&self) -> Option<u32> {
+        if let Some(mut class) = self.number() {
+            while class >= 10 {
+                class /= 10;
+            }
+            return Some(class);
+        }
+        self.as_str().chars().next()?.to_digit(10)
+    }
+
+    /// Whether the numeric value is within `range`, which it isn't for
+    /// accounts with letters.
+    #[must_use]
+    pub fn is_in(&self, range: &std::ops::RangeInclusive<u32>) -> bool {
+        self.number().is_some_and(|no| range.contains(&no))
+    }
+}
+
+impl From<u32> for AccountNo {
+    fn from(no: u32) -> Self {
+        let mut bytes = [0; Self::MAX_LEN];
+        let digits = no.to_string();
+        bytes[..digits.len()].copy_from_slice(digits.as_bytes());
+        Self {
+            // at most 10 digits
+            #[allow(clippy::cast_possible_truncation)]
+            len: digits.len() as u8,
+            bytes,
+        }
+    }
+}
+
+/// Equal if the account number has the numeric value of the `u32`.
+impl PartialEq<u32> for AccountNo {
+    fn eq(&self, other: &u32) -> bool {
+        self.number() == Some(*other)
+    }
+}
+
+impl Ord for AccountNo {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        match (self.number(), other.number()) {
+            // leading zeros last
+            (Some(a), Some(b)) => a
+                .cmp(&b)
+                .then_with(|| self.len.cmp(&other.len))
+                .then_with(|| self.as_str().cmp(other.as_str())),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => self.as_str().cmp(other.as_str()),
+        }
+    }
+}
+
+impl PartialOrd for AccountNo {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for AccountNo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::fmt::Debug for AccountNo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("AccountNo").field(&self.as_str()).finish()
+    }
+}
+
+/// The account number wasn't 1 to [`AccountNo::MAX_LEN`] ASCII letters
+/// and digits.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid account number `{0}`")]
+pub struct InvalidAccountNo(String);
+
+impl FromStr for AccountNo {
+    type Err = InvalidAccountNo;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || s.len() > Self::MAX_LEN || !s.bytes().all(|c| c.is_ascii_alphanumeric())
+        {
+            return Err(InvalidAccountNo(s.to_owned()));
+        }
+
+        let mut bytes = [0; Self::MAX_LEN];
+        bytes[..s.len()].copy_from_slice(s.as_bytes());
+        Ok(Self {
+            #[allow(clippy::cast_possible_truncation)]
+            len: s.len() as u8,
+            bytes,
+        })
+    }
+}
+
+/// Canonical account numbers are serialized as numbers, and the rest as
+/// strings.
+impl Serialize for AccountNo {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.number() {
+            Some(no) if self.is_canonical() => serializer.serialize_u32(no),
+            _ => serializer.serialize_str(self.as_str()),
+        }
+    }
+}
+
+impl<'a> Decode<'a> for AccountNo {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        // usually unquoted, but quoting is allowed as for any field
+        let text = fields.text(ErrorKind::TakeWhile1)?;
+        text.decode()
+            .parse()
+            .map_err(|_| fields.invalid_text(text, false))
+    }
+}
+
+/// A calendar month of a fiscal year, written as `YYYYMM`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct Period {
+    pub year: i32,
+    pub month: Month,
+}
+
+impl Period {
+    /// `YYYYMM`, or `YYMM` if there is a `century_pivot`.
+    fn from_digits(digits: &[u8], century_pivot: Option<u8>) -> Option<Self> {
+        let (year, month) = parsers::split_year(digits, 6, century_pivot)?;
+        let month = Month::try_from((month[0] - b'0') * 10 + (month[1] - b'0')).ok()?;
+        Some(Self { year, month })
+    }
+}
+
+impl std::fmt::Display for Period {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}{:02}", self.year, u8::from(self.month))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid period `{0}`")]
+pub struct InvalidPeriod(String);
+
+/// `YYYYMM`.
+impl FromStr for Period {
+    type Err = InvalidPeriod;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_digits(s.as_bytes(), None).ok_or_else(|| InvalidPeriod(s.to_owned()))
+    }
+}
+
+/// `YYYYMM`, or `YYMM` like the dates of
+/// [`Reader::century_pivot`](crate::Reader::century_pivot).
+impl<'a> Decode<'a> for Period {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        let options = fields.options;
+        let century_pivot = options.century_pivot.filter(|_| !options.strict);
+        let text = fields.text(ErrorKind::TakeWhile1)?;
+        Self::from_digits(text.raw, century_pivot).ok_or_else(|| fields.invalid_text(text, true))
+    }
+}
+
+macro_rules! decode_num_impl {
     ($ty:ty) => {
-        impl ParseField for $ty {
-            fn parse_field(i: Span) -> IResult<Span, Self>
-            where
-                Self: Sized,
-            {
-                map_res(recognize(preceded(opt(tag("-")), cut(digit1))), |b| {
-                    std::borrow::Cow::borrow_from_cp437(&b, &CP437_CONTROL).parse()
-                })(i)
+        impl<'a> Decode<'a> for $ty {
+            fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+                let Some(Token::Text(text)) = fields.peek() else {
+                    return Err(fields.error(ErrorKind::Digit, false));
+                };
+                // a sign must be followed by digits, but a missing number
+                // is fine for optional fields
+                let raw = text.raw;
+                let sign = usize::from(raw.first() == Some(&b'-'));
+                let digits = raw[sign..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_digit())
+                    .count();
+                if digits == 0 || sign + digits < raw.len() {
+                    return Err(fields.error(ErrorKind::Digit, sign == 1 && digits == 0));
+                }
+
+                // the number is ASCII
+                let value = std::str::from_utf8(raw)
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .ok_or_else(|| fields.error(ErrorKind::MapRes, false))?;
+                fields.advance();
+                Ok(value)
             }
         }
     };
 }
 
-parse_num_impl!(i32);
-parse_num_impl!(u32);
+decode_num_impl!(i32);
+decode_num_impl!(i64);
+decode_num_impl!(u32);
+
+/// Implement [`Field`] for types that don't borrow from the input.
+macro_rules! owned_field_impl {
+    ($($ty:ty),*) => {
+        $(
+            impl Field for $ty {
+                type Ref<'a> = Self;
+
+                fn from_ref(r: Self::Ref<'_>) -> Self {
+                    r
+                }
+
+                fn into_ref<'a>(self) -> Self::Ref<'a> {
+                    self
+                }
+            }
+        )*
+    };
+}
 
+owned_field_impl!(
+    bool,
+    i32,
+    i64,
+    u32,
+    AccountNo,
+    Date,
+    Currency,
+    Amount,
+    Decimal,
+    FormatType,
+    SniCode,
+    OrgNr,
+    Period,
+    TypeNo,
+    ChartAccountsType,
+    AccountKind
+);
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// Decoder for a field: the [`Decode`] implementation of its type,
+/// unless overridden with `field: Type => decoder`.
+macro_rules! field_decoder {
+    () => {
+        Decode::decode
+    };
+    ($decoder:path) => {
+        $decoder
+    };
+}
+
+/// Run `decode` on the record that `lexed` the start of `i`, as the nom
+/// parser of `i`. Whatever would have been skipped fails the parser.
+fn parse_lexed<'a, T>(
+    i: Span<'a>,
+    lexed: Result<(Record<'a>, usize), LexError>,
+    decode: impl FnOnce(&Record<'a>, &mut Found<'a>) -> Result<T, Invalid<'a>>,
+) -> IResult<Span<'a>, T> {
+    let (record, len) = lexed.map_err(|e| match e {
+        LexError::Incomplete => nom::Err::Incomplete(nom::Needed::Unknown),
+        LexError::Invalid {
+            kind: ErrorKind::Tag,
+            at,
+        } => nom::Err::Error(nom::error::Error::new(i.slice(at..), ErrorKind::Tag)),
+        LexError::Invalid { kind, at } => {
+            nom::Err::Failure(nom::error::Error::new(i.slice(at..), kind))
+        }
+    })?;
+
+    let mut found = Found::default();
+    let item = decode(&record, &mut found).map_err(|e| e.to_nom(i))?;
+    match found.skipped.first() {
+        Some(e) => Err(e.to_nom(i)),
+        None => Ok((i.slice(len..), item)),
+    }
+}
+
+/// An item read from the start of the input, see [`read`].
+#[derive(Debug)]
+pub(crate) struct Decoded<'a> {
+    pub item: ItemRef<'a>,
+    /// The number of bytes that the item was read from, including the
+    /// blank lines before it.
+    pub len: usize,
+    pub skipped: Vec<Invalid<'a>>,
+    /// See [`Found::warnings`].
+    pub warnings: Vec<reader::Warning>,
+}
+
+impl<'a> Decoded<'a> {
+    /// Fail with the first of what was skipped, if anything.
+    pub(crate) fn complete(mut self) -> Result<Self, ReadError<'a>> {
+        if self.skipped.is_empty() {
+            Ok(self)
+        } else {
+            Err(ReadError::Invalid(self.skipped.swap_remove(0)))
+        }
+    }
+}
+
+/// Why [`read`] didn't read an item.
+#[derive(Debug)]
+pub(crate) enum ReadError<'a> {
+    /// More of the input is needed, unless it has ended.
+    Incomplete,
+    Invalid(Invalid<'a>),
+}
+
+/// Read the item at the start of `src`, which starts on `line`, with
+/// `options` and the custom items of `custom`. Unless `eof`, more of the
+/// same item may follow `src`.
+pub(crate) fn read<'a>(
+    src: &'a [u8],
+    line: u32,
+    options: Options,
+    custom: Option<&custom::Registry>,
+    eof: bool,
+) -> Result<Decoded<'a>, ReadError<'a>> {
+    let mode = Mode {
+        strict: options.strict,
+        eof,
+        tolerant: false,
+    };
+    let (record, len) = tokens::lex(src, line, mode).map_err(|e| match e {
+        LexError::Incomplete => ReadError::Incomplete,
+        LexError::Invalid { kind, at: offset } => {
+            let (at, line, column) = tokens::locate(src, line, offset);
+            ReadError::Invalid(Invalid {
+                problem: Problem::Field(kind),
+                at,
+                line,
+                column,
+                offset,
+                hard: true,
+            })
+        }
+    })?;
+
+    let mut found = Found::default();
+    let item = ItemRef::decode(&record, options, custom, &mut found).map_err(ReadError::Invalid)?;
+    Ok(Decoded {
+        item,
+        len,
+        skipped: found.skipped,
+        warnings: found.warnings,
+    })
+}
+
+/// Whether items labeled `label` are followed by sub-entries, which
+/// [`tokens::lex`] then looks ahead for.
+pub(crate) fn has_sub_entries(label: &str) -> bool {
+    label == Ver::LABEL
+}
+
+/// Fields in the `+ { .. }` block following an item's fields are not part
+/// of its SIE4 representation. They are left out of the borrowed variant
+/// and default-initialized when parsing.
 macro_rules! item_impl {
-    ($name:ident ($group:ident) {
-        $($field:ident: $ty:ty,)*
-    }) => {
+    ($(#[$attr:meta])* $name:ident ($group:ident) {
+        $($field:ident: $ty:ty $(=> $parser:path)?,)*
+    } $(+ {
+        $($extra:ident: $extra_ty:ty,)*
+    })?) => {
         paste::paste! {
-            #[derive(Debug, PartialEq, Eq)]
+            $(#[$attr])*
+            #[derive(Debug, PartialEq, Eq, Serialize)]
             pub struct $name {
                 $(
                     pub $field: $ty,
                 )*
+                $($(
+                    #[serde(skip_serializing_if = "is_default")]
+                    pub $extra: $extra_ty,
+                )*)?
+            }
+
+            #[doc = concat!("Borrowed variant of [`", stringify!($name), "`].")]
+            #[derive(Debug, PartialEq, Eq)]
+            pub struct [<$name Ref>]<'a> {
+                $(
+                    pub $field: <$ty as Field>::Ref<'a>,
+                )*
+            }
+
+            impl<'a> [<$name Ref>]<'a> {
+                #[doc = concat!("Borrowing variant of [`", stringify!($name), "::parse`].")]
+                ///
+                /// # Errors
+                ///
+                /// Returns an error if the input is invalid or incomplete.
+                pub fn parse(i: Span<'a>) -> IResult<Span<'a>, Self> {
+                    let lexed = tokens::lex_fields(
+                        i.fragment(),
+                        i.location_line(),
+                        $name::LABEL,
+                        Mode::default(),
+                    );
+                    parse_lexed(i, lexed, |record, found| {
+                        Self::decode(&mut Fields::new(record, Options::default(), found))
+                    })
+                }
+
+                pub(crate) fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+                    $(
+                        let $field: <$ty as Field>::Ref<'a> = field_decoder!($($parser)?)(fields)?;
+                    )*
+                    fields.end();
+
+                    Ok(Self {
+                        $($field,)*
+                    })
+                }
+
+                /// Convert into the owned representation.
+                #[must_use]
+                pub fn into_owned(self) -> $name {
+                    $name {
+                        $($field: <$ty as Field>::from_ref(self.$field),)*
+                        $($($extra: <$extra_ty>::default(),)*)?
+                    }
+                }
+            }
+
+            impl<'a> DecodeEntry<'a> for [<$name Ref>]<'a> {
+                fn decode_entry(
+                    entry: &Record<'a>,
+                    options: Options,
+                    found: &mut Found<'a>,
+                ) -> Option<Result<Self, Invalid<'a>>> {
+                    (entry.label == $name::LABEL)
+                        .then(|| Self::decode(&mut Fields::new(entry, options, found)))
+                }
             }
 
             impl ItemType for $name {
@@ -213,46 +1225,335 @@ macro_rules! item_impl {
                 const GROUP: Group = Group::$group;
 
                 fn parse(i: Span) -> IResult<Span, Self> {
+                    map([<$name Ref>]::parse, [<$name Ref>]::into_owned)(i)
+                }
+            }
+
+            impl WriteFields for $name {
+                fn write_fields(&self, line: &mut Line) -> Result<(), writer::Error> {
                     $(
-                        let (i, _) = take_while(is_whitespace)(i)?;
-                        let (i, $field) = context(stringify!($field), <$ty>::parse_field)(i)?;
+                        self.$field.write_field(line)?;
                     )*
+                    Ok(())
+                }
+            }
 
-                    Ok((i, Self {
-                        $($field,)*
-                    }))
+            impl fmt::Display for $name {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    writer::display(Self::LABEL, self, f)
+                }
+            }
+
+            impl FromStr for $name {
+                type Err = ParseItemError;
+
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    Self::try_from(s.parse::<Item>()?).map_err(|item| ParseItemError::Label {
+                        expected: Self::LABEL,
+                        found: item.label(),
+                    })
+                }
+            }
+
+            impl Field for $name {
+                type Ref<'a> = [<$name Ref>]<'a>;
+
+                fn from_ref(r: Self::Ref<'_>) -> Self {
+                    r.into_owned()
+                }
+
+                fn into_ref<'a>(self) -> Self::Ref<'a> {
+                    [<$name Ref>] {
+                        $($field: <$ty as Field>::into_ref(self.$field),)*
+                    }
+                }
+            }
+
+            impl From<$name> for Item {
+                fn from(item: $name) -> Self {
+                    Self::$name(item)
+                }
+            }
+
+            /// Fails with the item if it is of another type.
+            impl TryFrom<Item> for $name {
+                type Error = Item;
+
+                fn try_from(item: Item) -> Result<Self, Self::Error> {
+                    match item {
+                        Item::$name(item) => Ok(item),
+                        item => Err(item),
+                    }
+                }
+            }
+
+            impl<'a> TryFrom<&'a Item> for &'a $name {
+                type Error = &'a Item;
+
+                fn try_from(item: &'a Item) -> Result<Self, Self::Error> {
+                    match item {
+                        Item::$name(item) => Ok(item),
+                        item => Err(item),
+                    }
+                }
+            }
+
+            impl<'a> TryFrom<&'a mut Item> for &'a mut $name {
+                type Error = &'a mut Item;
+
+                fn try_from(item: &'a mut Item) -> Result<Self, Self::Error> {
+                    match item {
+                        Item::$name(item) => Ok(item),
+                        item => Err(item),
+                    }
+                }
+            }
+
+            impl<'a> From<[<$name Ref>]<'a>> for ItemRef<'a> {
+                fn from(item: [<$name Ref>]<'a>) -> Self {
+                    Self::$name(item)
+                }
+            }
+
+            impl<'a> TryFrom<ItemRef<'a>> for [<$name Ref>]<'a> {
+                type Error = ItemRef<'a>;
+
+                fn try_from(item: ItemRef<'a>) -> Result<Self, Self::Error> {
+                    match item {
+                        ItemRef::$name(item) => Ok(item),
+                        item => Err(item),
+                    }
                 }
             }
         }
     };
 }
 
+/// Why text isn't an item, see the [`FromStr`] implementation of
+/// [`Item`].
+#[derive(Debug, thiserror::Error)]
+pub enum ParseItemError {
+    #[error(transparent)]
+    Read(#[from] reader::Error),
+    /// The text has characters that aren't in code page 437.
+    #[error("{0:?} cannot be represented in SIE4")]
+    Unrepresentable(String),
+    /// The text is another item than the one being parsed.
+    #[error("expected `#{expected}`, found `#{found}`")]
+    Label {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// The item is followed by something else, e.g. another item.
+    #[error("more than one item")]
+    Trailing,
+}
+
 macro_rules! items_impl {
-    {$($name:ident ($group:ident) $body:tt)*} => {
-        #[derive(Debug, PartialEq, Eq)]
+    {$($(#[$attr:meta])* $name:ident ($group:ident) $body:tt $(+ $extra:tt)?)*} => {
+        #[derive(Debug, PartialEq, Eq, Serialize)]
         pub enum Item {
             $(
                 $name($name),
             )*
+            /// An item that isn't part of SIE4, see [`custom`](crate::custom).
+            Custom(Custom),
+        }
+
+        /// Borrowed variant of [`Item`], parsed without allocating unless
+        /// text needs to be re-encoded.
+        #[derive(Debug, PartialEq, Eq)]
+        pub enum ItemRef<'a> {
+            $(
+                $name(paste::paste!([<$name Ref>]<'a>)),
+            )*
+            Custom(Custom),
         }
 
         $(
-            item_impl!($name ($group) $body);
+            item_impl!($(#[$attr])* $name ($group) $body $(+ $extra)?);
         )*
 
-        impl Item {
-            /// Parse an item from the beginning of the input.
+        impl Group {
+            /// The group of the items with the label `label`, e.g.
+            /// `b"VER"`, unless there is no such item.
+            #[must_use]
+            pub fn of_label(label: &[u8]) -> Option<Self> {
+                paste::paste! {
+                    $(
+                        if label == $name::LABEL.as_bytes() {
+                            return Some($name::GROUP);
+                        }
+                    )*
+                }
+                None
+            }
+
+            /// The labels of the items of the group, in alphabetical
+            /// order. [`Ksumma`] is listed in [`Group::Flag`], although it
+            /// may appear anywhere.
+            pub fn labels(self) -> impl Iterator<Item = &'static str> {
+                [$(($name::LABEL, $name::GROUP),)*]
+                    .into_iter()
+                    .filter(move |&(_, group)| group == self)
+                    .map(|(label, _)| label)
+            }
+        }
+
+        impl Item {
+            /// Parse an item from the beginning of the input.
+            ///
+            /// # Example
+            ///
+            /// ```
+            /// use sie4::{item::{Item, Program}, Span};
+            /// let span = Span::new(b"#PROGRAM \"Vi iMproved\" 9.0\n");
+            /// assert_eq!(
+            ///     Item::parse(span).unwrap().1,
+            ///     Item::Program(Program {
+            ///         name: "Vi iMproved".to_owned(),
+            ///         version: "9.0".to_owned(),
+            ///     }),
+            /// );
+            /// ```
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the input is invalid or incomplete.
+            pub fn parse(i: Span) -> IResult<Span, Self> {
+                map(ItemRef::parse, ItemRef::into_owned)(i)
+            }
+
+            /// See [`Group`].
+            #[must_use]
+            pub const fn group(&self) -> Group {
+                paste::paste! {
+                    match self {
+                        $(
+                            Self::$name(_) => $name::GROUP,
+                        )*
+                        Self::Custom(custom) => custom.group,
+                    }
+                }
+            }
+
+            /// See [`ItemType::LABEL`].
+            #[must_use]
+            pub const fn label(&self) -> &'static str {
+                match self {
+                    $(
+                        Self::$name(_) => $name::LABEL,
+                    )*
+                    Self::Custom(custom) => custom.label,
+                }
+            }
+
+            /// The labels of the items that this item is expected after,
+            /// which are those of the groups before its own. Nothing is
+            /// expected before [`Ksumma`], which may appear anywhere.
+            ///
+            /// ```
+            /// use sie4::item::Item;
+            ///
+            /// let konto = "#KONTO 1930 Bank".parse::<Item>().unwrap();
+            /// let after = konto.expected_after().collect::<Vec<_>>();
+            /// assert!(after.contains(&"FLAGGA") && after.contains(&"FNAMN"));
+            /// assert!(!after.contains(&"KONTO") && !after.contains(&"VER"));
+            /// ```
+            pub fn expected_after(&self) -> impl Iterator<Item = &'static str> {
+                let group = self.group();
+                let exempt = matches!(self, Self::Ksumma(_));
+                Group::ALL
+                    .into_iter()
+                    .filter(move |&before| before < group && !exempt)
+                    .flat_map(Group::labels)
+                    .filter(|&label| label != Ksumma::LABEL)
+            }
+        }
+
+        impl WriteFields for Item {
+            fn write_fields(&self, line: &mut Line) -> Result<(), writer::Error> {
+                match self {
+                    $(
+                        Self::$name(item) => item.write_fields(line),
+                    )*
+                    Self::Custom(item) => item.write_fields(line),
+                }
+            }
+        }
+
+        /// The item as the [`Writer`](crate::Writer) writes it by default,
+        /// with `\n` between the lines of items with sub-entries and no line
+        /// break at the end. The same goes for each item type.
+        ///
+        /// Formatting fails, e.g. [`ToString::to_string`] panics, if the
+        /// item has text that can't be written (see
+        /// [`writer::Error::Unrepresentable`]).
+        ///
+        /// ```
+        /// use sie4::item::{Item, Konto};
+        ///
+        /// let konto = Konto {
+        ///     no: 1930.into(),
+        ///     name: "Företagskonto".to_owned(),
+        /// };
+        /// assert_eq!(konto.to_string(), "#KONTO 1930 \"Företagskonto\"");
+        /// assert_eq!(Item::Konto(konto).to_string(), "#KONTO 1930 \"Företagskonto\"");
+        /// ```
+        impl fmt::Display for Item {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                writer::display(self.label(), self, f)
+            }
+        }
+
+        /// A single item, including its sub-entries, as written by
+        /// [`Display`](fmt::Display). Blank lines around it are ignored.
+        /// The same goes for each item type, which fails with
+        /// [`ParseItemError::Label`] for other items.
+        ///
+        /// ```
+        /// use sie4::item::{Item, Trans};
+        ///
+        /// let trans = "#TRANS 1930 {} -100.00".parse::<Trans>().unwrap();
+        /// assert_eq!(trans.account, 1930);
+        ///
+        /// let ver = "#VER A 1 20230110\n{\n#TRANS 1930 {} -100.00\n#TRANS 5010 {} 100.00\n}";
+        /// assert!(matches!(ver.parse(), Ok(Item::Ver(_))));
+        /// assert!(ver.parse::<Trans>().is_err());
+        /// ```
+        impl FromStr for Item {
+            type Err = ParseItemError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let buf = s
+                    .to_cp437(&CP437_CONTROL)
+                    .map_err(|_| ParseItemError::Unrepresentable(s.to_owned()))?;
+
+                let decoded = read(&buf, 1, Options::default(), None, true)
+                    .and_then(Decoded::complete)
+                    .map_err(|e| reader::complete_error(e, &buf, 1, 0))?;
+                if !buf[decoded.len..].iter().all(|&c| is_whitespace(c) || is_line_break(c)) {
+                    return Err(ParseItemError::Trailing);
+                }
+                Ok(decoded.item.into_owned())
+            }
+        }
+
+        impl<'a> ItemRef<'a> {
+            /// Parse an item from the beginning of the input, borrowing
+            /// from it.
             ///
             /// # Example
             ///
             /// ```
-            /// use sie4::{item::{Item, Program}, Span};
+            /// use std::borrow::Cow;
+            /// use sie4::{item::{ItemRef, ProgramRef}, Span};
             /// let span = Span::new(b"#PROGRAM \"Vi iMproved\" 9.0\n");
             /// assert_eq!(
-            ///     Item::parse(span).unwrap().1,
-            ///     Item::Program(Program {
-            ///         name: "Vi iMproved".to_owned(),
-            ///         version: "9.0".to_owned(),
+            ///     ItemRef::parse(span).unwrap().1,
+            ///     ItemRef::Program(ProgramRef {
+            ///         name: Cow::Borrowed("Vi iMproved"),
+            ///         version: Cow::Borrowed("9.0"),
             ///     }),
             /// );
             /// ```
@@ -260,28 +1561,84 @@ macro_rules! items_impl {
             /// # Errors
             ///
             /// Returns an error if the input is invalid or incomplete.
-            pub fn parse(i: Span) -> IResult<Span, Self> {
-                let (i, _) = take_while(|c| is_whitespace(c) || is_line_break(c))(i)?;
+            pub fn parse(i: Span<'a>) -> IResult<Span<'a>, Self> {
+                let lexed = tokens::lex(i.fragment(), i.location_line(), Mode::default());
+                parse_lexed(i, lexed, |record, found| {
+                    Self::decode(record, Options::default(), None, found)
+                })
+            }
 
+            /// Decode `record` with `options` and the custom items of
+            /// `custom`, adding what was skipped to `found`.
+            pub(crate) fn decode(
+                record: &Record<'a>,
+                options: Options,
+                custom: Option<&custom::Registry>,
+                found: &mut Found<'a>,
+            ) -> Result<Self, Invalid<'a>> {
+                let mut fields = Fields::new(record, options, found);
                 paste::paste! {
-                    preceded(tag("#"), alt(($(
-                        map(
-                            preceded(tag(stringify!([<$name:upper>])), $name::parse),
-                            Self::$name,
-                        ),
-                    )*)))(i)
+                    match record.label {
+                        $(
+                            stringify!([<$name:upper>]) => {
+                                return [<$name Ref>]::decode(&mut fields).map(Self::$name);
+                            }
+                        )*
+                        _ => {}
+                    }
+                }
+                if let Some(custom) =
+                    custom.and_then(|registry| custom::decode(record, registry, &mut fields))
+                {
+                    return Ok(Self::Custom(custom));
                 }
+
+                let label = record.offset(record.label.as_bytes());
+                Err(fields.invalid(Problem::Field(ErrorKind::Tag), label, false))
             }
 
             /// See [`Group`].
             #[must_use]
             pub const fn group(&self) -> Group {
-                paste::paste! {
-                    match self {
-                        $(
-                            Self::$name(_) => $name::GROUP,
-                        )*
-                    }
+                match self {
+                    $(
+                        Self::$name(_) => $name::GROUP,
+                    )*
+                    Self::Custom(custom) => custom.group,
+                }
+            }
+
+            /// See [`ItemType::LABEL`].
+            #[must_use]
+            pub const fn label(&self) -> &'static str {
+                match self {
+                    $(
+                        Self::$name(_) => $name::LABEL,
+                    )*
+                    Self::Custom(custom) => custom.label,
+                }
+            }
+
+            /// Convert into the owned representation.
+            #[must_use]
+            pub fn into_owned(self) -> Item {
+                match self {
+                    $(
+                        Self::$name(item) => Item::$name(item.into_owned()),
+                    )*
+                    Self::Custom(item) => Item::Custom(item),
+                }
+            }
+        }
+
+        /// Borrows nothing, see [`Field::into_ref`].
+        impl From<Item> for ItemRef<'_> {
+            fn from(item: Item) -> Self {
+                match item {
+                    $(
+                        Item::$name(item) => Self::$name(item.into_ref()),
+                    )*
+                    Item::Custom(item) => Self::Custom(item),
                 }
             }
         }
@@ -289,7 +1646,7 @@ macro_rules! items_impl {
 }
 
 /// "Format type" (encoding).
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Serialize)]
 pub enum FormatType {
     /// Code page 437, also known as PC-8.
     ///
@@ -297,32 +1654,26 @@ pub enum FormatType {
     PC8,
 }
 
-impl ParseField for FormatType {
-    fn parse_field(i: Span) -> IResult<Span, Self>
-    where
-        Self: Sized,
-    {
-        map(tag("PC8"), |_| FormatType::PC8)(i)
+impl<'a> Decode<'a> for FormatType {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        fields.tag(&["PC8"]).map(|_| Self::PC8)
     }
 }
 
 /// SIE "file type number" (version).
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Serialize)]
 pub enum TypeNo {
     /// Parsed from `4`.
     SIE4,
 }
 
-impl ParseField for TypeNo {
-    fn parse_field(i: Span) -> IResult<Span, Self>
-    where
-        Self: Sized,
-    {
-        map(tag("4"), |_| TypeNo::SIE4)(i)
+impl<'a> Decode<'a> for TypeNo {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        fields.tag(&["4"]).map(|_| Self::SIE4)
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Serialize)]
 pub enum ChartAccountsType {
     Bas95,
     Bas96,
@@ -330,20 +1681,71 @@ pub enum ChartAccountsType {
     Ne2007,
 }
 
-impl ParseField for ChartAccountsType {
-    fn parse_field(i: Span) -> IResult<Span, Self>
-    where
-        Self: Sized,
-    {
-        context(
-            "account type",
-            alt((
-                map(tag("BAS95"), |_| ChartAccountsType::Bas95),
-                map(tag("BAS96"), |_| ChartAccountsType::Bas96),
-                map(tag("EUBAS97"), |_| ChartAccountsType::EuBas97),
-                map(tag("NE2007"), |_| ChartAccountsType::Ne2007),
-            )),
-        )(i)
+impl<'a> Decode<'a> for ChartAccountsType {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        Ok(
+            match fields.tag(&["BAS95", "BAS96", "EUBAS97", "NE2007"])? {
+                0 => Self::Bas95,
+                1 => Self::Bas96,
+                2 => Self::EuBas97,
+                _ => Self::Ne2007,
+            },
+        )
+    }
+}
+
+/// The type of an account (`#KTYP`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum AccountKind {
+    /// Parsed from `T` (tillgång).
+    Asset,
+    /// Parsed from `S` (skuld), which includes equity.
+    Liability,
+    /// Parsed from `K` (kostnad).
+    Cost,
+    /// Parsed from `I` (intäkt).
+    Income,
+}
+
+impl AccountKind {
+    /// The type of an account in the BAS chart of accounts, by its number:
+    /// assets in class 1, equity and liabilities in class 2, revenue in
+    /// class 3, costs in classes 4 to 7 and financial items in class 8,
+    /// where 8000–8399 are income and the rest costs.
+    #[must_use]
+    pub fn from_bas(account: u32) -> Option<Self> {
+        match account {
+            1000..=1999 => Some(Self::Asset),
+            2000..=2999 => Some(Self::Liability),
+            3000..=3999 | 8000..=8399 => Some(Self::Income),
+            4000..=7999 | 8400..=8999 => Some(Self::Cost),
+            _ => None,
+        }
+    }
+
+    /// Whether the account is in the balance sheet, rather than the
+    /// income statement.
+    #[must_use]
+    pub fn is_balance(self) -> bool {
+        matches!(self, Self::Asset | Self::Liability)
+    }
+
+    /// Whether the balance is normally negative (credit), so that reports
+    /// flip its sign.
+    #[must_use]
+    pub fn is_credit(self) -> bool {
+        matches!(self, Self::Liability | Self::Income)
+    }
+}
+
+impl<'a> Decode<'a> for AccountKind {
+    fn decode(fields: &mut Fields<'a, '_>) -> Result<Self, Invalid<'a>> {
+        Ok(match fields.tag(&["T", "S", "K", "I"])? {
+            0 => Self::Asset,
+            1 => Self::Liability,
+            2 => Self::Cost,
+            _ => Self::Income,
+        })
     }
 }
 
@@ -354,8 +1756,14 @@ items_impl! {
         postal_address: String,
         phone: String,
     }
+    /// The industry of the company, see [`crate::sni`].
     BKod (Identification) {
-        sni: String,
+        sni: SniCode,
+    }
+    /// A dimension of objects, e.g. cost centres.
+    Dim (Account) {
+        no: u32,
+        name: String,
     }
     Flagga (Flag) {
         read: bool,
@@ -363,33 +1771,114 @@ items_impl! {
     FNamn (Identification) {
         name: String,
     }
+    /// The identifier of the company in the program that exported the
+    /// file, which tells apart the companies of a file with several, see
+    /// [`Document::by_company`](crate::Document::by_company).
+    Fnr (Identification) {
+        id: String,
+    }
     Format (Identification) {
         format: FormatType,
     }
+    /// The type of company, e.g. `AB` for a limited company, see
+    /// [`crate::company`].
+    FTyp (Identification) {
+        kind: String,
+    }
     Gen (Identification) {
         date: Date,
         signature: Option<String>,
     }
     Ib (Balance) {
         year: i32,
-        account: u32,
+        account: AccountNo,
         balance: Amount,
-        quantity: Option<String>,
+        quantity: Option<Decimal> => quantity,
     }
     Konto (Account) {
-        no: u32,
+        no: AccountNo,
         name: String,
     }
+    /// The type of an account (`#KTYP`), see [`crate::Document::account_kind`].
+    Ktyp (Account) {
+        account: AccountNo,
+        kind: AccountKind,
+    }
+    /// `#KSUMMA` appears twice: without a checksum at the start of the
+    /// checksummed items and with one after them, at the end of the file.
+    /// It is therefore exempt from the group ordering.
+    Ksumma (Flag) {
+        checksum: Option<i64>,
+    }
     KpTyp (Identification) {
         typ: ChartAccountsType,
     }
+    /// An object of a dimension (`#OBJEKT`), e.g. a cost centre, with
+    /// its name.
+    Objekt (Account) {
+        dimension: u32,
+        no: String,
+        name: String,
+    }
+    /// The date that the balances and results are calculated up to
+    /// (`#OMFATTN`), for files of type 1 to 3 that don't cover the whole
+    /// fiscal year.
+    Omfattn (Identification) {
+        date: Date,
+    }
+    /// Opening balance of an object (`#OIB`).
+    Oib (Balance) {
+        year: i32,
+        account: AccountNo,
+        object: Object,
+        balance: Amount,
+        quantity: Option<Decimal> => quantity,
+    }
+    /// The organisation number, see [`crate::company`], and the
+    /// acquisition number (`förvnr`) and activity number (`verknr`) that
+    /// number the businesses a company has acquired or runs.
     Orgnr (Identification) {
-        org_no: String,
+        org_no: OrgNr,
+        forv_no: Option<u32>,
+        verk_no: Option<u32>,
+    }
+    /// Closing balance of an object (`#OUB`).
+    Oub (Balance) {
+        year: i32,
+        account: AccountNo,
+        object: Object,
+        balance: Amount,
+        quantity: Option<Decimal> => quantity,
+    }
+    /// Budget of an account for a period (`#PBUDGET`), optionally
+    /// specified by object. The objects are empty (`{}`) if the budget is
+    /// for the whole account.
+    PBudget (Balance) {
+        year: i32,
+        period: Period,
+        account: AccountNo,
+        objects: List<(u32, String)>,
+        balance: Amount,
+        quantity: Option<Decimal> => quantity,
     }
     Program (Identification) {
         name: String,
         version: String,
     }
+    /// A free-text comment on the file (`#PROSA`).
+    Prosa (Identification) {
+        text: String,
+    }
+    /// Balance of an account at the end of a period (`#PSALDO`), like
+    /// [`PBudget`].
+    PSaldo (Balance) {
+        year: i32,
+        period: Period,
+        account: AccountNo,
+        objects: List<(u32, String)>,
+        balance: Amount,
+        quantity: Option<Decimal> => quantity,
+    }
     Rar (Identification) {
         no: i32,
         start: Date,
@@ -397,27 +1886,51 @@ items_impl! {
     }
     Res (Balance) {
         year: i32,
-        account: u32,
+        account: AccountNo,
         balance: Amount,
-        quantity: Option<String>,
+        quantity: Option<Decimal> => quantity,
     }
     SieTyp (Identification) {
         no: TypeNo,
     }
+    /// The field of the tax declaration (SRU code) that an account is
+    /// reported in (`#SRU`), see [`crate::sru`].
+    Sru (Account) {
+        account: AccountNo,
+        code: u32,
+    }
+    /// The tax year (`#TAXAR`) of the tax declaration that the file is
+    /// meant for, e.g. `2024` for the fiscal year 2023.
+    Taxar (Identification) {
+        year: i32,
+    }
+    /// A transaction, usually of a [`Ver`]. The foreign amount isn't
+    /// part of the file, see [`crate::currency`].
     Trans (Balance) {
-        account: u32,
-        objects: List<String>,
+        account: AccountNo,
+        objects: List<(u32, String)>,
         amount: Amount,
         date: Option<Date>,
         text: Option<String>,
-        quantity: Option<String>,
+        quantity: Option<Decimal> => quantity,
         signature: Option<String>,
+    } + {
+        tags: Tags,
+        foreign: Option<ForeignAmount>,
     }
     Ub (Balance) {
         year: i32,
-        account: u32,
+        account: AccountNo,
         balance: Amount,
-        quantity: Option<String>,
+        quantity: Option<Decimal> => quantity,
+    }
+    /// A dimension that is part of another (`#UNDERDIM`), e.g. cost
+    /// bearers (2) within cost centres (1). See
+    /// [`Dimensions`](crate::dimension::Dimensions) for the hierarchy.
+    UnderDim (Account) {
+        no: u32,
+        name: String,
+        parent: u32,
     }
     Valuta (Identification) {
         currency: Currency,
@@ -429,7 +1942,141 @@ items_impl! {
         text: Option<String>,
         reg_date: Option<Date>,
         sign: Option<String>,
-        transactions: SubEntries<Trans>,
+        transactions: SubEntries<TransLine>,
+    } + {
+        tags: Tags,
+    }
+}
+
+/// A transaction of a [`Ver`]. Verifications that have been corrected
+/// after they were registered list the transactions that were added
+/// (`#RTRANS`) and removed (`#BTRANS`).
+///
+/// For compatibility with programs that don't know about corrections,
+/// each `#RTRANS` is followed by an identical `#TRANS`. These pairs are
+/// read and written as a single [`TransLine::Added`].
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum TransLine {
+    /// `#TRANS`
+    Normal(Trans),
+    /// `#RTRANS`
+    Added(Trans),
+    /// `#BTRANS`
+    Removed(Trans),
+}
+
+impl TransLine {
+    #[must_use]
+    pub fn trans(&self) -> &Trans {
+        match self {
+            Self::Normal(trans) | Self::Added(trans) | Self::Removed(trans) => trans,
+        }
+    }
+
+    pub fn trans_mut(&mut self) -> &mut Trans {
+        match self {
+            Self::Normal(trans) | Self::Added(trans) | Self::Removed(trans) => trans,
+        }
+    }
+}
+
+/// Borrowed variant of [`TransLine`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum TransLineRef<'a> {
+    Normal(TransRef<'a>),
+    Added(TransRef<'a>),
+    Removed(TransRef<'a>),
+}
+
+impl<'a> DecodeEntry<'a> for TransLineRef<'a> {
+    fn decode_entry(
+        entry: &Record<'a>,
+        options: Options,
+        found: &mut Found<'a>,
+    ) -> Option<Result<Self, Invalid<'a>>> {
+        let line: fn(_) -> _ = match entry.label {
+            "TRANS" => Self::Normal,
+            "RTRANS" => Self::Added,
+            "BTRANS" => Self::Removed,
+            _ => return None,
+        };
+
+        Some(TransRef::decode(&mut Fields::new(entry, options, found)).map(line))
+    }
+
+    fn collect(entries: Vec<Self>) -> Vec<Self> {
+        let mut collected: Vec<Self> = Vec::with_capacity(entries.len());
+        // whether the last entry is an `#RTRANS` not yet followed by its `#TRANS`
+        let mut pending = false;
+
+        for entry in entries {
+            match (collected.last(), &entry) {
+                (Some(Self::Added(added)), Self::Normal(trans)) if pending && added == trans => {
+                    pending = false;
+                }
+                _ => {
+                    pending = matches!(entry, Self::Added(_));
+                    collected.push(entry);
+                }
+            }
+        }
+
+        collected
+    }
+}
+
+impl Field for TransLine {
+    type Ref<'a> = TransLineRef<'a>;
+
+    fn from_ref(r: Self::Ref<'_>) -> Self {
+        match r {
+            TransLineRef::Normal(trans) => Self::Normal(trans.into_owned()),
+            TransLineRef::Added(trans) => Self::Added(trans.into_owned()),
+            TransLineRef::Removed(trans) => Self::Removed(trans.into_owned()),
+        }
+    }
+
+    fn into_ref<'a>(self) -> Self::Ref<'a> {
+        match self {
+            Self::Normal(trans) => TransLineRef::Normal(trans.into_ref()),
+            Self::Added(trans) => TransLineRef::Added(trans.into_ref()),
+            Self::Removed(trans) => TransLineRef::Removed(trans.into_ref()),
+        }
+    }
+}
+
+impl Ver {
+    /// The transactions after corrections, i.e. all but the removed ones.
+    /// This is what balances are computed from.
+    pub fn corrected(&self) -> impl Iterator<Item = &Trans> {
+        self.transactions
+            .0
+            .iter()
+            .filter(|line| !matches!(line, TransLine::Removed(_)))
+            .map(TransLine::trans)
+    }
+
+    /// The transactions that balances are computed from: the corrected
+    /// ones, and the removed ones (`#BTRANS`) as well if `include_removed`,
+    /// e.g. for an audit view of everything that was ever booked. See
+    /// [`Document::include_removed`](crate::Document::include_removed).
+    pub fn counted(&self, include_removed: bool) -> impl Iterator<Item = &Trans> {
+        self.transactions
+            .0
+            .iter()
+            .filter(move |line| include_removed || !matches!(line, TransLine::Removed(_)))
+            .map(TransLine::trans)
+    }
+
+    /// The transactions as originally registered, i.e. all but the added
+    /// ones.
+    pub fn original(&self) -> impl Iterator<Item = &Trans> {
+        self.transactions
+            .0
+            .iter()
+            .filter(|line| !matches!(line, TransLine::Added(_)))
+            .map(TransLine::trans)
     }
 }
 
@@ -440,19 +2087,107 @@ mod tests {
     use rust_decimal_macros::dec;
     use time::macros::date;
 
+    /// Decode the first of the fields `i` as a `T` with the default
+    /// options, along with the number of fields left.
+    fn field<'a, T: Decode<'a>>(i: &'a [u8]) -> Option<(T, usize)> {
+        let mode = Mode {
+            eof: true,
+            ..Mode::default()
+        };
+        let (record, _) = tokens::lex_fields(i, 1, "TEST", mode).ok()?;
+        let mut found = Found::default();
+        let mut fields = Fields::new(&record, Options::default(), &mut found);
+        let value = T::decode(&mut fields).ok()?;
+        Some((value, fields.rest.len()))
+    }
+
     #[test]
     fn optional() {
         // invalid date
-        assert!(Option::<Date>::parse_field(Span::new(b"20201301 \"next\"")).is_err());
+        assert!(field::<Option<Date>>(b"20201301 \"next\"").is_none());
 
         // missing date
+        assert_eq!(field::<Option<Date>>(b""), Some((None, 0)));
+        assert_eq!(field::<Option<Date>>(b"{} next"), Some((None, 2)));
+
+        // invalid currency
+        assert!(field::<Option<Currency>>(b"BTC \"next\"").is_none());
+
+        // missing number
+        assert_eq!(field::<Option<i64>>(b"\n"), Some((None, 0)));
+        assert!(field::<Option<i64>>(b"-\n").is_none());
+    }
+
+    #[test]
+    fn empty_optional() {
+        // `""` is a missing field of any type, not an empty text
+        for input in [&b"\"\" next"[..], b"\"\"\n"] {
+            assert_eq!(field::<Option<Cow<str>>>(input).unwrap().0, None);
+            assert_eq!(field::<Option<Date>>(input).unwrap().0, None);
+            assert_eq!(field::<Option<i64>>(input).unwrap().0, None);
+        }
         assert_eq!(
-            Option::<Date>::parse_field(Span::new(b" \"next\"")),
-            Ok((Span::new(b" \"next\""), None))
+            field::<Option<Cow<str>>>(b"\" \" next")
+                .unwrap()
+                .0
+                .as_deref(),
+            Some(" ")
         );
 
-        // invalid currency
-        assert!(Option::<Currency>::parse_field(Span::new(b"BTC \"next\"")).is_err());
+        let trans = "#TRANS 1930 {} 1.00 \"\" \"\" 2 \"\""
+            .parse::<Trans>()
+            .unwrap();
+        assert_eq!(
+            (
+                trans.date,
+                trans.text.as_deref(),
+                trans.quantity,
+                trans.signature.as_deref()
+            ),
+            (None, None, Some(dec!(2)), None)
+        );
+        assert_eq!(trans.to_string(), "#TRANS 1930 {} 1.00 \"\" \"\" 2");
+    }
+
+    #[test]
+    fn parse_number() {
+        assert_eq!(field::<i32>(b"-1930 "), Some((-1930, 0)));
+        assert!(matches!(
+            Taxar::parse(Span::new(b" 2024")),
+            Err(nom::Err::Incomplete(_))
+        ));
+        // not numbers of the type, so optional numbers are left out
+        assert_eq!(field::<Option<u32>>(b"-1 "), Some((None, 1)));
+        assert_eq!(field::<Option<u32>>(b"99999999999 "), Some((None, 1)));
+        assert_eq!(field::<Option<u32>>(b"12x "), Some((None, 1)));
+    }
+
+    #[test]
+    fn parse_account_no() {
+        for (input, no, number) in [
+            (&b"1930 "[..], "1930", Some(1930)),
+            (b"01930 ", "01930", Some(1930)),
+            (b"1930A ", "1930A", None),
+            (b"\"2440\" ", "2440", Some(2440)),
+        ] {
+            let (parsed, _) = field::<AccountNo>(input).unwrap();
+            assert_eq!((parsed.as_str(), parsed.number()), (no, number));
+        }
+        assert!(field::<AccountNo>(b"19-30 ").is_none());
+        assert!(field::<AccountNo>(b"12345678901 ").is_none());
+
+        let ub = "#UB 0 01930 100.00".parse::<Ub>().unwrap();
+        assert_eq!(ub.to_string(), "#UB 0 01930 100.00");
+        let trans = "#TRANS 1930A {} 1.00".parse::<Trans>().unwrap();
+        assert_eq!(trans.to_string(), "#TRANS 1930A {} 1.00");
+
+        let mut accounts =
+            ["1930A", "1930", "01930", "900", "A1"].map(|no| no.parse::<AccountNo>().unwrap());
+        accounts.sort();
+        assert_eq!(
+            accounts.map(|no| no.to_string()),
+            ["900", "1930", "01930", "1930A", "A1"]
+        );
     }
 
     #[test]
@@ -462,7 +2197,7 @@ mod tests {
                 .unwrap()
                 .1,
             Item::Konto(Konto {
-                no: 1220,
+                no: 1220.into(),
                 name: "Inventarier och verktyg".to_owned()
             })
         );
@@ -487,25 +2222,30 @@ mod tests {
                 reg_date: Some(date!(2023 - 03 - 14)),
                 sign: None,
                 transactions: SubEntries(vec![
-                    Trans {
-                        account: 1930,
+                    TransLine::Normal(Trans {
+                        account: 1930.into(),
                         objects: List(vec![]),
-                        amount: dec!(-72.00),
+                        amount: dec!(-72.00).into(),
                         date: Some(date!(2023 - 02 - 28)),
                         text: Some("Pie".to_owned()),
                         quantity: None,
                         signature: None,
-                    },
-                    Trans {
-                        account: 4007,
+                        tags: Tags::default(),
+                        foreign: None,
+                    }),
+                    TransLine::Normal(Trans {
+                        account: 4007.into(),
                         objects: List(vec![]),
-                        amount: dec!(72.00),
+                        amount: dec!(72.00).into(),
                         date: Some(date!(2023 - 02 - 28)),
                         text: Some("Pie".to_owned()),
                         quantity: None,
                         signature: None,
-                    }
-                ])
+                        tags: Tags::default(),
+                        foreign: None,
+                    })
+                ]),
+                tags: Tags::default(),
             })
         );
     }
@@ -517,29 +2257,429 @@ mod tests {
                 .unwrap()
                 .1,
             Trans {
-                account: 1930,
-                objects: Default::default(),
-                amount: dec!(192.00),
+                account: 1930.into(),
+                objects: List::default(),
+                amount: dec!(192.00).into(),
                 date: Some(date!(2023 - 03 - 20)),
                 text: Some("Stonks".to_owned()),
                 quantity: None,
                 signature: None,
+                tags: Tags::default(),
+                foreign: None,
             }
         );
 
         assert_eq!(
             Trans::parse(Span::new(b" 1930 {}\t\t 583.52\n")).unwrap().1,
             Trans {
-                account: 1930,
-                objects: Default::default(),
-                amount: dec!(583.52),
+                account: 1930.into(),
+                objects: List::default(),
+                amount: dec!(583.52).into(),
                 date: None,
                 text: None,
                 quantity: None,
                 signature: None,
+                tags: Tags::default(),
+                foreign: None,
             }
         );
 
         assert!(Trans::parse(Span::new(b" 1930 {} 583.52 \"Stonks\"")).is_err());
     }
+
+    #[test]
+    fn parse_sub_entries() {
+        let entries = |i: &'static [u8]| -> IResult<_, Vec<_>> {
+            let (rest, ver) = VerRef::parse(Span::new(i))?;
+            Ok((rest, ver.transactions.0))
+        };
+
+        let (_, trans) =
+            entries(b" A 1 20230101\n{\n#RTRANS 1930 {} 1.00\n#TRANS 1930 {} 1.00\n}\n").unwrap();
+        assert_eq!(trans.len(), 1);
+
+        // a one-line block
+        let (_, trans) = entries(b" A 1 20230101 {#TRANS 1930 {} 1.00}\n").unwrap();
+        assert!(matches!(&trans[0], TransLineRef::Normal(trans) if trans.amount == dec!(1.00)));
+
+        let Err(nom::Err::Failure(e)) =
+            entries(b" A 1 20230101\n{\n#TRANS 1930 {} 1.00\n#TRANS\n}\n")
+        else {
+            panic!("expected failure");
+        };
+        assert_eq!(e.code, INVALID_SUB_ENTRY);
+        assert_eq!(e.input.location_line(), 4);
+
+        assert!(matches!(
+            entries(b" A 1 20230101\n{\n1930 {} 1.00\n}\n"),
+            Err(nom::Err::Failure(_))
+        ));
+    }
+
+    #[test]
+    fn parse_end_of_item() {
+        // trailing whitespace and the line break are consumed
+        let (rest, _) = Konto::parse(Span::new(b" 1930 Bank \t\r\n#KONTO")).unwrap();
+        assert_eq!(rest.fragment(), b"#KONTO");
+        let (rest, _) = Gen::parse(Span::new(b" 20230101\n\n")).unwrap();
+        assert_eq!(rest.fragment(), b"\n");
+
+        for (i, line) in [
+            (&b"#KONTO 1930 Bank 1\n"[..], 1),
+            (
+                b"#VER A 1 20230101\n{\n#TRANS 1930 {} 1.00 20230101 \"\" 1 AK 1\n}\n",
+                3,
+            ),
+        ] {
+            let Err(nom::Err::Failure(e)) = Item::parse(Span::new(i)) else {
+                panic!("expected failure");
+            };
+            assert_eq!(e.code, EXTRA_FIELDS);
+            assert_eq!(e.input.location_line(), line);
+        }
+    }
+
+    #[test]
+    fn parse_object_balance() {
+        assert_eq!(
+            Oib::parse(Span::new(b" 0 3010 {1 \"456\"} 1000.00\n"))
+                .unwrap()
+                .1,
+            Oib {
+                year: 0,
+                account: 3010.into(),
+                object: Object {
+                    dimension: 1,
+                    object: "456".to_owned(),
+                },
+                balance: dec!(1000.00).into(),
+                quantity: None,
+            }
+        );
+
+        let (_, oub) = Oub::parse(Span::new(b" -1 3010 { 6 P1 } -50.00 2\n")).unwrap();
+        assert_eq!(oub.object.dimension, 6);
+        assert_eq!(oub.object.object, "P1");
+        assert_eq!(oub.quantity, Some(dec!(2)));
+
+        let (_, oib) = Oib::parse(Span::new(b" 0 3010 {1 Z1} 1000.00\n")).unwrap();
+        assert_eq!(oib.object.object, "Z1");
+
+        assert!(Oib::parse(Span::new(b" 0 3010 {1} 1000.00\n")).is_err());
+        assert!(Oib::parse(Span::new(b" 0 3010 {1 2 3} 1000.00\n")).is_err());
+    }
+
+    #[test]
+    fn parse_period_balance() {
+        let (_, psaldo) = PSaldo::parse(Span::new(b" 0 202301 3010 {} -1500.00\n")).unwrap();
+        assert_eq!(
+            psaldo,
+            PSaldo {
+                year: 0,
+                period: Period {
+                    year: 2023,
+                    month: Month::January,
+                },
+                account: 3010.into(),
+                objects: List(vec![]),
+                balance: dec!(-1500.00).into(),
+                quantity: None,
+            }
+        );
+        assert_eq!(psaldo.period.to_string(), "202301");
+
+        let Item::PBudget(pbudget) = Item::parse(Span::new(
+            b"#PBUDGET -1 202212 3010 {1 \"456\"} -900.00 3\n",
+        ))
+        .unwrap()
+        .1
+        else {
+            panic!("expected #PBUDGET");
+        };
+        assert_eq!(pbudget.period.month, Month::December);
+        assert_eq!(pbudget.quantity, Some(dec!(3)));
+
+        assert!(PSaldo::parse(Span::new(b" 0 202313 3010 {} 1.00\n")).is_err());
+        assert!(PSaldo::parse(Span::new(b" 0 2023-01 3010 {} 1.00\n")).is_err());
+
+        // two-digit years only with a century pivot
+        let short = b"#PSALDO 0 2301 3010 {} 1.00\n";
+        assert!(Item::parse(Span::new(short)).is_err());
+        let options = Options {
+            century_pivot: Some(70),
+            ..Options::default()
+        };
+        let Ok(Decoded {
+            item: ItemRef::PSaldo(psaldo),
+            ..
+        }) = read(short, 1, options, None, false)
+        else {
+            panic!("expected #PSALDO");
+        };
+        assert_eq!(psaldo.period.to_string(), "202301");
+        assert!(PSaldo::parse(Span::new(b" 0 20231 3010 {} 1.00\n")).is_err());
+    }
+
+    #[test]
+    fn parse_corrections() {
+        let Item::Ver(ver) = Item::parse(Span::new(
+            b"#VER A 1 20230102 \"Rent\"
+{
+#TRANS 1930 {} -12000.00
+#BTRANS 5010 {} 12000.00
+#RTRANS 5011 {} 12000.00
+#TRANS 5011 {} 12000.00
+#TRANS 5011 {} 12000.00
+#RTRANS 5012 {} 1.00
+}
+",
+        ))
+        .unwrap()
+        .1
+        else {
+            panic!("expected #VER");
+        };
+
+        let kinds = ver
+            .transactions
+            .0
+            .iter()
+            .map(|line| match line {
+                TransLine::Normal(trans) => ("TRANS", trans.account),
+                TransLine::Added(trans) => ("RTRANS", trans.account),
+                TransLine::Removed(trans) => ("BTRANS", trans.account),
+            })
+            .collect::<Vec<_>>();
+        // only the first #TRANS after an #RTRANS belongs to it, and the
+        // second #RTRANS isn't followed by its #TRANS, but is still an
+        // addition
+        assert_eq!(
+            kinds,
+            [
+                ("TRANS", 1930.into()),
+                ("BTRANS", 5010.into()),
+                ("RTRANS", 5011.into()),
+                ("TRANS", 5011.into()),
+                ("RTRANS", 5012.into())
+            ]
+        );
+
+        let accounts = |trans: &mut dyn Iterator<Item = &Trans>| {
+            trans.map(|trans| trans.account).collect::<Vec<_>>()
+        };
+        assert_eq!(accounts(&mut ver.corrected()), [1930, 5011, 5011, 5012]);
+        assert_eq!(accounts(&mut ver.original()), [1930, 5010, 5011]);
+    }
+
+    #[test]
+    fn conversions() {
+        let mut item = Item::from(Konto {
+            no: 1930.into(),
+            name: "Bank".to_owned(),
+        });
+        assert!(<&Ver>::try_from(&item).is_err());
+        <&mut Konto>::try_from(&mut item).unwrap().no = 1920.into();
+        assert_eq!(<&Konto>::try_from(&item).unwrap().no, 1920);
+        let Err(Item::Konto(_)) = Ver::try_from(item) else {
+            panic!("expected the account back");
+        };
+
+        let item = ItemRef::parse(Span::new(b"#KONTO 1930 Bank\n")).unwrap().1;
+        assert_eq!(KontoRef::try_from(item).unwrap().name, "Bank");
+    }
+
+    #[test]
+    fn parse_object_list() {
+        let list = |i: &'static [u8]| field::<List<(u32, Cow<str>)>>(i).unwrap();
+
+        assert_eq!(
+            list(b"{1 \"456\" 7 \"47\"} 13200.00").0,
+            List(vec![(1, "456".into()), (7, "47".into())])
+        );
+
+        let (multiline, rest) = list(b"{\r\n\t1 \"456\"\r\n\t7  \"47\"\r\n} 13200.00");
+        assert_eq!(rest, 1);
+        assert_eq!(multiline, list(b"{1 \"456\" 7 \"47\"} 13200.00").0);
+
+        assert_eq!(list(b"{ \n }\n").0, List(vec![]));
+        assert_eq!(list(b"{1 456}").0, List(vec![(1, "456".into())]));
+        assert_eq!(
+            list(b"{\"6\" \"Proj X\"}").0,
+            List(vec![(6, "Proj X".into())])
+        );
+
+        for invalid in [
+            // stray braces are not objects
+            &b"{1 {456}} 0"[..],
+            // an object is missing
+            b"{1 \"456\" 7} 0",
+            b"{A \"456\"} 0",
+        ] {
+            assert!(
+                field::<List<(u32, Cow<str>)>>(invalid).is_none(),
+                "{invalid:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_multiline_object_list() {
+        let Item::Ver(ver) = Item::parse(Span::new(
+            b"#VER A 567 20081216 \"Cash salary\"\r
+{\r
+\t#TRANS 7010 {\"1\" \"456\"\r
+\t\t\"7\" \"47\"} 13200.00\r
+\t#TRANS 1910 {} -13200.00\r
+}\r
+",
+        ))
+        .unwrap()
+        .1
+        else {
+            panic!("expected #VER");
+        };
+
+        let [TransLine::Normal(salary), TransLine::Normal(cash)] = &ver.transactions.0[..] else {
+            panic!("expected two transactions");
+        };
+        assert_eq!(
+            salary.objects,
+            List(vec![(1, "456".into()), (7, "47".into())])
+        );
+        assert_eq!(salary.amount, dec!(13200.00));
+        assert_eq!(cash.account, 1910);
+        assert_eq!(cash.amount, dec!(-13200.00));
+    }
+
+    #[test]
+    fn parse_escaped_text() {
+        let Item::Ver(ver) = Item::parse(Span::new(
+            b"#VER A 1 20230101 \"C:\\ver\\\"
+{
+\t#TRANS 1930 {1 \"}\\\\\"} 1.00 20230101 \"{\\\"a\\\"}\"
+\t#TRANS 3010 {} -1.00 20230101 \"C:\\trans\\\"
+}
+",
+        ))
+        .unwrap()
+        .1
+        else {
+            panic!("expected #VER");
+        };
+
+        assert_eq!(ver.text.as_deref(), Some("C:\\ver\\"));
+        let [TransLine::Normal(bank), TransLine::Normal(sales)] = &ver.transactions.0[..] else {
+            panic!("expected two transactions");
+        };
+        assert_eq!(bank.objects, List(vec![(1, "}\\".into())]));
+        assert_eq!(bank.text.as_deref(), Some("{\"a\"}"));
+        assert_eq!(sales.text.as_deref(), Some("C:\\trans\\"));
+    }
+
+    #[test]
+    fn parse_quantity() {
+        assert_eq!(
+            Ib::parse(Span::new(b" 0 1460 12000.00 150\n")).unwrap().1,
+            Ib {
+                year: 0,
+                account: 1460.into(),
+                balance: dec!(12000.00).into(),
+                quantity: Some(dec!(150)),
+            }
+        );
+
+        assert_eq!(
+            Ub::parse(Span::new(b" -1 1460 8400.00 \"10,5\"\n"))
+                .unwrap()
+                .1
+                .quantity,
+            Some(dec!(10.5))
+        );
+
+        // unparseable quantities must not be mistaken for the signature
+        let trans = Trans::parse(Span::new(
+            b" 1460 {} 150.00 20230320 \"Pens\" \"\" \"AK\"\n",
+        ))
+        .unwrap()
+        .1;
+        assert_eq!(trans.quantity, None);
+        assert_eq!(trans.signature.as_deref(), Some("AK"));
+    }
+
+    #[test]
+    fn lenient_amounts() {
+        let data = b"#VER A 1 20230101\n{\n#TRANS 1930 {} \"1 000,50\"\n}\n";
+        assert!(ItemRef::parse(Span::new(data)).is_err());
+
+        let read = |data, options| read(data, 1, options, None, true).and_then(Decoded::complete);
+        let options = Options {
+            lenient_amounts: true,
+            ..Options::default()
+        };
+        let decoded = read(data, options).unwrap();
+        assert_eq!(
+            decoded.warnings,
+            [reader::Warning::LenientAmount {
+                text: "1 000,50".to_owned(),
+                line: 3
+            }]
+        );
+
+        // each item has its own amounts
+        let decoded = read(b"#UB 0 1930 1,5\n", options).unwrap();
+        assert_eq!(
+            decoded.warnings,
+            [reader::Warning::LenientAmount {
+                text: "1,5".to_owned(),
+                line: 1
+            }]
+        );
+        let strict = Options {
+            strict: true,
+            ..options
+        };
+        assert!(matches!(
+            read(data, strict),
+            Err(ReadError::Invalid(Invalid {
+                problem: Problem::Amount(10),
+                line: 3,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn from_str() {
+        let ib = "\n#IB 0 1930 100.00\n\n".parse::<Ib>().unwrap();
+        assert_eq!(ib.to_string(), "#IB 0 1930 100.00");
+
+        let ver =
+            "#VER A 1 20230110 \"Hyra\"\n{\n    #TRANS 1930 {} -100.00\n#TRANS 5010 {} 100.00 }"
+                .parse::<Ver>()
+                .unwrap();
+        assert_eq!(
+            ver.to_string(),
+            "#VER \"A\" 1 20230110 \"Hyra\"\n{\n#TRANS 1930 {} -100.00\n#TRANS 5010 {} 100.00\n}"
+        );
+
+        assert!(matches!(
+            "#UB 0 1930 100.00".parse::<Ib>(),
+            Err(ParseItemError::Label {
+                expected: "IB",
+                found: "UB"
+            })
+        ));
+        assert!(matches!(
+            "#IB 0 1930 1.00\n#IB 0 1940 2.00".parse::<Item>(),
+            Err(ParseItemError::Trailing)
+        ));
+        assert!(matches!(
+            "#IB 0 1930 x".parse::<Item>(),
+            Err(ParseItemError::Read(reader::Error::InvalidAmount { .. }))
+        ));
+        assert!(matches!(
+            "#FNAMN \"Łódź AB\"".parse::<Item>(),
+            Err(ParseItemError::Unrepresentable(_))
+        ));
+    }
 }
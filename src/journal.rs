@@ -0,0 +1,266 @@
+//! A minimal model of journal entries for exchange with other systems,
+//! without the details that are specific to SIE4.
+//!
+//! ```
+//! use rust_decimal_macros::dec;
+//! use sie4::{
+//!     journal::{JournalEntry, JournalLine},
+//!     Document,
+//! };
+//! use time::macros::date;
+//!
+//! let entry = JournalEntry {
+//!     journal: "A".to_owned(),
+//!     number: 1,
+//!     date: date!(2023 - 01 - 10),
+//!     description: Some("Rent".to_owned()),
+//!     lines: vec![
+//!         JournalLine::new("1930", dec!(-12000.00)),
+//!         JournalLine::new("5010", dec!(12000.00)),
+//!     ],
+//! };
+//!
+//! let doc = Document::try_from(vec![entry.clone()]).unwrap();
+//! let ver = doc.vouchers().next().unwrap();
+//! assert_eq!(ver.transactions.0[1].trans().account, 5010);
+//! assert_eq!(doc.journal_entries().collect::<Vec<_>>(), [entry]);
+//! ```
+
+#[cfg(feature = "csv")]
+pub mod csv;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use time::Date;
+
+use crate::{
+    item::{List, SubEntries, Trans, TransLine, Ver},
+    tags::Tags,
+    Document, Item,
+};
+
+/// A balanced set of lines booked on the same date, e.g. a voucher.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// The journal (series) that the entry is numbered within.
+    pub journal: String,
+    pub number: u32,
+    pub date: Date,
+    pub description: Option<String>,
+    pub lines: Vec<JournalLine>,
+}
+
+/// An amount debited (positive) or credited (negative) to an account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalLine {
+    pub account: String,
+    pub amount: Decimal,
+    pub description: Option<String>,
+    /// The date of the line, if it differs from that of the entry.
+    pub date: Option<Date>,
+    pub quantity: Option<Decimal>,
+    /// `(dimension, value)` pairs, e.g. `("1", "Nord")` for a cost
+    /// centre.
+    pub dimensions: Vec<(String, String)>,
+}
+
+/// Why a journal entry can't be represented in SIE4.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// The account isn't an [`AccountNo`](crate::item::AccountNo), i.e. 1
+    /// to [`MAX_LEN`](crate::item::AccountNo::MAX_LEN) ASCII letters and
+    /// digits.
+    #[error("account `{0}` is not 1 to 10 letters and digits")]
+    Account(String),
+    /// Dimensions are numbers in SIE4.
+    #[error("dimension `{0}` is not a number")]
+    Dimension(String),
+}
+
+impl JournalLine {
+    /// A line without a description, date, quantity or dimensions.
+    #[must_use]
+    pub fn new(account: impl Into<String>, amount: Decimal) -> Self {
+        Self {
+            account: account.into(),
+            amount,
+            description: None,
+            date: None,
+            quantity: None,
+            dimensions: Vec::new(),
+        }
+    }
+}
+
+impl From<&Trans> for JournalLine {
+    fn from(trans: &Trans) -> Self {
+        Self {
+            account: trans.account.to_string(),
+            amount: trans.amount.0,
+            description: trans.text.clone(),
+            date: trans.date,
+            quantity: trans.quantity,
+            dimensions: trans
+                .objects
+                .0
+                .iter()
+                .map(|(dimension, object)| (dimension.to_string(), object.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<JournalLine> for Trans {
+    type Error = Error;
+
+    fn try_from(line: JournalLine) -> Result<Self, Self::Error> {
+        let account = line
+            .account
+            .parse()
+            .map_err(|_| Error::Account(line.account))?;
+        let objects = line
+            .dimensions
+            .into_iter()
+            .map(|(dimension, value)| match dimension.parse() {
+                Ok(no) => Ok((no, value)),
+                Err(_) => Err(Error::Dimension(dimension)),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            account,
+            objects: List(objects),
+            amount: line.amount.into(),
+            date: line.date,
+            text: line.description,
+            quantity: line.quantity,
+            signature: None,
+            tags: Tags::default(),
+            foreign: None,
+        })
+    }
+}
+
+/// The transactions after corrections (see [`Ver::corrected`]).
+impl From<&Ver> for JournalEntry {
+    fn from(ver: &Ver) -> Self {
+        Self::new(ver, false)
+    }
+}
+
+impl JournalEntry {
+    /// The entry of the [counted](Ver::counted) transactions of `ver`.
+    fn new(ver: &Ver, include_removed: bool) -> Self {
+        Self {
+            journal: ver.series.clone(),
+            number: ver.no,
+            date: ver.date,
+            description: ver.text.clone(),
+            lines: ver
+                .counted(include_removed)
+                .map(JournalLine::from)
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<JournalEntry> for Ver {
+    type Error = Error;
+
+    fn try_from(entry: JournalEntry) -> Result<Self, Self::Error> {
+        Ok(Self {
+            series: entry.journal,
+            no: entry.number,
+            date: entry.date,
+            text: entry.description,
+            reg_date: None,
+            sign: None,
+            transactions: SubEntries(
+                entry
+                    .lines
+                    .into_iter()
+                    .map(|line| Trans::try_from(line).map(TransLine::Normal))
+                    .collect::<Result<_, _>>()?,
+            ),
+            tags: Tags::default(),
+        })
+    }
+}
+
+/// A document with only the vouchers of the entries.
+impl TryFrom<Vec<JournalEntry>> for Document {
+    type Error = Error;
+
+    fn try_from(entries: Vec<JournalEntry>) -> Result<Self, Self::Error> {
+        entries
+            .into_iter()
+            .map(|entry| Ver::try_from(entry).map(Item::Ver))
+            .collect()
+    }
+}
+
+impl Document {
+    /// The vouchers as journal entries, with the removed transactions if
+    /// they are [included](Document::include_removed).
+    pub fn journal_entries(&self) -> impl Iterator<Item = JournalEntry> + '_ {
+        self.vouchers()
+            .map(|ver| JournalEntry::new(ver, self.include_removed()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn corrections_and_errors() {
+        let doc = Document::from_reader(
+            &b"#VER A 1 20230110 \"Rent\" 20230111 AK
+{
+    #TRANS 1930 {} -100.00
+    #BTRANS 5010 {} 100.00
+    #RTRANS 5011 {1 \"Nord\"} 100.00 20230112 \"Office\" 2
+    #TRANS 5011 {1 \"Nord\"} 100.00 20230112 \"Office\" 2
+}
+"[..],
+        )
+        .unwrap();
+
+        let entry = doc.journal_entries().next().unwrap();
+        assert_eq!(
+            entry.lines,
+            [
+                JournalLine::new("1930", dec!(-100.00)),
+                JournalLine {
+                    description: Some("Office".to_owned()),
+                    date: Some(time::macros::date!(2023 - 01 - 12)),
+                    quantity: Some(dec!(2)),
+                    dimensions: vec![("1".to_owned(), "Nord".to_owned())],
+                    ..JournalLine::new("5011", dec!(100.00))
+                }
+            ]
+        );
+
+        let ver = Ver::try_from(entry.clone()).unwrap();
+        assert_eq!(ver.corrected().count(), 2);
+        assert_eq!(JournalEntry::from(&ver), entry);
+
+        let mut lettered = entry.clone();
+        lettered.lines[0].account = "1930A".to_owned();
+        assert!(Ver::try_from(lettered).is_ok());
+        let mut invalid = entry.clone();
+        invalid.lines[0].account = "1930-01".to_owned();
+        assert_eq!(
+            Ver::try_from(invalid),
+            Err(Error::Account("1930-01".to_owned()))
+        );
+        let mut invalid = entry;
+        invalid.lines[1].dimensions[0].0 = "region".to_owned();
+        assert_eq!(
+            Document::try_from(vec![invalid]).map(|doc| doc.items().len()),
+            Err(Error::Dimension("region".to_owned()))
+        );
+    }
+}
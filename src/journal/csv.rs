@@ -0,0 +1,439 @@
+//! Journal entries from CSV, e.g. of books kept in a spreadsheet, as an
+//! import file (SIE4I) to deliver to an accountant.
+//!
+//! The first row names the columns, with the names of
+//! [`export::csv::Column`]: `date`, `account` and `amount` are required,
+//! and `series`, `no`, `text`, `quantity`, `account_name` and the
+//! objects, e.g. `dim1`, are optional. Without a `no` column, the rows
+//! of an entry are those in a row with the same date and series, up to
+//! the one that balances them, and the entries are numbered from 1
+//! within each series. An entry that doesn't balance is an error.
+//!
+//! ```
+//! use sie4::{journal::csv::Converter, validate};
+//! use time::macros::date;
+//!
+//! let csv = "date,account,amount,text
+//! 2023-01-10,1930,-12000.00,Rent
+//! 2023-01-10,5010,12000.00,Rent
+//! 2023-01-12,1930,500.00,Sale
+//! 2023-01-12,3001,-400.00,Sale
+//! 2023-01-12,2611,-100.00,VAT
+//! ";
+//! let doc = Converter::new("Foreningen", date!(2023 - 02 - 01))
+//!     .document(csv.as_bytes())
+//!     .unwrap();
+//!
+//! let vouchers = doc.vouchers().collect::<Vec<_>>();
+//! assert_eq!((vouchers[1].no, vouchers[1].text.as_deref()), (2, Some("Sale")));
+//! assert_eq!(vouchers[1].transactions.0[2].trans().text.as_deref(), Some("VAT"));
+//! assert!(validate::validate(&doc).is_valid());
+//! ```
+//!
+//! [`export::csv::Column`]: crate::export::csv::Column
+
+use std::{collections::BTreeMap, io::Read};
+
+use rust_decimal::Decimal;
+use time::{macros::format_description, Date};
+
+use super::{JournalEntry, JournalLine};
+use crate::{
+    export::csv::{Column, UnknownColumn},
+    item::{
+        AccountNo, FNamn, Flagga, Format, FormatType, Gen, Konto, Program, SieTyp, TypeNo, Ver,
+    },
+    Document, Item,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+    #[error(transparent)]
+    UnknownColumn(#[from] UnknownColumn),
+    #[error(transparent)]
+    Journal(#[from] super::Error),
+    #[error("missing column `{0}`")]
+    MissingColumn(Column),
+    /// A column of [`export::csv`](crate::export::csv) that isn't part
+    /// of a journal entry, e.g. `signature`.
+    #[error("column `{0}` can't be converted")]
+    Unsupported(Column),
+    #[error("invalid {column} `{text}` on line {line}")]
+    Invalid {
+        column: Column,
+        text: String,
+        line: u64,
+    },
+    /// The entry that starts on `line` doesn't sum to zero.
+    #[error("the entry on line {line} is unbalanced by {amount}")]
+    Unbalanced { line: u64, amount: Decimal },
+}
+
+/// A row of the CSV.
+struct Row {
+    line: u64,
+    series: String,
+    no: Option<u32>,
+    date: Date,
+    account: AccountNo,
+    account_name: Option<String>,
+    amount: Decimal,
+    text: Option<String>,
+    quantity: Option<Decimal>,
+    dimensions: Vec<(String, String)>,
+}
+
+/// Converts CSV into journal entries or documents, see the
+/// [module](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Converter {
+    company: String,
+    generated: Date,
+    series: String,
+    swedish_numbers: bool,
+}
+
+impl Converter {
+    /// A converter of the books of `company`, whose files are generated
+    /// (`#GEN`) on `generated`.
+    pub fn new(company: impl Into<String>, generated: Date) -> Self {
+        Self {
+            company: company.into(),
+            generated,
+            series: "A".to_owned(),
+            swedish_numbers: false,
+        }
+    }
+
+    /// The series of the rows without one, `A` by default.
+    #[must_use]
+    pub fn series(mut self, series: impl Into<String>) -> Self {
+        self.series = series.into();
+        self
+    }
+
+    /// Read decimal commas, and semicolons between the fields, as
+    /// spreadsheets with a Swedish locale write them (see
+    /// [`Columns::swedish_numbers`](crate::export::csv::Columns::swedish_numbers)).
+    #[must_use]
+    pub fn swedish_numbers(mut self, swedish: bool) -> Self {
+        self.swedish_numbers = swedish;
+        self
+    }
+
+    /// The entries of the CSV, in the order of their rows.
+    ///
+    /// # Errors
+    ///
+    /// If the CSV can't be read, its columns are missing or unknown, a
+    /// field is invalid or an entry is unbalanced.
+    pub fn entries(&self, csv: impl Read) -> Result<Vec<JournalEntry>, Error> {
+        Ok(self.read(csv)?.0)
+    }
+
+    /// An import file (SIE4I) of the entries of the CSV, with the
+    /// accounts of the `account_name` column.
+    ///
+    /// # Errors
+    ///
+    /// See [`Converter::entries`].
+    pub fn document(&self, csv: impl Read) -> Result<Document, Error> {
+        let (entries, accounts) = self.read(csv)?;
+
+        let header = [
+            Item::Flagga(Flagga { read: false }),
+            Item::Program(Program {
+                name: "sie4".to_owned(),
+                version: env!("CARGO_PKG_VERSION").to_owned(),
+            }),
+            Item::Format(Format {
+                format: FormatType::PC8,
+            }),
+            Item::Gen(Gen {
+                date: self.generated,
+                signature: None,
+            }),
+            Item::SieTyp(SieTyp { no: TypeNo::SIE4 }),
+            Item::FNamn(FNamn {
+                name: self.company.clone(),
+            }),
+        ];
+        let accounts = accounts
+            .into_iter()
+            .map(|(no, name)| Item::Konto(Konto { no, name }));
+        // the accounts and dimensions have been read as numbers already
+        let vouchers = entries
+            .into_iter()
+            .map(|entry| Ver::try_from(entry).map(Item::Ver))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(header.into_iter().chain(accounts).chain(vouchers).collect())
+    }
+
+    /// The entries and the names of the accounts.
+    fn read(
+        &self,
+        csv: impl Read,
+    ) -> Result<(Vec<JournalEntry>, BTreeMap<AccountNo, String>), Error> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(if self.swedish_numbers { b';' } else { b',' })
+            .trim(csv::Trim::All)
+            .from_reader(csv);
+        let columns = reader
+            .headers()?
+            .iter()
+            .map(str::parse)
+            .collect::<Result<Vec<Column>, _>>()?;
+        for column in [Column::Date, Column::Account, Column::Amount] {
+            if !columns.contains(&column) {
+                return Err(Error::MissingColumn(column));
+            }
+        }
+        if let Some(&column) = columns
+            .iter()
+            .find(|column| matches!(column, Column::RegDate | Column::Signature))
+        {
+            return Err(Error::Unsupported(column));
+        }
+
+        let mut entries = Vec::new();
+        let mut accounts = BTreeMap::new();
+        let mut numbers = BTreeMap::<String, u32>::new();
+        // the open entry, the line it starts on and its sum
+        let mut open: Option<(JournalEntry, u64, Decimal)> = None;
+
+        for record in reader.records() {
+            let row = self.row(&columns, &record?)?;
+            if let Some(name) = &row.account_name {
+                accounts.entry(row.account).or_insert_with(|| name.clone());
+            }
+
+            let continues = open.as_ref().is_some_and(|(entry, _, sum)| {
+                entry.journal == row.series
+                    && match row.no {
+                        Some(no) => entry.number == no,
+                        None => entry.date == row.date && !sum.is_zero(),
+                    }
+            });
+            if !continues {
+                if let Some((entry, line, sum)) = open.take() {
+                    entries.push(balanced(entry, line, sum)?);
+                }
+                let number = numbers.entry(row.series.clone()).or_default();
+                *number += 1;
+                open = Some((
+                    JournalEntry {
+                        journal: row.series.clone(),
+                        number: row.no.unwrap_or(*number),
+                        date: row.date,
+                        description: row.text.clone(),
+                        lines: Vec::new(),
+                    },
+                    row.line,
+                    Decimal::ZERO,
+                ));
+            }
+
+            let (entry, _, sum) = open.as_mut().expect("opened above");
+            *sum += row.amount;
+            entry.lines.push(JournalLine {
+                account: row.account.to_string(),
+                amount: row.amount,
+                description: row
+                    .text
+                    .filter(|text| entry.description.as_ref() != Some(text)),
+                date: Some(row.date).filter(|&date| date != entry.date),
+                quantity: row.quantity,
+                dimensions: row.dimensions,
+            });
+        }
+        if let Some((entry, line, sum)) = open {
+            entries.push(balanced(entry, line, sum)?);
+        }
+
+        Ok((entries, accounts))
+    }
+
+    fn row(&self, columns: &[Column], record: &csv::StringRecord) -> Result<Row, Error> {
+        let line = record.position().map_or(0, csv::Position::line);
+        let invalid = |column, text: &str| Error::Invalid {
+            column,
+            text: text.to_owned(),
+            line,
+        };
+        let number = |column, text: &str| {
+            let mut number = text.replace([' ', '\u{a0}'], "");
+            if self.swedish_numbers {
+                number = number.replace(',', ".");
+            }
+            number.parse::<Decimal>().map_err(|_| invalid(column, text))
+        };
+
+        let (mut date, mut account, mut amount) = (None, None, None);
+        let mut row = Row {
+            line,
+            series: self.series.clone(),
+            no: None,
+            date: Date::MIN,
+            account: AccountNo::from(0),
+            account_name: None,
+            amount: Decimal::ZERO,
+            text: None,
+            quantity: None,
+            dimensions: Vec::new(),
+        };
+        for (&column, field) in columns.iter().zip(record) {
+            if field.is_empty() && column != Column::No {
+                continue;
+            }
+            match column {
+                Column::Series => field.clone_into(&mut row.series),
+                Column::No => row.no = Some(field.parse().map_err(|_| invalid(column, field))?),
+                Column::Date => {
+                    date = Some(parse_date(field).ok_or_else(|| invalid(column, field))?);
+                }
+                Column::Account => {
+                    account = Some(field.parse().map_err(|_| invalid(column, field))?);
+                }
+                Column::AccountName => row.account_name = Some(field.to_owned()),
+                Column::Amount => amount = Some(number(column, field)?),
+                Column::Quantity => row.quantity = Some(number(column, field)?),
+                Column::Text => row.text = Some(field.to_owned()),
+                Column::Object(dimension) => {
+                    row.dimensions
+                        .push((dimension.to_string(), field.to_owned()));
+                }
+                Column::RegDate | Column::Signature => (),
+            }
+        }
+
+        row.date = date.ok_or_else(|| invalid(Column::Date, ""))?;
+        row.account = account.ok_or_else(|| invalid(Column::Account, ""))?;
+        row.amount = amount.ok_or_else(|| invalid(Column::Amount, ""))?;
+        Ok(row)
+    }
+}
+
+/// `entry`, which starts on `line`, if its lines sum to zero.
+fn balanced(entry: JournalEntry, line: u64, sum: Decimal) -> Result<JournalEntry, Error> {
+    if sum.is_zero() {
+        Ok(entry)
+    } else {
+        Err(Error::Unbalanced { line, amount: sum })
+    }
+}
+
+/// A date such as `2023-01-10`, as exported, or `20230110`, as in SIE.
+fn parse_date(s: &str) -> Option<Date> {
+    Date::parse(s, format_description!("[year]-[month]-[day]"))
+        .or_else(|_| Date::parse(s, format_description!("[year][month][day]")))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+    use time::macros::date;
+
+    use super::*;
+    use crate::export::csv::{transactions, Columns};
+
+    fn converter() -> Converter {
+        Converter::new("AB", date!(2023 - 02 - 01))
+    }
+
+    #[test]
+    fn exported() {
+        let doc = Document::from_reader(
+            &b"#KONTO 1930 \"Bank\"
+#VER B 7 20230102 \"Rent\"
+{
+    #TRANS 1930 {1 \"Nord\"} -12000.50
+    #TRANS 5010 {} 12000.50 20230103 \"Office\"
+}
+#VER B 8 20230102 \"Sale\"
+{
+    #TRANS 1930 {} 100.00
+    #TRANS 3001 {} -100.00
+}
+"[..],
+        )
+        .unwrap();
+        let columns = Columns::new([
+            Column::Series,
+            Column::No,
+            Column::Date,
+            Column::Account,
+            Column::AccountName,
+            Column::Amount,
+            Column::Text,
+            Column::Object(1),
+        ])
+        .swedish_numbers(true);
+        let mut csv = Vec::new();
+        transactions(&doc, &columns, &mut csv).unwrap();
+
+        let converted = converter()
+            .swedish_numbers(true)
+            .document(&csv[..])
+            .unwrap();
+        assert_eq!(
+            converted.journal_entries().collect::<Vec<_>>(),
+            doc.journal_entries().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            converted
+                .accounts()
+                .map(|konto| konto.name.as_str())
+                .collect::<Vec<_>>(),
+            ["Bank"]
+        );
+        assert!(crate::validate::validate(&converted).is_valid());
+    }
+
+    #[test]
+    fn errors() {
+        let entries = |csv: &str| converter().entries(csv.as_bytes());
+
+        assert!(matches!(
+            entries("date,account,text\n"),
+            Err(Error::MissingColumn(Column::Amount))
+        ));
+        assert!(matches!(
+            entries("date,account,amount,signature\n"),
+            Err(Error::Unsupported(Column::Signature))
+        ));
+        assert!(matches!(
+            entries("date,konto\n"),
+            Err(Error::UnknownColumn(_))
+        ));
+        assert!(matches!(
+            entries("date,account,amount\n20230101,1930,1\n20230101,1930,x\n"),
+            Err(Error::Invalid { column: Column::Amount, ref text, line: 3 }) if text == "x"
+        ));
+
+        // the second entry starts on another date before the first balances
+        assert!(matches!(
+            entries("date,account,amount\n20230101,1930,1\n20230102,3001,-1\n"),
+            Err(Error::Unbalanced { line: 2, amount }) if amount == dec!(1)
+        ));
+        assert!(matches!(
+            entries("series,no,date,account,amount\nA,3,20230101,1930,1\nA,3,20230101,3001,-0.5\n"),
+            Err(Error::Unbalanced { line: 2, amount }) if amount == dec!(0.5)
+        ));
+
+        let entries = entries(
+            "series,date,account,amount\nA,20230101,1930,1\nA,20230101,3001,-1\nB,20230101,1930,2\nB,20230101,3001,-2\nA,20230101,1930,3\nA,20230101,3001,-3\n",
+        )
+        .unwrap();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.journal.as_str(), entry.number))
+                .collect::<Vec<_>>(),
+            [("A", 1), ("B", 1), ("A", 2)]
+        );
+    }
+}
@@ -1,16 +1,112 @@
 //! SIE4 is a Swedish file format for accounting data.
 //! See [the specification](https://sie.se/wp-content/uploads/2020/05/SIE_filformat_ver_4B_ENGLISH.pdf)
 //! for more information.
+//!
+//! Reading, writing, the [`Document`] model and [validation](validate)
+//! are always built. The reports and the integrations are behind
+//! features, none of which are enabled by default:
+//!
+//! - `reports`: `budget`, `consolidate`, `diff`, `duplicates`, `reports`
+//!   and `vat`.
+//! - `anonymize`: `anonymize`, hashing texts with `blake3`.
+//! - `csv`: `export::csv` and `journal::csv`, with the `csv` crate.
+//! - `toml`: reading the rules of `reports::statements::Mapping` from
+//!   TOML, with `toml_edit`. Implies `reports`.
+//! - `mmap`: `reader::Mmap`, reading memory-mapped files with `memmap2`.
+//! - `trace`: `tracing` events while reading.
+//! - `english-names`: the deprecated English names of `compat`.
+//! - `conformance`: the test vectors of `conformance`.
+//! - `diagnostics`: `diagnostics`, errors rendered with the lines of the
+//!   input, also through `miette`.
+//! - `wasm`: bindings for JavaScript, with `wasm-bindgen`.
+//!
+//! The command-line tool, with its dependencies such as the XLSX export,
+//! is the separate `sie4-cli` crate.
 
 #![warn(clippy::pedantic)]
 
-mod parsers;
+/// A `tracing` event, if the `trace` feature is enabled.
+macro_rules! event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "trace")]
+        tracing::event!($($arg)*);
+    };
+}
 
+pub mod accounts;
+pub mod amount;
+#[cfg(feature = "anonymize")]
+pub mod anonymize;
+#[cfg(feature = "reports")]
+pub mod budget;
+pub mod chart;
+pub mod company;
+#[cfg(feature = "english-names")]
+pub mod compat;
+#[cfg(feature = "conformance")]
+pub mod conformance;
+#[cfg(feature = "reports")]
+pub mod consolidate;
+pub mod currency;
+pub mod custom;
+#[cfg(feature = "diagnostics")]
+pub mod diagnostics;
+#[cfg(feature = "reports")]
+pub mod diff;
+pub mod dimension;
+pub mod document;
+#[cfg(feature = "reports")]
+pub mod duplicates;
+pub mod export;
+pub mod filter;
+pub mod fiscal;
+pub mod import;
 pub mod item;
+pub mod journal;
+pub mod metrics;
+pub mod parsers;
 pub mod reader;
+#[cfg(feature = "reports")]
+pub mod reports;
+pub mod resolve;
+pub mod series;
+pub mod signatures;
+pub mod sni;
+pub mod sru;
+pub mod tags;
+pub mod tokens;
+pub mod validate;
+#[cfg(feature = "reports")]
+pub mod vat;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod writer;
 
-pub use item::Item;
-pub use reader::Reader;
+pub use document::Document;
+pub use item::{Item, ItemRef};
+pub use reader::{Reader, SliceReader};
+pub use writer::Writer;
 
-/// See [`nom_locate::LocatedSpan`].
-pub type Span<'a> = nom_locate::LocatedSpan<&'a [u8]>;
+/// Parse the items of a whole file in memory, decoding the text from code
+/// page 437. For large files, prefer iterating a [`SliceReader`] or a
+/// [`Reader`], which don't keep every item in memory.
+///
+/// ```
+/// use sie4::Item;
+///
+/// let items = sie4::parse_items(b"#FLAGGA 0\n#FNAMN \"R\x84ksm\x94rg\x86s AB\"\n").unwrap();
+/// assert!(matches!(&items[1], Item::FNamn(fnamn) if fnamn.name == "R\u{e4}ksm\u{f6}rg\u{e5}s AB"));
+/// ```
+///
+/// # Errors
+///
+/// Returns the first error encountered by the [`SliceReader`].
+pub fn parse_items(data: &[u8]) -> Result<Vec<Item>, reader::Error> {
+    SliceReader::new(data)
+        .map(|res| res.map(ItemRef::into_owned))
+        .collect()
+}
+
+/// See [`nom_locate::LocatedSpan`]. The [`parsers`] keep the `extra` of
+/// their input, if any.
+pub type Span<'a, X = ()> = nom_locate::LocatedSpan<&'a [u8], X>;
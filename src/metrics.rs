@@ -0,0 +1,138 @@
+//! Counters of what a reader parses, e.g. to monitor the ingestion of
+//! uploaded files.
+//!
+//! A [`Metrics`] implementation is given to [`Reader::metrics`] (or
+//! [`SliceReader::metrics`]), which reports to it as it reads. Bridging
+//! to a metrics library such as `prometheus` is a matter of implementing
+//! the trait for a struct of its counters; [`Counters`] just keeps the
+//! totals.
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use sie4::{metrics::Counters, Reader};
+//!
+//! let data = b"#FLAGGA 0
+//! #KONTO 1930 \"Bank\"
+//! #KONTO 2440 \"Skulder\"
+//! #VER A 1 20230102
+//! {
+//! #TRANS {} 100.00
+//! }
+//! ";
+//! let counters = Arc::new(Counters::default());
+//! let items = Reader::new(&data[..]).metrics(counters.clone()).count();
+//!
+//! assert_eq!(items, 4);
+//! assert_eq!(counters.bytes(), data.len() as u64);
+//! assert_eq!(counters.items("KONTO"), 2);
+//! assert_eq!(counters.errors(), 1);
+//! ```
+//!
+//! [`Reader::metrics`]: crate::Reader::metrics
+//! [`SliceReader::metrics`]: crate::SliceReader::metrics
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use crate::reader::Error;
+
+/// Receives the counters of a reader. Every method does nothing by
+/// default, so implementations only need the ones they track.
+///
+/// The methods take `&self` since the metrics are usually shared with
+/// whatever collects them, e.g. in an [`Arc`](std::sync::Arc).
+pub trait Metrics {
+    /// `n` bytes of input have been parsed or skipped.
+    fn bytes(&self, n: u64) {
+        let _ = n;
+    }
+
+    /// An item with the label `label`, e.g. `"VER"`, has been read.
+    fn item(&self, label: &'static str) {
+        let _ = label;
+    }
+
+    /// An error has been reported, e.g. an invalid item that the reader
+    /// skipped.
+    fn error(&self, error: &Error) {
+        let _ = error;
+    }
+
+    /// Time spent reading and parsing an item or error.
+    fn duration(&self, duration: Duration) {
+        let _ = duration;
+    }
+}
+
+/// [`Metrics`] that keep the totals.
+#[derive(Debug, Default)]
+pub struct Counters {
+    bytes: AtomicU64,
+    items: Mutex<BTreeMap<&'static str, u64>>,
+    errors: AtomicU64,
+    nanos: AtomicU64,
+}
+
+impl Counters {
+    #[must_use]
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// The number of items with the label `label`.
+    #[must_use]
+    pub fn items(&self, label: &str) -> u64 {
+        self.item_counts().get(label).copied().unwrap_or_default()
+    }
+
+    /// The number of items of each label.
+    #[must_use]
+    pub fn item_counts(&self) -> BTreeMap<&'static str, u64> {
+        self.items
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    #[must_use]
+    pub fn errors(&self) -> u64 {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    /// The total time spent reading.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Relaxed))
+    }
+}
+
+impl Metrics for Counters {
+    fn bytes(&self, n: u64) {
+        self.bytes.fetch_add(n, Ordering::Relaxed);
+    }
+
+    fn item(&self, label: &'static str) {
+        *self
+            .items
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .entry(label)
+            .or_default() += 1;
+    }
+
+    fn error(&self, _: &Error) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn duration(&self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
@@ -1,80 +1,488 @@
+//! Low-level parsers for the building blocks of items.
+//!
+//! These are the parsers that items are built from, useful for parsing
+//! custom items or fragments of files. Like the rest of the crate, they
+//! are [streaming](nom#streaming--complete) parsers: reaching the end of
+//! the input returns [`nom::Err::Incomplete`] unless the result can't be
+//! affected by more input.
+//!
+//! Text is decoded from CP437 (see [`crate::item::FormatType::PC8`]).
+//!
+//! Since the parsers are streaming, a fragment that ends with a field,
+//! e.g. a line stored on its own in a database, should end with a line
+//! break, like the lines of a file:
+//!
+//! ```
+//! use sie4::{
+//!     parsers::{date, unquoted_text},
+//!     Span,
+//! };
+//! use time::macros::date;
+//!
+//! assert!(matches!(unquoted_text(Span::new(b"20230102")), Err(nom::Err::Incomplete(_))));
+//! let (_, field) = unquoted_text(Span::new(b"20230102\n")).unwrap();
+//! assert_eq!(date(field).unwrap().1, date!(2023 - 01 - 02));
+//! ```
+//!
+//! The parsers take spans with any `Copy`
+//! [`extra`](nom_locate::LocatedSpan::extra), which they keep but don't
+//! read. What they accept depends only on their input and arguments; the
+//! options of a [`Reader`](crate::Reader) don't apply to them.
+//!
+//! # Stability
+//!
+//! The parsers follow semantic versioning like the rest of the crate:
+//! what they accept and return only changes in breaking releases, so
+//! that tools that parse fragments of files can rely on reading them as
+//! the items of a [`Reader`](crate::Reader) with the default options are
+//! read.
+
 use std::{borrow::Cow, str::FromStr};
 
 use codepage_437::{BorrowFromCp437, CP437_CONTROL};
-use memchr::memchr3;
 use nom::{
     branch::alt,
-    bytes::streaming::{escaped, tag, take_while1},
-    character::streaming::{char, none_of},
+    bytes::streaming::tag,
+    character::streaming::char,
     combinator::map,
     error::{Error, ErrorKind, FromExternalError},
     sequence::delimited,
     Err, IResult, Slice,
 };
+use rust_decimal::Decimal;
 use time::{format_description::FormatItem, macros::format_description, Date};
 
-use crate::Span;
+use crate::{amount::Amount, item::INVALID_AMOUNT, Span};
 
-pub fn is_whitespace(c: u8) -> bool {
+/// Fields are separated by spaces or tabs.
+#[must_use]
+pub const fn is_whitespace(c: u8) -> bool {
     c == b' ' || c == b'\t'
 }
 
-pub fn is_line_break(c: u8) -> bool {
+/// Items end with a line feed, optionally preceded by a carriage return.
+#[must_use]
+pub const fn is_line_break(c: u8) -> bool {
     c == b'\n' || c == b'\r'
 }
+
+/// Dates are written as `YYYYMMDD`.
 pub const DATE_FORMAT: &[FormatItem] = format_description!("[year][month][day]");
 
-pub fn in_curly_braces(i: Span) -> IResult<Span, Span> {
+/// A century pivot for [`short_date`] and
+/// [`Reader::century_pivot`](crate::Reader::century_pivot) that reads
+/// two-digit years as 1970 to 2069.
+pub const DEFAULT_CENTURY_PIVOT: u8 = 70;
+
+/// What the item parsers read, besides the specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct Options {
+    /// Whether dates may have two-digit years, and if so, the years
+    /// below which they are in the 2000s rather than the 1900s.
+    pub century_pivot: Option<u8>,
+    /// Whether amounts may have decimal commas and thousands separators.
+    pub lenient_amounts: bool,
+    /// Whether to read only what the specification allows, see
+    /// [`Reader::strict`](crate::Reader::strict). Overrides the other
+    /// options.
+    pub strict: bool,
+}
+
+/// The year and the rest of `digits`, whose year has four digits, or two
+/// if `digits` is `len - 2` digits long and there is a `century_pivot`:
+/// two-digit years below it are in the 2000s, the rest in the 1900s.
+pub(crate) fn split_year(
+    digits: &[u8],
+    len: usize,
+    century_pivot: Option<u8>,
+) -> Option<(i32, &[u8])> {
+    if !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    let number = |digits: &[u8]| digits.iter().fold(0, |n, &d| n * 10 + i32::from(d - b'0'));
+
+    match (digits.len(), century_pivot) {
+        (n, _) if n == len => Some((number(&digits[..4]), &digits[4..])),
+        (n, Some(pivot)) if n + 2 == len => {
+            let yy = number(&digits[..2]);
+            let century = if yy < i32::from(pivot) { 2000 } else { 1900 };
+            Some((century + yy, &digits[2..]))
+        }
+        _ => None,
+    }
+}
+
+/// The contents of a pair of curly braces, which may be nested.
+///
+/// ```
+/// use sie4::{parsers::in_curly_braces, Span};
+///
+/// let (rest, list) = in_curly_braces(Span::new(b"{1 \"456\" {}} 13200.00")).unwrap();
+/// assert_eq!(list.fragment(), b"1 \"456\" {}");
+/// assert_eq!(rest.fragment(), b" 13200.00");
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the input doesn't start with `{`, and
+/// [`nom::Err::Incomplete`] if the braces aren't closed; see also
+/// [`take_until_unbalanced`].
+pub fn in_curly_braces<X: Copy>(i: Span<X>) -> IResult<Span<X>, Span<X>> {
     delimited(char('{'), take_until_unbalanced(b'{', b'}'), char('}'))(i)
 }
 
-pub fn unquoted_text(i: Span) -> IResult<Span, Span> {
-    take_while1(|c: u8| {
-        !is_whitespace(c) && !is_line_break(c) && c != b'#' && c != b'{' && c != b'}'
-    })(i)
+/// A field without quotes, i.e. anything up to whitespace, a line break,
+/// `#`, `{` or `}`.
+///
+/// ```
+/// use sie4::{parsers::unquoted_text, Span};
+///
+/// let (rest, text) = unquoted_text(Span::new(b"12000.00 \"next\"")).unwrap();
+/// assert_eq!(text.fragment(), b"12000.00");
+/// assert_eq!(rest.fragment(), b" \"next\"");
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the field is empty, and [`nom::Err::Incomplete`]
+/// if the input ends before the field does.
+pub fn unquoted_text<X: Copy>(i: Span<X>) -> IResult<Span<X>, Span<X>> {
+    match i.iter().position(|&c| ENDS_UNQUOTED[usize::from(c)]) {
+        Some(0) => Err(Err::Error(Error::new(i, ErrorKind::TakeWhile1))),
+        Some(n) => Ok((i.slice(n..), i.slice(..n))),
+        None => Err(Err::Incomplete(nom::Needed::new(1))),
+    }
 }
 
-pub fn quoted_text(i: Span) -> IResult<Span, Span> {
-    let esc = escaped(none_of("\\\""), '\\', tag("\""));
-    let esc_or_empty = alt((esc, tag("")));
-    delimited(tag("\""), esc_or_empty, tag("\""))(i)
+/// The bytes that end [`unquoted_text`], as a table since it is the
+/// hottest loop of the parser.
+pub(crate) static ENDS_UNQUOTED: [bool; 256] = {
+    let mut table = [false; 256];
+    let mut c = 0;
+    while c < 256 {
+        #[allow(clippy::cast_possible_truncation)]
+        let b = c as u8;
+        table[c] = is_whitespace(b) || is_line_break(b) || b == b'#' || b == b'{' || b == b'}';
+        c += 1;
+    }
+    table
+};
+
+/// A field in double quotes. Within the quotes, `\"` is a quote and `\\`
+/// a backslash; any other backslash is just a backslash. The escapes are
+/// included in the result as is, see [`text`] for the unescaped field.
+///
+/// ```
+/// use sie4::{parsers::quoted_text, Span};
+///
+/// let (rest, text) = quoted_text(Span::new(b"\"12\\\" pizza\" 2")).unwrap();
+/// assert_eq!(text.fragment(), b"12\\\" pizza");
+/// assert_eq!(rest.fragment(), b" 2");
+/// ```
+///
+/// Fields can't span lines, so a field that isn't closed before the end
+/// of the line is closed by its last escaped quote instead. This reads
+/// e.g. `"C:\temp\"` from programs that don't escape backslashes.
+///
+/// # Errors
+///
+/// Returns an error if the input doesn't start with `"` or the line ends
+/// without a quote that could close the field, and
+/// [`nom::Err::Incomplete`] if the input ends before the field does.
+pub fn quoted_text<X: Copy>(i: Span<X>) -> IResult<Span<X>, Span<X>> {
+    let (i, _) = tag("\"")(i)?;
+
+    match closing_quote(&i, false) {
+        Quoted::Closed(n) => Ok((i.slice(n + 1..), i.slice(..n))),
+        Quoted::Unclosed => Err(Err::Incomplete(nom::Needed::Unknown)),
+        Quoted::EndOfLine(n) => Err(Err::Error(Error::new(i.slice(n..), ErrorKind::Escaped))),
+    }
 }
 
-pub fn text(i: Span) -> IResult<Span, String> {
-    map(alt((quoted_text, unquoted_text)), |span| {
-        Cow::borrow_from_cp437(span.as_ref(), &CP437_CONTROL).into_owned()
-    })(i)
+pub(crate) enum Quoted {
+    /// At this index.
+    Closed(usize),
+    Unclosed,
+    /// The line ends at this index, without a quote to close the field.
+    EndOfLine(usize),
 }
 
-pub fn date(i: Span) -> IResult<Span, Date> {
-    let s = Cow::borrow_from_cp437(&i, &CP437_CONTROL);
-    let date = Date::parse(&s, DATE_FORMAT)
-        .map_err(|e| nom::Err::Error(Error::from_external_error(i, ErrorKind::MapRes, e)))?;
-    Ok((i.slice(i.len()..), date))
+/// Find the quote that closes a quoted field, given the input after the
+/// opening quote. This is the only place where escapes are interpreted,
+/// so that [`quoted_text`], [`take_until_unbalanced`] and the
+/// [`Tokenizer`](crate::tokens::Tokenizer) agree on where fields end. If
+/// `eof`, the end of the input is the end of the line.
+pub(crate) fn closing_quote(i: &[u8], eof: bool) -> Quoted {
+    let mut pos = 0;
+    let mut last_escaped = None;
+
+    while let Some(n) = memchr::memchr3(b'"', b'\\', b'\n', &i[pos..]) {
+        pos += n;
+        match i[pos] {
+            b'"' => return Quoted::Closed(pos),
+            b'\\' => match i.get(pos + 1) {
+                Some(b'"') => {
+                    last_escaped = Some(pos + 1);
+                    pos += 2;
+                }
+                Some(b'\\') => pos += 2,
+                Some(_) => pos += 1,
+                None => break,
+            },
+            _ => {
+                return last_escaped.map_or(Quoted::EndOfLine(pos), Quoted::Closed);
+            }
+        }
+    }
+
+    if eof {
+        last_escaped.map_or(Quoted::EndOfLine(i.len()), Quoted::Closed)
+    } else {
+        Quoted::Unclosed
+    }
+}
+
+/// Resolve the escapes of a [quoted field](quoted_text).
+pub(crate) fn unescape(text: Cow<str>) -> Cow<str> {
+    if !text.contains('\\') {
+        return text;
+    }
+
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(&next @ ('"' | '\\'))) => {
+                unescaped.push(next);
+                chars.next();
+            }
+            (c, _) => unescaped.push(c),
+        }
+    }
+    Cow::Owned(unescaped)
+}
+
+/// [Quoted](quoted_text) or [unquoted](unquoted_text) text, decoded from
+/// CP437 and with the escapes of quoted text resolved. The text is only
+/// copied if it contains non-ASCII characters or escapes.
+///
+/// ```
+/// use sie4::{parsers::text, Span};
+///
+/// let (_, name) = text(Span::new(b"\"R\x84ksm\x94rg\x86s AB\"\n")).unwrap();
+/// assert_eq!(name, "Räksmörgås AB");
+///
+/// let (_, path) = text(Span::new(b"\"C:\\\\SIE \\\"4\\\"\"\n")).unwrap();
+/// assert_eq!(path, "C:\\SIE \"4\"");
+/// ```
+///
+/// # Errors
+///
+/// See [`quoted_text`] and [`unquoted_text`].
+pub fn text<X: Copy>(i: Span<X>) -> IResult<Span<X>, Cow<str>> {
+    let (rest, text) = alt((
+        map(quoted_text, |span| unescape(decode(&span))),
+        map(unquoted_text, |span| decode(&span)),
+    ))(i)?;
+    event!(tracing::Level::TRACE, line = i.location_line(), %text, "text");
+    Ok((rest, text))
+}
+
+/// Decode text from CP437, borrowing it if it is ASCII, as most text is.
+pub(crate) fn decode(i: &[u8]) -> Cow<'_, str> {
+    match std::str::from_utf8(i) {
+        Ok(s) if s.is_ascii() => Cow::Borrowed(s),
+        _ => Cow::borrow_from_cp437(i, &CP437_CONTROL),
+    }
+}
+
+/// A date in the [`DATE_FORMAT`]. The whole input is consumed, so this
+/// is meant to be applied to the output of e.g. [`unquoted_text`].
+///
+/// ```
+/// use sie4::{parsers::date, Span};
+/// use time::macros::date;
+///
+/// assert_eq!(date(Span::new(b"20230314")).unwrap().1, date!(2023 - 03 - 14));
+/// assert!(date(Span::new(b"230314")).is_err());
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the input isn't a valid date.
+pub fn date<X: Copy>(i: Span<X>) -> IResult<Span<X>, Date> {
+    date_with(i, None)
 }
 
-pub fn from_str<T: FromStr>(i: Span) -> IResult<Span, T> {
+/// A [`date`], or one shortened to `YYMMDD` as some programs write it,
+/// where two-digit years below `century_pivot` are in the 2000s and the
+/// rest in the 1900s (see [`DEFAULT_CENTURY_PIVOT`]).
+///
+/// ```
+/// use sie4::{
+///     parsers::{short_date, DEFAULT_CENTURY_PIVOT},
+///     Span,
+/// };
+/// use time::macros::date;
+///
+/// let parse = short_date(DEFAULT_CENTURY_PIVOT);
+/// assert_eq!(parse(Span::new(b"20230314")).unwrap().1, date!(2023 - 03 - 14));
+/// assert_eq!(parse(Span::new(b"230314")).unwrap().1, date!(2023 - 03 - 14));
+/// assert_eq!(parse(Span::new(b"991231")).unwrap().1, date!(1999 - 12 - 31));
+/// ```
+///
+/// # Errors
+///
+/// The parser returns an error if the input isn't a valid date.
+pub fn short_date<X: Copy>(century_pivot: u8) -> impl Fn(Span<X>) -> IResult<Span<X>, Date> {
+    move |i| date_with(i, Some(century_pivot))
+}
+
+/// A [`date`], or a [`short_date`] if there is a `century_pivot`.
+pub(crate) fn date_with<X: Copy>(i: Span<X>, century_pivot: Option<u8>) -> IResult<Span<X>, Date> {
+    match parse_date(&i, century_pivot) {
+        Some(date) => Ok((i.slice(i.len()..), date)),
+        None => Err(nom::Err::Error(Error::new(i, ErrorKind::MapRes))),
+    }
+}
+
+/// [`DATE_FORMAT`] without the generality of [`Date::parse`].
+pub(crate) fn parse_date(i: &[u8], century_pivot: Option<u8>) -> Option<Date> {
+    let (year, i) = split_year(i, 8, century_pivot)?;
+
+    let number = |digits: &[u8]| digits.iter().fold(0, |n, &d| n * 10 + (d - b'0'));
+    let month = time::Month::try_from(number(&i[..2])).ok()?;
+    let day = number(&i[2..]);
+
+    Date::from_calendar_date(year, month, day).ok()
+}
+
+/// Any type implementing [`FromStr`]. Like [`date`], this consumes the
+/// whole input.
+///
+/// # Errors
+///
+/// Returns an error if the input can't be parsed as `T`.
+pub fn from_str<T: FromStr, X: Copy>(i: Span<X>) -> IResult<Span<X>, T> {
     let v = Cow::borrow_from_cp437(&i, &CP437_CONTROL)
         .parse()
         .map_err(|e| nom::Err::Error(Error::from_external_error(i, ErrorKind::MapRes, e)))?;
     Ok((i.slice(i.len()..), v))
 }
 
-pub fn take_until_unbalanced(opening: u8, closing: u8) -> impl Fn(Span) -> IResult<Span, Span> {
-    move |i: Span| {
+/// An amount, e.g. of a `#TRANS`, in the standard format: with `.` as
+/// the decimal separator and no thousands separators.
+///
+/// ```
+/// use rust_decimal_macros::dec;
+/// use sie4::{parsers::amount, Span};
+///
+/// let (rest, balance) = amount(Span::new(b"-1234.56 20230102\n")).unwrap();
+/// assert_eq!(balance, dec!(-1234.56));
+/// assert_eq!(rest.fragment(), b" 20230102\n");
+/// assert!(amount(Span::new(b"1234,56\n")).is_err());
+/// ```
+///
+/// # Errors
+///
+/// Returns [`nom::Err::Failure`] if the field isn't an amount, and
+/// [`nom::Err::Incomplete`] if the input ends before the field does.
+pub fn amount<X: Copy>(i: Span<X>) -> IResult<Span<X>, Amount> {
+    let (rest, s) = unquoted_text(i)?;
+    match parse_amount(&s) {
+        Some(value) => Ok((rest, Amount(value))),
+        None => Err(nom::Err::Failure(Error::new(s, INVALID_AMOUNT))),
+    }
+}
+
+/// The amount `i`, as [`amount`] reads it.
+pub(crate) fn parse_amount(i: &[u8]) -> Option<Decimal> {
+    plain_amount(i).or_else(|| std::str::from_utf8(i).ok()?.parse().ok())
+}
+
+/// Amounts of up to 18 digits with an optional sign and decimals, as
+/// almost all are, without going through [`Decimal::from_str`].
+fn plain_amount(i: &[u8]) -> Option<Decimal> {
+    let (negative, digits) = match i {
+        [b'-', digits @ ..] => (true, digits),
+        _ => (false, i),
+    };
+    if !(1..=18).contains(&digits.len()) {
+        return None;
+    }
+    let (int, frac) = match memchr::memchr(b'.', digits) {
+        Some(n) => (&digits[..n], &digits[n + 1..]),
+        None => (digits, &[][..]),
+    };
+    if int.is_empty() || (frac.is_empty() && int.len() < digits.len()) {
+        return None;
+    }
+
+    let mut mantissa = 0_i64;
+    for &c in int.iter().chain(frac) {
+        if !c.is_ascii_digit() {
+            return None;
+        }
+        mantissa = mantissa * 10 + i64::from(c - b'0');
+    }
+    // zero keeps its sign
+    if negative && mantissa == 0 {
+        return None;
+    }
+    let mantissa = if negative { -mantissa } else { mantissa };
+    Some(Decimal::new(mantissa, u32::try_from(frac.len()).ok()?))
+}
+
+/// Take input until the first `closing` character that isn't balanced by
+/// a preceding `opening` character, without consuming it. Characters in
+/// [quoted fields](quoted_text) are skipped, using the same rules for
+/// escapes; outside of quotes, a backslash is just a backslash.
+///
+/// ```
+/// use sie4::{parsers::take_until_unbalanced, Span};
+///
+/// let mut parser = take_until_unbalanced(b'{', b'}');
+/// let (rest, taken) = parser(Span::new(b"a {b} \"c}\\\\\" d\\} e")).unwrap();
+/// assert_eq!(taken.fragment(), b"a {b} \"c}\\\\\" d\\");
+/// assert_eq!(rest.fragment(), b"} e");
+/// ```
+///
+/// If there is no unbalanced closing character, the whole input is taken
+/// if it is balanced.
+///
+/// # Errors
+///
+/// Returns [`nom::Err::Incomplete`] if the input ends with unbalanced
+/// opening characters or in a quoted field.
+pub fn take_until_unbalanced<X: Copy>(
+    opening: u8,
+    closing: u8,
+) -> impl Fn(Span<X>) -> IResult<Span<X>, Span<X>> {
+    move |i: Span<X>| {
         let mut index = 0;
         let mut bracket_counter = 0;
-        while let Some(n) = memchr3(opening, closing, b'\\', &i[index..]) {
+        while let Some(n) = i[index..]
+            .iter()
+            .position(|&c| c == opening || c == closing || c == b'"')
+        {
             index += n;
-            let mut it = i[index..].iter().copied();
-            match it.next().unwrap_or_default() {
-                b'\\' => {
-                    // Skip the escape char `\`.
-                    index += 1;
-                    // Skip also the following char.
-                    it.next();
-                    index += 1;
+            match i[index] {
+                // Quotes only start a field after a separator, like in
+                // `text`; elsewhere they're part of unquoted text.
+                b'"' if index == 0
+                    || matches!(i[index - 1], c if c == opening || c == closing
+                        || is_whitespace(c) || is_line_break(c)) =>
+                {
+                    // not `quoted_text`, since slicing a span counts the
+                    // lines before it
+                    match closing_quote(&i[index + 1..], false) {
+                        Quoted::Closed(n) => index += n + 2,
+                        Quoted::Unclosed => return Err(Err::Incomplete(nom::Needed::Unknown)),
+                        // read as unquoted text by `text`
+                        Quoted::EndOfLine(_) => index += 1,
+                    }
                 }
+                b'"' => index += 1,
                 c if c == opening => {
                     bracket_counter += 1;
                     index += 1;
@@ -85,13 +493,13 @@ pub fn take_until_unbalanced(opening: u8, closing: u8) -> impl Fn(Span) -> IResu
                     index += 1;
                 }
                 _ => unreachable!(),
-            };
+            }
             // We found the unmatched closing bracket.
             if bracket_counter == -1 {
                 // We do not consume it.
                 index -= 1;
                 return Ok((i.slice(index..), i.slice(..index)));
-            };
+            }
         }
 
         if bracket_counter == 0 {
@@ -101,3 +509,242 @@ pub fn take_until_unbalanced(opening: u8, closing: u8) -> impl Fn(Span) -> IResu
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn parts<'a>(res: IResult<Span<'a>, Span<'a>>) -> (&'a [u8], &'a [u8]) {
+        let (rest, o) = res.unwrap();
+        (rest.fragment(), o.fragment())
+    }
+
+    #[test]
+    fn curly_braces() {
+        let parse = |i| in_curly_braces(Span::new(i));
+
+        assert_eq!(parts(parse(b"{} 1")), (&b" 1"[..], &b""[..]));
+        assert_eq!(
+            parts(parse(b"{{a}{b{c}}} 1")),
+            (&b" 1"[..], &b"{a}{b{c}}"[..])
+        );
+        // backslashes only escape within quotes
+        assert_eq!(parts(parse(b"{\\}}")), (&b"}"[..], &b"\\"[..]));
+        assert_eq!(
+            parts(parse(b"{\n  1 \"x\"\n}\n")),
+            (&b"\n"[..], &b"\n  1 \"x\"\n"[..])
+        );
+
+        assert!(matches!(parse(b"{"), Err(Err::Incomplete(_))));
+        assert!(matches!(parse(b"{{}"), Err(Err::Incomplete(_))));
+        assert!(matches!(parse(b"{\\"), Err(Err::Incomplete(_))));
+        assert!(matches!(parse(b"1}"), Err(Err::Error(_))));
+    }
+
+    #[test]
+    fn unbalanced() {
+        let parse = |i| take_until_unbalanced(b'(', b')')(Span::new(i));
+
+        assert_eq!(parts(parse(b"")), (&b""[..], &b""[..]));
+        assert_eq!(parts(parse(b"()")), (&b""[..], &b"()"[..]));
+        assert_eq!(parts(parse(b"a)b")), (&b")b"[..], &b"a"[..]));
+        // trailing escape
+        assert_eq!(parts(parse(b"a\\")), (&b""[..], &b"a\\"[..]));
+        assert_eq!(parts(parse(b"(\\))")), (&b")"[..], &b"(\\)"[..]));
+
+        // quoted fields, but not quotes within unquoted text
+        assert_eq!(
+            parts(parse(b"\")\" (x))y")),
+            (&b")y"[..], &b"\")\" (x)"[..])
+        );
+        assert_eq!(parts(parse(b"a\"b)\"")), (&b")\""[..], &b"a\"b"[..]));
+        assert!(matches!(parse(b"a \")"), Err(Err::Incomplete(_))));
+
+        // escapes, as in `quoted_text`
+        assert_eq!(
+            parts(parse(b"\"\\\")\")x")),
+            (&b")x"[..], &b"\"\\\")\""[..])
+        );
+        assert_eq!(parts(parse(b"\"\\\\\")x")), (&b")x"[..], &b"\"\\\\\""[..]));
+        assert_eq!(
+            parts(parse(b"\"C:\\)\\\" x)\n")),
+            (&b")\n"[..], &b"\"C:\\)\\\" x"[..])
+        );
+        // an unclosed quote is unquoted text
+        assert_eq!(parts(parse(b" \"a)\n")), (&b")\n"[..], &b" \"a"[..]));
+    }
+
+    #[test]
+    fn quoted() {
+        let parse = |i| quoted_text(Span::new(i));
+
+        assert_eq!(parts(parse(b"\"\" 1")), (&b" 1"[..], &b""[..]));
+        assert_eq!(parts(parse(b"\"a b\"c")), (&b"c"[..], &b"a b"[..]));
+        assert_eq!(parts(parse(b"\"\\\"\\\"\"")), (&b""[..], &b"\\\"\\\""[..]));
+        assert_eq!(parts(parse(b"\"#{}\"")), (&b""[..], &b"#{}"[..]));
+
+        assert!(matches!(parse(b"\"abc"), Err(Err::Incomplete(_))));
+        assert!(matches!(parse(b"\"a\\\""), Err(Err::Incomplete(_))));
+        assert!(matches!(parse(b"\"a\\"), Err(Err::Incomplete(_))));
+        assert!(matches!(parse(b"\"abc\n\""), Err(Err::Error(_))));
+        assert!(matches!(parse(b"abc"), Err(Err::Error(_))));
+    }
+
+    #[test]
+    fn escapes() {
+        let parse = |i| text(Span::new(i)).map(|(rest, text)| (text, *rest.fragment()));
+
+        for (input, unescaped, rest) in [
+            (&b"\"a\\\"b\" 1"[..], "a\"b", &b" 1"[..]),
+            (b"\"a\\\\b\" 1", "a\\b", b" 1"),
+            (b"\"a\\\\\" 1", "a\\", b" 1"),
+            (b"\"\\\\\\\"\" 1", "\\\"", b" 1"),
+            // other backslashes are kept
+            (b"\"a\\b\\n\" 1", "a\\b\\n", b" 1"),
+            (b"\"\\{\\}\" 1", "\\{\\}", b" 1"),
+            // braces and `#` are text within quotes
+            (b"\"}{ #VER\"}", "}{ #VER", b"}"),
+            // closed at the end of the line by the last escaped quote
+            (b"\"C:\\path\\\"\n", "C:\\path\\", b"\n"),
+            (b"\"C:\\path\\\" 1\r\n", "C:\\path\\", b" 1\r\n"),
+            (b"\"a \\\"b\\\" c\" d\\\"\n", "a \"b\" c", b" d\\\"\n"),
+            // backslashes and quotes in unquoted text aren't escapes
+            (b"C:\\path\\ 1", "C:\\path\\", b" 1"),
+            (b"a\\\"b 1", "a\\\"b", b" 1"),
+        ] {
+            assert_eq!(
+                parse(input).unwrap(),
+                (unescaped.into(), rest),
+                "{}",
+                String::from_utf8_lossy(input)
+            );
+        }
+
+        assert!(matches!(
+            parse(b"\"a \\\"b\\\" c\" 1"),
+            Ok((Cow::Owned(_), _))
+        ));
+        assert!(matches!(parse(b"\"a b\" 1"), Ok((Cow::Borrowed(_), _))));
+    }
+
+    #[test]
+    fn unquoted() {
+        let parse = |i| unquoted_text(Span::new(i));
+
+        assert_eq!(parts(parse(b"abc def")), (&b" def"[..], &b"abc"[..]));
+        assert_eq!(parts(parse(b"a\"b\tc")), (&b"\tc"[..], &b"a\"b"[..]));
+        assert_eq!(parts(parse(b"1930{}")), (&b"{}"[..], &b"1930"[..]));
+        assert_eq!(parts(parse(b"A#B\r\n")), (&b"#B\r\n"[..], &b"A"[..]));
+
+        assert!(matches!(parse(b"abc"), Err(Err::Incomplete(_))));
+        assert!(matches!(parse(b" abc"), Err(Err::Error(_))));
+        assert!(matches!(parse(b"{"), Err(Err::Error(_))));
+    }
+
+    #[test]
+    fn decoded_text() {
+        let parse = |i| text(Span::new(i)).map(|(_, text)| text);
+
+        assert!(matches!(parse(b"\"abc\" "), Ok(Cow::Borrowed("abc"))));
+        assert!(matches!(parse(b"abc "), Ok(Cow::Borrowed("abc"))));
+        assert_eq!(parse(b"\x8e\x99 ").unwrap(), "ÄÖ");
+        assert!(matches!(parse(b"\x8e\x99 "), Ok(Cow::Owned(_))));
+    }
+
+    #[test]
+    fn ascii() {
+        for c in 0..=127 {
+            let c = [c];
+            let expected: Cow<str> = Cow::borrow_from_cp437(&c[..], &CP437_CONTROL);
+            assert_eq!(decode(&c), expected, "{c:?}");
+        }
+    }
+
+    #[test]
+    fn dates() {
+        let parse = |i| date(Span::new(i)).map(|(_, date)| date);
+
+        assert_eq!(
+            parse(b"20240229").unwrap(),
+            Date::from_calendar_date(2024, time::Month::February, 29).unwrap()
+        );
+        assert!(parse(b"20230229").is_err());
+        assert!(parse(b"2023-03-14").is_err());
+        assert!(parse(b"202303141").is_err());
+        assert!(parse(b"2303141").is_err());
+        assert!(parse(b"").is_err());
+
+        assert!(parse(b"691231").is_err());
+        for (pivot, year) in [(70, 2069), (50, 1969)] {
+            assert_eq!(
+                short_date(pivot)(Span::new(b"691231")).unwrap().1.year(),
+                year
+            );
+        }
+        assert_eq!(
+            short_date(0)(Span::new(b"20240229")).unwrap().1,
+            parse(b"20240229").unwrap()
+        );
+        assert!(short_date(70)(Span::new(b"2303141")).is_err());
+    }
+
+    #[test]
+    fn parse_from_str() {
+        assert_eq!(from_str::<u32, _>(Span::new(b"1930")).unwrap().1, 1930);
+        assert!(from_str::<u32, _>(Span::new(b"-1")).is_err());
+    }
+
+    #[test]
+    fn amounts() {
+        let parse = |i| amount(Span::new(i)).map(|(rest, amount)| (*rest.fragment(), amount));
+        assert_eq!(parse(b"0\n"), Ok((&b"\n"[..], Amount::ZERO)));
+        assert_eq!(parse(b"-12.50}"), Ok((&b"}"[..], dec!(-12.50).into())));
+        assert!(matches!(parse(b"12.50"), Err(Err::Incomplete(_))));
+        assert!(matches!(parse(b"12,50\n"), Err(Err::Failure(_))));
+        assert!(matches!(parse(b" 1\n"), Err(Err::Error(_))));
+
+        // the same as through `str`, down to the scale and the sign of zero
+        for i in [
+            "0",
+            "-0",
+            "-0.00",
+            "007",
+            "1.5",
+            "-1234.50",
+            "1.",
+            ".5",
+            "-",
+            "1.2.3",
+            "1e3",
+            "123456789012345678",
+            "1234567890123456789.50",
+            "+1",
+            "1_000",
+        ] {
+            let expected = i.parse::<Decimal>().ok();
+            let parsed = parse_amount(i.as_bytes());
+            assert_eq!(parsed, expected, "{i}");
+            assert_eq!(
+                parsed.map(|d| (d.scale(), d.is_sign_negative())),
+                expected.map(|d| (d.scale(), d.is_sign_negative())),
+                "{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn independent_of_options() {
+        let options = Options {
+            century_pivot: Some(DEFAULT_CENTURY_PIVOT),
+            lenient_amounts: true,
+            strict: false,
+        };
+        let span = |i: &'static [u8]| Span::new_extra(i, options);
+
+        assert!(amount(span(b"12,50\n")).is_err());
+        assert!(date(span(b"230314")).is_err());
+        assert_eq!(text(span(b"\"a b\"\n")).unwrap().0.extra, options);
+    }
+}
@@ -1,68 +1,2601 @@
-use std::io::{BufRead, Read};
+use std::{
+    borrow::Cow,
+    collections::VecDeque,
+    io::{self, Read},
+    marker::PhantomData,
+    ops::RangeInclusive,
+    sync::Arc,
+    time::Instant,
+};
 
-use nom_bufreader::bufreader::BufReader;
+use codepage_437::{BorrowFromCp437, CP437_CONTROL};
+use nom::Slice;
+use time::Date;
 
+use self::ksumma::Checksum;
 use crate::{
-    item::{Group, Item},
-    Span,
+    custom::Registry,
+    item::{
+        self, AccountNo, Decoded, Flagga, Group, Invalid, Item, ItemRef, ItemType, Problem,
+        ReadError, TransLineRef, Ver, VerRef,
+    },
+    metrics::Metrics,
+    parsers::{is_line_break, is_whitespace, Options},
+    resolve::Verifications,
+    Document, Span,
 };
 
+mod ksumma;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod push;
+
+#[cfg(feature = "mmap")]
+pub use self::mmap::Mmap;
+pub use self::push::{Handler, Parser};
+
 const BUF_SIZE: usize = 8192;
 
+/// Default for [`Reader::max_item_size`].
+pub const DEFAULT_MAX_ITEM_SIZE: usize = 16 * 1024 * 1024;
+
+/// A buffer that grows until it fits the item being parsed.
+struct Buffer<R> {
+    inner: R,
+    buf: Vec<u8>,
+    pos: usize,
+    /// The number of bytes consumed so far.
+    consumed: u64,
+    /// Whether the end of the input has been read.
+    eof: bool,
+}
+
+impl<R: Read> Buffer<R> {
+    fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..]
+    }
+
+    fn consume(&mut self, n: usize) {
+        let pos = (self.pos + n).min(self.buf.len());
+        self.consumed += (pos - self.pos) as u64;
+        self.pos = pos;
+    }
+
+    /// Read more data into the buffer, growing it if it already holds
+    /// `max` bytes. Returns the number of bytes read, which is `0` at the
+    /// end of the input, or `None` if the buffer can't grow any further.
+    fn fill(&mut self, max: usize) -> io::Result<Option<usize>> {
+        if self.pos > 0 {
+            self.buf.drain(..self.pos);
+            self.pos = 0;
+        }
+
+        let len = self.buf.len();
+        if len >= max {
+            return Ok(None);
+        }
+
+        self.buf.resize(len + BUF_SIZE.min(max - len), 0);
+        loop {
+            match self.inner.read(&mut self.buf[len..]) {
+                Ok(read) => {
+                    self.buf.truncate(len + read);
+                    return Ok(Some(read));
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.buf.truncate(len);
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
 pub struct Reader<R: Read> {
-    inner: BufReader<R>,
-    group: Group,
+    inner: Buffer<R>,
+    order: Order,
+    /// Line number at the start of the buffer.
+    line: u32,
+    lenient_sub_entries: bool,
+    ordering: OrderingPolicy,
+    extra_fields: ExtraFieldsPolicy,
+    max_item_size: usize,
+    options: Options,
+    pending: VecDeque<Result<Item, Error>>,
+    /// Whether a `#FLAGGA` after other items, or a `#FNR` after the
+    /// identification, starts a new document, see
+    /// [`Reader::next_document`].
+    documents: bool,
+    /// Whether the last item started a new document.
+    new_document: bool,
+    ksumma: Ksumma,
+    vouchers: Vouchers,
+    custom: Option<Arc<Registry>>,
+    metrics: Option<Arc<dyn Metrics + Send + Sync>>,
+    warnings: Vec<Warning>,
+    /// Where the item last read starts.
+    position: Option<Position>,
 }
 
 impl<R: Read> Reader<R> {
+    /// A reader of the items of `reader`.
+    ///
+    /// A byte order mark at the start of the input, and lines before the
+    /// first item that aren't items, e.g. comments, are skipped with a
+    /// [`Warning`] unless [strict](Reader::strict). If there is no item
+    /// after them, they are read as items, and fail.
+    ///
+    /// ```
+    /// use sie4::{reader::Warning, Reader};
+    ///
+    /// let data = b"\xef\xbb\xbfExported by Vi iMproved\r\n#FLAGGA 0\r\n";
+    /// let mut reader = Reader::new(&data[..]);
+    /// assert!(reader.next().unwrap().is_ok());
+    /// assert_eq!(
+    ///     reader.warnings(),
+    ///     [
+    ///         Warning::ByteOrderMark,
+    ///         Warning::Preamble {
+    ///             line: 1,
+    ///             text: "Exported by Vi iMproved".to_owned()
+    ///         }
+    ///     ]
+    /// );
+    ///
+    /// assert!(Reader::new(&data[..]).strict(true).next().unwrap().is_err());
+    /// ```
     pub fn new(reader: R) -> Self {
         Self {
-            inner: BufReader::with_capacity(BUF_SIZE, reader),
-            group: Group::Flag,
+            inner: Buffer {
+                inner: reader,
+                buf: Vec::with_capacity(BUF_SIZE),
+                pos: 0,
+                consumed: 0,
+                eof: false,
+            },
+            order: Order::default(),
+            line: 1,
+            lenient_sub_entries: false,
+            ordering: OrderingPolicy::default(),
+            extra_fields: ExtraFieldsPolicy::default(),
+            max_item_size: DEFAULT_MAX_ITEM_SIZE,
+            options: Options::default(),
+            pending: VecDeque::new(),
+            documents: false,
+            new_document: false,
+            ksumma: Ksumma::default(),
+            vouchers: Vouchers::default(),
+            custom: None,
+            metrics: None,
+            warnings: Vec::new(),
+            position: None,
+        }
+    }
+
+    /// An item with invalid sub-entries, such as a `#VER` with a
+    /// malformed `#TRANS`, is always preceded by an
+    /// [`Error::InvalidSubEntry`] (or [`Error::InvalidAmount`]) for each
+    /// of them. By default, the item
+    /// itself is then skipped. If `lenient`, it is yielded with the
+    /// remaining sub-entries.
+    ///
+    /// ```
+    /// use sie4::{item::Item, reader::Error, Reader};
+    ///
+    /// let data = b"#VER A 1 20230102
+    /// {
+    /// #TRANS 1930 {} -12000.00
+    /// #TRANS {} 12000.00
+    /// }
+    /// ";
+    /// let mut reader = Reader::new(&data[..]).lenient_sub_entries(true);
+    ///
+    /// assert!(matches!(reader.next(), Some(Err(Error::InvalidSubEntry { line: 4, .. }))));
+    /// let Some(Ok(Item::Ver(ver))) = reader.next() else { panic!() };
+    /// assert_eq!(ver.transactions.0.len(), 1);
+    /// ```
+    #[must_use]
+    pub fn lenient_sub_entries(mut self, lenient: bool) -> Self {
+        self.lenient_sub_entries = lenient;
+        self
+    }
+
+    /// How the order of the items is checked. Items that violate the
+    /// policy are reported as [`Error::OutOfOrder`], or as [warnings]
+    /// with [`OrderingPolicy::Warn`].
+    ///
+    /// [warnings]: Reader::warnings
+    ///
+    /// ```
+    /// use sie4::{
+    ///     item::{Group, Item},
+    ///     reader::OrderingPolicy,
+    ///     Reader,
+    /// };
+    ///
+    /// // accounts declared after the balances that use them
+    /// let data = b"#UB 0 1930 100.00\n#KONTO 1930 \"Bank\"\n";
+    /// let policy = OrderingPolicy::Custom(|current, next| {
+    ///     current <= next || (current, next) == (Group::Balance, Group::Account)
+    /// });
+    /// let items = Reader::new(&data[..])
+    ///     .ordering(policy)
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert!(matches!(items[1], Item::Konto(_)));
+    /// ```
+    #[must_use]
+    pub fn ordering(mut self, policy: OrderingPolicy) -> Self {
+        self.ordering = policy;
+        self
+    }
+
+    /// The warnings about the items read so far, in the order of the
+    /// items. None are collected unless the reader is configured to,
+    /// e.g. with [`OrderingPolicy::Warn`].
+    ///
+    /// ```
+    /// use sie4::{
+    ///     item::Group,
+    ///     reader::{OrderingPolicy, Warning},
+    ///     Document, Reader,
+    /// };
+    ///
+    /// let data = b"#UB 0 1930 100.00\n#KONTO 1930 \"Bank\"\n#VER A 1 20230101\n{\n}\n";
+    /// let mut reader = Reader::new(&data[..]).ordering(OrderingPolicy::Warn);
+    /// let doc = reader.by_ref().collect::<Result<Document, _>>().unwrap();
+    ///
+    /// assert_eq!(doc.items().len(), 3);
+    /// assert_eq!(
+    ///     reader.warnings(),
+    ///     [Warning::OutOfOrder {
+    ///         label: "KONTO",
+    ///         line: 2,
+    ///         after: Group::Balance,
+    ///         before: "UB",
+    ///     }]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Take the [warnings](Reader::warnings) collected so far, e.g. to
+    /// report them while reading.
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// What to do with items that have more fields than they should,
+    /// e.g. fields added by a newer version of the format. They are
+    /// ignored by default, and denied in [strict mode](Reader::strict).
+    ///
+    /// ```
+    /// use sie4::{item::Item, reader::{Error, ExtraFieldsPolicy}, Reader};
+    ///
+    /// let data = b"#KONTO 1930 \"Bank\" 1\n#KONTO 2440 \"Skulder\"\n";
+    ///
+    /// let reader = Reader::new(&data[..]);
+    /// assert_eq!(reader.collect::<Result<Vec<_>, _>>().unwrap().len(), 2);
+    ///
+    /// let mut reader = Reader::new(&data[..]).extra_fields(ExtraFieldsPolicy::Deny);
+    /// assert!(matches!(reader.next(), Some(Err(Error::ExtraFields { line: 1, .. }))));
+    /// assert!(matches!(reader.next(), Some(Ok(Item::Konto(konto))) if konto.no == 2440));
+    /// ```
+    #[must_use]
+    pub fn extra_fields(mut self, policy: ExtraFieldsPolicy) -> Self {
+        self.extra_fields = policy;
+        self
+    }
+
+    /// The largest item, in bytes, that the reader will buffer before
+    /// giving up with [`Error::ItemTooLarge`]. Verifications with many
+    /// transactions can get large, so the default is
+    /// [`DEFAULT_MAX_ITEM_SIZE`].
+    #[must_use]
+    pub fn max_item_size(mut self, max: usize) -> Self {
+        self.max_item_size = max;
+        self
+    }
+
+    /// Read dates shortened to `YYMMDD` (and periods to `YYMM`), as some
+    /// programs write them: two-digit years below `pivot` are in the
+    /// 2000s and the rest in the 1900s, e.g. with
+    /// [`DEFAULT_CENTURY_PIVOT`](crate::parsers::DEFAULT_CENTURY_PIVOT).
+    /// By default, dates must have four-digit years.
+    ///
+    /// ```
+    /// use sie4::{item::Item, Reader};
+    ///
+    /// let data = b"#RAR 0 690101 691231\n";
+    /// assert!(Reader::new(&data[..]).next().unwrap().is_err());
+    ///
+    /// let Some(Ok(Item::Rar(rar))) = Reader::new(&data[..]).century_pivot(70).next() else {
+    ///     panic!()
+    /// };
+    /// assert_eq!(rar.start.year(), 2069);
+    ///
+    /// let Some(Ok(Item::Rar(rar))) = Reader::new(&data[..]).century_pivot(50).next() else {
+    ///     panic!()
+    /// };
+    /// assert_eq!(rar.start.year(), 1969);
+    /// ```
+    #[must_use]
+    pub fn century_pivot(mut self, pivot: u8) -> Self {
+        self.options.century_pivot = Some(pivot);
+        self
+    }
+
+    /// Whether to accept amounts with a decimal comma and thousands
+    /// separators, e.g. `1234,56` or `"1 234,56"`, as some programs write
+    /// them. Each is read as if it were in the standard format and
+    /// reported as a [`Warning::LenientAmount`]. By default, they are
+    /// [`Error::InvalidAmount`].
+    ///
+    /// ```
+    /// use sie4::{item::Item, reader::Warning, Reader};
+    ///
+    /// let data = b"#IB 0 1930 \"1 234,56\"\n";
+    /// assert!(Reader::new(&data[..]).next().unwrap().is_err());
+    ///
+    /// let mut reader = Reader::new(&data[..]).lenient_amounts(true);
+    /// let Some(Ok(Item::Ib(ib))) = reader.next() else { panic!() };
+    /// assert_eq!(ib.balance.to_string(), "1234.56");
+    /// assert_eq!(
+    ///     reader.warnings(),
+    ///     [Warning::LenientAmount {
+    ///         text: "1 234,56".to_owned(),
+    ///         line: 1
+    ///     }]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn lenient_amounts(mut self, lenient: bool) -> Self {
+        self.options.lenient_amounts = lenient;
+        self
+    }
+
+    /// Read only what the specification allows, e.g. to check the files
+    /// of a program that writes SIE4, instead of also what other programs
+    /// are known to write. If `strict`:
+    ///
+    /// - The items must be in order ([`OrderingPolicy::Strict`]) and
+    ///   have no extra fields ([`ExtraFieldsPolicy::Deny`]).
+    /// - Items with invalid sub-entries are skipped, see
+    ///   [`Reader::lenient_sub_entries`].
+    /// - Amounts must be in the standard format, see
+    ///   [`Reader::lenient_amounts`].
+    /// - Dates must have four-digit years, see [`Reader::century_pivot`].
+    /// - Quantities must be numbers, instead of being read as missing
+    ///   otherwise.
+    /// - Lines must end with a line feed, optionally preceded by a
+    ///   carriage return, and not with a carriage return alone.
+    /// - Items must start at the beginning of their line; only the
+    ///   sub-entries of a block may be indented.
+    /// - Fields must be separated by a single space, not by tabs or runs
+    ///   of spaces. Whitespace at the end of a line is ignored.
+    /// - The input must start with an item, see [`Reader::new`].
+    ///
+    /// Strict mode overrides the builders that relax these rules, whether
+    /// they are called before or after it.
+    ///
+    /// ```
+    /// use sie4::Reader;
+    ///
+    /// let data = b"#RAR 0 230101 231231\n";
+    /// let reader = || Reader::new(&data[..]).century_pivot(70);
+    /// assert!(reader().next().unwrap().is_ok());
+    /// assert!(reader().strict(true).next().unwrap().is_err());
+    /// ```
+    #[must_use]
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.options.strict = strict;
+        self
+    }
+
+    /// The ordering policy, unless [strict](Reader::strict).
+    fn ordering_policy(&self) -> OrderingPolicy {
+        if self.options.strict {
+            OrderingPolicy::Strict
+        } else {
+            self.ordering
+        }
+    }
+
+    /// The extra fields policy, unless [strict](Reader::strict).
+    fn extra_fields_policy(&self) -> ExtraFieldsPolicy {
+        if self.options.strict {
+            ExtraFieldsPolicy::Deny
+        } else {
+            self.extra_fields
+        }
+    }
+
+    /// Whether to check that the checksum of `#KSUMMA` matches the items
+    /// before it, if the file has one. A mismatch is reported as
+    /// [`Error::Checksum`] in place of the `#KSUMMA`.
+    ///
+    /// Regardless of this, a file that starts the checksummed items but
+    /// ends before the checksum is reported as [`Error::Truncated`].
+    ///
+    /// ```
+    /// use sie4::{reader::Error, Reader};
+    ///
+    /// let data = b"#FLAGGA 0\n#KSUMMA\n#KONTO 1930 Bank\n#KSUMMA 1\n";
+    /// let mut reader = Reader::new(&data[..]).verify_checksum(true);
+    ///
+    /// assert!(reader.by_ref().take(3).all(|res| res.is_ok()));
+    /// assert!(matches!(reader.next(), Some(Err(Error::Checksum { expected: 1, .. }))));
+    /// ```
+    #[must_use]
+    pub fn verify_checksum(mut self, verify: bool) -> Self {
+        self.ksumma.verify = verify;
+        self
+    }
+
+    /// Skip the vouchers (`#VER`) dated outside `dates`. Like the other
+    /// filters, this is applied as the items are read, so that the
+    /// skipped vouchers are never copied. The other items are kept, with
+    /// the balances of the whole file.
+    ///
+    /// ```
+    /// use sie4::{item::Ver, Reader};
+    /// use time::macros::date;
+    ///
+    /// let data = b"#VER A 1 20230110\n{\n#TRANS 1930 {} -1.00\n#TRANS 5010 {} 1.00\n}
+    /// #VER A 2 20230210\n{\n#TRANS 1930 {} -2.00\n#TRANS 6110 {} 2.00\n}
+    /// #VER B 1 20230115\n{\n#TRANS 1930 {} -3.00\n#TRANS 6110 {} 3.00\n}
+    /// ";
+    /// let vouchers = |reader: Reader<&[u8]>| {
+    ///     reader
+    ///         .items_of::<Ver>()
+    ///         .map(|ver| ver.map(|ver| format!("{}{}", ver.series, ver.no)))
+    ///         .collect::<Result<Vec<_>, _>>()
+    ///         .unwrap()
+    /// };
+    ///
+    /// let january = date!(2023 - 01 - 01)..=date!(2023 - 01 - 31);
+    /// assert_eq!(vouchers(Reader::new(&data[..]).date_range(january)), ["A1", "B1"]);
+    /// assert_eq!(vouchers(Reader::new(&data[..]).accounts([6110])), ["A2", "B1"]);
+    /// assert_eq!(vouchers(Reader::new(&data[..]).accounts([6110]).series(["A"])), ["A2"]);
+    /// ```
+    #[must_use]
+    pub fn date_range(mut self, dates: RangeInclusive<Date>) -> Self {
+        self.vouchers.dates = Some(dates);
+        self
+    }
+
+    /// Skip the vouchers without a transaction, after corrections (see
+    /// [`Ver::corrected`]), on any of `accounts`. See
+    /// [`Reader::date_range`].
+    #[must_use]
+    pub fn accounts(mut self, accounts: impl IntoIterator<Item = impl Into<AccountNo>>) -> Self {
+        self.vouchers.accounts = accounts.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Skip the vouchers of other series than `series`. See
+    /// [`Reader::date_range`].
+    #[must_use]
+    pub fn series(mut self, series: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.vouchers.series = series.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Read the items with the labels of `registry` as
+    /// [`Item::Custom`], see [`custom`](crate::custom). Other unknown
+    /// labels still fail to parse.
+    #[must_use]
+    pub fn custom_items(mut self, registry: Arc<Registry>) -> Self {
+        self.custom = Some(registry);
+        self
+    }
+
+    /// Report the bytes, items, errors and time spent reading to
+    /// `metrics`, see [`metrics`](crate::metrics).
+    #[must_use]
+    pub fn metrics(mut self, metrics: Arc<dyn Metrics + Send + Sync>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Read the items of the next document, for input that consists of
+    /// several files concatenated back to back, as some archives store
+    /// them. A `#FLAGGA` after other items starts a new document instead
+    /// of being [`Error::OutOfOrder`], as does a `#FNR` after the
+    /// identification items, for files with several companies (see
+    /// [`Document::by_company`]). Mixing this with plain iteration of the
+    /// reader also splits the items at each new document.
+    ///
+    /// Returns `None` at the end of the input, and the first error of a
+    /// document instead of the document.
+    ///
+    /// ```
+    /// use sie4::Reader;
+    ///
+    /// let data = b"#FLAGGA 0\n#KONTO 1930 \"Bank\"\n#FLAGGA 1\n#KONTO 1930 \"Kassa\"\n";
+    /// let mut reader = Reader::new(&data[..]);
+    ///
+    /// let first = reader.next_document().unwrap().unwrap();
+    /// assert_eq!(first.account(1930).unwrap().name, "Bank");
+    /// let second = reader.next_document().unwrap().unwrap();
+    /// assert_eq!(second.account(1930).unwrap().name, "Kassa");
+    /// assert!(reader.next_document().is_none());
+    /// ```
+    pub fn next_document(&mut self) -> Option<Result<Document, Error>> {
+        self.documents = true;
+        let mut items = Vec::new();
+
+        while let Some(res) = self.next() {
+            let item = match res {
+                Ok(item) => item,
+                Err(e) => return Some(Err(e)),
+            };
+
+            if std::mem::take(&mut self.new_document) && !items.is_empty() {
+                self.pending.push_front(Ok(item));
+                break;
+            }
+
+            items.push(item);
+        }
+
+        (!items.is_empty()).then(|| Ok(items.into_iter().collect()))
+    }
+
+    /// Read only the flag, identification and account groups, e.g. the
+    /// company name and the chart of accounts, and stop at the first item
+    /// of the balance group without reading it. The rest of the input is
+    /// left unread, so a large file doesn't have to be downloaded in
+    /// full just for its header.
+    ///
+    /// ```
+    /// use std::io::{self, Read};
+    ///
+    /// use sie4::{item::Item, Reader};
+    ///
+    /// // as if the rest of the file were still being downloaded
+    /// let data = b"#FLAGGA 0\n#FNAMN \"Acme AB\"\n#KONTO 1930 \"Bank\"\n#VER A 1 2023";
+    /// let input = (&data[..]).chain(io::repeat(b' '));
+    ///
+    /// let header = Reader::new(input).identification_only().unwrap();
+    /// assert!(header.more);
+    /// assert!(matches!(&header.document.items()[1], Item::FNamn(fnamn) if fnamn.name == "Acme AB"));
+    /// assert_eq!(header.document.account(1930).unwrap().name, "Bank");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error of the items that are read.
+    pub fn identification_only(mut self) -> Result<Header, Error> {
+        let mut items = Vec::new();
+
+        loop {
+            if self.pending.is_empty()
+                && self
+                    .next_group()?
+                    .is_some_and(|group| group > Group::Account)
+            {
+                return Ok(Header {
+                    document: items.into_iter().collect(),
+                    more: true,
+                });
+            }
+
+            match self.next() {
+                Some(Ok(item)) => items.push(item),
+                Some(Err(e)) => return Err(e),
+                None => break,
+            }
+        }
+
+        Ok(Header {
+            document: items.into_iter().collect(),
+            more: false,
+        })
+    }
+
+    /// The group of the next item, by its label, which is all that is
+    /// read of it. `None` at the end of the input or if the label is
+    /// unknown.
+    fn next_group(&mut self) -> io::Result<Option<Group>> {
+        loop {
+            let buf = self.inner.buffer();
+            let start = blank_len(buf);
+            if let Some(len) = buf[start..]
+                .iter()
+                .position(|&c| is_whitespace(c) || is_line_break(c))
+            {
+                let label = &buf[start..start + len];
+                return Ok(label.strip_prefix(b"#").and_then(Group::of_label));
+            }
+
+            match self.inner.fill(self.max_item_size)? {
+                Some(0) | None => return Ok(None),
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// Where the item last returned by [`Reader::next`] starts, or `None`
+    /// if no item has been read yet. An error, such as an
+    /// [`Error::InvalidSubEntry`], that precedes the item it belongs to
+    /// is already at the position of that item.
+    ///
+    /// ```
+    /// use sie4::{reader::Position, Reader};
+    ///
+    /// let data = b"#FLAGGA 0\n\n#KONTO 1930 \"Bank\"\n";
+    /// let mut reader = Reader::new(&data[..]);
+    /// assert_eq!(reader.position(), None);
+    ///
+    /// reader.next().unwrap().unwrap();
+    /// reader.next().unwrap().unwrap();
+    /// assert_eq!(reader.position(), Some(Position { line: 3, offset: 11 }));
+    /// ```
+    #[must_use]
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    /// The items with their [positions](Reader::position), e.g. to point
+    /// back at the line of an accounting entry in an audit trail.
+    ///
+    /// ```
+    /// use sie4::{item::Item, Reader};
+    ///
+    /// let data = b"#FLAGGA 0\n#VER A 1 20230101\n{\n}\n#VER A 2 20230102\n{\n}\n";
+    /// let lines = Reader::new(&data[..])
+    ///     .located()
+    ///     .filter_map(Result::ok)
+    ///     .filter(|located| matches!(located.item, Item::Ver(_)))
+    ///     .map(|located| located.position.line)
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(lines, [2, 5]);
+    /// ```
+    pub fn located(self) -> LocatedItems<R> {
+        LocatedItems { reader: self }
+    }
+
+    /// Only the items of type `T`, e.g. the accounts:
+    ///
+    /// ```
+    /// use sie4::{item::Konto, Reader};
+    ///
+    /// let data = b"#FLAGGA 0\n#KONTO 1930 \"Bank\"\n#KONTO 5010 \"Lokalhyra\"\n#VER A 1 20230101\n{\n}\n";
+    /// let accounts = Reader::new(&data[..])
+    ///     .items_of::<Konto>()
+    ///     .map(|konto| konto.map(|konto| konto.no))
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(accounts, [1930, 5010]);
+    /// ```
+    pub fn items_of<T: ItemType + TryFrom<Item>>(self) -> ItemsOf<R, T> {
+        ItemsOf {
+            reader: self,
+            item: PhantomData,
+        }
+    }
+
+    /// Only the verifications, with the account names, dates and objects
+    /// of their transactions resolved (see [`resolve`](crate::resolve)).
+    /// Transactions of accounts that haven't been declared are kept,
+    /// without a name.
+    pub fn verifications(self) -> Verifications<R> {
+        Verifications::new(self)
+    }
+
+    /// Consume the `n` bytes that an item labeled `label` was parsed
+    /// from, returning where it starts and the result of
+    /// [`Ksumma::update`].
+    #[cfg_attr(not(feature = "trace"), allow(unused_variables))]
+    fn advance(
+        &mut self,
+        n: usize,
+        label: &'static str,
+        ksumma: Option<&crate::item::Ksumma>,
+    ) -> (Position, Result<(), Error>) {
+        let raw = &self.inner.buffer()[..n];
+        let position = Position {
+            line: self
+                .line
+                .saturating_add(count_lines(&raw[..blank_len(raw)])),
+            offset: self.inner.consumed + blank_len(raw) as u64,
+        };
+        self.position = Some(position);
+        event!(
+            tracing::Level::TRACE,
+            label,
+            line = position.line,
+            bytes = n,
+            "item"
+        );
+        let ksumma = self.ksumma.update(raw, position, ksumma);
+        self.line = self.line.saturating_add(count_lines(raw));
+        self.inner.consume(n);
+
+        (position, ksumma)
+    }
+
+    /// Check that an item of `group`, starting at `position`, is in order.
+    fn check_order(
+        &mut self,
+        label: &'static str,
+        group: Group,
+        position: Position,
+    ) -> Result<(), Error> {
+        if let Some(warning) =
+            self.ordering_policy()
+                .check(&mut self.order, label, group, position)?
+        {
+            self.warnings.push(warning);
+        }
+        Ok(())
+    }
+
+    /// Consume the `n` bytes of a voucher that is filtered out, see
+    /// [`Reader::date_range`].
+    fn skip(&mut self, n: usize) -> Result<(), Error> {
+        let (position, ksumma) = self.advance(n, Ver::LABEL, None);
+        ksumma?;
+        self.check_order(Ver::LABEL, Ver::GROUP, position)
+    }
+
+    /// Consume the `n` bytes that `item` was parsed from.
+    fn accept(&mut self, n: usize, item: Item) -> Result<Item, Error> {
+        let (position, ksumma) = self.advance(
+            n,
+            item.label(),
+            match &item {
+                Item::Ksumma(ksumma) => Some(ksumma),
+                _ => None,
+            },
+        );
+
+        // the items of the next company may start with `#FNR` instead
+        let next = match item {
+            Item::Flagga(_) => Some(Group::Flag),
+            Item::Fnr(_) => Some(Group::Identification),
+            _ => None,
+        };
+        if self.documents && next.is_some_and(|group| self.order.group > group) {
+            self.order = Order::default();
+            self.new_document = true;
+
+            // the previous document was cut off before its checksum
+            let truncated = self.ksumma.end();
+            self.ksumma.reset();
+            if let Some(err) = truncated {
+                self.pending.push_back(Ok(item));
+                return Err(err);
+            }
         }
+
+        ksumma?;
+
+        // see `Ksumma` on why it's exempt
+        if !matches!(item, Item::Ksumma(_)) {
+            self.check_order(item.label(), item.group(), position)?;
+        }
+
+        Ok(item)
     }
 }
 
+/// An error of a reader. Those about a part of the input have the `line`
+/// that it is on, counted from 1, and its `offset` in bytes, counted from
+/// 0 like [`Position::offset`].
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
-    #[error(transparent)]
-    Io(std::io::Error),
+    #[error("failed to read input")]
+    Io(#[from] std::io::Error),
     #[error("parse error")]
-    Parse,
-    /// SIE4 items must be ordered in ascending order by group
-    /// (see [`crate::item::Group`]).
-    #[error("items out of order")]
-    OutOfOrder,
+    Parse(#[from] ParseError),
+    /// A sub-entry, e.g. a `#TRANS` of a `#VER`, is invalid even though
+    /// the item it belongs to is otherwise valid.
+    #[error("invalid sub-entry on line {line}")]
+    InvalidSubEntry { line: u32, offset: u64 },
+    /// An amount, e.g. of a `#TRANS` or `#UB`, isn't a number. The column
+    /// is counted in bytes, starting at 1.
+    #[error("invalid amount `{text}` on line {line}, column {column}")]
+    InvalidAmount {
+        text: String,
+        line: u32,
+        column: usize,
+        offset: u64,
+    },
+    /// An item, or a sub-entry of it, that otherwise is valid has more
+    /// fields than it should, see [`Reader::extra_fields`].
+    #[error("extra fields on line {line}")]
+    ExtraFields { line: u32, offset: u64 },
+    /// The input ended before the file did.
+    #[error(transparent)]
+    Truncated(#[from] Truncation),
+    /// The checksum of the `#KSUMMA` that ends the checksummed items
+    /// doesn't match them, see [`Reader::verify_checksum`].
+    #[error("checksum {expected} of #KSUMMA doesn't match the items, whose checksum is {actual}")]
+    Checksum { expected: i64, actual: u32 },
+    /// The item starting on `line` is larger than [`Reader::max_item_size`].
+    #[error("item on line {line} is larger than {max} bytes")]
+    ItemTooLarge { line: u32, offset: u64, max: usize },
+    /// SIE4 items must be ordered in ascending order by group (see
+    /// [`crate::item::Group`]), so the item labeled `label`, starting on
+    /// `line`, must appear before the first item of a later group, which
+    /// is labeled `before`.
+    #[error("`#{label}` on line {line} must appear before `#{before}`")]
+    OutOfOrder {
+        label: &'static str,
+        line: u32,
+        offset: u64,
+        before: &'static str,
+    },
+}
+
+/// Something wrong with the input that didn't stop it from being read,
+/// see [`Reader::warnings`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Warning {
+    /// An item of a group that should come before `after`, which it
+    /// follows, i.e. before the first item of `after`, which is labeled
+    /// `before`. See [`OrderingPolicy::Warn`].
+    #[error("`#{label}` on line {line} should appear before `#{before}`, in the {after} group")]
+    OutOfOrder {
+        label: &'static str,
+        line: u32,
+        after: Group,
+        before: &'static str,
+    },
+    /// An amount that isn't in the standard format, read as if it were,
+    /// see [`Reader::lenient_amounts`].
+    #[error("amount `{text}` on line {line} isn't in the standard format")]
+    LenientAmount { text: String, line: u32 },
+    /// A quantity, e.g. of a `#TRANS`, that isn't a number, which was read
+    /// as missing, unless [strict](Reader::strict).
+    #[error("quantity `{text}` on line {line} isn't a number and was left out")]
+    DroppedQuantity { text: String, line: u32 },
+    /// A UTF-8 byte order mark at the start of the input, which was
+    /// skipped, see [`Reader::new`].
+    #[error("skipped byte order mark at the start of the input")]
+    ByteOrderMark,
+    /// A line before the first item that isn't an item, which was
+    /// skipped, see [`Reader::new`]. The text is cut off after 40 bytes.
+    #[error("skipped line {line} before the first item: `{text}`")]
+    Preamble { line: u32, text: String },
+}
+
+/// The items before the balance group, from
+/// [`Reader::identification_only`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Header {
+    /// The flag, identification and account items.
+    pub document: Document,
+    /// Whether the input continues with the balance group, which wasn't
+    /// read.
+    pub more: bool,
+}
+
+/// Where an item starts in the input, after any blank lines before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    /// Counted from 1.
+    pub line: u32,
+    /// In bytes, counted from 0.
+    pub offset: u64,
+}
+
+/// An item and where it starts, from [`Reader::located`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct Located<T> {
+    pub item: T,
+    pub position: Position,
+}
+
+/// See [`Reader::located`].
+pub struct LocatedItems<R: Read> {
+    reader: Reader<R>,
+}
+
+impl<R: Read> LocatedItems<R> {
+    /// See [`Reader::warnings`].
+    #[must_use]
+    pub fn warnings(&self) -> &[Warning] {
+        self.reader.warnings()
+    }
+
+    pub fn into_inner(self) -> Reader<R> {
+        self.reader
+    }
+}
+
+impl<R: Read> Iterator for LocatedItems<R> {
+    type Item = Result<Located<Item>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.reader.next()?;
+        Some(item.map(|item| {
+            Located {
+                item,
+                // set by every item read
+                position: self
+                    .reader
+                    .position
+                    .unwrap_or(Position { line: 1, offset: 0 }),
+            }
+        }))
+    }
+}
+
+/// See [`Reader::items_of`].
+pub struct ItemsOf<R: Read, T> {
+    reader: Reader<R>,
+    item: PhantomData<T>,
+}
+
+impl<R: Read, T> ItemsOf<R, T> {
+    /// See [`Reader::warnings`].
+    #[must_use]
+    pub fn warnings(&self) -> &[Warning] {
+        self.reader.warnings()
+    }
+
+    pub fn into_inner(self) -> Reader<R> {
+        self.reader
+    }
+}
+
+impl<R: Read, T: TryFrom<Item>> Iterator for ItemsOf<R, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.reader.next()? {
+                Ok(item) => {
+                    if let Ok(item) = T::try_from(item) {
+                        return Some(Ok(item));
+                    }
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// How the input is known to be truncated, with the line and offset of
+/// the item concerned, see [`Error`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Truncation {
+    /// The input ended in the middle of the item with the label `label`,
+    /// e.g. a `#VER` whose braces are unbalanced, that starts on `line`.
+    #[error("unexpected end of file in `{label}` on line {line}")]
+    Item {
+        label: String,
+        line: u32,
+        offset: u64,
+    },
+    /// The input ended after the `#KSUMMA` on `line` that starts the
+    /// checksummed items, without the `#KSUMMA` with the checksum that
+    /// ends them (see [`crate::item::Ksumma`]).
+    #[error("unexpected end of file after `#KSUMMA` on line {line}, without a checksum")]
+    Ksumma { line: u32, offset: u64 },
+}
+
+/// Where and why an item couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{kind:?} on line {line}, column {column}: `{snippet}`")]
+pub struct ParseError {
+    /// The kind of the innermost nom error. Items that end prematurely
+    /// are reported as [`nom::error::ErrorKind::Complete`].
+    pub kind: nom::error::ErrorKind,
+    pub line: u32,
+    /// Counted in bytes, starting at 1.
+    pub column: usize,
+    /// See [`Error`].
+    pub offset: u64,
+    /// The input where the error occurred, up to the end of the line.
+    pub snippet: String,
+}
+
+impl ParseError {
+    /// Maximum length of [`ParseError::snippet`], in bytes of input.
+    const SNIPPET_LEN: usize = 40;
+
+    /// An error of `kind` at `at`, the input from `line`, `column` and
+    /// `offset`.
+    fn new(kind: nom::error::ErrorKind, at: &[u8], line: u32, column: usize, offset: u64) -> Self {
+        let len = at
+            .iter()
+            .take(Self::SNIPPET_LEN)
+            .take_while(|&&c| !is_line_break(c))
+            .count();
+
+        Self {
+            kind,
+            line,
+            column,
+            offset,
+            snippet: Cow::borrow_from_cp437(&at[..len], &CP437_CONTROL).into_owned(),
+        }
+    }
+}
+
+/// How the order of the items in a file is checked.
+///
+/// Many programs export files whose items aren't quite in order, e.g.
+/// with `#KONTO` after the balances or `#VER` before `#UB`, which
+/// [`Warn`](Self::Warn) reads while keeping track of.
+///
+/// `#KSUMMA` is exempt from every policy, see [`crate::item::Ksumma`].
+#[derive(Debug, Clone, Copy, Default)]
+pub enum OrderingPolicy {
+    /// Items must be in ascending order by [`Group`], as required by the
+    /// specification.
+    #[default]
+    Strict,
+    /// Items may appear in any order, but those that are out of order
+    /// are collected as [`Warning::OutOfOrder`].
+    Warn,
+    /// Items may appear in any order.
+    Ignore,
+    /// Whether an item of the second group may follow an item of the
+    /// first group.
+    Custom(fn(Group, Group) -> bool),
+}
+
+impl OrderingPolicy {
+    /// Check that the item labeled `label`, of group `next`, at
+    /// `position`, may follow the items read so far, and update their
+    /// `order`. Returns the [warning](Self::Warn) about it, if any.
+    fn check(
+        self,
+        order: &mut Order,
+        label: &'static str,
+        next: Group,
+        position: Position,
+    ) -> Result<Option<Warning>, Error> {
+        let Position { line, offset } = position;
+        let allowed = match self {
+            Self::Strict => order.group <= next,
+            Self::Warn if next < order.group => {
+                // keep comparing to the highest group, so that the items
+                // that follow in order aren't warned about
+                return Ok(Some(Warning::OutOfOrder {
+                    label,
+                    line,
+                    after: order.group,
+                    before: order.first,
+                }));
+            }
+            Self::Warn | Self::Ignore => true,
+            Self::Custom(f) => f(order.group, next),
+        };
+
+        if !allowed {
+            return Err(Error::OutOfOrder {
+                label,
+                line,
+                offset,
+                before: order.first,
+            });
+        }
+
+        if next != order.group {
+            *order = Order {
+                group: next,
+                first: label,
+            };
+        }
+
+        Ok(None)
+    }
+}
+
+/// Where the items read so far are in the order of the file, see
+/// [`OrderingPolicy`].
+#[derive(Debug, Clone, Copy)]
+struct Order {
+    /// The group of the last item.
+    group: Group,
+    /// The label of the first item of the group, which the items that
+    /// are out of order should have appeared before.
+    first: &'static str,
+}
+
+impl Default for Order {
+    fn default() -> Self {
+        Self {
+            group: Group::Flag,
+            first: Flagga::LABEL,
+        }
+    }
+}
+
+/// What to do with the extra fields of an item, see
+/// [`Reader::extra_fields`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExtraFieldsPolicy {
+    /// Report them as [`Error::ExtraFields`] and skip the item, unless
+    /// [lenient](Reader::lenient_sub_entries). Always the policy in
+    /// [strict mode](Reader::strict).
+    Deny,
+    /// Read the item as if they weren't there.
+    #[default]
+    Ignore,
+}
+
+/// Where the checksummed items are, see [`crate::item::Ksumma`].
+#[derive(Debug, Default)]
+struct Ksumma {
+    /// See [`Reader::verify_checksum`].
+    verify: bool,
+    /// Where the `#KSUMMA` that starts the checksummed items is, and
+    /// their checksum so far, while they are read.
+    open: Option<(Position, Checksum)>,
+    /// Whether the checksummed items have ended, or the input has.
+    closed: bool,
+}
+
+impl Ksumma {
+    /// Keep track of an item parsed from `raw`, which starts at
+    /// `position`. If it's a `#KSUMMA`, `checksum` is its checksum.
+    fn update(
+        &mut self,
+        raw: &[u8],
+        position: Position,
+        ksumma: Option<&crate::item::Ksumma>,
+    ) -> Result<(), Error> {
+        let checksum = ksumma.and_then(|ksumma| ksumma.checksum);
+
+        match (&mut self.open, checksum) {
+            (None, None) if ksumma.is_some() && !self.closed => {
+                let mut checksum = Checksum::default();
+                if self.verify {
+                    checksum.update(raw);
+                }
+                self.open = Some((position, checksum));
+            }
+            (Some((_, checksum)), Some(expected)) => {
+                let checksum = *checksum;
+                self.open = None;
+                self.closed = true;
+                if self.verify && !checksum.matches(expected) {
+                    return Err(Error::Checksum {
+                        expected,
+                        actual: checksum.value(),
+                    });
+                }
+            }
+            (Some((_, checksum)), None) if self.verify => checksum.update(raw),
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    /// The error, if any, at the end of the input. Reported only once.
+    fn end(&mut self) -> Option<Error> {
+        self.closed = true;
+        self.open
+            .take()
+            .map(|(Position { line, offset }, _)| Truncation::Ksumma { line, offset }.into())
+    }
+
+    /// Start over, for the next of concatenated files.
+    fn reset(&mut self) {
+        self.open = None;
+        self.closed = false;
+    }
+}
+
+/// The error `e` from reading the item at the start of `rest`, which is
+/// the rest of the input and starts on `line`, at `offset`.
+pub(crate) fn complete_error(e: ReadError, rest: &[u8], line: u32, offset: u64) -> Error {
+    match e {
+        // the whole input is available, so the item is truncated
+        ReadError::Incomplete => {
+            let blank = blank_len(rest);
+            unexpected_eof(
+                &rest[blank..],
+                line.saturating_add(count_lines(&rest[..blank])),
+                offset + blank as u64,
+            )
+        }
+        ReadError::Invalid(invalid) => Error::invalid(&invalid, offset),
+    }
+}
+
+impl Error {
+    /// The error of `invalid`, from an input that starts at `base`.
+    pub(crate) fn invalid(invalid: &Invalid, base: u64) -> Self {
+        let &Invalid {
+            problem,
+            at,
+            line,
+            column,
+            offset,
+            ..
+        } = invalid;
+        let offset = base + offset as u64;
+        match problem {
+            Problem::Field(kind) => ParseError::new(kind, at, line, column, offset).into(),
+            Problem::Amount(len) => Self::InvalidAmount {
+                text: String::from_utf8_lossy(&at[..len]).into_owned(),
+                line,
+                column,
+                offset,
+            },
+            Problem::SubEntry => Self::InvalidSubEntry { line, offset },
+            Problem::ExtraFields => Self::ExtraFields { line, offset },
+        }
+    }
+}
+
+/// [`Truncation::Item`] for the incomplete `item` starting on `line`, at
+/// `offset`.
+fn unexpected_eof(item: &[u8], line: u32, offset: u64) -> Error {
+    let label = item
+        .iter()
+        .take_while(|&&c| !is_whitespace(c) && !is_line_break(c))
+        .count();
+
+    Truncation::Item {
+        label: String::from_utf8_lossy(&item[..label]).into_owned(),
+        line,
+        offset,
+    }
+    .into()
+}
+
+/// Length of the whitespace and line breaks at the start of `buf`.
+fn blank_len(buf: &[u8]) -> usize {
+    buf.iter()
+        .take_while(|&&c| is_whitespace(c) || is_line_break(c))
+        .count()
+}
+
+fn count_lines(buf: &[u8]) -> u32 {
+    u32::try_from(memchr::memchr_iter(b'\n', buf).count()).unwrap_or(u32::MAX)
+}
+
+const BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// What comes before the first item of an input, see [`Reader::new`].
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Preamble {
+    /// The number of bytes, up to the blank lines before the first item.
+    len: usize,
+    warnings: Vec<Warning>,
+}
+
+/// The preamble at the start of `buf`, which starts on `line`, or `None`
+/// if more of the input is needed to tell, unless `eof`.
+fn preamble(buf: &[u8], line: u32, eof: bool) -> Option<Preamble> {
+    if !eof && buf.len() < BOM.len() && BOM.starts_with(buf) {
+        return None;
+    }
+
+    let bom = if buf.starts_with(BOM) { BOM.len() } else { 0 };
+    let mut preamble = Preamble {
+        len: bom,
+        warnings: Vec::new(),
+    };
+    if bom > 0 {
+        preamble.warnings.push(Warning::ByteOrderMark);
+    }
+    loop {
+        let start = preamble.len + blank_len(&buf[preamble.len..]);
+        let rest = &buf[start..];
+        match rest.first() {
+            Some(b'#') => return Some(preamble),
+            Some(_) => {}
+            // without an item to skip to, the lines are read as items
+            None if eof => {
+                preamble.len = bom;
+                preamble.warnings.truncate(usize::from(bom > 0));
+                return Some(preamble);
+            }
+            None => return None,
+        }
+
+        let (text, len) = match memchr::memchr(b'\n', rest) {
+            Some(end) => (&rest[..end], end + 1),
+            None if eof => (rest, rest.len()),
+            None => return None,
+        };
+        let text = text.strip_suffix(b"\r").unwrap_or(text);
+        preamble.warnings.push(Warning::Preamble {
+            line: line.saturating_add(count_lines(&buf[..start])),
+            text: Cow::borrow_from_cp437(
+                &text[..text.len().min(ParseError::SNIPPET_LEN)],
+                &CP437_CONTROL,
+            )
+            .into_owned(),
+        });
+        preamble.len = start + len;
+    }
 }
 
 impl<R: Read> Iterator for Reader<R> {
     type Item = Result<Item, Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let start = self.metrics.is_some().then(Instant::now);
+        let consumed = self.inner.consumed;
+        let res = match self.pending.pop_front() {
+            Some(res) => Some(res),
+            None => self.read(),
+        };
+
+        if let (Some(metrics), Some(start)) = (&self.metrics, start) {
+            record(
+                &**metrics,
+                res.as_ref().map(|res| res.as_ref().map(Item::label)),
+                self.inner.consumed - consumed,
+                start,
+            );
+        }
+
+        #[cfg(feature = "trace")]
+        if let Some(Err(e)) = &res {
+            tracing::debug!(error = %e, "invalid item");
+        }
+
+        res
+    }
+}
+
+impl<R: Read> Reader<R> {
+    /// Skip the preamble, see [`Reader::new`], filling the buffer as
+    /// needed. If it doesn't fit, nothing is skipped. Skipping nothing
+    /// again has no effect.
+    fn skip_preamble(&mut self) -> io::Result<()> {
+        let mut eof = false;
+        let preamble = loop {
+            if let Some(preamble) = preamble(self.inner.buffer(), self.line, eof) {
+                break preamble;
+            }
+            match self.inner.fill(self.max_item_size)? {
+                Some(0) => eof = true,
+                Some(_) => {}
+                None => break Preamble::default(),
+            }
+        };
+
+        self.line = self
+            .line
+            .saturating_add(count_lines(&self.inner.buffer()[..preamble.len]));
+        self.inner.consume(preamble.len);
+        self.warnings.extend(preamble.warnings);
+        Ok(())
+    }
+
+    /// Parse the next item, filling the buffer as needed.
+    fn read(&mut self) -> Option<Result<Item, Error>> {
+        // until something is skipped or read, e.g. after an error
+        if self.inner.consumed == 0 && !self.options.strict {
+            if let Err(e) = self.skip_preamble() {
+                return Some(Err(e.into()));
+            }
+        }
+
         loop {
             let buf = self.inner.buffer();
-            let before_len = buf.len();
+            let custom = self.custom.as_deref();
+            let base = self.inner.consumed;
+            match item::read(buf, self.line, self.options, custom, self.inner.eof) {
+                Ok(Decoded {
+                    item,
+                    len: n,
+                    skipped,
+                    warnings,
+                }) => {
+                    // The item is read without what was skipped, which is
+                    // reported first, and kept only if sub-entries may be
+                    // skipped.
+                    let ignore_extra = self.extra_fields_policy() == ExtraFieldsPolicy::Ignore;
+                    let errors: Vec<_> = skipped
+                        .into_iter()
+                        .filter(|invalid| {
+                            !(ignore_extra && invalid.problem == Problem::ExtraFields)
+                        })
+                        .map(|invalid| Error::invalid(&invalid, base))
+                        .collect();
+                    let keep =
+                        errors.is_empty() || (self.lenient_sub_entries && !self.options.strict);
+                    // the vouchers filtered out are never copied
+                    let item = match item {
+                        ItemRef::Ver(ver) if !self.vouchers.keeps_ref(&ver) => None,
+                        item => Some(item.into_owned()),
+                    };
 
-            match Item::parse(Span::new(buf)) {
-                Ok((rest, item)) => {
-                    let offset = rest.location_offset();
-                    self.inner.consume(offset);
-
-                    if self.group > item.group() {
-                        return Some(Err(Error::OutOfOrder));
+                    let res = match item {
+                        Some(item) => Some(self.accept(n, item)).filter(|_| keep),
+                        None => self.skip(n).err().map(Err),
+                    };
+                    self.warnings.extend(warnings);
+                    // before anything that `accept` has queued
+                    for res in errors.into_iter().map(Err).chain(res).rev() {
+                        self.pending.push_front(res);
+                    }
+                    if let Some(res) = self.pending.pop_front() {
+                        return Some(res);
                     }
+                }
+                Err(ReadError::Incomplete) => match self.inner.fill(self.max_item_size) {
+                    Ok(Some(0)) => {
+                        let buf = self.inner.buffer();
+                        let blank = blank_len(buf);
+                        if blank == buf.len() {
+                            self.inner.consume(blank);
+                            return self.ksumma.end().map(Err);
+                        }
 
-                    self.group = item.group();
+                        // the last item may end without a line break
+                        if !self.inner.eof {
+                            self.inner.eof = true;
+                            continue;
+                        }
 
-                    return Some(Ok(item));
-                }
-                Err(nom::Err::Incomplete(_)) => match self.inner.fill_buf() {
-                    Ok(buf) if buf.len() == before_len => return None,
-                    Ok(_) => continue,
-                    Err(e) => return Some(Err(Error::Io(e))),
+                        let err = unexpected_eof(
+                            &buf[blank..],
+                            self.line.saturating_add(count_lines(&buf[..blank])),
+                            base + blank as u64,
+                        );
+                        self.ksumma.closed = true;
+                        self.inner.consume(buf.len());
+                        return Some(Err(err));
+                    }
+                    Ok(Some(_)) => {}
+                    Ok(None) => {
+                        let buf = self.inner.buffer();
+                        let blank = blank_len(buf);
+                        return Some(Err(Error::ItemTooLarge {
+                            line: self.line.saturating_add(count_lines(&buf[..blank])),
+                            offset: base + blank as u64,
+                            max: self.max_item_size,
+                        }));
+                    }
+                    Err(e) => return Some(Err(e.into())),
                 },
-                Err(nom::Err::Error(_e) | nom::Err::Failure(_e)) => return Some(Err(Error::Parse)),
+                Err(ReadError::Invalid(invalid)) => {
+                    return Some(Err(Error::invalid(&invalid, base)));
+                }
+            }
+        }
+    }
+}
+
+/// Reads items from a complete in-memory buffer without copying text
+/// fields (see [`ItemRef`]). Unlike [`Reader`], it doesn't go through
+/// [`std::io`], see also [`Document::from_slice`]. Otherwise it reads the
+/// same items and errors, e.g. continuing after an invalid sub-entry.
+///
+/// ```
+/// use sie4::{item::ItemRef, reader::SliceReader};
+///
+/// let data = b"#FLAGGA 0\n#KONTO 1930 \"F\x94retagskonto\"\n";
+/// let items = SliceReader::new(data).collect::<Result<Vec<_>, _>>().unwrap();
+/// assert!(matches!(&items[1], ItemRef::Konto(konto) if konto.name == "F\u{f6}retagskonto"));
+/// ```
+pub struct SliceReader<'a> {
+    rest: Span<'a>,
+    order: Order,
+    lenient_sub_entries: bool,
+    ordering: OrderingPolicy,
+    extra_fields: ExtraFieldsPolicy,
+    options: Options,
+    pending: VecDeque<Result<ItemRef<'a>, Error>>,
+    ksumma: Ksumma,
+    vouchers: Vouchers,
+    custom: Option<Arc<Registry>>,
+    metrics: Option<Arc<dyn Metrics + Send + Sync>>,
+    warnings: Vec<Warning>,
+}
+
+impl<'a> SliceReader<'a> {
+    #[must_use]
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self {
+            rest: Span::new(buf),
+            order: Order::default(),
+            lenient_sub_entries: false,
+            ordering: OrderingPolicy::default(),
+            extra_fields: ExtraFieldsPolicy::default(),
+            options: Options::default(),
+            pending: VecDeque::new(),
+            ksumma: Ksumma::default(),
+            vouchers: Vouchers::default(),
+            custom: None,
+            metrics: None,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// See [`Reader::lenient_sub_entries`].
+    #[must_use]
+    pub fn lenient_sub_entries(mut self, lenient: bool) -> Self {
+        self.lenient_sub_entries = lenient;
+        self
+    }
+
+    /// See [`Reader::ordering`].
+    #[must_use]
+    pub fn ordering(mut self, policy: OrderingPolicy) -> Self {
+        self.ordering = policy;
+        self
+    }
+
+    /// See [`Reader::extra_fields`].
+    #[must_use]
+    pub fn extra_fields(mut self, policy: ExtraFieldsPolicy) -> Self {
+        self.extra_fields = policy;
+        self
+    }
+
+    /// See [`Reader::century_pivot`].
+    #[must_use]
+    pub fn century_pivot(mut self, pivot: u8) -> Self {
+        self.options.century_pivot = Some(pivot);
+        self
+    }
+
+    /// See [`Reader::lenient_amounts`].
+    #[must_use]
+    pub fn lenient_amounts(mut self, lenient: bool) -> Self {
+        self.options.lenient_amounts = lenient;
+        self
+    }
+
+    /// See [`Reader::strict`].
+    #[must_use]
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.options.strict = strict;
+        self
+    }
+
+    /// See [`Reader::verify_checksum`].
+    #[must_use]
+    pub fn verify_checksum(mut self, verify: bool) -> Self {
+        self.ksumma.verify = verify;
+        self
+    }
+
+    /// See [`Reader::date_range`].
+    #[must_use]
+    pub fn date_range(mut self, dates: RangeInclusive<Date>) -> Self {
+        self.vouchers.dates = Some(dates);
+        self
+    }
+
+    /// See [`Reader::accounts`].
+    #[must_use]
+    pub fn accounts(mut self, accounts: impl IntoIterator<Item = impl Into<AccountNo>>) -> Self {
+        self.vouchers.accounts = accounts.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// See [`Reader::series`].
+    #[must_use]
+    pub fn series(mut self, series: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.vouchers.series = series.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// See [`Reader::custom_items`].
+    #[must_use]
+    pub fn custom_items(mut self, registry: Arc<Registry>) -> Self {
+        self.custom = Some(registry);
+        self
+    }
+
+    /// See [`Reader::metrics`].
+    #[must_use]
+    pub fn metrics(mut self, metrics: Arc<dyn Metrics + Send + Sync>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// See [`Reader::warnings`].
+    #[must_use]
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// See [`Reader::take_warnings`].
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+}
+
+impl<'a> Iterator for SliceReader<'a> {
+    type Item = Result<ItemRef<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.metrics.is_some().then(Instant::now);
+        let len = self.rest.len();
+        let res = self.read();
+
+        if let (Some(metrics), Some(start)) = (&self.metrics, start) {
+            record(
+                &**metrics,
+                res.as_ref().map(|res| res.as_ref().map(ItemRef::label)),
+                (len - self.rest.len()) as u64,
+                start,
+            );
+        }
+
+        res
+    }
+}
+
+impl<'a> SliceReader<'a> {
+    /// Read the next item that isn't filtered out, or an error.
+    fn read(&mut self) -> Option<Result<ItemRef<'a>, Error>> {
+        loop {
+            if let Some(res) = self.pending.pop_front() {
+                return Some(res);
+            }
+            if self.read_one().is_none() {
+                return self.pending.pop_front();
+            }
+        }
+    }
+
+    /// Queue the results of the next record, as [`Reader`] would yield
+    /// them, returning `None` at the end of the input.
+    fn read_one(&mut self) -> Option<()> {
+        if self.rest.location_offset() == 0 && !self.options.strict {
+            let preamble =
+                preamble(&self.rest, self.rest.location_line(), true).unwrap_or_default();
+            self.rest = self.rest.slice(preamble.len..);
+            self.warnings.extend(preamble.warnings);
+        }
+
+        if self
+            .rest
+            .iter()
+            .all(|&c| is_whitespace(c) || is_line_break(c))
+        {
+            self.pending.extend(self.ksumma.end().map(Err));
+            return None;
+        }
+
+        let line = self.rest.location_line();
+        let base = self.rest.location_offset() as u64;
+        let res = item::read(&self.rest, line, self.options, self.custom.as_deref(), true);
+        let Decoded {
+            item,
+            len,
+            skipped,
+            warnings,
+        } = match res {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                // the end of the record is unknown, so nothing more is read
+                let err = complete_error(e, &self.rest, line, base);
+                self.rest = Span::new(&[]);
+                self.ksumma.closed = true;
+                self.pending.push_back(Err(err));
+                return Some(());
+            }
+        };
+        self.warnings.extend(warnings);
+
+        // see `Reader::read` on what is skipped
+        let ignore_extra = !self.options.strict && self.extra_fields == ExtraFieldsPolicy::Ignore;
+        let errors = skipped
+            .into_iter()
+            .filter(|invalid| !(ignore_extra && invalid.problem == Problem::ExtraFields))
+            .map(|invalid| Error::invalid(&invalid, base))
+            .collect::<Vec<_>>();
+        let keep = errors.is_empty() || (self.lenient_sub_entries && !self.options.strict);
+        self.pending.extend(errors.into_iter().map(Err));
+
+        let raw = &self.rest[..len];
+        let blank = blank_len(raw);
+        let position = Position {
+            line: line + count_lines(&raw[..blank]),
+            offset: base + blank as u64,
+        };
+        let ksumma = match &item {
+            ItemRef::Ksumma(ksumma) => Some(crate::item::Ksumma {
+                checksum: ksumma.checksum,
+            }),
+            _ => None,
+        };
+        self.rest = self.rest.slice(len..);
+        let res = self
+            .ksumma
+            .update(raw, position, ksumma.as_ref())
+            .and_then(|()| self.check_order(&item, position))
+            .map(|()| item);
+
+        match res {
+            Ok(ItemRef::Ver(ver)) if !self.vouchers.keeps_ref(&ver) => {}
+            res if keep => self.pending.push_back(res),
+            _ => {}
+        }
+        Some(())
+    }
+
+    /// Check that `item`, starting at `position`, is in order.
+    fn check_order(&mut self, item: &ItemRef, position: Position) -> Result<(), Error> {
+        // see `Ksumma` on why it's exempt
+        if matches!(item, ItemRef::Ksumma(_)) {
+            return Ok(());
+        }
+        let ordering = if self.options.strict {
+            OrderingPolicy::Strict
+        } else {
+            self.ordering
+        };
+        if let Some(warning) =
+            ordering.check(&mut self.order, item.label(), item.group(), position)?
+        {
+            self.warnings.push(warning);
+        }
+        Ok(())
+    }
+}
+
+/// The vouchers to read, see [`Reader::date_range`]. Empty filters keep
+/// all vouchers.
+#[derive(Debug, Clone, Default)]
+struct Vouchers {
+    dates: Option<RangeInclusive<Date>>,
+    accounts: Vec<AccountNo>,
+    series: Vec<String>,
+}
+
+impl Vouchers {
+    fn keeps_ref(&self, ver: &VerRef) -> bool {
+        self.keeps_with(
+            &ver.series,
+            ver.date,
+            ver.transactions.0.iter().filter_map(|line| match line {
+                TransLineRef::Normal(trans) | TransLineRef::Added(trans) => Some(trans.account),
+                TransLineRef::Removed(_) => None,
+            }),
+        )
+    }
+
+    fn keeps_with(
+        &self,
+        series: &str,
+        date: Date,
+        mut accounts: impl Iterator<Item = AccountNo>,
+    ) -> bool {
+        self.dates
+            .as_ref()
+            .is_none_or(|dates| dates.contains(&date))
+            && (self.series.is_empty() || self.series.iter().any(|s| s == series))
+            && (self.accounts.is_empty()
+                || accounts.any(|account| self.accounts.contains(&account)))
+    }
+}
+
+/// Report the result of a call of `next` that consumed `bytes`.
+fn record(
+    metrics: &dyn Metrics,
+    res: Option<Result<&'static str, &Error>>,
+    bytes: u64,
+    start: Instant,
+) {
+    if bytes > 0 {
+        metrics.bytes(bytes);
+    }
+    match res {
+        Some(Ok(label)) => metrics.item(label),
+        Some(Err(e)) => metrics.error(e),
+        None => return,
+    }
+    metrics.duration(start.elapsed());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use time::macros::date;
+
+    use super::*;
+    use crate::item::{Konto, Ub, Ver};
+
+    #[test]
+    fn slice_reader() {
+        let data = b"#FLAGGA 0
+#PROGRAM \"Vi iMproved\" 9.0
+#FNAMN \"R\x84ksm\x94rg\x86s AB\"
+#KONTO 1930 \"F\x94retagskonto\"
+";
+        let items = SliceReader::new(data)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(items.len(), 4);
+        let ItemRef::Program(program) = &items[1] else {
+            panic!("expected #PROGRAM");
+        };
+        assert!(matches!(program.name, Cow::Borrowed("Vi iMproved")));
+        let ItemRef::FNamn(fnamn) = &items[2] else {
+            panic!("expected #FNAMN");
+        };
+        assert!(matches!(&fnamn.name, Cow::Owned(name) if name == "R\u{e4}ksm\u{f6}rg\u{e5}s AB"));
+    }
+
+    #[test]
+    fn extra_fields() {
+        let data = b"#VER A 1 20230102\n{\n#TRANS 1930 {} 1.00 20230102 \"\" 1 AK 1\n}\n";
+
+        let mut reader = Reader::new(&data[..]).extra_fields(ExtraFieldsPolicy::Deny);
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::ExtraFields { line: 3, .. }))
+        ));
+        assert!(reader.next().is_none());
+        assert!(matches!(
+            Reader::new(&data[..]).strict(true).next(),
+            Some(Err(Error::ExtraFields { line: 3, .. }))
+        ));
+
+        let items = Reader::new(&data[..])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let [Item::Ver(ver)] = &items[..] else {
+            panic!("expected #VER");
+        };
+        assert_eq!(
+            ver.transactions.0[0].trans().signature.as_deref(),
+            Some("AK")
+        );
+
+        assert!(matches!(
+            SliceReader::new(data)
+                .extra_fields(ExtraFieldsPolicy::Deny)
+                .next(),
+            Some(Err(Error::ExtraFields { line: 3, .. }))
+        ));
+        let items = SliceReader::new(data)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(matches!(&items[..], [ItemRef::Ver(_)]));
+    }
+
+    #[test]
+    fn identification_only() {
+        let header = Reader::new(&b"#FLAGGA 0\n#KONTO 1930 Bank"[..])
+            .identification_only()
+            .unwrap();
+        assert!(!header.more);
+        assert_eq!(header.document.items().len(), 2);
+
+        let header = Reader::new(&b"#FLAGGA 0\n\n  #UB 0 1930 1.00\n"[..])
+            .identification_only()
+            .unwrap();
+        assert!(header.more);
+        assert_eq!(header.document.items().len(), 1);
+
+        assert!(matches!(
+            Reader::new(&b"#FLAGGA 0\n#KONTO Bank\n#UB 0 1930 1.00\n"[..]).identification_only(),
+            Err(Error::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn metrics() {
+        use crate::metrics::Counters;
+
+        let data = b"#FLAGGA 0\n#VER A 1 20230102\n{\n#TRANS 1930 {} 1.00\n}\n#KONTO 1930 Bank";
+        let reader = Arc::new(Counters::default());
+        let slice_reader = Arc::new(Counters::default());
+        let _ = Reader::new(&data[..]).metrics(reader.clone()).count();
+        let _ = SliceReader::new(data).metrics(slice_reader.clone()).count();
+
+        for counters in [reader, slice_reader] {
+            assert_eq!(counters.bytes(), data.len() as u64);
+            assert_eq!(
+                counters.item_counts(),
+                [("FLAGGA", 1), ("VER", 1)].into_iter().collect()
+            );
+            // out of order
+            assert_eq!(counters.errors(), 1);
+        }
+    }
+
+    #[test]
+    fn slice_reader_unterminated() {
+        // a missing line break at the end is fine
+        let items = SliceReader::new(b"#FLAGGA 0")
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(matches!(&items[..], [ItemRef::Flagga(_)]));
+
+        let items = SliceReader::new(b"#FLAGGA 0\n#KONTO 1930 \"Bank\"")
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(matches!(&items[1], ItemRef::Konto(konto) if konto.name == "Bank"));
+
+        let data = b"#FLAGGA 0\n#VER A 1 20230101\n{\n#TRANS 1930 {} 1.00\n#TRANS 2640 {} -1.00\n}";
+        let items = SliceReader::new(data)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(matches!(&items[1], ItemRef::Ver(ver) if ver.transactions.0.len() == 2));
+        assert_eq!(Document::from_slice(data).unwrap().items().len(), 2);
+        assert_eq!(crate::parse_items(data).unwrap().len(), 2);
+
+        // errors in the last item are still found where they are
+        let mut reader =
+            SliceReader::new(b"#FLAGGA 0\n#VER A 1 20230101\n{\n#TRANS 1930 {} 1,00\n}");
+        assert!(matches!(reader.next(), Some(Ok(ItemRef::Flagga(_)))));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::InvalidAmount { text, line: 4, column: 16, .. })) if text == "1,00"
+        ));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn slice_reader_errors() {
+        let mut reader = SliceReader::new(b"#KONTO 1930 \"Bank\"\n#FLAGGA 0\n");
+        assert!(matches!(reader.next(), Some(Ok(ItemRef::Konto(_)))));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::OutOfOrder {
+                label: "FLAGGA",
+                line: 2,
+                offset: 19,
+                before: "KONTO"
+            }))
+        ));
+        assert!(reader.next().is_none());
+
+        let mut reader =
+            SliceReader::new(b"#FLAGGA 0\n#KONTO 19-30 \"Bank\"\n#KONTO 1930 \"Bank\"\n");
+        assert!(matches!(reader.next(), Some(Ok(ItemRef::Flagga(_)))));
+        let Some(Err(Error::Parse(e))) = reader.next() else {
+            panic!("expected parse error");
+        };
+        assert_eq!(e.line, 2);
+        assert_eq!(e.column, 8);
+        assert_eq!(e.snippet, "19-30 \"Bank\"");
+        assert_eq!(
+            e.to_string(),
+            "MapRes on line 2, column 8: `19-30 \"Bank\"`"
+        );
+        assert!(reader.next().is_none());
+
+        let mut reader =
+            SliceReader::new(b"#FLAGGA 0\n\n#VER A 1 20230101\n{\n#TRANS 1930 {} 1.00");
+        assert!(matches!(reader.next(), Some(Ok(ItemRef::Flagga(_)))));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::Truncated(Truncation::Item { label, line: 3, .. }))) if label == "#VER"
+        ));
+        assert!(reader.next().is_none());
+
+        let mut reader = SliceReader::new(b"#VER A 1 20230101\n{\n#TRANS {} 1.00\n}\n");
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::InvalidSubEntry { line: 3, .. }))
+        ));
+        assert!(reader.next().is_none());
+
+        let mut reader = SliceReader::new(b"#VER A 1 20230101\n{\n#TRANS 1930 {} 1,00\n}\n");
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::InvalidAmount { text, line: 3, column: 16, .. })) if text == "1,00"
+        ));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn io_error() {
+        struct Denied;
+
+        impl Read for Denied {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::PermissionDenied.into())
             }
         }
+
+        let err = Reader::new(Denied).next().unwrap().unwrap_err();
+        let source = std::error::Error::source(&err)
+            .and_then(|e| e.downcast_ref::<std::io::Error>())
+            .unwrap();
+        assert_eq!(source.kind(), std::io::ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn unexpected_eof() {
+        // a missing line break at the end is fine
+        let items = Reader::new(&b"#FLAGGA 0\n#KONTO 1930 Bank"[..])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(matches!(&items[1], Item::Konto(konto) if konto.name == "Bank"));
+
+        let mut reader =
+            Reader::new(&b"#FLAGGA 0\n\n#VER A 1 20230101\n{\n#TRANS 1930 {} 1.00\n"[..]);
+        assert!(matches!(reader.next(), Some(Ok(Item::Flagga(_)))));
+        let Some(Err(err)) = reader.next() else {
+            panic!("expected error");
+        };
+        assert_eq!(
+            err.to_string(),
+            "unexpected end of file in `#VER` on line 3"
+        );
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn ksumma() {
+        let data = b"#FLAGGA 0\n#KSUMMA\n#KONTO 1930 \"Bank\"\n#KSUMMA -1371167454\n";
+        let items = Reader::new(&data[..])
+            .verify_checksum(true)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(items.len(), 4);
+        let items = SliceReader::new(data)
+            .verify_checksum(true)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(items.len(), 4);
+
+        // cut off between items
+        let data = &data[..data.len() - 20];
+        let mut reader = Reader::new(data);
+        assert!(reader.by_ref().take(3).all(|res| res.is_ok()));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::Truncated(Truncation::Ksumma { line: 2, .. })))
+        ));
+        assert!(reader.next().is_none());
+        let mut reader = SliceReader::new(data);
+        assert!(reader.by_ref().take(3).all(|res| res.is_ok()));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::Truncated(Truncation::Ksumma { line: 2, .. })))
+        ));
+        assert!(reader.next().is_none());
+
+        let data = b"#FLAGGA 0\n#KSUMMA\n#KONTO 1930 \"Kassa\"\n#KSUMMA -1371167454\n";
+        assert!(Reader::new(&data[..]).all(|res| res.is_ok()));
+        let mut reader = Reader::new(&data[..]).verify_checksum(true);
+        assert!(reader.by_ref().take(3).all(|res| res.is_ok()));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::Checksum {
+                expected: -1_371_167_454,
+                ..
+            }))
+        ));
+
+        // a document of concatenated files that is cut off
+        let data = b"#FLAGGA 0\n#KSUMMA\n#KONTO 1930 Bank\n#FLAGGA 0\n#KONTO 1930 Bank\n";
+        let mut reader = Reader::new(&data[..]);
+        assert!(matches!(
+            reader.next_document(),
+            Some(Err(Error::Truncated(Truncation::Ksumma { line: 2, .. })))
+        ));
+        assert!(reader.next_document().unwrap().is_ok());
+    }
+
+    #[test]
+    fn large_items() {
+        let mut data = b"#FLAGGA 0\n#VER A 1 20230101\n{\n".to_vec();
+        for _ in 0..1000 {
+            data.extend_from_slice(
+                b"#TRANS 1930 {} 1.00 20230101 \"Lorem ipsum dolor sit amet\"\n",
+            );
+        }
+        data.extend_from_slice(b"}\n#VER A 2 20230102\n{\n}\n");
+        assert!(data.len() > BUF_SIZE * 4);
+
+        let items = Reader::new(&data[..])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let [Item::Flagga(_), Item::Ver(ver), Item::Ver(_)] = &items[..] else {
+            panic!("expected #FLAGGA and two #VER");
+        };
+        assert_eq!(ver.transactions.0.len(), 1000);
+
+        let mut reader = Reader::new(&data[..]).max_item_size(BUF_SIZE);
+        assert!(matches!(reader.next(), Some(Ok(Item::Flagga(_)))));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::ItemTooLarge {
+                line: 2,
+                max: BUF_SIZE,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn invalid_amount() {
+        let mut reader = Reader::new(&b"#FLAGGA 0\n#UB 0 1930 1.000,00\n"[..]);
+        assert!(matches!(reader.next(), Some(Ok(Item::Flagga(_)))));
+        let Some(Err(err)) = reader.next() else {
+            panic!("expected error");
+        };
+        assert_eq!(
+            err.to_string(),
+            "invalid amount `1.000,00` on line 2, column 12"
+        );
+    }
+
+    #[test]
+    fn strict() {
+        struct Nothing;
+        impl Handler for Nothing {}
+
+        let first = |data: &[u8], strict| {
+            let mut reader = Reader::new(data)
+                .extra_fields(ExtraFieldsPolicy::Ignore)
+                .strict(strict);
+            (reader.next(), reader.next())
+        };
+        // the relaxing builders don't undo strict mode
+        let relaxed = |data: &[u8]| {
+            Reader::new(data)
+                .strict(true)
+                .ordering(OrderingPolicy::Ignore)
+                .extra_fields(ExtraFieldsPolicy::Ignore)
+                .lenient_sub_entries(true)
+                .lenient_amounts(true)
+                .century_pivot(70)
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        for data in [
+            &b"#KONTO 1930 \"Bank\" 1\n"[..],
+            b"#RAR 0 230101 231231\n",
+            b"#IB 0 1930 1.00 \"10,5\"\n",
+            b"#VER A 1 20230101\n{\n#TRANS 1930 {} 1.00 \"\" \"\" x\n}\n",
+            b"#UB 0 1930 1000,00\n",
+            b"  #KONTO 1930 Bank\n",
+            b"#KONTO\t1930 Bank\n",
+            b"#KONTO 1930  Bank\n",
+        ] {
+            let text = String::from_utf8_lossy(data);
+            let mut lenient = Reader::new(data)
+                .extra_fields(ExtraFieldsPolicy::Ignore)
+                .lenient_amounts(true)
+                .century_pivot(70);
+            assert!(
+                matches!((lenient.next(), lenient.next()), (Some(Ok(_)), None)),
+                "{text}"
+            );
+            assert!(matches!(first(data, true), (Some(Err(_)), _)), "{text}");
+            assert!(relaxed(data).is_err(), "{text}");
+        }
+        assert!(relaxed(b"#KSUMMA\n#UB 0 1930 1.00\n#KONTO 1930 Bank\n").is_err());
+
+        // sub-entries may be indented, and lines may end with whitespace
+        let data = b"#KONTO 1930 Bank \n#VER A 1 20230101\n{\n\t#TRANS 1930 {} 1.00\n}\n";
+        assert!(matches!(first(data, true), (Some(Ok(_)), Some(Ok(_)))));
+
+        // the optional fields of the specification aren't extra
+        let data = b"#ORGNR \"556000-0001\" \"\" \"\"\n#ORGNR 5560000001 1 2\n";
+        let (Some(Ok(Item::Orgnr(one))), Some(Ok(Item::Orgnr(two)))) = first(data, true) else {
+            panic!("expected two #ORGNR");
+        };
+        assert_eq!((one.forv_no, one.verk_no), (None, None));
+        assert_eq!((two.forv_no, two.verk_no), (Some(1), Some(2)));
+
+        assert!(matches!(
+            first(b"#KONTO 1930 Bank\r\n#KONTO 1940 Kassa\n", true),
+            (Some(Ok(_)), Some(Ok(_)))
+        ));
+        assert!(matches!(
+            first(b"#FLAGGA 0\r#KONTO 1930 Bank\n", false),
+            (Some(Ok(_)), Some(Ok(_)))
+        ));
+        assert!(matches!(
+            first(b"#FLAGGA 0\r#KONTO 1930 Bank\n", true),
+            (
+                Some(Err(Error::Parse(ParseError {
+                    kind: nom::error::ErrorKind::CrLf,
+                    column: 10,
+                    ..
+                }))),
+                _
+            )
+        ));
+
+        let mut reader = SliceReader::new(b"#RAR 0 230101 231231\n").strict(true);
+        assert!(matches!(reader.next(), Some(Err(Error::Parse(_)))));
+
+        // a carriage return may still be split from its line feed
+        let mut parser = Parser::new().strict(true);
+        parser.feed(b"#FLAGGA 0\r", &mut Nothing).unwrap();
+        parser.feed(b"\n#KONTO 1930 Bank", &mut Nothing).unwrap();
+        assert!(parser.finish(&mut Nothing).is_ok());
+    }
+
+    #[test]
+    fn positions() {
+        let data = b"#FLAGGA 0\r\n\r\n#VER A 1 20230101\r\n{\r\n#TRANS {} 1.00\r\n}\r\n  #KONTO 1930 \"Bank\"";
+        let located = Reader::new(&data[..])
+            .ordering(OrderingPolicy::Ignore)
+            .lenient_sub_entries(true)
+            .located()
+            .map(|res| res.map(|located| (located.item.label(), located.position)))
+            .collect::<Vec<_>>();
+
+        let [Ok(flagga), Err(Error::InvalidSubEntry { line: 5, .. }), Ok(ver), Ok(konto)] =
+            &located[..]
+        else {
+            panic!("{located:?}");
+        };
+        assert_eq!(*flagga, ("FLAGGA", Position { line: 1, offset: 0 }));
+        assert_eq!(
+            *ver,
+            (
+                "VER",
+                Position {
+                    line: 3,
+                    offset: 13
+                }
+            )
+        );
+        assert_eq!(
+            *konto,
+            (
+                "KONTO",
+                Position {
+                    line: 7,
+                    offset: 56
+                }
+            )
+        );
+        assert_eq!(&data[56..62], b"#KONTO");
+    }
+
+    #[test]
+    fn items_of() {
+        let data = b"#KONTO 1930 \"Bank\"\n#VER A 1 20230101\n{\n#TRANS 1930 {} 1.00\n}\n#VER A 2 20230102\n{\n}\n";
+        let vers = Reader::new(&data[..])
+            .items_of::<Ver>()
+            .map(|ver| ver.unwrap().no)
+            .collect::<Vec<_>>();
+        assert_eq!(vers, [1, 2]);
+
+        // errors in the items skipped are returned as well
+        let mut ubs = Reader::new(&b"#KONTO 1930\n#UB 0 1930 1.00\n"[..]).items_of::<Ub>();
+        assert!(matches!(ubs.next(), Some(Err(Error::Parse(_)))));
+
+        let ver = Item::from("#VER A 1 20230101\n{\n}".parse::<Ver>().unwrap());
+        assert!(matches!(Konto::try_from(ver), Err(Item::Ver(_))));
+    }
+
+    #[test]
+    fn dropped_quantities() {
+        let data = b"#VER A 1 20230101\n{\n#TRANS 1460 {} 150.00 20230320 \"Pens\" \"tio\" AK\n#TRANS 1930 {} -150.00 \"\" \"\" \"\"\n}\n";
+        let warnings = [Warning::DroppedQuantity {
+            text: "tio".to_owned(),
+            line: 3,
+        }];
+
+        let mut reader = Reader::new(&data[..]);
+        let Some(Ok(Item::Ver(ver))) = reader.next() else {
+            panic!("expected #VER");
+        };
+        assert_eq!(ver.transactions.0[0].trans().quantity, None);
+        assert_eq!(reader.warnings(), warnings);
+
+        let mut reader = SliceReader::new(data);
+        assert!(reader.by_ref().all(|res| res.is_ok()));
+        assert_eq!(reader.warnings(), warnings);
+        assert_eq!(
+            warnings[0].to_string(),
+            "quantity `tio` on line 3 isn't a number and was left out"
+        );
+
+        assert!(Reader::new(&data[..]).strict(true).next().unwrap().is_err());
+    }
+
+    #[test]
+    fn lenient_amounts() {
+        let data = b"#FLAGGA 0\n#UB 0 1930 1.000,00\n#VER A 1 20230101\n{\n#TRANS 1930 {} \"-1 234,5\"\n#TRANS 3010 {} 1234,50\n}\n#RES 0 3010 1,234.50\n";
+
+        let warnings = [
+            Warning::LenientAmount {
+                text: "1.000,00".to_owned(),
+                line: 2,
+            },
+            Warning::LenientAmount {
+                text: "-1 234,5".to_owned(),
+                line: 5,
+            },
+            Warning::LenientAmount {
+                text: "1234,50".to_owned(),
+                line: 6,
+            },
+            Warning::LenientAmount {
+                text: "1,234.50".to_owned(),
+                line: 8,
+            },
+        ];
+
+        let mut reader = Reader::new(&data[..]).lenient_amounts(true);
+        let items = reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+        let [Item::Flagga(_), Item::Ub(ub), Item::Ver(ver), Item::Res(res)] = &items[..] else {
+            panic!("expected #FLAGGA, #UB, #VER and #RES");
+        };
+        assert_eq!(ub.balance.to_string(), "1000.00");
+        let amounts = ver
+            .transactions
+            .0
+            .iter()
+            .map(|line| line.trans().amount.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(amounts, ["-1234.5", "1234.50"]);
+        assert_eq!(res.balance.to_string(), "1234.50");
+        assert_eq!(reader.warnings(), warnings);
+
+        let mut reader = SliceReader::new(data).lenient_amounts(true);
+        assert!(reader.by_ref().all(|res| res.is_ok()));
+        assert_eq!(reader.warnings(), warnings);
+        assert_eq!(
+            warnings[0].to_string(),
+            "amount `1.000,00` on line 2 isn't in the standard format"
+        );
+
+        // still rejected unless it's unambiguous
+        let mut reader = Reader::new(&b"#UB 0 1930 1,000,00\n"[..]).lenient_amounts(true);
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::InvalidAmount { .. }))
+        ));
+    }
+
+    #[test]
+    fn ordering() {
+        let data = b"#FLAGGA 0\n#UB 0 1930 1.00\n#KONTO 1930 \"Bank\"\n#FNAMN \"AB\"\n";
+        let read = |policy| {
+            Reader::new(&data[..])
+                .ordering(policy)
+                .map(|res| res.is_ok())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(read(OrderingPolicy::Strict), [true, true, false, false]);
+        assert_eq!(read(OrderingPolicy::Ignore), [true, true, true, true]);
+        assert_eq!(
+            read(OrderingPolicy::Custom(|current, next| {
+                current <= next || next == Group::Account
+            })),
+            [true, true, true, false]
+        );
+
+        let mut reader = SliceReader::new(data).ordering(OrderingPolicy::Ignore);
+        assert!(reader.all(|res| res.is_ok()));
+        assert!(reader.warnings().is_empty());
+
+        let warnings = [
+            Warning::OutOfOrder {
+                label: "KONTO",
+                line: 3,
+                after: Group::Balance,
+                before: "UB",
+            },
+            Warning::OutOfOrder {
+                label: "FNAMN",
+                line: 4,
+                after: Group::Balance,
+                before: "UB",
+            },
+        ];
+        let mut reader = Reader::new(&data[..]).ordering(OrderingPolicy::Warn);
+        assert!(reader.by_ref().all(|res| res.is_ok()));
+        assert_eq!(reader.take_warnings(), warnings);
+        assert!(reader.warnings().is_empty());
+
+        let mut reader = SliceReader::new(data).ordering(OrderingPolicy::Warn);
+        assert!(reader.by_ref().all(|res| res.is_ok()));
+        assert_eq!(reader.warnings(), warnings);
+        assert_eq!(
+            warnings[0].to_string(),
+            "`#KONTO` on line 3 should appear before `#UB`, in the balances and verifications group"
+        );
+        assert_eq!(
+            Reader::new(&data[..])
+                .nth(2)
+                .unwrap()
+                .unwrap_err()
+                .to_string(),
+            "`#KONTO` on line 3 must appear before `#UB`"
+        );
+    }
+
+    #[test]
+    fn invalid_sub_entries() {
+        let data = b"#FLAGGA 0
+#VER A 1 20230101
+{
+    #TRANS 1930 {} 1.00
+    #TRANS 1930 {} x
+    #TRANS {} -1.00}
+#VER A 2 20230102
+{
+    #TRANS 1930 {} 2.00
+}
+";
+        let mut reader = Reader::new(&data[..]);
+        assert!(matches!(reader.next(), Some(Ok(Item::Flagga(_)))));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::InvalidAmount { text, line: 5, column: 20, .. })) if text == "x"
+        ));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::InvalidSubEntry { line: 6, .. }))
+        ));
+        assert!(matches!(reader.next(), Some(Ok(Item::Ver(ver))) if ver.no == 2));
+        assert!(reader.next().is_none());
+
+        let items = Reader::new(&data[..])
+            .lenient_sub_entries(true)
+            .filter_map(Result::ok)
+            .collect::<Vec<_>>();
+        let [Item::Flagga(_), Item::Ver(first), Item::Ver(second)] = &items[..] else {
+            panic!("expected #FLAGGA and two #VER");
+        };
+        assert_eq!(first.transactions.0.len(), 1);
+        assert_eq!(second.transactions.0.len(), 1);
+
+        // the same items and errors without copying
+        for lenient in [false, true] {
+            let owned = Reader::new(&data[..])
+                .lenient_sub_entries(lenient)
+                .map(|res| format!("{res:?}"))
+                .collect::<Vec<_>>();
+            let borrowed = SliceReader::new(data)
+                .lenient_sub_entries(lenient)
+                .map(|res| format!("{:?}", res.map(ItemRef::into_owned)))
+                .collect::<Vec<_>>();
+            assert_eq!(owned, borrowed);
+        }
+    }
+
+    #[test]
+    fn voucher_filters() {
+        fn vouchers(items: impl Iterator<Item = Result<Item, Error>>) -> Vec<String> {
+            items
+                .filter_map(|item| match item.unwrap() {
+                    Item::Ver(ver) => Some(format!("{}{}", ver.series, ver.no)),
+                    _ => None,
+                })
+                .collect()
+        }
+
+        let data = b"#FLAGGA 0
+#KSUMMA
+#KONTO 1930 \"Bank\"
+#VER A 1 20230110
+{
+    #TRANS 1930 {} -1.00
+    #TRANS 5010 {} 1.00
+}
+#VER A 2 20230210
+{
+    #TRANS 1930 {} -2.00
+    #BTRANS 6110 {} 2.00
+    #RTRANS 5010 {} 2.00
+    #TRANS 5010 {} 2.00
+}
+#VER B 1 20230115
+{
+    #TRANS 1930 {} -3.00
+    #TRANS 6110 {} 3.00
+}
+#KSUMMA 1134146061
+";
+
+        let reader = || Reader::new(&data[..]).verify_checksum(true);
+        let january = date!(2023 - 01 - 01)..=date!(2023 - 01 - 31);
+        assert_eq!(vouchers(reader().date_range(january.clone())), ["A1", "B1"]);
+        // the corrections are applied
+        assert_eq!(vouchers(reader().accounts([6110])), ["B1"]);
+        assert_eq!(
+            vouchers(reader().accounts([5010, 6110]).series(["A"])),
+            ["A1", "A2"]
+        );
+        // the other items are kept
+        assert_eq!(reader().series(["C"]).count(), 4);
+
+        let slice = SliceReader::new(data)
+            .verify_checksum(true)
+            .date_range(january.clone())
+            .accounts([5010])
+            .map(|item| item.map(ItemRef::into_owned));
+        assert_eq!(vouchers(slice), ["A1"]);
+
+        // errors in the vouchers filtered out are still reported
+        let data = b"#VER A 1 20230101
+{
+    #TRANS 1930 {} x
+}
+#VER A 2 20230301
+{
+    #TRANS 1930 {} 2.00
+}
+";
+        let mut reader = Reader::new(&data[..]).date_range(january);
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::InvalidAmount { line: 3, .. }))
+        ));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn concatenated() {
+        let data = b"#FLAGGA 0
+#KSUMMA
+#KONTO 1930 \"Bank\"
+#UB 0 1930 1.00
+#KSUMMA 123
+#FLAGGA 0
+#KSUMMA
+#KONTO 19-30 \"Bank\"
+#UB 0 1930 2.00
+#KSUMMA 456
+";
+        // plain iteration still requires the order
+        assert!(Reader::new(&data[..]).any(|res| matches!(res, Err(Error::OutOfOrder { .. }))));
+
+        let mut reader = Reader::new(&data[..]);
+        let first = reader.next_document().unwrap().unwrap();
+        assert_eq!(first.items().len(), 5);
+        assert!(matches!(reader.next_document(), Some(Err(Error::Parse(_)))));
+
+        let data = String::from_utf8(data.to_vec())
+            .unwrap()
+            .replace(" 19-30 ", " 1930 ");
+        let docs = Document::from_concatenated(data.as_bytes()).unwrap();
+        assert_eq!(docs.len(), 2);
+        assert!(
+            matches!(docs[1].items(), [Item::Flagga(_), _, _, Item::Ub(ub), _] if ub.balance.to_string() == "2.00")
+        );
+    }
+
+    #[test]
+    fn preamble() {
+        let data = b"\xef\xbb\xbf\r\n;; export\r\n  \x94vrigt\r\n\r\n#FLAGGA 0\r\n#KONTO 1930 \"Bank\"\r\n";
+        let warnings = vec![
+            Warning::ByteOrderMark,
+            Warning::Preamble {
+                line: 2,
+                text: ";; export".to_owned(),
+            },
+            Warning::Preamble {
+                line: 3,
+                text: "\u{f6}vrigt".to_owned(),
+            },
+        ];
+
+        // with the byte order mark split across reads
+        let mut reader = Reader::new(io::Read::chain(&data[..1], &data[1..]));
+        assert!(matches!(reader.next(), Some(Ok(Item::Flagga(_)))));
+        assert_eq!(reader.position().unwrap().line, 5);
+        assert_eq!(reader.warnings(), warnings);
+        assert!(matches!(reader.next(), Some(Ok(Item::Konto(_)))));
+        assert!(reader.next().is_none());
+
+        let mut reader = SliceReader::new(data);
+        assert!(matches!(reader.next(), Some(Ok(ItemRef::Flagga(_)))));
+        assert_eq!(reader.warnings(), warnings);
+
+        // not a file at all
+        let data = b"\xef\xbb\xbfHello\nworld\n";
+        let mut reader = Reader::new(&data[..]);
+        assert!(matches!(reader.next(), Some(Err(Error::Parse(e))) if e.line == 1));
+        assert_eq!(reader.warnings(), [Warning::ByteOrderMark]);
+        assert!(matches!(
+            SliceReader::new(data).next(),
+            Some(Err(Error::Parse(e))) if e.line == 1
+        ));
     }
 }
@@ -0,0 +1,96 @@
+//! The checksum of `#KSUMMA`.
+
+/// CRC-32 (IEEE) of the items between the `#KSUMMA` at the start of the
+/// checksummed items and the one with the checksum.
+///
+/// The checksum covers the labels and field contents of the items,
+/// including the first `#KSUMMA` and the braces of object lists, but not
+/// the separators between fields, the quotes around them, line breaks or
+/// the braces of sub-entry blocks.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Checksum(u32);
+
+impl Default for Checksum {
+    fn default() -> Self {
+        Self(!0)
+    }
+}
+
+impl Checksum {
+    /// Add the raw bytes of an item, e.g. a `#VER` with its sub-entries.
+    pub(super) fn update(&mut self, item: &[u8]) {
+        for line in item.split(|&c| c == b'\n' || c == b'\r') {
+            let line = line.trim_ascii();
+            if line == b"{" || line == b"}" {
+                continue;
+            }
+
+            let mut quoted = false;
+            let mut escaped = false;
+            for &c in line {
+                match c {
+                    _ if escaped => {
+                        escaped = false;
+                        // only quotes and backslashes are escaped
+                        if c != b'"' && c != b'\\' {
+                            self.byte(b'\\');
+                        }
+                        self.byte(c);
+                    }
+                    b'\\' if quoted => escaped = true,
+                    b'"' => quoted = !quoted,
+                    b' ' | b'\t' if !quoted => (),
+                    c => self.byte(c),
+                }
+            }
+        }
+    }
+
+    fn byte(&mut self, c: u8) {
+        self.0 ^= u32::from(c);
+        for _ in 0..8 {
+            self.0 = if self.0 & 1 == 1 {
+                (self.0 >> 1) ^ 0xedb8_8320
+            } else {
+                self.0 >> 1
+            };
+        }
+    }
+
+    pub(super) fn value(self) -> u32 {
+        !self.0
+    }
+
+    /// Whether `checksum` is the value, which programs write either as an
+    /// unsigned or as a signed 32-bit integer.
+    pub(super) fn matches(self, checksum: i64) -> bool {
+        let value = self.value();
+        checksum == i64::from(value) || checksum == i64::from(value.cast_signed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum() {
+        let mut checksum = Checksum::default();
+        checksum.update(b"123456789");
+        assert_eq!(checksum.value(), 0xcbf4_3926);
+
+        let mut checksum = Checksum::default();
+        checksum.update(b"#KSUMMA\n");
+        checksum.update(b"#KONTO 1930 \"F\\\"retag\"\r\n");
+        checksum.update(b"#VER A 1 20230101\n{\n  #TRANS 1930 {1 \"Z 1\"} 1.00\n}\n");
+        let mut expected = Checksum::default();
+        for &c in b"#KSUMMA#KONTO1930F\"retag#VERA120230101#TRANS1930{1Z 1}1.00" {
+            expected.byte(c);
+        }
+        assert_eq!(checksum.value(), expected.value());
+
+        assert!(checksum.matches(checksum.value().into()));
+        assert!(checksum.matches(checksum.value().cast_signed().into()));
+        assert!(!checksum.matches(0));
+    }
+}
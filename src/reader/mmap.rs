@@ -0,0 +1,151 @@
+//! Reading memory-mapped files, see [`Mmap`].
+
+use std::{fs::File, io, ops::Deref, path::Path};
+
+use super::{Reader, SliceReader};
+
+/// A file mapped into memory, read-only, with `memmap2`.
+///
+/// Reading the mapping with [`Mmap::items`] borrows the text of the items
+/// from it, so that a large file is neither copied into a buffer nor
+/// into the items. The pages are loaded by the operating system as they
+/// are read, and may be dropped again under memory pressure.
+///
+/// Mapping a file is unsafe, since it must not be modified while it is
+/// mapped, see [`Mmap::new`].
+///
+/// ```no_run
+/// use sie4::{item::ItemRef, reader::Mmap};
+///
+/// // SAFETY: nothing else writes to the export
+/// let map = unsafe { Mmap::open("export.se")? };
+/// let mut accounts = 0;
+/// for item in map.items() {
+///     if let ItemRef::Konto(_) = item? {
+///         accounts += 1;
+///     }
+/// }
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct Mmap(memmap2::Mmap);
+
+impl Mmap {
+    /// Map the file at `path`.
+    ///
+    /// # Safety
+    ///
+    /// See [`Mmap::new`].
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be opened or mapped.
+    pub unsafe fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        // SAFETY: upheld by the caller
+        unsafe { Self::new(&File::open(path)?) }
+    }
+
+    /// Map `file`, which must be open for reading.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this process or any
+    /// other, until the mapping is dropped. The mapping is read as a
+    /// `&[u8]` that the items borrow from, so a change to the file is
+    /// undefined behavior, and reading a page that was truncated away
+    /// kills the process with `SIGBUS` on Unix.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be mapped, or is too large for the address
+    /// space.
+    pub unsafe fn new(file: &File) -> io::Result<Self> {
+        // SAFETY: upheld by the caller
+        unsafe { memmap2::Mmap::map(file) }.map(Self)
+    }
+
+    /// The items of the file, borrowing their text from the mapping (see
+    /// [`SliceReader`]).
+    #[must_use]
+    pub fn items(&self) -> SliceReader<'_> {
+        SliceReader::new(self)
+    }
+}
+
+impl Deref for Mmap {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Mmap {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl Reader<io::Cursor<Mmap>> {
+    /// Read owned items from the memory-mapped file at `path`, e.g. to
+    /// keep them after the mapping is dropped. The file is read from the
+    /// mapping rather than with system calls, but the items are copied;
+    /// use [`Mmap::items`] to borrow them instead.
+    ///
+    /// # Safety
+    ///
+    /// See [`Mmap::new`].
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be opened or mapped, see [`Mmap::open`].
+    pub unsafe fn from_mmap(path: impl AsRef<Path>) -> io::Result<Self> {
+        // SAFETY: upheld by the caller
+        Ok(Self::new(io::Cursor::new(unsafe { Mmap::open(path)? })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::*;
+    use crate::{Document, ItemRef};
+
+    #[test]
+    fn mapped() {
+        let path = std::env::temp_dir().join(format!("sie4-mmap-{}.se", std::process::id()));
+        std::fs::write(
+            &path,
+            b"#FLAGGA 0\n#KONTO 1930 \"Bank\"\n#UB 0 1930 100.00\n",
+        )
+        .unwrap();
+
+        // SAFETY: the file is only written to while it isn't mapped
+        let map = unsafe { Mmap::open(&path) }.unwrap();
+        let items = map.items().collect::<Result<Vec<_>, _>>().unwrap();
+        // borrowed from the mapping
+        assert!(matches!(
+            &items[1],
+            ItemRef::Konto(konto) if matches!(konto.name, Cow::Borrowed("Bank"))
+        ));
+
+        // SAFETY: as above, and the file isn't written to until the reader
+        // is dropped
+        let owned = unsafe { Reader::from_mmap(&path) }
+            .unwrap()
+            .collect::<Result<Document, _>>()
+            .unwrap();
+        assert_eq!(owned, Document::from_slice(&map).unwrap());
+        drop(map);
+
+        std::fs::write(&path, b"").unwrap();
+        // SAFETY: the mapping is dropped before the file is removed
+        assert!(unsafe { Mmap::open(&path) }
+            .unwrap()
+            .items()
+            .next()
+            .is_none());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,339 @@
+//! Reading input that is pushed in chunks, see [`Parser`].
+
+use std::{
+    collections::VecDeque,
+    io::{self, Read},
+    ops::RangeInclusive,
+    sync::Arc,
+};
+
+use time::Date;
+
+use super::{Error, ExtraFieldsPolicy, OrderingPolicy, Reader, Warning};
+use crate::{
+    custom::Registry,
+    item::{AccountNo, Item, Konto, TransLine, Ver},
+};
+
+/// Callbacks for the items read by a [`Parser`]. Every method does
+/// nothing by default.
+pub trait Handler {
+    /// An account (`#KONTO`).
+    fn on_account(&mut self, _konto: &Konto) {}
+
+    /// A voucher, before [`Handler::on_trans`] is called for each of its
+    /// transactions.
+    fn on_verification_start(&mut self, _ver: &Ver) {}
+
+    /// A transaction of the voucher last started, including corrections
+    /// (`#RTRANS` and `#BTRANS`).
+    fn on_trans(&mut self, _line: &TransLine) {}
+
+    /// The voucher last started, after its transactions.
+    fn on_verification_end(&mut self, _ver: &Ver) {}
+
+    /// Every item, after the more specific methods above.
+    fn on_item(&mut self, _item: Item) {}
+}
+
+/// The chunks fed to a [`Parser`], which end when it is finished.
+#[derive(Debug, Default)]
+struct Chunks {
+    buf: VecDeque<u8>,
+    finished: bool,
+}
+
+/// Reading past the chunks fed so far fails with
+/// [`io::ErrorKind::WouldBlock`], which leaves the [`Reader`] where it was.
+impl Read for Chunks {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.buf.is_empty() && !self.finished {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        self.buf.read(buf)
+    }
+}
+
+/// Reads items from input that arrives in chunks, e.g. the parts of a
+/// multipart upload, and calls a [`Handler`] for each of them. Unlike
+/// [`Reader`], which pulls the input from a [`Read`], the input is
+/// pushed with [`Parser::feed`].
+///
+/// Items that span several chunks are buffered until they are complete,
+/// which the last item can't be until [`Parser::finish`].
+///
+/// ```
+/// use sie4::{
+///     item::{AccountNo, Konto, TransLine},
+///     reader::{Handler, Parser},
+/// };
+///
+/// #[derive(Default)]
+/// struct Count {
+///     accounts: Vec<AccountNo>,
+///     transactions: usize,
+/// }
+///
+/// impl Handler for Count {
+///     fn on_account(&mut self, konto: &Konto) {
+///         self.accounts.push(konto.no);
+///     }
+///
+///     fn on_trans(&mut self, _line: &TransLine) {
+///         self.transactions += 1;
+///     }
+/// }
+///
+/// let data = b"#KONTO 1930 \"Bank\"\n#KONTO 5010 \"Lokalhyra\"\n#VER A 1 20230110\n{\n#TRANS 1930 {} -100.00\n#TRANS 5010 {} 100.00\n}\n";
+/// let mut parser = Parser::new();
+/// let mut count = Count::default();
+/// for chunk in data.chunks(7) {
+///     parser.feed(chunk, &mut count).unwrap();
+/// }
+/// parser.finish(&mut count).unwrap();
+///
+/// assert_eq!(count.accounts, [1930, 5010]);
+/// assert_eq!(count.transactions, 2);
+/// ```
+pub struct Parser {
+    reader: Reader<Chunks>,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Parser {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            reader: Reader::new(Chunks::default()),
+        }
+    }
+
+    /// See [`Reader::lenient_sub_entries`].
+    #[must_use]
+    pub fn lenient_sub_entries(mut self, lenient: bool) -> Self {
+        self.reader = self.reader.lenient_sub_entries(lenient);
+        self
+    }
+
+    /// See [`Reader::ordering`].
+    #[must_use]
+    pub fn ordering(mut self, policy: OrderingPolicy) -> Self {
+        self.reader = self.reader.ordering(policy);
+        self
+    }
+
+    /// See [`Reader::extra_fields`].
+    #[must_use]
+    pub fn extra_fields(mut self, policy: ExtraFieldsPolicy) -> Self {
+        self.reader = self.reader.extra_fields(policy);
+        self
+    }
+
+    /// See [`Reader::max_item_size`].
+    #[must_use]
+    pub fn max_item_size(mut self, max: usize) -> Self {
+        self.reader = self.reader.max_item_size(max);
+        self
+    }
+
+    /// See [`Reader::century_pivot`].
+    #[must_use]
+    pub fn century_pivot(mut self, pivot: u8) -> Self {
+        self.reader = self.reader.century_pivot(pivot);
+        self
+    }
+
+    /// See [`Reader::lenient_amounts`].
+    #[must_use]
+    pub fn lenient_amounts(mut self, lenient: bool) -> Self {
+        self.reader = self.reader.lenient_amounts(lenient);
+        self
+    }
+
+    /// See [`Reader::strict`].
+    #[must_use]
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.reader = self.reader.strict(strict);
+        self
+    }
+
+    /// See [`Reader::verify_checksum`].
+    #[must_use]
+    pub fn verify_checksum(mut self, verify: bool) -> Self {
+        self.reader = self.reader.verify_checksum(verify);
+        self
+    }
+
+    /// See [`Reader::date_range`].
+    #[must_use]
+    pub fn date_range(mut self, dates: RangeInclusive<Date>) -> Self {
+        self.reader = self.reader.date_range(dates);
+        self
+    }
+
+    /// See [`Reader::accounts`].
+    #[must_use]
+    pub fn accounts(mut self, accounts: impl IntoIterator<Item = impl Into<AccountNo>>) -> Self {
+        self.reader = self.reader.accounts(accounts);
+        self
+    }
+
+    /// See [`Reader::series`].
+    #[must_use]
+    pub fn series(mut self, series: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.reader = self.reader.series(series);
+        self
+    }
+
+    /// See [`Reader::custom_items`].
+    #[must_use]
+    pub fn custom_items(mut self, registry: Arc<Registry>) -> Self {
+        self.reader = self.reader.custom_items(registry);
+        self
+    }
+
+    /// See [`Reader::warnings`].
+    #[must_use]
+    pub fn warnings(&self) -> &[Warning] {
+        self.reader.warnings()
+    }
+
+    /// See [`Reader::take_warnings`].
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        self.reader.take_warnings()
+    }
+
+    /// Add `chunk` to the input and handle the items that are complete.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error that the [`Reader`] would, after which
+    /// the items that follow it in the chunks fed so far are left to the
+    /// next call, which may be with an empty chunk.
+    ///
+    /// # Panics
+    ///
+    /// If the parser is already [finished](Parser::finish).
+    pub fn feed(&mut self, chunk: &[u8], handler: &mut impl Handler) -> Result<(), Error> {
+        let chunks = &mut self.reader.inner.inner;
+        assert!(!chunks.finished, "fed a finished parser");
+        chunks.buf.extend(chunk);
+
+        self.handle(handler)
+    }
+
+    /// End the input and handle the rest of the items.
+    ///
+    /// # Errors
+    ///
+    /// As [`Parser::feed`], and if the input ends in the middle of an
+    /// item, see [`Error::Truncated`].
+    pub fn finish(&mut self, handler: &mut impl Handler) -> Result<(), Error> {
+        self.reader.inner.inner.finished = true;
+
+        self.handle(handler)
+    }
+
+    fn handle(&mut self, handler: &mut impl Handler) -> Result<(), Error> {
+        loop {
+            let item = match self.reader.next() {
+                Some(Ok(item)) => item,
+                Some(Err(Error::Io(e))) if e.kind() == io::ErrorKind::WouldBlock => {
+                    return Ok(());
+                }
+                Some(Err(e)) => return Err(e),
+                None => return Ok(()),
+            };
+
+            match &item {
+                Item::Konto(konto) => handler.on_account(konto),
+                Item::Ver(ver) => {
+                    handler.on_verification_start(ver);
+                    for line in &ver.transactions.0 {
+                        handler.on_trans(line);
+                    }
+                    handler.on_verification_end(ver);
+                }
+                _ => (),
+            }
+            handler.on_item(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Events(Vec<String>);
+
+    impl Handler for Events {
+        fn on_verification_start(&mut self, ver: &Ver) {
+            self.0.push(format!("start {}", ver.no));
+        }
+
+        fn on_trans(&mut self, line: &TransLine) {
+            self.0.push(format!("trans {}", line.trans().account));
+        }
+
+        fn on_verification_end(&mut self, ver: &Ver) {
+            self.0.push(format!("end {}", ver.no));
+        }
+
+        fn on_item(&mut self, item: Item) {
+            self.0.push(item.label().to_owned());
+        }
+    }
+
+    #[test]
+    fn chunks() {
+        let data = b"#FLAGGA 0\r\n#VER A 1 20230110\r\n{\r\n#TRANS 1930 {} -1.00\r\n#TRANS 5010 {} 1.00\r\n}\r\n#UB 0 1930 1.00";
+
+        for size in [1, 2, 5, data.len()] {
+            let mut parser = Parser::new();
+            let mut events = Events::default();
+            for chunk in data.chunks(size) {
+                parser.feed(chunk, &mut events).unwrap();
+            }
+            // the last item could have more fields
+            assert_eq!(events.0.len(), 6, "{size}");
+            parser.finish(&mut events).unwrap();
+            assert_eq!(
+                events.0,
+                [
+                    "FLAGGA",
+                    "start 1",
+                    "trans 1930",
+                    "trans 5010",
+                    "end 1",
+                    "VER",
+                    "UB"
+                ],
+                "{size}"
+            );
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let mut parser = Parser::new().extra_fields(ExtraFieldsPolicy::Deny);
+        let mut events = Events::default();
+        parser
+            .feed(b"#KONTO 1930 \"Bank\" 1\n#KONTO 2440", &mut events)
+            .unwrap_err();
+        parser.feed(b" \"Skulder\"\n", &mut events).unwrap();
+        parser.feed(b"#VER A 1 20230110\n{\n", &mut events).unwrap();
+        assert_eq!(events.0, ["KONTO"]);
+
+        assert!(matches!(
+            parser.finish(&mut events),
+            Err(Error::Truncated(_))
+        ));
+    }
+}
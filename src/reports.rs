@@ -0,0 +1,226 @@
+//! Reports on how the books were kept, e.g. for internal controls, and
+//! the [statements] of an annual report.
+//!
+//! ```
+//! use sie4::{reports::activity_by_signature, Document};
+//! use time::macros::date;
+//!
+//! let doc = Document::from_reader(&b"#VER A 1 20230110 \"\" 20230111 AK
+//! {
+//!     #TRANS 1930 {} -100.00
+//!     #TRANS 5010 {} 100.00
+//! }
+//! #VER A 2 20230210 \"\" 20230211 XY
+//! {
+//!     #TRANS 1930 {} -200.00
+//!     #TRANS 5010 {} 200.00
+//! }
+//! "[..])
+//! .unwrap();
+//!
+//! let activity = activity_by_signature(&doc, date!(2023 - 01 - 01)..=date!(2023 - 12 - 31));
+//! assert_eq!(activity.rows.len(), 2);
+//! assert_eq!(activity.rows[0].signature.as_deref(), Some("AK"));
+//! assert_eq!(activity.rows[0].transactions, 2);
+//!
+//! let unauthorized = activity.unauthorized(&["AK"]);
+//! assert_eq!(unauthorized.len(), 1);
+//! assert_eq!((unauthorized[0].series.as_str(), unauthorized[0].no), ("A", 2));
+//! ```
+
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+use serde::Serialize;
+use time::Date;
+
+use crate::{item::Period, signatures::Entry, Document};
+
+pub mod statements;
+
+/// What a signature registered in a period.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SignatureActivity {
+    /// `None` for what was registered without a signature.
+    pub signature: Option<String>,
+    pub period: Period,
+    /// The vouchers registered with the signature (`#VER`).
+    pub vouchers: usize,
+    /// The transactions registered with the signature, including
+    /// corrections (`#RTRANS` and `#BTRANS`). A transaction without a
+    /// signature of its own is registered with that of its voucher.
+    pub transactions: usize,
+}
+
+/// A voucher to which a signature registered the voucher itself or any
+/// of its transactions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Registration {
+    pub series: String,
+    pub no: u32,
+    /// The registration date of the voucher, or else its date.
+    pub date: Date,
+    pub signature: Option<String>,
+}
+
+/// See [`activity_by_signature`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Activity {
+    /// By signature and period, with unsigned registrations first.
+    pub rows: Vec<SignatureActivity>,
+    /// In the order of the vouchers, once per voucher and signature.
+    pub registrations: Vec<Registration>,
+}
+
+impl Activity {
+    /// The registrations by signatures that aren't in `allowed`,
+    /// including those without a signature.
+    #[must_use]
+    pub fn unauthorized(&self, allowed: &[&str]) -> Vec<&Registration> {
+        self.registrations
+            .iter()
+            .filter(|reg| {
+                reg.signature
+                    .as_deref()
+                    .is_none_or(|signature| !allowed.contains(&signature))
+            })
+            .collect()
+    }
+}
+
+/// How many vouchers and transactions each signature (`sign` of `#VER`
+/// and `signature` of `#TRANS`) registered per month. Vouchers are
+/// counted by their registration date, or else their date, and only
+/// those registered within `period` are included. The counts are of the
+/// [entries](Document::signatures) of the signatures, and of those
+/// without a signature.
+#[must_use]
+pub fn activity_by_signature(doc: &Document, period: RangeInclusive<Date>) -> Activity {
+    let years = doc.fiscal_years();
+    let mut rows = BTreeMap::<(Option<String>, Period), (usize, usize)>::new();
+    let mut registrations = Vec::new();
+
+    for ver in doc.vouchers() {
+        for (signature, entry) in Entry::of(ver, &years) {
+            if !period.contains(&entry.registered) {
+                continue;
+            }
+            let month = Period {
+                year: entry.registered.year(),
+                month: entry.registered.month(),
+            };
+
+            let row = rows.entry((signature.clone(), month)).or_default();
+            row.0 += usize::from(entry.voucher);
+            row.1 += entry.transactions.len();
+            registrations.push(Registration {
+                series: ver.series.clone(),
+                no: ver.no,
+                date: entry.registered,
+                signature,
+            });
+        }
+    }
+
+    Activity {
+        rows: rows
+            .into_iter()
+            .map(
+                |((signature, period), (vouchers, transactions))| SignatureActivity {
+                    signature,
+                    period,
+                    vouchers,
+                    transactions,
+                },
+            )
+            .collect(),
+        registrations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{macros::date, Month};
+
+    use super::*;
+
+    #[test]
+    fn corrections_and_periods() {
+        let doc = Document::from_reader(
+            &b"#VER A 1 20230110 \"\" 20230111 AK
+{
+    #TRANS 1930 {} -100.00
+    #TRANS 5010 {} 100.00
+    #RTRANS 5011 {} 100.00 20230301 \"\" 1 XY
+    #BTRANS 5010 {} 100.00 20230301 \"\" 1 XY
+}
+#VER A 2 20230201 \"\" 20230301
+{
+    #TRANS 1930 {} -200.00
+    #TRANS 5010 {} 200.00
+}
+#VER A 3 20230131
+{
+    #TRANS 1930 {} -200.00 \"\" \"\" 1 AK
+    #TRANS 5010 {} 200.00 \"\" \"\" 1 AK
+}
+#VER A 4 20240101 \"\" 20240101 ZZ
+{
+}
+"[..],
+        )
+        .unwrap();
+
+        let activity = activity_by_signature(&doc, date!(2023 - 01 - 01)..=date!(2023 - 12 - 31));
+        let rows = activity
+            .rows
+            .iter()
+            .map(|row| {
+                (
+                    row.signature.as_deref(),
+                    row.period.month,
+                    row.vouchers,
+                    row.transactions,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [
+                (None, Month::January, 1, 0),
+                (None, Month::March, 1, 2),
+                (Some("AK"), Month::January, 1, 4),
+                (Some("XY"), Month::January, 0, 2),
+            ]
+        );
+
+        let unauthorized = activity
+            .unauthorized(&["AK"])
+            .into_iter()
+            .map(|reg| (reg.no, reg.signature.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(unauthorized, [(1, Some("XY")), (2, None), (3, None)]);
+        assert_eq!(activity.unauthorized(&["AK", "XY"]).len(), 2);
+
+        // the same entries as `sie4 audit --by-user`
+        for (signature, entries) in doc.signatures() {
+            let (vouchers, transactions) = entries
+                .iter()
+                .filter(|entry| entry.registered.year() == 2023)
+                .fold((0, 0), |(v, t), entry| {
+                    (v + usize::from(entry.voucher), t + entry.transactions.len())
+                });
+            let rows = activity
+                .rows
+                .iter()
+                .filter(|row| row.signature.as_deref() == Some(signature.as_str()));
+            assert_eq!(
+                rows.clone().map(|row| row.vouchers).sum::<usize>(),
+                vouchers
+            );
+            assert_eq!(
+                rows.map(|row| row.transactions).sum::<usize>(),
+                transactions
+            );
+        }
+    }
+}
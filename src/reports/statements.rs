@@ -0,0 +1,526 @@
+//! Income statements and balance sheets, with the accounts mapped to the
+//! lines of an annual report.
+//!
+//! ```
+//! use rust_decimal_macros::dec;
+//! use sie4::{
+//!     reports::statements::{statements, Mapping},
+//!     Document,
+//! };
+//!
+//! let doc = Document::from_reader(&b"#UB 0 1930 300.00
+//! #UB 0 2081 -100.00
+//! #UB 0 2440 -200.00
+//! #RES 0 3010 -1000.00
+//! #RES 0 3041 -500.00
+//! #RES 0 5010 1200.00
+//! "[..])
+//! .unwrap();
+//!
+//! let statements = statements(&doc, &Mapping::bas(), 0);
+//! assert_eq!(statements.income[0].name, "Nettoomsättning");
+//! assert_eq!(statements.income[0].amount, dec!(-1500.00));
+//! assert_eq!(statements.result(), dec!(-300.00));
+//! assert_eq!(statements.balance.len(), 3);
+//! ```
+//!
+//! Amounts are signed as in the file, so income, equity and liabilities
+//! are negative. The balance sheet is made of the closing balances
+//! (`#UB`), which only include the result of the year once it has been
+//! booked to equity.
+
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::{
+    item::{AccountKind, AccountNo, Res, TransLine, Ub},
+    Document,
+};
+
+/// The statement that a line is part of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Statement {
+    /// Resultaträkning, of the results (`#RES`).
+    Income,
+    /// Balansräkning, of the closing balances (`#UB`).
+    Balance,
+}
+
+/// A line of a statement and the accounts that it is the sum of.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LineRule {
+    pub name: String,
+    pub statement: Statement,
+    pub accounts: Vec<RangeInclusive<u32>>,
+}
+
+impl LineRule {
+    fn new(name: &str, statement: Statement, accounts: &[RangeInclusive<u32>]) -> Self {
+        Self {
+            name: name.to_owned(),
+            statement,
+            accounts: accounts.to_vec(),
+        }
+    }
+
+    #[must_use]
+    pub fn contains(&self, account: impl Into<AccountNo>) -> bool {
+        let account = account.into();
+        self.accounts.iter().any(|range| account.is_in(range))
+    }
+}
+
+/// The lines of the statements, in the order that they are presented.
+/// An account belongs to the first line of its statement that contains
+/// it, so that a line for a few accounts may precede one for the rest
+/// of their class.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Mapping {
+    lines: Vec<LineRule>,
+}
+
+impl Mapping {
+    #[must_use]
+    pub fn new(lines: Vec<LineRule>) -> Self {
+        Self { lines }
+    }
+
+    /// The lines of an annual report after K2, with the accounts of the
+    /// BAS chart of accounts. The accounts of the year's result (8990 to
+    /// 8999) are left out, since the result is the sum of the income
+    /// statement.
+    #[must_use]
+    pub fn bas() -> Self {
+        use Statement::{Balance, Income};
+
+        Self::new(vec![
+            LineRule::new("Nettoomsättning", Income, &[3000..=3799]),
+            LineRule::new("Övriga rörelseintäkter", Income, &[3800..=3999]),
+            LineRule::new("Råvaror och förnödenheter", Income, &[4000..=4999]),
+            LineRule::new("Övriga externa kostnader", Income, &[5000..=6999]),
+            LineRule::new("Personalkostnader", Income, &[7000..=7699]),
+            LineRule::new(
+                "Av- och nedskrivningar av materiella och immateriella anläggningstillgångar",
+                Income,
+                &[7700..=7899],
+            ),
+            LineRule::new("Övriga rörelsekostnader", Income, &[7900..=7999]),
+            LineRule::new(
+                "Resultat från andelar i koncernföretag",
+                Income,
+                &[8000..=8099],
+            ),
+            LineRule::new(
+                "Resultat från andelar i intresseföretag",
+                Income,
+                &[8100..=8199],
+            ),
+            LineRule::new(
+                "Resultat från övriga finansiella anläggningstillgångar",
+                Income,
+                &[8200..=8299],
+            ),
+            LineRule::new(
+                "Övriga ränteintäkter och liknande resultatposter",
+                Income,
+                &[8300..=8399],
+            ),
+            LineRule::new(
+                "Räntekostnader och liknande resultatposter",
+                Income,
+                &[8400..=8499],
+            ),
+            LineRule::new("Bokslutsdispositioner", Income, &[8800..=8899]),
+            LineRule::new("Skatt på årets resultat", Income, &[8900..=8989]),
+            LineRule::new(
+                "Immateriella anläggningstillgångar",
+                Balance,
+                &[1000..=1099],
+            ),
+            LineRule::new("Byggnader och mark", Balance, &[1100..=1199]),
+            LineRule::new("Maskiner och inventarier", Balance, &[1200..=1299]),
+            LineRule::new("Finansiella anläggningstillgångar", Balance, &[1300..=1399]),
+            LineRule::new("Varulager m.m.", Balance, &[1400..=1499]),
+            LineRule::new("Kundfordringar", Balance, &[1500..=1599]),
+            LineRule::new("Övriga fordringar", Balance, &[1600..=1699]),
+            LineRule::new(
+                "Förutbetalda kostnader och upplupna intäkter",
+                Balance,
+                &[1700..=1799],
+            ),
+            LineRule::new("Kortfristiga placeringar", Balance, &[1800..=1899]),
+            LineRule::new("Kassa och bank", Balance, &[1900..=1999]),
+            LineRule::new("Eget kapital", Balance, &[2000..=2099]),
+            LineRule::new("Obeskattade reserver", Balance, &[2100..=2199]),
+            LineRule::new("Avsättningar", Balance, &[2200..=2299]),
+            LineRule::new("Långfristiga skulder", Balance, &[2300..=2399]),
+            LineRule::new("Leverantörsskulder", Balance, &[2440..=2449]),
+            LineRule::new("Skatteskulder", Balance, &[2500..=2599]),
+            LineRule::new(
+                "Övriga kortfristiga skulder",
+                Balance,
+                &[2400..=2439, 2450..=2499, 2600..=2899],
+            ),
+            LineRule::new(
+                "Upplupna kostnader och förutbetalda intäkter",
+                Balance,
+                &[2900..=2999],
+            ),
+        ])
+    }
+
+    /// Rules written in TOML as an array of tables, one per line:
+    ///
+    /// ```
+    /// use sie4::reports::statements::{Mapping, Statement};
+    ///
+    /// let mapping = Mapping::from_toml(
+    ///     r#"
+    /// [[line]]
+    /// name = "Intäkter"
+    /// statement = "income"
+    /// accounts = ["3000-3999", 8310]
+    ///
+    /// [[line]]
+    /// name = "Kostnader"
+    /// statement = "income"
+    /// accounts = ["4000-8999"]
+    /// "#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(mapping.line_of(Statement::Income, 8310).unwrap().name, "Intäkter");
+    /// assert_eq!(mapping.line_of(Statement::Income, 8410).unwrap().name, "Kostnader");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text isn't TOML or a rule is invalid.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(s: &str) -> Result<Self, RulesError> {
+        let doc = s.parse::<toml_edit::Document<String>>()?;
+        let Some(lines) = doc.get("line") else {
+            return Ok(Self::new(Vec::new()));
+        };
+        let lines = lines.as_array_of_tables().ok_or(RulesError::Invalid {
+            rule: 0,
+            message: "`line` is not an array of tables",
+        })?;
+
+        lines
+            .iter()
+            .enumerate()
+            .map(|(i, table)| {
+                let invalid = |message| RulesError::Invalid {
+                    rule: i + 1,
+                    message,
+                };
+                let name = table
+                    .get("name")
+                    .and_then(toml_edit::Item::as_str)
+                    .ok_or(invalid("`name` is not a string"))?;
+                let statement = match table.get("statement").and_then(toml_edit::Item::as_str) {
+                    Some("income") => Statement::Income,
+                    Some("balance") => Statement::Balance,
+                    _ => return Err(invalid("`statement` is neither \"income\" nor \"balance\"")),
+                };
+                let accounts = table
+                    .get("accounts")
+                    .and_then(toml_edit::Item::as_array)
+                    .ok_or(invalid("`accounts` is not an array"))?
+                    .iter()
+                    .map(|value| account_range(value).ok_or(invalid("invalid account range")))
+                    .collect::<Result<_, _>>()?;
+
+                Ok(LineRule {
+                    name: name.to_owned(),
+                    statement,
+                    accounts,
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map(Self::new)
+    }
+
+    #[must_use]
+    pub fn lines(&self) -> &[LineRule] {
+        &self.lines
+    }
+
+    /// The line of `statement` that `account` belongs to.
+    #[must_use]
+    pub fn line_of(
+        &self,
+        statement: Statement,
+        account: impl Into<AccountNo>,
+    ) -> Option<&LineRule> {
+        let account = account.into();
+        self.lines
+            .iter()
+            .find(|line| line.statement == statement && line.contains(account))
+    }
+}
+
+/// See [`Mapping::bas`].
+impl Default for Mapping {
+    fn default() -> Self {
+        Self::bas()
+    }
+}
+
+/// An account (`3010`) or a range of them (`"3000-3799"`).
+#[cfg(feature = "toml")]
+fn account_range(value: &toml_edit::Value) -> Option<RangeInclusive<u32>> {
+    if let Some(account) = value.as_integer() {
+        let account = u32::try_from(account).ok()?;
+        return Some(account..=account);
+    }
+
+    let (start, end) = value.as_str()?.split_once('-')?;
+    let (start, end) = (start.trim().parse().ok()?, end.trim().parse().ok()?);
+    (start <= end).then_some(start..=end)
+}
+
+/// Why rules couldn't be read, see [`Mapping::from_toml`].
+#[cfg(feature = "toml")]
+#[derive(Debug, thiserror::Error)]
+pub enum RulesError {
+    #[error(transparent)]
+    Toml(#[from] toml_edit::TomlError),
+    /// The rule of the `rule`th line, counted from 1, is invalid.
+    #[error("rule {rule}: {message}")]
+    Invalid { rule: usize, message: &'static str },
+}
+
+/// A line of a statement with the accounts that it is the sum of.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StatementLine {
+    pub name: String,
+    pub amount: Decimal,
+    /// By number.
+    pub accounts: Vec<(AccountNo, Decimal)>,
+}
+
+/// See [`statements`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Statements {
+    pub income: Vec<StatementLine>,
+    pub balance: Vec<StatementLine>,
+    /// The accounts with a result or balance that no line of its
+    /// statement contains, by number.
+    pub unmapped: Vec<(AccountNo, Statement, Decimal)>,
+}
+
+impl Statements {
+    /// The sum of the income statement, which is negative for a profit.
+    #[must_use]
+    pub fn result(&self) -> Decimal {
+        self.income.iter().map(|line| line.amount).sum()
+    }
+}
+
+/// The income statement and balance sheet of the fiscal year `year`,
+/// where `0` is the current one. Lines without any accounts are left
+/// out.
+///
+/// The statements are of the results and closing balances of the year
+/// (`#RES` and `#UB`), which the removed transactions (`#BTRANS`) of the
+/// vouchers aren't part of. If they are
+/// [included](Document::include_removed), those dated within the year,
+/// which must be declared with `#RAR`, are added to the accounts as well.
+#[must_use]
+pub fn statements(doc: &Document, mapping: &Mapping, year: i32) -> Statements {
+    let mut accounts = BTreeMap::<(Statement, AccountNo), Decimal>::new();
+    if let Some(year) = doc
+        .include_removed()
+        .then(|| doc.fiscal_years().get(year).copied())
+        .flatten()
+    {
+        let kinds = doc.account_kinds();
+        for ver in doc.vouchers() {
+            for trans in ver.transactions.0.iter().filter_map(|line| match line {
+                TransLine::Removed(trans) => Some(trans),
+                _ => None,
+            }) {
+                if !year.contains(doc.date_policy().date(ver, trans)) {
+                    continue;
+                }
+                let balance = kinds
+                    .get(trans.account)
+                    .is_some_and(AccountKind::is_balance);
+                let statement = if balance {
+                    Statement::Balance
+                } else {
+                    Statement::Income
+                };
+                *accounts.entry((statement, trans.account)).or_default() += trans.amount.0;
+            }
+        }
+    }
+    for res in doc.items_of::<Res>().filter(|res| res.year == year) {
+        *accounts
+            .entry((Statement::Income, res.account))
+            .or_default() += res.balance;
+    }
+    for ub in doc.items_of::<Ub>().filter(|ub| ub.year == year) {
+        *accounts
+            .entry((Statement::Balance, ub.account))
+            .or_default() += ub.balance;
+    }
+
+    let mut lines = vec![Vec::new(); mapping.lines.len()];
+    let mut statements = Statements::default();
+    for ((statement, account), amount) in accounts {
+        match mapping
+            .lines
+            .iter()
+            .position(|line| line.statement == statement && line.contains(account))
+        {
+            Some(i) => lines[i].push((account, amount)),
+            None => statements.unmapped.push((account, statement, amount)),
+        }
+    }
+
+    for (rule, accounts) in mapping.lines.iter().zip(lines) {
+        if accounts.is_empty() {
+            continue;
+        }
+        let line = StatementLine {
+            name: rule.name.clone(),
+            amount: accounts.iter().map(|(_, amount)| amount).sum(),
+            accounts,
+        };
+        match rule.statement {
+            Statement::Income => statements.income.push(line),
+            Statement::Balance => statements.balance.push(line),
+        }
+    }
+
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn bas() {
+        fn lines(lines: &[StatementLine]) -> Vec<(&str, Decimal)> {
+            lines
+                .iter()
+                .map(|line| (line.name.as_str(), line.amount))
+                .collect()
+        }
+
+        let doc = Document::from_reader(
+            &b"#UB 0 1930 300.00
+#UB 0 2440 -200.00
+#UB 0 2610 -25.00
+#UB 0 2710 -75.00
+#UB -1 1930 100.00
+#RES 0 3010 -1000.00
+#RES 0 7010 600.00
+#RES 0 8999 400.00
+"[..],
+        )
+        .unwrap();
+
+        let statements = statements(&doc, &Mapping::default(), 0);
+        assert_eq!(
+            lines(&statements.income),
+            [
+                ("Nettoomsättning", dec!(-1000.00)),
+                ("Personalkostnader", dec!(600.00))
+            ]
+        );
+        assert_eq!(
+            lines(&statements.balance),
+            [
+                ("Kassa och bank", dec!(300.00)),
+                ("Leverantörsskulder", dec!(-200.00)),
+                ("Övriga kortfristiga skulder", dec!(-100.00)),
+            ]
+        );
+        assert_eq!(
+            statements.balance[2].accounts,
+            [(2610.into(), dec!(-25.00)), (2710.into(), dec!(-75.00))]
+        );
+        assert_eq!(
+            statements.unmapped,
+            [(8999.into(), Statement::Income, dec!(400.00))]
+        );
+        assert_eq!(statements.result(), dec!(-400.00));
+    }
+
+    #[test]
+    fn removed() {
+        let mut doc = Document::from_reader(
+            &b"#RAR 0 20230101 20231231
+#UB 0 1930 300.00
+#RES 0 3010 -300.00
+#VER A 1 20230110
+{
+    #TRANS 1930 {} 300.00
+    #TRANS 3010 {} -300.00
+    #BTRANS 1930 {} 50.00
+    #BTRANS 3010 {} -50.00
+}
+"[..],
+        )
+        .unwrap();
+        let amounts = |doc: &Document| {
+            let statements = statements(doc, &Mapping::default(), 0);
+            (statements.balance[0].amount, statements.result())
+        };
+
+        assert_eq!(amounts(&doc), (dec!(300.00), dec!(-300.00)));
+        doc.set_include_removed(true);
+        assert_eq!(amounts(&doc), (dec!(350.00), dec!(-350.00)));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml() {
+        let mapping = Mapping::from_toml(
+            r#"
+[[line]]
+name = "Tillgångar"
+statement = "balance"
+accounts = ["1000 - 1999"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            mapping.lines(),
+            [LineRule::new(
+                "Tillgångar",
+                Statement::Balance,
+                &[1000..=1999]
+            )]
+        );
+        assert_eq!(Mapping::from_toml("").unwrap().lines(), []);
+
+        for (rules, rule) in [
+            ("line = 1", 0),
+            ("[[line]]\nstatement = \"income\"\naccounts = []", 1),
+            ("[[line]]\nname = \"\"\nstatement = \"cash\"\naccounts = []", 1),
+            (
+                "[[line]]\nname = \"\"\nstatement = \"income\"\naccounts = []\n[[line]]\nname = \"\"\nstatement = \"income\"\naccounts = [\"3999-3000\"]",
+                2,
+            ),
+        ] {
+            assert!(
+                matches!(Mapping::from_toml(rules), Err(RulesError::Invalid { rule: r, .. }) if r == rule),
+                "{rules}"
+            );
+        }
+        assert!(matches!(
+            Mapping::from_toml("[[line]"),
+            Err(RulesError::Toml(_))
+        ));
+    }
+}
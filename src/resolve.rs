@@ -0,0 +1,262 @@
+//! Verifications with the references of their transactions resolved.
+//!
+//! ```
+//! use sie4::Reader;
+//!
+//! let data = b"#KONTO 1930 \"Bank\"
+//! #DIM 1 \"Kostnadsst\x84lle\"
+//! #OBJEKT 1 \"Z1\" \"Stockholm\"
+//! #VER A 1 20230102 \"Rent\"
+//! {
+//!     #TRANS 1930 {} -12000.00
+//!     #TRANS 5010 {1 \"Z1\"} 12000.00 20230101
+//! }
+//! ";
+//!
+//! let ver = Reader::new(&data[..]).verifications().next().unwrap().unwrap();
+//! let [bank, rent] = &ver.transactions[..] else { panic!() };
+//! assert_eq!(bank.account_name.as_deref(), Some("Bank"));
+//! assert_eq!(bank.date.to_string(), "2023-01-02");
+//! assert_eq!(rent.account_name, None);
+//! assert_eq!(rent.date.to_string(), "2023-01-01");
+//! assert_eq!(rent.objects[0].name.as_deref(), Some("Stockholm"));
+//! ```
+
+use std::{collections::HashMap, io::Read};
+
+use iso_currency::Currency;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use time::Date;
+
+use crate::{
+    document::DatePolicy,
+    item::{AccountNo, Trans, Ver},
+    reader::Error,
+    Item, Reader,
+};
+
+/// A verification whose transactions are resolved against the
+/// declarations read before it. Only the transactions after corrections
+/// are included (see [`Ver::corrected`]), unless removed ones are
+/// [included](Verifications::include_removed) as well.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResolvedVer {
+    pub series: String,
+    pub no: u32,
+    pub date: Date,
+    pub text: Option<String>,
+    pub reg_date: Option<Date>,
+    pub sign: Option<String>,
+    pub transactions: Vec<ResolvedTrans>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResolvedTrans {
+    pub account: AccountNo,
+    /// Name of the account, if it has been declared with `#KONTO`.
+    pub account_name: Option<String>,
+    pub objects: Vec<ResolvedObject>,
+    pub amount: Decimal,
+    /// Currency of the amount, i.e. of the books (`#VALUTA`).
+    pub currency: Currency,
+    /// Date of the transaction, or else of the verification, unless
+    /// another [`Verifications::date_policy`] is used.
+    pub date: Date,
+    pub text: Option<String>,
+    pub quantity: Option<Decimal>,
+    pub signature: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResolvedObject {
+    pub dimension: u32,
+    /// Name of the dimension, if it has been declared with `#DIM` or
+    /// `#UNDERDIM`.
+    pub dimension_name: Option<String>,
+    pub no: String,
+    /// Name of the object, if it has been declared with `#OBJEKT`.
+    pub name: Option<String>,
+}
+
+/// Iterator returned by [`Reader::verifications`].
+pub struct Verifications<R: Read> {
+    reader: Reader<R>,
+    accounts: HashMap<AccountNo, String>,
+    dimensions: HashMap<u32, String>,
+    objects: HashMap<(u32, String), String>,
+    currency: Currency,
+    dates: DatePolicy,
+    removed: bool,
+}
+
+impl<R: Read> Verifications<R> {
+    pub(crate) fn new(reader: Reader<R>) -> Self {
+        Self {
+            reader,
+            accounts: HashMap::new(),
+            dimensions: HashMap::new(),
+            objects: HashMap::new(),
+            currency: Currency::SEK,
+            dates: DatePolicy::default(),
+            removed: false,
+        }
+    }
+
+    /// How [`ResolvedTrans::date`] is chosen, see [`DatePolicy`].
+    #[must_use]
+    pub fn date_policy(mut self, policy: DatePolicy) -> Self {
+        self.dates = policy;
+        self
+    }
+
+    /// Whether to resolve the removed transactions (`#BTRANS`) as well,
+    /// see [`Document::include_removed`](crate::Document::include_removed).
+    #[must_use]
+    pub fn include_removed(mut self, include: bool) -> Self {
+        self.removed = include;
+        self
+    }
+
+    fn resolve(&self, ver: Ver) -> ResolvedVer {
+        let transactions = ver
+            .counted(self.removed)
+            .map(|trans| self.resolve_trans(trans, self.dates.date(&ver, trans)))
+            .collect();
+
+        ResolvedVer {
+            series: ver.series,
+            no: ver.no,
+            date: ver.date,
+            text: ver.text,
+            reg_date: ver.reg_date,
+            sign: ver.sign,
+            transactions,
+        }
+    }
+
+    fn resolve_trans(&self, trans: &Trans, date: Date) -> ResolvedTrans {
+        let objects = trans
+            .objects
+            .0
+            .iter()
+            .map(|(dimension, no)| ResolvedObject {
+                dimension: *dimension,
+                dimension_name: self.dimensions.get(dimension).cloned(),
+                name: self.objects.get(&(*dimension, no.clone())).cloned(),
+                no: no.clone(),
+            })
+            .collect();
+
+        ResolvedTrans {
+            account: trans.account,
+            account_name: self.accounts.get(&trans.account).cloned(),
+            objects,
+            amount: trans.amount.0,
+            currency: self.currency,
+            date,
+            text: trans.text.clone(),
+            quantity: trans.quantity,
+            signature: trans.signature.clone(),
+        }
+    }
+}
+
+impl<R: Read> Iterator for Verifications<R> {
+    type Item = Result<ResolvedVer, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.reader.next()? {
+                Ok(Item::Konto(konto)) => {
+                    self.accounts.insert(konto.no, konto.name);
+                }
+                Ok(Item::Dim(dim)) => {
+                    self.dimensions.insert(dim.no, dim.name);
+                }
+                Ok(Item::UnderDim(dim)) => {
+                    self.dimensions.insert(dim.no, dim.name);
+                }
+                Ok(Item::Objekt(objekt)) => {
+                    self.objects
+                        .insert((objekt.dimension, objekt.no), objekt.name);
+                }
+                Ok(Item::Valuta(valuta)) => self.currency = valuta.currency,
+                Ok(Item::Ver(ver)) => return Some(Ok(self.resolve(ver))),
+                Ok(_) => (),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::reader::OrderingPolicy;
+
+    #[test]
+    fn corrections_and_declarations() {
+        let data = b"#VER A 1 20230102
+{
+    #TRANS 1930 {} -1.00
+    #TRANS 1510 {} 1.00
+}
+#KONTO 1930 \"Bank\"
+#UNDERDIM 2 \"Kostnadsb\x84rare\" 1
+#VER A 2 20230103
+{
+    #TRANS 1930 {2 B1 6 P1} -2.00
+    #BTRANS 5010 {} 2.00
+    #RTRANS 5011 {} 2.00
+    #TRANS 5011 {} 2.00
+}
+";
+        let vers = Reader::new(&data[..])
+            .ordering(OrderingPolicy::Ignore)
+            .verifications()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        // accounts are only known once they have been declared
+        assert_eq!(vers[0].transactions[0].account_name, None);
+
+        let accounts = vers[1]
+            .transactions
+            .iter()
+            .map(|trans| trans.account)
+            .collect::<Vec<_>>();
+        assert_eq!(accounts, [1930, 5011]);
+
+        let vers = Reader::new(&data[..])
+            .ordering(OrderingPolicy::Ignore)
+            .verifications()
+            .include_removed(true)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let accounts = vers[1]
+            .transactions
+            .iter()
+            .map(|trans| trans.account)
+            .collect::<Vec<_>>();
+        assert_eq!(accounts, [1930, 5010, 5011]);
+        assert_eq!(
+            vers[1].transactions[0].objects,
+            [
+                ResolvedObject {
+                    dimension: 2,
+                    dimension_name: Some("Kostnadsbärare".to_owned()),
+                    no: "B1".to_owned(),
+                    name: None,
+                },
+                ResolvedObject {
+                    dimension: 6,
+                    dimension_name: None,
+                    no: "P1".to_owned(),
+                    name: None,
+                },
+            ]
+        );
+    }
+}
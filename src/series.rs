@@ -0,0 +1,432 @@
+//! The numbering of the vouchers in each series, which is to be
+//! consecutive: a gap or a number used twice is one of the first things
+//! an auditor looks for.
+//!
+//! ```
+//! use sie4::{series::NumberingIssue, Document};
+//!
+//! let doc = Document::from_reader(&b"#VER A 1 20230101
+//! {
+//! }
+//! #VER A 4 20230102
+//! {
+//! }
+//! #VER A 4 20230103
+//! {
+//! }
+//! "[..])
+//! .unwrap();
+//!
+//! assert_eq!(
+//!     doc.numbering_issues(),
+//!     [
+//!         NumberingIssue::Gap {
+//!             fiscal_year: 2023,
+//!             series: "A".to_owned(),
+//!             first: 2,
+//!             last: 3
+//!         },
+//!         NumberingIssue::Duplicate {
+//!             fiscal_year: 2023,
+//!             series: "A".to_owned(),
+//!             no: 4,
+//!             count: 2
+//!         },
+//!     ]
+//! );
+//! ```
+//!
+//! Each fiscal year has series of its own, since the same series and
+//! numbers are used again in the next one. For the same reason, a
+//! voucher is referred to across files by its [`VerId`].
+
+use std::{cmp::Ordering, collections::BTreeMap, fmt, str::FromStr};
+
+use serde::Serialize;
+
+use crate::{fiscal::FiscalYears, item::Ver, Document};
+
+/// Identifies a voucher by its series and number within the fiscal year
+/// that it belongs to, see [`Document::ver_id`].
+///
+/// Unlike the relative year numbers of `#RAR`, the fiscal year is the
+/// calendar year that it starts in, so the identifier of a voucher is
+/// the same in every file that includes it. Identifiers are ordered by
+/// fiscal year, series and number, and written as
+/// `{fiscal_year}/{series}/{no}`:
+///
+/// ```
+/// use sie4::series::VerId;
+///
+/// let id = VerId {
+///     series: "A".to_owned(),
+///     no: 12,
+///     fiscal_year: 2023,
+/// };
+/// assert_eq!(id.to_string(), "2023/A/12");
+/// assert_eq!("2023/A/12".parse(), Ok(id));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct VerId {
+    pub series: String,
+    pub no: u32,
+    /// The calendar year in which the fiscal year starts.
+    pub fiscal_year: i32,
+}
+
+impl VerId {
+    pub(crate) fn new(ver: &Ver, years: &FiscalYears) -> Self {
+        Self {
+            series: ver.series.clone(),
+            no: ver.no,
+            fiscal_year: years
+                .containing(ver.date)
+                .map_or(ver.date.year(), |year| year.start.year()),
+        }
+    }
+}
+
+impl Ord for VerId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.fiscal_year, &self.series, self.no).cmp(&(other.fiscal_year, &other.series, other.no))
+    }
+}
+
+impl PartialOrd for VerId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for VerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.fiscal_year, self.series, self.no)
+    }
+}
+
+/// The string was not a `{fiscal_year}/{series}/{no}` [`VerId`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid voucher identifier `{0}`")]
+pub struct InvalidVerId(String);
+
+impl FromStr for VerId {
+    type Err = InvalidVerId;
+
+    /// The series may itself contain `/`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidVerId(s.to_owned());
+        let (fiscal_year, rest) = s.split_once('/').ok_or_else(invalid)?;
+        let (series, no) = rest.rsplit_once('/').ok_or_else(invalid)?;
+
+        Ok(Self {
+            series: series.to_owned(),
+            no: no.parse().map_err(|_| invalid())?,
+            fiscal_year: fiscal_year.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// The vouchers of a series (`#VER`) in a fiscal year, by number.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Series {
+    /// See [`VerId::fiscal_year`].
+    pub fiscal_year: i32,
+    pub name: String,
+    /// How many vouchers have each number.
+    pub numbers: BTreeMap<u32, usize>,
+}
+
+impl Series {
+    /// The lowest number, if the series has any vouchers.
+    #[must_use]
+    pub fn first(&self) -> Option<u32> {
+        self.numbers.keys().next().copied()
+    }
+
+    /// The highest number, if the series has any vouchers.
+    #[must_use]
+    pub fn last(&self) -> Option<u32> {
+        self.numbers.keys().next_back().copied()
+    }
+
+    /// The first and last number of each run of numbers that are missing
+    /// between [`Series::first`] and [`Series::last`]. The numbers
+    /// before the first are not missing, since a series may continue
+    /// from an earlier file.
+    #[must_use]
+    pub fn gaps(&self) -> Vec<(u32, u32)> {
+        self.numbers
+            .keys()
+            .zip(self.numbers.keys().skip(1))
+            .filter(|(a, b)| *b - *a > 1)
+            .map(|(a, b)| (a + 1, b - 1))
+            .collect()
+    }
+
+    /// The numbers of more than one voucher, with how many.
+    #[must_use]
+    pub fn duplicates(&self) -> Vec<(u32, usize)> {
+        self.numbers
+            .iter()
+            .filter(|(_, count)| **count > 1)
+            .map(|(no, count)| (*no, *count))
+            .collect()
+    }
+}
+
+/// Something wrong with the numbering of a series, see
+/// [`Document::numbering_issues`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NumberingIssue {
+    /// The numbers `first..=last` are missing, see [`Series::gaps`].
+    Gap {
+        fiscal_year: i32,
+        series: String,
+        first: u32,
+        last: u32,
+    },
+    /// `count` vouchers have the number `no`.
+    Duplicate {
+        fiscal_year: i32,
+        series: String,
+        no: u32,
+        count: usize,
+    },
+}
+
+impl fmt::Display for NumberingIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gap {
+                fiscal_year,
+                series,
+                first,
+                last,
+            } if first == last => write!(f, "{series} {first} is missing in {fiscal_year}"),
+            Self::Gap {
+                fiscal_year,
+                series,
+                first,
+                last,
+            } => write!(f, "{series} {first}-{last} are missing in {fiscal_year}"),
+            Self::Duplicate {
+                fiscal_year,
+                series,
+                no,
+                count,
+            } => {
+                write!(
+                    f,
+                    "{series} {no} is used by {count} vouchers in {fiscal_year}"
+                )
+            }
+        }
+    }
+}
+
+impl Document {
+    /// The stable identifier of `ver`. The fiscal year is the one of the
+    /// `#RAR` that contains the voucher date, or the year of the date if
+    /// the file declares no such year.
+    #[must_use]
+    pub fn ver_id(&self, ver: &Ver) -> VerId {
+        VerId::new(ver, &self.fiscal_years())
+    }
+
+    /// The voucher with the identifier `id`.
+    #[must_use]
+    pub fn voucher(&self, id: &VerId) -> Option<&Ver> {
+        let years = self.fiscal_years();
+        self.vouchers().find(|ver| {
+            ver.series == id.series && ver.no == id.no && VerId::new(ver, &years) == *id
+        })
+    }
+
+    /// Every voucher by its identifier. Vouchers that share an
+    /// identifier, which are [numbering issues](Document::numbering_issues),
+    /// are listed in the order of the document.
+    #[must_use]
+    pub fn vouchers_by_id(&self) -> BTreeMap<VerId, Vec<&Ver>> {
+        let years = self.fiscal_years();
+        let mut vouchers = BTreeMap::<_, Vec<_>>::new();
+        for ver in self.vouchers() {
+            vouchers
+                .entry(VerId::new(ver, &years))
+                .or_default()
+                .push(ver);
+        }
+        vouchers
+    }
+
+    /// The series of the vouchers, by fiscal year (see
+    /// [`Document::ver_id`]) and name.
+    #[must_use]
+    pub fn series(&self) -> Vec<Series> {
+        let years = self.fiscal_years();
+        let mut series = BTreeMap::<(i32, &str), BTreeMap<u32, usize>>::new();
+        for ver in self.vouchers() {
+            let fiscal_year = VerId::new(ver, &years).fiscal_year;
+            *series
+                .entry((fiscal_year, &ver.series))
+                .or_default()
+                .entry(ver.no)
+                .or_default() += 1;
+        }
+
+        series
+            .into_iter()
+            .map(|((fiscal_year, name), numbers)| Series {
+                fiscal_year,
+                name: name.to_owned(),
+                numbers,
+            })
+            .collect()
+    }
+
+    /// The gaps and duplicates in the numbering of each series, by
+    /// fiscal year, series and number.
+    #[must_use]
+    pub fn numbering_issues(&self) -> Vec<NumberingIssue> {
+        let mut issues = Vec::new();
+
+        for series in self.series() {
+            let gaps = series.gaps().into_iter().map(|(first, last)| {
+                (
+                    first,
+                    NumberingIssue::Gap {
+                        fiscal_year: series.fiscal_year,
+                        series: series.name.clone(),
+                        first,
+                        last,
+                    },
+                )
+            });
+            let duplicates = series.duplicates().into_iter().map(|(no, count)| {
+                (
+                    no,
+                    NumberingIssue::Duplicate {
+                        fiscal_year: series.fiscal_year,
+                        series: series.name.clone(),
+                        no,
+                        count,
+                    },
+                )
+            });
+
+            let mut found = gaps.chain(duplicates).collect::<Vec<_>>();
+            found.sort_by_key(|(no, _)| *no);
+            issues.extend(found.into_iter().map(|(_, issue)| issue));
+        }
+
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbering() {
+        let doc = Document::from_reader(
+            &b"#VER B 10 20230101\n{\n}\n#VER A 3 20230101\n{\n}\n#VER A 1 20230101\n{\n}\n#VER B 14 20230101\n{\n}\n#VER B 10 20230102\n{\n}\n#VER B 12 20230102\n{\n}\n"[..],
+        )
+        .unwrap();
+
+        let series = doc.series();
+        assert_eq!(
+            series.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(),
+            ["A", "B"]
+        );
+        assert_eq!((series[1].first(), series[1].last()), (Some(10), Some(14)));
+        assert_eq!(series[1].gaps(), [(11, 11), (13, 13)]);
+
+        let issues = doc
+            .numbering_issues()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            issues,
+            [
+                "A 2 is missing in 2023",
+                "B 10 is used by 2 vouchers in 2023",
+                "B 11 is missing in 2023",
+                "B 13 is missing in 2023"
+            ]
+        );
+
+        assert_eq!(Document::default().numbering_issues(), []);
+    }
+
+    #[test]
+    fn numbering_by_year() {
+        // the series starts over each fiscal year
+        let doc = Document::from_reader(
+            &b"#RAR 0 20230701 20240630
+#RAR -1 20220701 20230630
+#VER A 1 20220801
+{
+}
+#VER A 2 20230301
+{
+}
+#VER A 1 20230801
+{
+}
+#VER A 3 20240301
+{
+}
+"[..],
+        )
+        .unwrap();
+
+        let series = doc.series();
+        assert_eq!(
+            series
+                .iter()
+                .map(|s| (s.fiscal_year, s.name.as_str(), s.first(), s.last()))
+                .collect::<Vec<_>>(),
+            [(2022, "A", Some(1), Some(2)), (2023, "A", Some(1), Some(3))]
+        );
+        assert_eq!(
+            doc.numbering_issues()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["A 2 is missing in 2023"]
+        );
+    }
+
+    #[test]
+    fn ver_ids() {
+        let doc = Document::from_reader(
+            &b"#RAR 0 20230501 20240430\n#VER A 1 20230501\n{\n}\n#VER A 1 20240301\n{\n}\n#VER A 1 20220301\n{\n}\n#VER A 2 20230601 \"First\"\n{\n}\n#VER A 2 20230602 \"Second\"\n{\n}\n"[..],
+        )
+        .unwrap();
+
+        let ids = doc.vouchers_by_id();
+        assert_eq!(
+            ids.iter()
+                .map(|(id, vers)| (id.to_string(), vers.len()))
+                .collect::<Vec<_>>(),
+            [
+                ("2022/A/1".to_owned(), 1),
+                ("2023/A/1".to_owned(), 2),
+                ("2023/A/2".to_owned(), 2)
+            ]
+        );
+        let texts = ids[&"2023/A/2".parse().unwrap()]
+            .iter()
+            .map(|ver| ver.text.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, [Some("First"), Some("Second")]);
+        let id = "2023/A/1".parse().unwrap();
+        assert_eq!(doc.ver_id(doc.voucher(&id).unwrap()), id);
+        assert!(doc.voucher(&"2024/A/1".parse().unwrap()).is_none());
+
+        assert!("2023/A".parse::<VerId>().is_err());
+        assert_eq!("2023/A/B/1".parse::<VerId>().unwrap().series, "A/B");
+    }
+}
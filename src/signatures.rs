@@ -0,0 +1,168 @@
+//! Who registered what, from the signatures of vouchers (`#VER`) and
+//! transactions (`#TRANS`), e.g. to list everything that a user booked
+//! in a month.
+//!
+//! The signature of a voucher is of the user who registered it, and
+//! that of a transaction of the user who registered the transaction,
+//! e.g. when correcting the voucher. Transactions without a signature
+//! were registered with their voucher. The activity report
+//! (`reports::activity_by_signature`, with the `reports` feature) counts
+//! the same entries.
+//!
+//! ```
+//! use sie4::{series::VerId, Document};
+//! use time::macros::date;
+//!
+//! let doc = Document::from_reader(&b"#VER A 1 20230302 \"Rent\" 20230303 \"AA\"
+//! {
+//!     #TRANS 1930 {} -12000.00
+//!     #TRANS 5010 {} 12000.00
+//! }
+//! #VER A 2 20230410 \"Sale\" 20230410 \"BB\"
+//! {
+//!     #TRANS 1930 {} 500.00
+//!     #TRANS 3001 {} -500.00 \"\" \"\" \"\" \"AA\"
+//! }
+//! "[..])
+//! .unwrap();
+//!
+//! let march = doc.signed_by("AA", date!(2023 - 03 - 01)..=date!(2023 - 03 - 31));
+//! assert_eq!(march.len(), 1);
+//! assert_eq!(march[0].ver.to_string(), "2023/A/1");
+//! assert_eq!(march[0].transactions, [0, 1]);
+//!
+//! let users = doc.signatures();
+//! assert_eq!(users["AA"].len(), 2);
+//! assert!(!users["AA"][1].voucher);
+//! assert_eq!(users["BB"][0].transactions, [0]);
+//! ```
+
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+use serde::Serialize;
+use time::Date;
+
+use crate::{fiscal::FiscalYears, item::Ver, series::VerId, Document};
+
+/// What a user registered of a voucher, see [`Document::signatures`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Entry {
+    pub ver: VerId,
+    /// The registration date of the voucher, or its date if it has none.
+    pub registered: Date,
+    /// Whether the user signed the voucher, rather than only some of its
+    /// transactions.
+    pub voucher: bool,
+    /// The indices of the transactions (see [`Ver::transactions`])
+    /// signed by the user, and, if they signed the voucher, of those
+    /// without a signature.
+    pub transactions: Vec<usize>,
+}
+
+impl Entry {
+    /// The entries of `ver`, by signature, where `None` is of what was
+    /// registered without one.
+    pub(crate) fn of(ver: &Ver, years: &FiscalYears) -> BTreeMap<Option<String>, Self> {
+        let new = || Self {
+            ver: VerId::new(ver, years),
+            registered: ver.reg_date.unwrap_or(ver.date),
+            voucher: false,
+            transactions: Vec::new(),
+        };
+        let mut entries = BTreeMap::new();
+
+        entries.entry(ver.sign.clone()).or_insert_with(new).voucher = true;
+        for (i, line) in ver.transactions.0.iter().enumerate() {
+            let signature = line.trans().signature.as_ref().or(ver.sign.as_ref());
+            entries
+                .entry(signature.cloned())
+                .or_insert_with(new)
+                .transactions
+                .push(i);
+        }
+
+        entries
+    }
+}
+
+impl Document {
+    /// The entries of each signature, in the order of the vouchers.
+    /// Vouchers and transactions without signatures aren't included.
+    #[must_use]
+    pub fn signatures(&self) -> BTreeMap<String, Vec<Entry>> {
+        let years = self.fiscal_years();
+        let mut signatures = BTreeMap::<_, Vec<_>>::new();
+        for ver in self.vouchers() {
+            for (signature, entry) in Entry::of(ver, &years) {
+                if let Some(signature) = signature {
+                    signatures.entry(signature).or_default().push(entry);
+                }
+            }
+        }
+        signatures
+    }
+
+    /// The entries of `signature` that were registered within `dates`,
+    /// see [`Entry::registered`].
+    #[must_use]
+    pub fn signed_by(&self, signature: &str, dates: RangeInclusive<Date>) -> Vec<Entry> {
+        let years = self.fiscal_years();
+        self.vouchers()
+            .filter_map(|ver| Entry::of(ver, &years).remove(&Some(signature.to_owned())))
+            .filter(|entry| dates.contains(&entry.registered))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+
+    use super::*;
+
+    #[test]
+    fn signatures() {
+        let doc = Document::from_reader(
+            &b"#RAR 0 20230101 20231231
+#VER A 1 20230102 \"Rent\" \"\" \"AA\"
+{
+    #TRANS 1930 {} -12000.00
+    #BTRANS 5010 {} 12000.00 \"\" \"\" \"\" \"BB\"
+    #RTRANS 5011 {} 12000.00 \"\" \"\" \"\" \"BB\"
+    #TRANS 5011 {} 12000.00 \"\" \"\" \"\" \"BB\"
+}
+#VER A 2 20230201 \"Unsigned\"
+{
+    #TRANS 1930 {} 100.00
+    #TRANS 3001 {} -100.00
+}
+"[..],
+        )
+        .unwrap();
+
+        let signatures = doc.signatures();
+        assert_eq!(signatures.keys().collect::<Vec<_>>(), ["AA", "BB"]);
+        assert_eq!(
+            signatures["AA"],
+            [Entry {
+                ver: VerId {
+                    series: "A".to_owned(),
+                    no: 1,
+                    fiscal_year: 2023,
+                },
+                registered: date!(2023 - 01 - 02),
+                voucher: true,
+                transactions: vec![0],
+            }]
+        );
+        // the correction is a single line
+        assert_eq!(signatures["BB"][0].transactions, [1, 2]);
+        assert!(!signatures["BB"][0].voucher);
+
+        assert_eq!(doc.signed_by("BB", Date::MIN..=Date::MAX).len(), 1);
+        assert!(doc
+            .signed_by("AA", date!(2023 - 02 - 01)..=date!(2023 - 02 - 28))
+            .is_empty());
+        assert!(doc.signed_by("CC", Date::MIN..=Date::MAX).is_empty());
+    }
+}
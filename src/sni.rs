@@ -0,0 +1,248 @@
+//! SNI codes (`#BKOD`): the industry of a company according to the
+//! Swedish Standard Industrial Classification, SNI 2007.
+//!
+//! A code has five digits, e.g. `62010` for computer programming, which
+//! are read from left to right as the division (`62`), the group (`620`),
+//! the class (`6201`) and the detailed group (`62010`). Every division
+//! belongs to a lettered [`Section`].
+//!
+//! ```
+//! use sie4::{sni::Section, Document};
+//!
+//! let doc = Document::from_reader(&b"#BKOD 62010\n"[..]).unwrap();
+//! let sni = doc.sni().unwrap();
+//! assert_eq!(sni.division(), 62);
+//! assert_eq!(sni.group(), 620);
+//! assert_eq!(sni.section(), Some(Section::J));
+//! assert_eq!(Section::J.name(), "Informations- och kommunikationsverksamhet");
+//! ```
+
+use std::{fmt, str::FromStr};
+
+use serde::Serialize;
+
+use crate::{Document, Item};
+
+/// A five-digit SNI 2007 code. `00000` is the code of an unknown
+/// industry, which belongs to no section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SniCode(u32);
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid SNI code `{0}`")]
+pub struct InvalidSni(String);
+
+/// A section of SNI 2007, i.e. the coarsest level of the classification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum Section {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+}
+
+/// The sections with the first and last of their divisions and their
+/// Swedish names.
+const SECTIONS: [(Section, u32, u32, &str); 21] = [
+    (Section::A, 1, 3, "Jordbruk, skogsbruk och fiske"),
+    (Section::B, 5, 9, "Utvinning av mineral"),
+    (Section::C, 10, 33, "Tillverkning"),
+    (Section::D, 35, 35, "Försörjning av el, gas, värme och kyla"),
+    (
+        Section::E,
+        36,
+        39,
+        "Vattenförsörjning; avloppsrening, avfallshantering och sanering",
+    ),
+    (Section::F, 41, 43, "Byggverksamhet"),
+    (
+        Section::G,
+        45,
+        47,
+        "Handel; reparation av motorfordon och motorcyklar",
+    ),
+    (Section::H, 49, 53, "Transport och magasinering"),
+    (Section::I, 55, 56, "Hotell- och restaurangverksamhet"),
+    (
+        Section::J,
+        58,
+        63,
+        "Informations- och kommunikationsverksamhet",
+    ),
+    (Section::K, 64, 66, "Finans- och försäkringsverksamhet"),
+    (Section::L, 68, 68, "Fastighetsverksamhet"),
+    (
+        Section::M,
+        69,
+        75,
+        "Verksamhet inom juridik, ekonomi, vetenskap och teknik",
+    ),
+    (
+        Section::N,
+        77,
+        82,
+        "Uthyrning, fastighetsservice, resetjänster och andra stödtjänster",
+    ),
+    (
+        Section::O,
+        84,
+        84,
+        "Offentlig förvaltning och försvar; obligatorisk socialförsäkring",
+    ),
+    (Section::P, 85, 85, "Utbildning"),
+    (Section::Q, 86, 88, "Vård och omsorg; sociala tjänster"),
+    (Section::R, 90, 93, "Kultur, nöje och fritid"),
+    (Section::S, 94, 96, "Annan serviceverksamhet"),
+    (
+        Section::T,
+        97,
+        98,
+        "Förvärvsarbete i hushåll; hushållens produktion av diverse varor och tjänster för eget bruk",
+    ),
+    (
+        Section::U,
+        99,
+        99,
+        "Verksamhet vid internationella organisationer, utländska ambassader o.d.",
+    ),
+];
+
+impl Section {
+    /// The Swedish name of the section.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        SECTIONS[self as usize].3
+    }
+
+    /// The first and last division of the section.
+    #[must_use]
+    pub fn divisions(self) -> (u32, u32) {
+        let (_, first, last, _) = SECTIONS[self as usize];
+        (first, last)
+    }
+
+    fn of(division: u32) -> Option<Self> {
+        SECTIONS
+            .iter()
+            .find(|(_, first, last, _)| (*first..=*last).contains(&division))
+            .map(|(section, ..)| *section)
+    }
+}
+
+impl SniCode {
+    /// The section of the division, unless the industry is unknown.
+    #[must_use]
+    pub fn section(self) -> Option<Section> {
+        Section::of(self.division())
+    }
+
+    /// The first two digits, e.g. `62`.
+    #[must_use]
+    pub fn division(self) -> u32 {
+        self.0 / 1000
+    }
+
+    /// The first three digits, e.g. `620`.
+    #[must_use]
+    pub fn group(self) -> u32 {
+        self.0 / 100
+    }
+
+    /// The first four digits, e.g. `6201`.
+    #[must_use]
+    pub fn class(self) -> u32 {
+        self.0 / 10
+    }
+
+    /// All five digits as a number, e.g. `62010`.
+    #[must_use]
+    pub fn code(self) -> u32 {
+        self.0
+    }
+}
+
+/// Five digits, optionally with a period after the division as in
+/// `62.010`.
+impl FromStr for SniCode {
+    type Err = InvalidSni;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = match s.split_once('.') {
+            Some((division, rest)) if division.len() == 2 => format!("{division}{rest}"),
+            Some(_) => return Err(InvalidSni(s.to_owned())),
+            None => s.to_owned(),
+        };
+        if digits.len() != 5 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(InvalidSni(s.to_owned()));
+        }
+
+        let code = Self(digits.parse().map_err(|_| InvalidSni(s.to_owned()))?);
+        if code.0 != 0 && code.section().is_none() {
+            return Err(InvalidSni(s.to_owned()));
+        }
+        Ok(code)
+    }
+}
+
+impl fmt::Display for SniCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:05}", self.0)
+    }
+}
+
+impl Serialize for SniCode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl Document {
+    /// The industry of the company (`#BKOD`).
+    #[must_use]
+    pub fn sni(&self) -> Option<SniCode> {
+        self.items().iter().find_map(|item| match item {
+            Item::BKod(bkod) => Some(bkod.sni),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!("01110".parse(), Ok(SniCode(1110)));
+        assert_eq!("62.010".parse(), Ok(SniCode(62010)));
+        assert_eq!("00000".parse::<SniCode>().unwrap().section(), None);
+        assert_eq!(SniCode(1110).to_string(), "01110");
+        assert_eq!(SniCode(1110).section(), Some(Section::A));
+        assert_eq!(SniCode(99000).section(), Some(Section::U));
+
+        for invalid in ["6201", "620100", "6201a", "620.10", "04100", "-1000", ""] {
+            assert_eq!(
+                invalid.parse::<SniCode>(),
+                Err(InvalidSni(invalid.to_owned())),
+                "{invalid}"
+            );
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! SRU codes (`#SRU`): the fields of the tax declaration, e.g. INK2 or
+//! NE, that accounts are reported in.
+//!
+//! ```
+//! use rust_decimal_macros::dec;
+//! use sie4::{
+//!     sru::{totals, Sign, SruCodes},
+//!     Document,
+//! };
+//!
+//! let doc = Document::from_reader(&b"#SRU 1930 7281
+//! #SRU 1940 7281
+//! #SRU 2440 7368
+//! #UB 0 1930 1000.00
+//! #UB 0 1940 500.00
+//! #UB 0 2440 -300.00
+//! "[..])
+//! .unwrap();
+//!
+//! let codes = SruCodes::from_document(&doc);
+//! assert_eq!(codes.codes(1930), [7281]);
+//! assert_eq!(codes.accounts(7281).collect::<Vec<_>>(), [1930, 1940]);
+//!
+//! let totals = totals(&doc, &codes, 0, Sign::Declaration);
+//! assert_eq!(totals[&7281], dec!(1500.00));
+//! assert_eq!(totals[&7368], dec!(300.00));
+//! ```
+
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
+
+use crate::{
+    item::{AccountKind, AccountNo},
+    Document, Item,
+};
+
+/// The SRU codes of each account.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SruCodes {
+    codes: BTreeMap<AccountNo, Vec<u32>>,
+}
+
+impl SruCodes {
+    /// The `#SRU` declarations of `doc`. An account may be reported in
+    /// more than one field, e.g. of both the balance sheet (INK2R) and the
+    /// tax adjustments (INK2S).
+    #[must_use]
+    pub fn from_document(doc: &Document) -> Self {
+        let mut codes = BTreeMap::<_, Vec<_>>::new();
+
+        for item in doc.items() {
+            if let Item::Sru(sru) = item {
+                let account = codes.entry(sru.account).or_default();
+                if !account.contains(&sru.code) {
+                    account.push(sru.code);
+                }
+            }
+        }
+
+        Self { codes }
+    }
+
+    /// The codes of `account`, in the order they are declared.
+    #[must_use]
+    pub fn codes(&self, account: impl Into<AccountNo>) -> &[u32] {
+        self.codes.get(&account.into()).map_or(&[], Vec::as_slice)
+    }
+
+    /// The accounts that are reported in the field `code`.
+    pub fn accounts(&self, code: u32) -> impl Iterator<Item = AccountNo> + '_ {
+        self.codes
+            .iter()
+            .filter(move |(_, codes)| codes.contains(&code))
+            .map(|(&account, _)| account)
+    }
+
+    /// The accounts and their codes, by account.
+    pub fn iter(&self) -> impl Iterator<Item = (AccountNo, &[u32])> {
+        self.codes
+            .iter()
+            .map(|(&account, codes)| (account, codes.as_slice()))
+    }
+}
+
+/// The sign of the amounts returned by [`totals`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Sign {
+    /// As in the books, where credit balances are negative.
+    #[default]
+    Books,
+    /// As in the declaration, where the fields of liabilities and revenue
+    /// are positive: balances of accounts whose kind is credit (see
+    /// [`AccountKind::is_credit`]) are negated.
+    Declaration,
+}
+
+/// The closing balances of fiscal year `year` (`#UB` of balance accounts
+/// and `#RES` of result accounts, where `0` is the current year) summed
+/// per SRU code.
+#[must_use]
+pub fn totals(doc: &Document, codes: &SruCodes, year: i32, sign: Sign) -> BTreeMap<u32, Decimal> {
+    let mut balances = BTreeMap::<AccountNo, Decimal>::new();
+
+    for item in doc.items() {
+        let (account, balance) = match item {
+            Item::Ub(ub) if ub.year == year => (ub.account, ub.balance),
+            Item::Res(res) if res.year == year => (res.account, res.balance),
+            _ => continue,
+        };
+        *balances.entry(account).or_default() += balance;
+    }
+
+    let mut totals = BTreeMap::new();
+    for (account, balance) in balances {
+        let balance = match sign {
+            Sign::Declaration
+                if doc
+                    .account_kind(account)
+                    .is_some_and(AccountKind::is_credit) =>
+            {
+                -balance
+            }
+            _ => balance,
+        };
+
+        for &code in codes.codes(account) {
+            *totals.entry(code).or_default() += balance;
+        }
+    }
+
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn totals_per_code() {
+        let doc = Document::from_reader(
+            &b"#SRU 3010 7410
+#SRU 3010 7410
+#SRU 5010 7513
+#SRU 8910 7528
+#SRU 8910 7651
+#KTYP 8910 K
+#RES 0 3010 -1000.00
+#RES 0 5010 400.00
+#RES -1 5010 300.00
+#RES 0 8910 100.00
+#RES 0 6110 50.00
+"[..],
+        )
+        .unwrap();
+        let codes = SruCodes::from_document(&doc);
+        assert_eq!(codes.codes(3010), [7410]);
+        assert_eq!(codes.codes(8910), [7528, 7651]);
+        assert!(codes.codes(6110).is_empty());
+
+        assert_eq!(
+            totals(&doc, &codes, 0, Sign::Books),
+            BTreeMap::from([
+                (7410, dec!(-1000.00)),
+                (7513, dec!(400.00)),
+                (7528, dec!(100.00)),
+                (7651, dec!(100.00)),
+            ])
+        );
+        assert_eq!(
+            totals(&doc, &codes, 0, Sign::Declaration)[&7410],
+            dec!(1000.00)
+        );
+        assert_eq!(
+            totals(&doc, &codes, -1, Sign::Books),
+            BTreeMap::from([(7513, dec!(300.00))])
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Metadata attached to verifications and transactions.
+//!
+//! Tags are never read from or written to SIE4 files. They let analysis
+//! passes annotate the data they have been given (e.g. with anomaly
+//! scores or counterparties) and are carried along into exports.
+//!
+//! ```
+//! use sie4::{tags::Tag, Document};
+//!
+//! let mut doc = Document::from_reader(
+//!     &b"#VER A 1 20230102 \"Rent\"\n{\n#TRANS 1930 {} -12000.00\n}\n"[..],
+//! )
+//! .unwrap();
+//!
+//! for ver in doc.vouchers_mut() {
+//!     ver.tags.insert("reviewed", true);
+//!     ver.transactions.0[0]
+//!         .trans_mut()
+//!         .tags
+//!         .insert("counterparty", "Landlord AB");
+//! }
+//!
+//! let ver = doc.vouchers().next().unwrap();
+//! assert_eq!(ver.tags.get("reviewed"), Some(&Tag::Bool(true)));
+//! ```
+
+use std::{collections::BTreeMap, fmt};
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Tag {
+    Bool(bool),
+    Int(i64),
+    Decimal(Decimal),
+    Text(String),
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(b) => b.fmt(f),
+            Self::Int(i) => i.fmt(f),
+            Self::Decimal(d) => d.fmt(f),
+            Self::Text(s) => s.fmt(f),
+        }
+    }
+}
+
+macro_rules! tag_from_impl {
+    ($($ty:ty => $variant:ident,)*) => {
+        $(
+            impl From<$ty> for Tag {
+                fn from(value: $ty) -> Self {
+                    Self::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+tag_from_impl! {
+    bool => Bool,
+    i64 => Int,
+    Decimal => Decimal,
+    String => Text,
+    &str => Text,
+}
+
+/// Tags by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct Tags(BTreeMap<String, Tag>);
+
+impl Tags {
+    /// Set a tag, returning the previous value.
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<Tag>) -> Option<Tag> {
+        self.0.insert(name.into(), value.into())
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        self.0.get(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Tag> {
+        self.0.remove(name)
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over the tags, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Tag)> {
+        self.0.iter().map(|(name, tag)| (name.as_str(), tag))
+    }
+}
+
+/// Formats the tags as `name=value` pairs separated by `; `.
+impl fmt::Display for Tags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, tag)) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{name}={tag}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,856 @@
+//! Splitting files into the records of their items before decoding them,
+//! see [`Tokenizer`].
+//!
+//! Every reader reads items in two steps: the input is split into
+//! [`Record`]s, a label with its fields as text borrowed from the input,
+//! and each record is then decoded into an item by the fields that its
+//! label has. The tokenizer gives access to the records themselves, which
+//! also works for the items of other versions of SIE and of unknown
+//! extensions. A line that can't be split is reported with its number,
+//! and the tokenizer continues with the next line. Records can then be
+//! decoded into items with [`Record::item`].
+//!
+//! ```
+//! use sie4::{custom::Value, tokens::{Error, Tokenizer}, ItemRef};
+//!
+//! let data = b"#FLAGGA 0
+//! #FORTNOXEXT 1 {1 \"Sales\"}
+//! Not an item
+//! #VER A 1 20230102
+//! {
+//!     #TRANS 1930 {} -100.00
+//!     #TRANS 3001 {} 100.00
+//! }
+//! ";
+//! let mut records = Tokenizer::new(data);
+//!
+//! assert!(matches!(records.next().unwrap()?.item()?, ItemRef::Flagga(_)));
+//!
+//! let ext = records.next().unwrap()?;
+//! assert_eq!(ext.label, "FORTNOXEXT");
+//! assert_eq!(ext.fields[1].to_value(), Value::List(vec!["1".to_owned(), "Sales".to_owned()]));
+//! assert!(ext.item().is_err());
+//!
+//! assert_eq!(records.next().unwrap(), Err(Error::NotAnItem { line: 3 }));
+//!
+//! let ver = records.next().unwrap()?;
+//! assert_eq!((ver.line, ver.sub_entries.len()), (4, 2));
+//! assert_eq!(ver.sub_entries[0].raw, b"    #TRANS 1930 {} -100.00");
+//! assert!(matches!(ver.item()?, ItemRef::Ver(ver) if ver.transactions.0.len() == 2));
+//! assert!(records.next().is_none());
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use std::{borrow::Cow, collections::VecDeque};
+
+use nom::error::ErrorKind;
+
+use crate::{
+    custom::Value,
+    item::{self, Found, ItemRef},
+    parsers::{self, is_line_break, is_whitespace, Options, Quoted, ENDS_UNQUOTED},
+    reader,
+};
+
+/// A line that couldn't be split into a [`Record`], see the
+/// [module](self).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// Neither an item, which starts with `#`, nor a curly brace.
+    #[error("line {line} is not an item")]
+    NotAnItem { line: u32 },
+    /// The fields can't be split, e.g. because of a list without a
+    /// closing brace.
+    #[error("invalid fields on line {line}")]
+    InvalidFields { line: u32 },
+    /// A `{` that doesn't follow an item, or a `}` without a `{`.
+    #[error("unmatched curly brace on line {line}")]
+    UnmatchedBrace { line: u32 },
+    /// The sub-entries of the item on `line` are never closed with `}`.
+    #[error("unclosed sub-entries of the item on line {line}")]
+    Unclosed { line: u32 },
+}
+
+/// A field of a [`Record`], borrowed from the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token<'a> {
+    /// Text, with or without quotes.
+    Text(Text<'a>),
+    /// A list in curly braces, e.g. an object list (`{1 "456"}`).
+    List(List<'a>),
+}
+
+impl<'a> Token<'a> {
+    /// The field as written.
+    #[must_use]
+    pub fn raw(&self) -> &'a [u8] {
+        match self {
+            Self::Text(text) => text.raw,
+            Self::List(list) => list.raw,
+        }
+    }
+
+    /// The field as the field of a [`Custom`](crate::custom::Custom)
+    /// item.
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        match self {
+            Self::Text(text) => Value::Text(text.decode().into_owned()),
+            Self::List(list) => Value::List(
+                list.items
+                    .iter()
+                    .map(|text| text.decode().into_owned())
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// A field of text, see [`parsers::text`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Text<'a> {
+    /// The field as written, including the quotes and escapes, if any.
+    pub raw: &'a [u8],
+    pub quoted: bool,
+}
+
+impl<'a> Text<'a> {
+    /// The field without its quotes, if any.
+    #[must_use]
+    pub fn inner(&self) -> &'a [u8] {
+        if self.quoted {
+            &self.raw[1..self.raw.len() - 1]
+        } else {
+            self.raw
+        }
+    }
+
+    /// The text, decoded from CP437 and with the escapes of quoted text
+    /// resolved. The text is only copied if it contains non-ASCII
+    /// characters or escapes.
+    #[must_use]
+    pub fn decode(&self) -> Cow<'a, str> {
+        let text = parsers::decode(self.inner());
+        if self.quoted {
+            parsers::unescape(text)
+        } else {
+            text
+        }
+    }
+}
+
+/// A list in curly braces, whose items may be wrapped over several
+/// lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct List<'a> {
+    /// The list as written, including the braces.
+    pub raw: &'a [u8],
+    pub items: Vec<Text<'a>>,
+}
+
+/// An item, split into its label and fields, and the items of its
+/// sub-entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record<'a> {
+    /// Without the `#`.
+    pub label: &'a str,
+    pub fields: Vec<Token<'a>>,
+    /// The line of the item, without the line break.
+    pub raw: &'a [u8],
+    /// The number of the line, starting at 1.
+    pub line: u32,
+    /// The records in curly braces on the lines after the item.
+    pub sub_entries: Vec<Record<'a>>,
+    /// The input that the record was split from, which starts on line
+    /// `base`, at `origin` of the whole input, for the positions of its
+    /// fields.
+    src: &'a [u8],
+    base: u32,
+    origin: u64,
+    /// Where the fields end, as an offset of `src`.
+    end: usize,
+    /// Where the block of sub-entries starts, if there is one.
+    block: Option<usize>,
+    /// Where the fields following the block start, if any.
+    trailing: Option<usize>,
+    /// The lines of the block that couldn't be split, with the number of
+    /// sub-entries before each and where it starts.
+    errors: Vec<(usize, Error, usize)>,
+}
+
+impl<'a> Record<'a> {
+    /// Decode the record as an item, borrowing text from the input.
+    ///
+    /// # Errors
+    ///
+    /// If the record isn't an item of SIE4, or if its fields or
+    /// sub-entries aren't of its item.
+    pub fn item(&self) -> Result<ItemRef<'a>, reader::Error> {
+        let mut found = Found::default();
+        let item = ItemRef::decode(self, Options::default(), None, &mut found)
+            .map_err(|e| reader::Error::invalid(&e, self.origin))?;
+        match found.skipped.into_iter().next() {
+            Some(e) => Err(reader::Error::invalid(&e, self.origin)),
+            None => Ok(item),
+        }
+    }
+
+    /// Set where `src` starts in the whole input, see [`Tokenizer`].
+    fn set_origin(&mut self, origin: u64) {
+        self.origin = origin;
+        for entry in &mut self.sub_entries {
+            entry.set_origin(origin);
+        }
+    }
+
+    /// The offset of `slice`, which is part of the input, in `src`.
+    pub(crate) fn offset(&self, slice: &[u8]) -> usize {
+        slice.as_ptr() as usize - self.src.as_ptr() as usize
+    }
+
+    /// Where the fields end, as an offset (see [`Record::offset`]).
+    pub(crate) fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Where the block of sub-entries starts, if there is one.
+    pub(crate) fn block(&self) -> Option<usize> {
+        self.block
+    }
+
+    /// Where the fields following the block start, if any. They are
+    /// skipped to the end of the line.
+    pub(crate) fn trailing(&self) -> Option<usize> {
+        self.trailing
+    }
+
+    /// What couldn't be split in the block of sub-entries, see
+    /// [`Record::errors`].
+    pub(crate) fn errors(&self) -> &[(usize, Error, usize)] {
+        &self.errors
+    }
+
+    /// The input from `offset`, and the line and column (in bytes,
+    /// starting at 1) that it is on.
+    pub(crate) fn locate(&self, offset: usize) -> (&'a [u8], u32, usize) {
+        locate(self.src, self.base, offset)
+    }
+}
+
+/// See [`Record::locate`].
+pub(crate) fn locate(src: &[u8], base: u32, offset: usize) -> (&[u8], u32, usize) {
+    let before = &src[..offset];
+    let lines = u32::try_from(memchr::memchr_iter(b'\n', before).count()).unwrap_or(u32::MAX);
+    let column = offset - memchr::memrchr(b'\n', before).map_or(0, |n| n + 1) + 1;
+    (&src[offset..], base.saturating_add(lines), column)
+}
+
+/// How the input is split, see [`lex`].
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Mode {
+    /// See [`Reader::strict`](crate::Reader::strict).
+    pub strict: bool,
+    /// Whether the input ends where it does, rather than possibly
+    /// continuing with more of the same item.
+    pub eof: bool,
+    /// Whether any item may have sub-entries, which end with the input
+    /// if they aren't closed, as the [`Tokenizer`] reads them. Otherwise
+    /// only items whose fields have sub-entries are followed by them.
+    pub tolerant: bool,
+}
+
+/// Why the input couldn't be split into a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LexError {
+    /// More of the input is needed.
+    Incomplete,
+    /// An error of `kind` at the offset `at` of the input.
+    Invalid { kind: ErrorKind, at: usize },
+}
+
+/// Split the item at the start of `src`, which starts on `line`, after
+/// any blank lines, returning it with the number of bytes that it spans,
+/// including the blank lines.
+pub(crate) fn lex(src: &[u8], line: u32, mode: Mode) -> Result<(Record<'_>, usize), LexError> {
+    let mut lexer = Lexer::new(src, line, mode);
+    lexer.blank();
+    // only the sub-entries of a block may be indented
+    if mode.strict && lexer.pos > 0 && is_whitespace(src[lexer.pos - 1]) {
+        let indent = src[..lexer.pos]
+            .iter()
+            .rev()
+            .take_while(|&&c| is_whitespace(c))
+            .count();
+        return Err(invalid(ErrorKind::Space, lexer.pos - indent));
+    }
+
+    match lexer.peek() {
+        Some(b'#') => {}
+        Some(_) => return Err(invalid(ErrorKind::Tag, lexer.pos)),
+        None => return Err(LexError::Incomplete),
+    }
+    let mut record = lexer.record(false)?;
+    lexer.end_of_item(&mut record)?;
+    Ok((record, lexer.pos))
+}
+
+/// Split the fields at the start of `src`, which starts on `line`, of
+/// an item labeled `label`, as [`lex`] does after the label.
+pub(crate) fn lex_fields<'a>(
+    src: &'a [u8],
+    line: u32,
+    label: &'a str,
+    mode: Mode,
+) -> Result<(Record<'a>, usize), LexError> {
+    let mut lexer = Lexer::new(src, line, mode);
+    let fields = lexer.fields(false)?;
+    let mut record = lexer.new_record(label, fields, 0, line);
+    lexer.end_of_item(&mut record)?;
+    Ok((record, lexer.pos))
+}
+
+struct Lexer<'a> {
+    src: &'a [u8],
+    pos: usize,
+    /// The line of `pos`.
+    line: u32,
+    /// The line of the start of `src`.
+    base: u32,
+    mode: Mode,
+}
+
+/// An error of `kind` at the offset `at`.
+fn invalid(kind: ErrorKind, at: usize) -> LexError {
+    LexError::Invalid { kind, at }
+}
+
+impl<'a> Lexer<'a> {
+    fn new(src: &'a [u8], line: u32, mode: Mode) -> Self {
+        Self {
+            src,
+            pos: 0,
+            line,
+            base: line,
+            mode,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    /// The input ends here, which more input may follow unless
+    /// [`Mode::eof`].
+    fn end(&self) -> Result<(), LexError> {
+        if self.mode.eof {
+            Ok(())
+        } else {
+            Err(LexError::Incomplete)
+        }
+    }
+
+    /// Skip spaces and tabs, returning how many.
+    fn whitespace(&mut self) -> usize {
+        let n = self.src[self.pos..]
+            .iter()
+            .take_while(|&&c| is_whitespace(c))
+            .count();
+        self.pos += n;
+        n
+    }
+
+    /// Skip whitespace and line breaks.
+    fn blank(&mut self) {
+        while let Some(c) = self.peek() {
+            match c {
+                b'\n' => self.line += 1,
+                b'\r' => {}
+                c if is_whitespace(c) => {}
+                _ => break,
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn new_record(
+        &self,
+        label: &'a str,
+        fields: Vec<Token<'a>>,
+        start: usize,
+        line: u32,
+    ) -> Record<'a> {
+        Record {
+            label,
+            fields,
+            raw: &self.src[start..self.pos],
+            line,
+            sub_entries: Vec::new(),
+            src: self.src,
+            base: self.base,
+            origin: 0,
+            end: self.pos,
+            block: None,
+            trailing: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// An item, starting at its `#`, up to the end of its fields.
+    fn record(&mut self, in_block: bool) -> Result<Record<'a>, LexError> {
+        let (start, line) = (self.pos, self.line);
+        let indent = self.src[..start]
+            .iter()
+            .rev()
+            .take_while(|&&c| is_whitespace(c))
+            .count();
+
+        self.pos += 1;
+        let rest = &self.src[self.pos..];
+        let len = if let Some(len) = rest
+            .iter()
+            .position(|&c| is_whitespace(c) || is_line_break(c))
+        {
+            len
+        } else {
+            self.end()?;
+            rest.len()
+        };
+        let label = std::str::from_utf8(&rest[..len])
+            .ok()
+            .filter(|label| !label.is_empty())
+            .ok_or_else(|| invalid(ErrorKind::Tag, self.pos))?;
+        self.pos += len;
+
+        let fields = self.fields(in_block)?;
+        Ok(self.new_record(label, fields, start - indent, line))
+    }
+
+    /// The fields of an item, up to the end of its line, the `}` of the
+    /// block that it is in or the `{` of a block on the same line.
+    fn fields(&mut self, in_block: bool) -> Result<Vec<Token<'a>>, LexError> {
+        // enough for most items without growing
+        let mut fields = Vec::with_capacity(8);
+        loop {
+            let space = self.whitespace();
+            let Some(c) = self.peek() else {
+                self.end()?;
+                return Ok(fields);
+            };
+            match c {
+                b'}' => return Ok(fields),
+                c if is_line_break(c) => return Ok(fields),
+                b'{' if !in_block && self.opens_block()? => return Ok(fields),
+                // fields are separated by a single space, and only the
+                // whitespace at the end of a line may be otherwise
+                _ if self.mode.strict && (space != 1 || self.src[self.pos - 1] != b' ') => {
+                    return Err(invalid(ErrorKind::Space, self.pos - space));
+                }
+                b'{' => fields.push(Token::List(self.list()?)),
+                _ => fields.push(Token::Text(self.text()?)),
+            }
+        }
+    }
+
+    /// Whether the `{` at `pos` opens a block of sub-entries on the line
+    /// of the item, rather than a list.
+    fn opens_block(&self) -> Result<bool, LexError> {
+        match self.src[self.pos + 1..]
+            .iter()
+            .find(|&&c| !is_whitespace(c) && !is_line_break(c))
+        {
+            Some(&c) => Ok(c == b'#'),
+            None => self.end().map(|()| false),
+        }
+    }
+
+    /// Quoted or unquoted text, see [`parsers::text`].
+    fn text(&mut self) -> Result<Text<'a>, LexError> {
+        let start = self.pos;
+        if self.src[start] == b'"' {
+            match parsers::closing_quote(&self.src[start + 1..], self.mode.eof) {
+                Quoted::Closed(n) => {
+                    self.pos = start + n + 2;
+                    return Ok(Text {
+                        raw: &self.src[start..self.pos],
+                        quoted: true,
+                    });
+                }
+                Quoted::Unclosed => return Err(LexError::Incomplete),
+                // read as unquoted text
+                Quoted::EndOfLine(_) => {}
+            }
+        }
+
+        // the first character is text even if it would end it, e.g. `#`
+        self.pos = if let Some(n) = self.src[start + 1..]
+            .iter()
+            .position(|&c| ENDS_UNQUOTED[usize::from(c)])
+        {
+            start + 1 + n
+        } else {
+            self.end()?;
+            self.src.len()
+        };
+        Ok(Text {
+            raw: &self.src[start..self.pos],
+            quoted: false,
+        })
+    }
+
+    /// A list, starting at its `{`. Any whitespace separates its items,
+    /// which can't be lists or items themselves.
+    fn list(&mut self) -> Result<List<'a>, LexError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.blank();
+            match self.peek() {
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(List {
+                        raw: &self.src[start..self.pos],
+                        items,
+                    });
+                }
+                Some(b'{' | b'#') => return Err(invalid(ErrorKind::Char, self.pos)),
+                Some(_) => items.push(self.text()?),
+                None => {
+                    self.end()?;
+                    return Err(invalid(ErrorKind::Char, start));
+                }
+            }
+        }
+    }
+
+    /// The line break that ends the fields of an item, unless they end
+    /// at a `}` or the end of the input.
+    fn end_of_line(&mut self) -> Result<(), LexError> {
+        match self.src[self.pos..] {
+            [b'\r', b'\n', ..] => {
+                self.pos += 2;
+                self.line += 1;
+            }
+            // either the line feed hasn't been read yet or it's missing
+            [b'\r'] if self.mode.strict && !self.mode.eof => return Err(LexError::Incomplete),
+            [b'\r', _, ..] if self.mode.strict => {
+                return Err(invalid(ErrorKind::CrLf, self.pos));
+            }
+            [b'\n', ..] => {
+                self.pos += 1;
+                self.line += 1;
+            }
+            [b'\r', ..] => self.pos += 1,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// What follows the fields of `record`: the line break, and the
+    /// block of sub-entries, if any, with its line break.
+    fn end_of_item(&mut self, record: &mut Record<'a>) -> Result<(), LexError> {
+        match self.peek() {
+            // a block on the line of the item
+            Some(b'{') => {}
+            // read as the start of the next item
+            Some(b'}') => return Ok(()),
+            _ => {
+                self.end_of_line()?;
+                if !self.mode.tolerant && !item::has_sub_entries(record.label) {
+                    return Ok(());
+                }
+
+                let (pos, line) = (self.pos, self.line);
+                self.blank();
+                match self.peek() {
+                    Some(b'{') => {}
+                    None if !self.mode.eof => return Err(LexError::Incomplete),
+                    _ => {
+                        (self.pos, self.line) = (pos, line);
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        self.block(record)?;
+        self.whitespace();
+        match self.peek() {
+            None => self.end(),
+            Some(b'}') => Ok(()),
+            Some(c) if is_line_break(c) => self.end_of_line(),
+            Some(_) => {
+                record.trailing = Some(self.pos);
+                self.skip_line();
+                match self.peek() {
+                    None => self.end(),
+                    Some(_) => self.end_of_line(),
+                }
+            }
+        }
+    }
+
+    /// The sub-entries of `record`, from the `{` that opens them to the
+    /// `}` that closes them. Lines that can't be split are added to the
+    /// errors of the record and skipped.
+    fn block(&mut self, record: &mut Record<'a>) -> Result<(), LexError> {
+        record.block = Some(self.pos);
+        self.pos += 1;
+
+        loop {
+            self.blank();
+            let (start, line) = (self.pos, self.line);
+            let error = match self.peek() {
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some(b'#') => match self.record(true).and_then(|entry| {
+                    self.end_of_line()?;
+                    Ok(entry)
+                }) {
+                    Ok(entry) => {
+                        record.sub_entries.push(entry);
+                        continue;
+                    }
+                    Err(LexError::Incomplete) => return Err(LexError::Incomplete),
+                    Err(LexError::Invalid {
+                        kind: ErrorKind::Tag,
+                        ..
+                    }) => Error::NotAnItem { line },
+                    Err(LexError::Invalid { .. }) => Error::InvalidFields { line },
+                },
+                Some(_) => Error::NotAnItem { line },
+                None if self.mode.eof && self.mode.tolerant => {
+                    record.errors.push((
+                        record.sub_entries.len(),
+                        Error::Unclosed { line: record.line },
+                        start,
+                    ));
+                    return Ok(());
+                }
+                None => return Err(LexError::Incomplete),
+            };
+
+            record.errors.push((record.sub_entries.len(), error, start));
+            (self.pos, self.line) = (start, line);
+            self.skip_line();
+        }
+    }
+
+    /// Skip to the end of the line, or to a `}` that closes the block
+    /// that it is in.
+    fn skip_line(&mut self) {
+        let mut depth = 0_usize;
+        while let Some(c) = self.peek() {
+            match c {
+                c if is_line_break(c) => return,
+                b'{' => depth += 1,
+                b'}' if depth == 0 => return,
+                b'}' => depth -= 1,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+/// Splits the input into [`Record`]s, see the [module](self). The input
+/// is decoded from code page 437.
+#[derive(Debug, Clone)]
+pub struct Tokenizer<'a> {
+    input: &'a [u8],
+    pos: usize,
+    /// The line of `pos`.
+    line: u32,
+    /// An item and the errors in its sub-entries, which are returned
+    /// first.
+    pending: VecDeque<Result<Record<'a>, Error>>,
+}
+
+impl<'a> Tokenizer<'a> {
+    #[must_use]
+    pub fn new(input: &'a [u8]) -> Self {
+        Self {
+            input,
+            pos: 0,
+            line: 1,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Move past the `n` bytes at `pos`.
+    fn advance(&mut self, n: usize) {
+        let skipped = &self.input[self.pos..self.pos + n];
+        self.line += u32::try_from(memchr::memchr_iter(b'\n', skipped).count()).unwrap_or(u32::MAX);
+        self.pos += n;
+    }
+
+    /// Move past the line that starts `blank` bytes after `pos`.
+    fn skip_line(&mut self, blank: usize) {
+        let rest = &self.input[self.pos + blank..];
+        let len = memchr::memchr(b'\n', rest).map_or(rest.len(), |n| n + 1);
+        self.advance(blank + len);
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<Record<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(res) = self.pending.pop_front() {
+            return Some(res);
+        }
+
+        let rest = &self.input[self.pos..];
+        let blank = rest
+            .iter()
+            .take_while(|&&c| is_whitespace(c) || is_line_break(c))
+            .count();
+        if blank == rest.len() {
+            self.advance(blank);
+            return None;
+        }
+
+        let (_, line, _) = locate(rest, self.line, blank);
+        let text = &rest[blank..];
+        let len = text
+            .iter()
+            .position(|&c| is_line_break(c))
+            .unwrap_or(text.len());
+        let error = match text[..len].trim_ascii_end() {
+            b"{" | b"}" => Error::UnmatchedBrace { line },
+            [b'#', ..] => {
+                let mode = Mode {
+                    strict: false,
+                    eof: true,
+                    tolerant: true,
+                };
+                match lex(rest, self.line, mode) {
+                    Ok((mut record, n)) => {
+                        record.set_origin(self.pos as u64);
+                        self.advance(n);
+                        // like the reader, errors in the sub-entries come
+                        // before the item
+                        self.pending
+                            .extend(record.errors.iter().map(|(_, e, _)| Err(e.clone())));
+                        self.pending.push_back(Ok(record));
+                        return self.pending.pop_front();
+                    }
+                    Err(LexError::Invalid {
+                        kind: ErrorKind::Tag,
+                        ..
+                    }) => Error::NotAnItem { line },
+                    Err(_) => Error::InvalidFields { line },
+                }
+            }
+            _ => Error::NotAnItem { line },
+        };
+
+        self.skip_line(blank);
+        Some(Err(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tolerant() {
+        let data = b"#FLAGGA 0\r
+}\r
+#OIB 0 3010 {1 \"Z 1\" 1000.00\r
+\r
+#VER A 1 20230102\r
+{\r
+#TRANS 1930 {} -100.00\r
+TRANS 3001 {} 100.00\r
+}\r
+#KONTO 1930 Bank\r
+{\r
+#SRU 1930 7281";
+        let records = Tokenizer::new(data).collect::<Vec<_>>();
+
+        assert_eq!(records[1], Err(Error::UnmatchedBrace { line: 2 }));
+        assert_eq!(records[2], Err(Error::InvalidFields { line: 3 }));
+        assert_eq!(records[3], Err(Error::NotAnItem { line: 8 }));
+        let ver = records[4].as_ref().unwrap();
+        assert_eq!((ver.label, ver.line), ("VER", 5));
+        assert_eq!(ver.sub_entries[0].raw, b"#TRANS 1930 {} -100.00");
+        assert_eq!(ver.sub_entries[0].line, 7);
+        assert!(matches!(
+            ver.sub_entries[0].item(),
+            Ok(ItemRef::Trans(trans)) if trans.account == 1930
+        ));
+        // but the item is decoded with its sub-entries, including the
+        // invalid line
+        assert!(ver.item().is_err());
+
+        assert_eq!(records[5], Err(Error::Unclosed { line: 10 }));
+        let konto = records[6].as_ref().unwrap();
+        assert_eq!(konto.sub_entries[0].label, "SRU");
+        assert_eq!(
+            konto.sub_entries[0]
+                .fields
+                .iter()
+                .map(Token::to_value)
+                .collect::<Vec<_>>(),
+            [
+                Value::Text("1930".to_owned()),
+                Value::Text("7281".to_owned())
+            ]
+        );
+        assert_eq!(records.len(), 7);
+    }
+
+    #[test]
+    fn lexing() {
+        let eof = Mode {
+            eof: true,
+            ..Mode::default()
+        };
+        let (record, len) = lex(b"\n#KONTO 1930 \"Bank \\\"A\\\"\" {1 2}\n#KONTO", 1, eof).unwrap();
+        assert_eq!((record.label, record.line, len), ("KONTO", 2, 32));
+        let texts = match &record.fields[..] {
+            [Token::Text(no), Token::Text(name), Token::List(list)] => {
+                [no.decode(), name.decode(), list.items[1].decode()]
+            }
+            fields => panic!("unexpected fields {fields:?}"),
+        };
+        assert_eq!(texts, ["1930", "Bank \"A\"", "2"]);
+
+        // more may follow an item at the end of the input
+        for input in [
+            &b"#KONTO 1930 Bank"[..],
+            b"#VER A 1 20230101\n",
+            b"#KONTO 1930 \"Bank",
+        ] {
+            assert_eq!(
+                lex(input, 1, Mode::default()).map(|_| ()),
+                Err(LexError::Incomplete)
+            );
+            assert!(lex(input, 1, eof).is_ok());
+        }
+
+        // a block on the line of the item, and one that isn't a block
+        let (ver, _) = lex(b"#VER A 1 20230101 {#TRANS 1930 {} 1.00}\n", 1, eof).unwrap();
+        assert_eq!((ver.fields.len(), ver.sub_entries.len()), (3, 1));
+        let (ver, _) = lex(b"#VER A 1 20230101 {}\n", 1, eof).unwrap();
+        assert_eq!((ver.fields.len(), ver.block()), (4, None));
+
+        assert_eq!(
+            lex(b"#KONTO 1930 {1 #KONTO}\n", 1, eof).map(|_| ()),
+            Err(LexError::Invalid {
+                kind: ErrorKind::Char,
+                at: 15
+            })
+        );
+        let strict = Mode {
+            strict: true,
+            ..eof
+        };
+        assert!(lex(b"#KONTO 1930  Bank\n", 1, strict).is_err());
+        assert!(lex(b"#KONTO 1930 Bank \n", 1, strict).is_ok());
+    }
+}
@@ -0,0 +1,1083 @@
+//! Validation of files against the specification.
+//!
+//! ```
+//! use sie4::validate::{self, Rule};
+//!
+//! let report = validate::validate_bytes(b"#FLAGGA 0
+//! #VER A 1 20230102 \"Rent\"
+//! {
+//!     #TRANS 1930 {} -12000.00
+//!     #TRANS 5010 {} 1200.00
+//! }
+//! ");
+//! assert!(!report.is_valid());
+//! assert!(report
+//!     .findings
+//!     .iter()
+//!     .any(|finding| finding.rule == Rule::UnbalancedVoucher && finding.line == Some(2)));
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use nom::Slice;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::{
+    dimension::Dimensions,
+    document::DatePolicy,
+    fiscal::{FiscalYear, FiscalYears},
+    item::{
+        self, AccountNo, Decoded, Flagga, Group, Invalid, ItemType, Problem, ReadError, TransLine,
+    },
+    parsers::{is_line_break, is_whitespace, Options},
+    reader::Position,
+    Document, Item, Span,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// The rules that are checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// The file couldn't be parsed. No further items are checked.
+    Parse,
+    /// Items must be ordered by group (see [`Group`]).
+    Order,
+    /// Some items are mandatory in every file.
+    Mandatory,
+    /// The transactions of a verification must sum to zero, both before
+    /// and after corrections.
+    UnbalancedVoucher,
+    /// The closing balance (`#UB`) or result (`#RES`) of the current year
+    /// must equal the opening balance (`#IB`) plus the transactions, not
+    /// counting removed ones (`#BTRANS`).
+    ClosingBalance,
+    /// The opening balance of the current year should equal the closing
+    /// balance of the previous year.
+    OpeningBalance,
+    /// If the file starts with `#KSUMMA`, it must end with a `#KSUMMA`
+    /// with the checksum. The checksum itself is verified by the reader
+    /// (see [`crate::Reader::verify_checksum`]).
+    Checksum,
+    /// Sub-dimensions (`#UNDERDIM`) must be part of declared dimensions,
+    /// without cycles (see [`Dimensions`]).
+    DimensionHierarchy,
+    /// The objects of transactions should belong to declared dimensions.
+    UndeclaredDimension,
+    /// Transactions above a materiality threshold are listed for review,
+    /// see [`Config::materiality`].
+    Materiality,
+    /// If the file declares the currency of the books (`#VALUTA`), once,
+    /// the amounts must be in it, i.e. have no more decimals than it,
+    /// and [foreign amounts](crate::item::Trans::foreign) in it must
+    /// equal the amounts of their transactions.
+    Currency,
+}
+
+impl Rule {
+    /// Identifier of the rule, as [serialized](Serialize).
+    #[must_use]
+    pub const fn id(self) -> &'static str {
+        match self {
+            Self::Parse => "parse",
+            Self::Order => "order",
+            Self::Mandatory => "mandatory",
+            Self::UnbalancedVoucher => "unbalanced-voucher",
+            Self::ClosingBalance => "closing-balance",
+            Self::OpeningBalance => "opening-balance",
+            Self::Checksum => "checksum",
+            Self::DimensionHierarchy => "dimension-hierarchy",
+            Self::UndeclaredDimension => "undeclared-dimension",
+            Self::Materiality => "materiality",
+            Self::Currency => "currency",
+        }
+    }
+
+    /// The part of the [SIE 4B specification](crate) that the rule is
+    /// based on. Sections are referred to by heading and item label,
+    /// which unlike the numbering are the same in the Swedish and English
+    /// editions.
+    #[must_use]
+    pub const fn citation(self) -> &'static str {
+        match self {
+            Self::Parse => "SIE 4B, File format: items, fields and character set",
+            Self::Order => "SIE 4B, File format: order of items",
+            Self::Mandatory => "SIE 4B, Items per file type: mandatory items in type 4",
+            Self::UnbalancedVoucher => "SIE 4B, Item descriptions: #VER and #TRANS",
+            Self::ClosingBalance => "SIE 4B, Item descriptions: #IB, #UB and #RES",
+            Self::OpeningBalance => "SIE 4B, Item descriptions: #IB and #UB",
+            Self::Checksum => "SIE 4B, Item descriptions: #KSUMMA; Checksum calculation",
+            Self::DimensionHierarchy => "SIE 4B, Item descriptions: #DIM and #UNDERDIM",
+            Self::UndeclaredDimension => "SIE 4B, Item descriptions: #DIM and #TRANS",
+            Self::Materiality => "Not part of SIE 4B; a review of #TRANS amounts",
+            Self::Currency => "SIE 4B, Item descriptions: #VALUTA",
+        }
+    }
+
+    #[must_use]
+    pub const fn severity(self) -> Severity {
+        match self {
+            Self::OpeningBalance | Self::UndeclaredDimension | Self::Materiality => {
+                Severity::Warning
+            }
+            _ => Severity::Error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    pub rule: Rule,
+    pub severity: Severity,
+    pub message: String,
+    /// See [`Rule::citation`].
+    pub citation: &'static str,
+    /// Line of the item concerned, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// Column of the field concerned, in bytes counted from 1, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    /// Offset of the field concerned, or else of the item, in bytes
+    /// counted from 0, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Report {
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    /// Whether there are no findings of [`Severity::Error`].
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.count(Severity::Error) == 0
+    }
+
+    /// The number of findings with the given severity.
+    #[must_use]
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|finding| finding.severity == severity)
+            .count()
+    }
+}
+
+/// Rules that are only checked if configured.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    /// See [`Rule::Materiality`].
+    pub materiality: Option<Materiality>,
+}
+
+/// Thresholds for [`Rule::Materiality`]: a transaction is material if
+/// its amount, debit or credit, is larger than the threshold of its
+/// account.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Materiality {
+    /// The threshold of accounts without one of their own class.
+    pub default: Option<Threshold>,
+    /// Thresholds by account class, i.e. the first digit of the number,
+    /// e.g. `1` for assets.
+    pub classes: BTreeMap<u32, Threshold>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Threshold {
+    Amount(Decimal),
+    /// A percentage of the turnover of the current fiscal year, i.e. the
+    /// net credit of the revenue accounts 3000–3799 of BAS. No
+    /// transactions are material if there is no turnover.
+    PercentOfTurnover(Decimal),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid threshold `{0}`, expected an amount or a percentage such as `2%`")]
+pub struct InvalidThreshold(String);
+
+/// An amount, e.g. `50000`, or a percentage of the turnover, e.g. `2%`.
+impl std::str::FromStr for Threshold {
+    type Err = InvalidThreshold;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidThreshold(s.to_owned());
+        match s.trim().strip_suffix('%') {
+            Some(percent) => percent
+                .trim()
+                .parse()
+                .map(Self::PercentOfTurnover)
+                .map_err(|_| invalid()),
+            None => s.trim().parse().map(Self::Amount).map_err(|_| invalid()),
+        }
+    }
+}
+
+impl std::fmt::Display for Threshold {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Amount(amount) => write!(f, "{amount}"),
+            Self::PercentOfTurnover(percent) => write!(f, "{percent}% of the turnover"),
+        }
+    }
+}
+
+impl Materiality {
+    fn threshold(&self, account: AccountNo) -> Option<Threshold> {
+        account
+            .class()
+            .and_then(|class| self.classes.get(&class))
+            .copied()
+            .or(self.default)
+    }
+}
+
+struct Context<'a> {
+    items: &'a [Item],
+    /// Where the items are, if known.
+    positions: &'a [Position],
+    dates: DatePolicy,
+    /// See [`Document::include_removed`].
+    removed: bool,
+    report: Report,
+}
+
+impl Context<'_> {
+    fn report(&mut self, rule: Rule, index: Option<usize>, message: String) {
+        let position = index.and_then(|i| self.positions.get(i));
+        self.report.findings.push(Finding {
+            rule,
+            severity: rule.severity(),
+            message,
+            citation: rule.citation(),
+            line: position.map(|position| position.line),
+            column: None,
+            offset: position.map(|position| position.offset),
+        });
+    }
+}
+
+/// Validate a document.
+#[must_use]
+pub fn validate(doc: &Document) -> Report {
+    validate_with(doc, &Config::default())
+}
+
+/// [`validate`] with the rules of `config` too.
+#[must_use]
+pub fn validate_with(doc: &Document, config: &Config) -> Report {
+    run(
+        doc.items(),
+        &[],
+        doc.date_policy(),
+        doc.include_removed(),
+        config,
+        Report::default(),
+    )
+}
+
+/// Parse and validate a complete file. Unlike [`crate::Reader`], this
+/// doesn't stop at items that are out of order, and findings include
+/// line numbers.
+#[must_use]
+pub fn validate_bytes(data: &[u8]) -> Report {
+    validate_bytes_with(data, &Config::default())
+}
+
+/// [`validate_bytes`] with the rules of `config` too.
+#[must_use]
+pub fn validate_bytes_with(data: &[u8], config: &Config) -> Report {
+    let mut rest = Span::new(data);
+    let mut items = Vec::new();
+    let mut positions = Vec::new();
+    let mut report = Report::default();
+
+    loop {
+        let blank = rest
+            .iter()
+            .take_while(|&&c| is_whitespace(c) || is_line_break(c))
+            .count();
+        rest = rest.slice(blank..);
+
+        if rest.is_empty() {
+            break;
+        }
+
+        let line = rest.location_line();
+        let base = rest.location_offset() as u64;
+        match item::read(&rest, line, Options::default(), None, true).and_then(Decoded::complete) {
+            Ok(decoded) => {
+                positions.push(Position { line, offset: base });
+                items.push(decoded.item.into_owned());
+                rest = rest.slice(decoded.len..);
+            }
+            Err(e) => {
+                let label = |at: &[u8]| {
+                    let len = at
+                        .iter()
+                        .take_while(|&&c| !is_whitespace(c) && !is_line_break(c))
+                        .count();
+                    String::from_utf8_lossy(&at[..len]).into_owned()
+                };
+                let (message, line, column, offset) = match e {
+                    ReadError::Invalid(Invalid {
+                        problem: Problem::Amount(len),
+                        at,
+                        line,
+                        column,
+                        offset,
+                        ..
+                    }) => (
+                        format!("invalid amount `{}`", String::from_utf8_lossy(&at[..len])),
+                        line,
+                        column,
+                        offset,
+                    ),
+                    ReadError::Invalid(Invalid {
+                        problem: Problem::ExtraFields,
+                        line,
+                        column,
+                        offset,
+                        ..
+                    }) => ("extra fields".to_owned(), line, column, offset),
+                    ReadError::Invalid(Invalid {
+                        problem: Problem::SubEntry,
+                        at,
+                        line,
+                        column,
+                        offset,
+                        ..
+                    }) => (
+                        format!("invalid or incomplete sub-entry `{}`", label(at)),
+                        line,
+                        column,
+                        offset,
+                    ),
+                    _ => (
+                        format!("invalid or incomplete item `{}`", label(&rest)),
+                        line,
+                        rest.get_column(),
+                        0,
+                    ),
+                };
+                report.findings.push(Finding {
+                    rule: Rule::Parse,
+                    severity: Rule::Parse.severity(),
+                    message,
+                    citation: Rule::Parse.citation(),
+                    line: Some(line),
+                    column: Some(column),
+                    offset: Some(base + offset as u64),
+                });
+                break;
+            }
+        }
+    }
+
+    run(
+        &items,
+        &positions,
+        DatePolicy::default(),
+        false,
+        config,
+        report,
+    )
+}
+
+fn run(
+    items: &[Item],
+    positions: &[Position],
+    dates: DatePolicy,
+    removed: bool,
+    config: &Config,
+    report: Report,
+) -> Report {
+    let mut cx = Context {
+        items,
+        positions,
+        dates,
+        removed,
+        report,
+    };
+
+    order(&mut cx);
+    mandatory(&mut cx);
+    unbalanced_vouchers(&mut cx);
+    balances(&mut cx);
+    checksum(&mut cx);
+    dimensions(&mut cx);
+    currency(&mut cx);
+    if let Some(materiality) = &config.materiality {
+        material_transactions(&mut cx, materiality);
+    }
+
+    cx.report
+}
+
+fn order(cx: &mut Context) {
+    let mut group = Group::Flag;
+    // the first item of `group`
+    let mut first = Flagga::LABEL;
+
+    for (i, item) in cx.items.iter().enumerate() {
+        // see `Ksumma` on why it's exempt
+        if matches!(item, Item::Ksumma(_)) {
+            continue;
+        }
+
+        if item.group() < group {
+            cx.report(
+                Rule::Order,
+                Some(i),
+                format!(
+                    "#{} must appear before #{first}, since the {} group comes before the {group} group",
+                    item.label(),
+                    item.group(),
+                ),
+            );
+        } else if item.group() > group {
+            group = item.group();
+            first = item.label();
+        }
+    }
+}
+
+fn mandatory(cx: &mut Context) {
+    const MANDATORY: [&str; 6] = ["FLAGGA", "PROGRAM", "FORMAT", "GEN", "SIETYP", "FNAMN"];
+
+    for label in MANDATORY {
+        if !cx.items.iter().any(|item| item.label() == label) {
+            cx.report(Rule::Mandatory, None, format!("missing #{label}"));
+        }
+    }
+}
+
+fn unbalanced_vouchers(cx: &mut Context) {
+    for (i, item) in cx.items.iter().enumerate() {
+        let Item::Ver(ver) = item else {
+            continue;
+        };
+
+        let sum = ver
+            .counted(cx.removed)
+            .map(|trans| trans.amount)
+            .sum::<Decimal>();
+        let original = ver.original().map(|trans| trans.amount).sum::<Decimal>();
+
+        if !sum.is_zero() {
+            cx.report(
+                Rule::UnbalancedVoucher,
+                Some(i),
+                format!(
+                    "verification {} {} is unbalanced by {sum}",
+                    ver.series, ver.no
+                ),
+            );
+        } else if !original.is_zero() {
+            cx.report(
+                Rule::UnbalancedVoucher,
+                Some(i),
+                format!(
+                    "verification {} {} was unbalanced by {original} before it was corrected",
+                    ver.series, ver.no
+                ),
+            );
+        }
+    }
+}
+
+fn balances(cx: &mut Context) {
+    let current = cx
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Rar(rar) => Some(FiscalYear::from(rar)),
+            _ => None,
+        })
+        .collect::<FiscalYears>()
+        .current()
+        .copied();
+
+    let mut opening = BTreeMap::<AccountNo, Decimal>::new();
+    let mut previous_closing = BTreeMap::<AccountNo, Decimal>::new();
+    let mut closing = Vec::new();
+    let mut sums = BTreeMap::<AccountNo, Decimal>::new();
+
+    for (i, item) in cx.items.iter().enumerate() {
+        match item {
+            Item::Ib(ib) if ib.year == 0 => {
+                *opening.entry(ib.account).or_default() += ib.balance;
+            }
+            Item::Ub(ub) if ub.year == -1 => {
+                *previous_closing.entry(ub.account).or_default() += ub.balance;
+            }
+            Item::Ub(ub) if ub.year == 0 => closing.push((i, ub.account, ub.balance, true)),
+            Item::Res(res) if res.year == 0 => {
+                closing.push((i, res.account, res.balance, false));
+            }
+            Item::Ver(ver) => {
+                for trans in ver.counted(cx.removed) {
+                    let date = cx.dates.date(ver, trans);
+                    if current.is_none_or(|year| year.contains(date)) {
+                        *sums.entry(trans.account).or_default() += trans.amount;
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    for (i, account, balance, is_balance) in closing {
+        let change = sums.get(&account).copied().unwrap_or_default();
+        let expected = if is_balance {
+            opening.get(&account).copied().unwrap_or_default() + change
+        } else {
+            change
+        };
+
+        if balance != expected {
+            cx.report(
+                Rule::ClosingBalance,
+                Some(i),
+                format!(
+                    "{} of account {account} is {balance}, but should be {expected}",
+                    if is_balance {
+                        "closing balance"
+                    } else {
+                        "result"
+                    }
+                ),
+            );
+        }
+    }
+
+    for (account, previous) in previous_closing {
+        let balance = opening.get(&account).copied().unwrap_or_default();
+        if balance != previous {
+            cx.report(
+                Rule::OpeningBalance,
+                None,
+                format!(
+                    "opening balance of account {account} is {balance}, but the previous year closed at {previous}"
+                ),
+            );
+        }
+    }
+}
+
+fn checksum(cx: &mut Context) {
+    let sums = cx
+        .items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| match item {
+            Item::Ksumma(ksumma) => Some((i, ksumma.checksum)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    match sums[..] {
+        [] | [(_, None), (_, Some(_))] => (),
+        [(i, None)] => cx.report(
+            Rule::Checksum,
+            Some(i),
+            "#KSUMMA at the start of the file but no checksum at the end".to_owned(),
+        ),
+        [(i, Some(_)), ..] => cx.report(
+            Rule::Checksum,
+            Some(i),
+            "the first #KSUMMA must not have a checksum".to_owned(),
+        ),
+        [_, _, (i, _), ..] => cx.report(
+            Rule::Checksum,
+            Some(i),
+            "#KSUMMA may only appear twice".to_owned(),
+        ),
+        [_, (i, None)] => cx.report(
+            Rule::Checksum,
+            Some(i),
+            "the last #KSUMMA must have a checksum".to_owned(),
+        ),
+    }
+}
+
+fn dimensions(cx: &mut Context) {
+    let dims = cx.items.iter().filter_map(|item| match item {
+        Item::Dim(dim) => Some(dim),
+        _ => None,
+    });
+    let sub_dims = cx.items.iter().filter_map(|item| match item {
+        Item::UnderDim(dim) => Some(dim),
+        _ => None,
+    });
+    if let Err(e) = Dimensions::new(dims, sub_dims) {
+        cx.report(Rule::DimensionHierarchy, None, e.to_string());
+    }
+
+    let mut declared = cx
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Dim(dim) => Some(dim.no),
+            Item::UnderDim(dim) => Some(dim.no),
+            _ => None,
+        })
+        .collect::<BTreeSet<_>>();
+
+    for (i, item) in cx.items.iter().enumerate() {
+        let Item::Ver(ver) = item else {
+            continue;
+        };
+
+        for line in &ver.transactions.0 {
+            for (dimension, object) in &line.trans().objects.0 {
+                // only report the first use of each dimension
+                if declared.insert(*dimension) {
+                    cx.report(
+                        Rule::UndeclaredDimension,
+                        Some(i),
+                        format!(
+                            "object {:?} of verification {} {} belongs to undeclared dimension {dimension}",
+                            object, ver.series, ver.no
+                        ),
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn currency(cx: &mut Context) {
+    let mut book = None;
+    for (i, item) in cx.items.iter().enumerate() {
+        let Item::Valuta(valuta) = item else {
+            continue;
+        };
+        match book {
+            None => book = Some(valuta.currency),
+            Some(book) if book != valuta.currency => cx.report(
+                Rule::Currency,
+                Some(i),
+                format!(
+                    "#VALUTA {} conflicts with the earlier #VALUTA {}",
+                    valuta.currency.code(),
+                    book.code()
+                ),
+            ),
+            Some(_) => (),
+        }
+    }
+    let Some(book) = book else {
+        return;
+    };
+    let decimals = book.exponent().map(u32::from);
+
+    for (i, item) in cx.items.iter().enumerate() {
+        let balance = match item {
+            Item::Ib(ib) => ib.balance,
+            Item::Ub(ub) => ub.balance,
+            Item::Res(res) => res.balance,
+            Item::Oib(oib) => oib.balance,
+            Item::Oub(oub) => oub.balance,
+            Item::PSaldo(psaldo) => psaldo.balance,
+            Item::PBudget(pbudget) => pbudget.balance,
+            Item::Ver(ver) => {
+                for trans in ver.transactions.0.iter().map(TransLine::trans) {
+                    if decimals
+                        .is_some_and(|decimals| trans.amount.0.normalize().scale() > decimals)
+                    {
+                        cx.report(
+                            Rule::Currency,
+                            Some(i),
+                            format!(
+                                "transaction of {} on account {} in verification {} {} has more decimals than {}",
+                                trans.amount, trans.account, ver.series, ver.no, book.code()
+                            ),
+                        );
+                    }
+                    if let Some(foreign) = trans.foreign.filter(|foreign| {
+                        foreign.currency == book && foreign.amount != trans.amount
+                    }) {
+                        cx.report(
+                            Rule::Currency,
+                            Some(i),
+                            format!(
+                                "transaction of {} on account {} in verification {} {} has a foreign amount of {} {}, the currency of the books",
+                                trans.amount, trans.account, ver.series, ver.no, foreign.amount, book.code()
+                            ),
+                        );
+                    }
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        if decimals.is_some_and(|decimals| balance.0.normalize().scale() > decimals) {
+            cx.report(
+                Rule::Currency,
+                Some(i),
+                format!(
+                    "#{} of {balance} has more decimals than {}",
+                    item.label(),
+                    book.code()
+                ),
+            );
+        }
+    }
+}
+
+fn material_transactions(cx: &mut Context, materiality: &Materiality) {
+    let current = cx
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Rar(rar) => Some(FiscalYear::from(rar)),
+            _ => None,
+        })
+        .collect::<FiscalYears>()
+        .current()
+        .copied();
+
+    // revenue is credited, i.e. negative
+    let mut turnover = Decimal::ZERO;
+    for item in cx.items {
+        let Item::Ver(ver) = item else {
+            continue;
+        };
+        for trans in ver.counted(cx.removed) {
+            let date = cx.dates.date(ver, trans);
+            if trans.account.is_in(&(3000..=3799)) && current.is_none_or(|year| year.contains(date))
+            {
+                turnover -= trans.amount;
+            }
+        }
+    }
+
+    for (i, item) in cx.items.iter().enumerate() {
+        let Item::Ver(ver) = item else {
+            continue;
+        };
+
+        for trans in ver.counted(cx.removed) {
+            let Some(threshold) = materiality.threshold(trans.account) else {
+                continue;
+            };
+            let limit = match threshold {
+                Threshold::Amount(amount) => amount,
+                Threshold::PercentOfTurnover(_) if turnover <= Decimal::ZERO => continue,
+                Threshold::PercentOfTurnover(percent) => turnover * percent / Decimal::ONE_HUNDRED,
+            };
+
+            if trans.amount.abs().0 > limit {
+                cx.report(
+                    Rule::Materiality,
+                    Some(i),
+                    format!(
+                        "transaction of {} on account {} in verification {} {} exceeds the threshold of {threshold}",
+                        trans.amount, trans.account, ver.series, ver.no
+                    ),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &[u8] = b"#FLAGGA 0
+#KSUMMA
+#PROGRAM \"Vi iMproved\" 9.0
+#FORMAT PC8
+#GEN 20230320
+#SIETYP 4
+#FNAMN \"Bolaget AB\"
+#RAR 0 20230101 20231231
+#RAR -1 20220101 20221231
+#KONTO 1930 \"Bank\"
+#KONTO 5010 \"Lokalhyra\"
+#UB -1 1930 15000.00
+#IB 0 1930 15000.00
+#UB 0 1930 3000.00
+#RES 0 5010 12000.00
+#VER A 1 20230102 \"Rent\"
+{
+    #TRANS 1930 {} -12000.00
+    #TRANS 5010 {} 12000.00
+}
+#KSUMMA 1234567890
+";
+
+    fn rules(report: &Report) -> Vec<(Rule, Option<u32>)> {
+        report
+            .findings
+            .iter()
+            .map(|finding| (finding.rule, finding.line))
+            .collect()
+    }
+
+    #[test]
+    fn valid() {
+        let report = validate_bytes(VALID);
+        assert_eq!(report, Report::default());
+    }
+
+    #[test]
+    fn invalid() {
+        let data = String::from_utf8(VALID.to_vec())
+            .unwrap()
+            .replace("#IB 0 1930 15000.00", "#IB 0 1930 14000.00")
+            .replace("#TRANS 5010 {} 12000.00", "#TRANS 5010 {} 12000.01")
+            .replace("#KSUMMA 1234567890\n", "#KONTO 1910 \"Kassa\"\n")
+            .replace("#SIETYP 4\n", "");
+        let report = validate_bytes(data.as_bytes());
+
+        assert_eq!(
+            rules(&report),
+            [
+                (Rule::Order, Some(20)),
+                (Rule::Mandatory, None),
+                (Rule::UnbalancedVoucher, Some(15)),
+                (Rule::ClosingBalance, Some(13)),
+                (Rule::ClosingBalance, Some(14)),
+                (Rule::OpeningBalance, None),
+                (Rule::Checksum, Some(2)),
+            ]
+        );
+        assert_eq!(report.count(Severity::Warning), 1);
+        assert_eq!(
+            report.findings[0].message,
+            "#KONTO must appear before #UB, since the chart of accounts group comes before the balances and verifications group"
+        );
+    }
+
+    #[test]
+    fn corrections() {
+        let data = String::from_utf8(VALID.to_vec()).unwrap().replace(
+            "#TRANS 5010 {} 12000.00\n",
+            "#BTRANS 5010 {} 12000.00\n#RTRANS 5010 {} 12000.00 20230103\n#TRANS 5010 {} 12000.00 20230103\n",
+        );
+        assert_eq!(validate_bytes(data.as_bytes()), Report::default());
+
+        let data = data.replace("#BTRANS 5010 {} 12000.00", "#BTRANS 5010 {} 1.00");
+        let report = validate_bytes(data.as_bytes());
+        assert_eq!(rules(&report), [(Rule::UnbalancedVoucher, Some(16))]);
+        assert!(report.findings[0]
+            .message
+            .contains("before it was corrected"));
+    }
+
+    #[test]
+    fn parse_error() {
+        let report = validate_bytes(b"#FLAGGA 0\n\n#KONTO 19-30 \"Bank\"\n");
+        assert_eq!(report.findings[0].rule, Rule::Parse);
+        assert_eq!(report.findings[0].line, Some(3));
+        assert_eq!(report.findings[0].citation, Rule::Parse.citation());
+        assert_eq!(
+            report.findings[0].message,
+            "invalid or incomplete item `#KONTO`"
+        );
+
+        let report = validate_bytes(
+            b"#VER A 1 20230101\n{\n#TRANS 1930 {} 1.00\n#TRANS 19-30 {} -1.00\n}\n",
+        );
+        assert_eq!(report.findings[0].line, Some(4));
+        assert_eq!(
+            report.findings[0].message,
+            "invalid or incomplete sub-entry `#TRANS`"
+        );
+
+        let report =
+            validate_bytes(b"#VER A 1 20230101\n{\n#TRANS 1930 {} 1.00\n#TRANS 3001 {} -1,00\n}\n");
+        assert_eq!(report.findings[0].line, Some(4));
+        assert_eq!(report.findings[0].message, "invalid amount `-1,00`");
+    }
+
+    #[test]
+    fn dimensions() {
+        let report = validate_bytes(
+            b"#DIM 1 \"Kostnadsst\x84lle\"
+#UNDERDIM 2 \"Kostnadsb\x84rare\" 1
+#UNDERDIM 3 \"Produkt\" 4
+#VER A 1 20230102
+{
+    #TRANS 1930 {1 Z1 2 B1} -100.00
+    #TRANS 5010 {6 P1} 100.00
+}
+#VER A 2 20230103
+{
+    #TRANS 1930 {6 P2} -100.00
+    #TRANS 5010 {6 P2} 100.00
+}
+",
+        );
+        let findings = report
+            .findings
+            .iter()
+            .filter(|finding| finding.rule != Rule::Mandatory)
+            .map(|finding| (finding.rule, finding.line, finding.message.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            findings,
+            [
+                (
+                    Rule::DimensionHierarchy,
+                    None,
+                    "dimension 3 is part of dimension 4, which isn't declared"
+                ),
+                (
+                    Rule::UndeclaredDimension,
+                    Some(4),
+                    "object \"P1\" of verification A 1 belongs to undeclared dimension 6"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn materiality() {
+        let data = b"#RAR 0 20230101 20231231
+#VER A 1 20230102
+{
+    #TRANS 1930 {} 100000.00
+    #TRANS 3010 {} -100000.00
+}
+#VER A 2 20230103
+{
+    #TRANS 1930 {} -12000.00
+    #TRANS 5010 {} 12000.00
+}
+#VER A 3 20230104
+{
+    #TRANS 1930 {} -4000.00
+    #TRANS 6110 {} 4000.00
+}
+";
+        let findings = |materiality| {
+            validate_bytes_with(
+                data,
+                &Config {
+                    materiality: Some(materiality),
+                },
+            )
+            .findings
+            .into_iter()
+            .filter(|finding| finding.rule == Rule::Materiality)
+            .map(|finding| (finding.line, finding.message))
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            findings(Materiality {
+                default: Some("5%".parse().unwrap()),
+                classes: BTreeMap::from([
+                    (1, "50000".parse().unwrap()),
+                    (3, "1000000".parse().unwrap()),
+                ]),
+            }),
+            [
+                (
+                    Some(2),
+                    "transaction of 100000.00 on account 1930 in verification A 1 exceeds the threshold of 50000"
+                        .to_owned()
+                ),
+                (
+                    Some(7),
+                    "transaction of 12000.00 on account 5010 in verification A 2 exceeds the threshold of 5% of the turnover"
+                        .to_owned()
+                ),
+            ]
+        );
+        assert!(findings(Materiality::default()).is_empty());
+        assert!(validate_bytes(data)
+            .findings
+            .iter()
+            .all(|finding| finding.rule != Rule::Materiality));
+
+        assert_eq!(
+            "2.5 %".parse(),
+            Ok(Threshold::PercentOfTurnover(Decimal::new(25, 1)))
+        );
+        assert!("2.5 percent".parse::<Threshold>().is_err());
+    }
+
+    #[test]
+    fn currency() {
+        let data = b"#VALUTA JPY
+#VALUTA EUR
+#IB 0 1930 1000.00
+#UB 0 1930 1000.50
+#VER A 1 20230102
+{
+    #TRANS 1930 {} 100.5
+    #TRANS 3010 {} -100.5
+}
+";
+        let findings = |report: Report| {
+            report
+                .findings
+                .into_iter()
+                .filter(|finding| finding.rule == Rule::Currency)
+                .map(|finding| (finding.line, finding.message))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            findings(validate_bytes(data)),
+            [
+                (
+                    Some(2),
+                    "#VALUTA EUR conflicts with the earlier #VALUTA JPY".to_owned()
+                ),
+                (
+                    Some(4),
+                    "#UB of 1000.50 has more decimals than JPY".to_owned()
+                ),
+                (
+                    Some(5),
+                    "transaction of 100.5 on account 1930 in verification A 1 has more decimals than JPY"
+                        .to_owned()
+                ),
+                (
+                    Some(5),
+                    "transaction of -100.5 on account 3010 in verification A 1 has more decimals than JPY"
+                        .to_owned()
+                ),
+            ]
+        );
+
+        // only checked with a #VALUTA
+        assert!(findings(validate_bytes(&data[24..])).is_empty());
+
+        let mut doc = Document::from_reader(&data[12..]).unwrap();
+        doc.set_foreign_amounts(|_, trans| {
+            Some(crate::currency::ForeignAmount {
+                currency: iso_currency::Currency::EUR,
+                amount: trans.amount.0.round(),
+            })
+        });
+        assert_eq!(
+            findings(validate(&doc)),
+            [(
+                None,
+                "transaction of 100.5 on account 1930 in verification A 1 has a foreign amount of 100 EUR, the currency of the books"
+                    .to_owned()
+            ),
+             (
+                None,
+                "transaction of -100.5 on account 3010 in verification A 1 has a foreign amount of -100 EUR, the currency of the books"
+                    .to_owned()
+            )]
+        );
+    }
+}
@@ -0,0 +1,325 @@
+//! The boxes (rutor) of the Swedish VAT declaration, summed from the
+//! transactions of a period. The vouchers that settle the VAT of an
+//! earlier period, by moving it to the settlement account (2650 in BAS),
+//! are left out, see [`Rules::settlement`].
+//!
+//! ```
+//! use rust_decimal_macros::dec;
+//! use sie4::{vat::Report, Document};
+//! use time::macros::date;
+//!
+//! let doc = Document::from_reader(&b"#VER A 1 20230110 \"Sale\"
+//! {
+//!     #TRANS 1510 {} 1250.00
+//!     #TRANS 3001 {} -1000.00
+//!     #TRANS 2611 {} -250.00
+//! }
+//! #VER A 2 20230115 \"Purchase\"
+//! {
+//!     #TRANS 1930 {} -500.00
+//!     #TRANS 4010 {} 400.00
+//!     #TRANS 2641 {} 100.00
+//! }
+//! "[..])
+//! .unwrap();
+//!
+//! let report = Report::compute(&doc, date!(2023 - 01 - 01)..=date!(2023 - 01 - 31));
+//! assert_eq!(report.get(5), dec!(1000.00));
+//! assert_eq!(report.get(10), dec!(250.00));
+//! assert_eq!(report.get(48), dec!(100.00));
+//! assert_eq!(report.get(49), dec!(150.00));
+//! ```
+
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use time::Date;
+
+use crate::{
+    item::{AccountNo, Ver},
+    Document,
+};
+
+/// The box of the VAT to pay (or to get back, if negative), see
+/// [`Report::vat_to_pay`].
+pub const VAT_TO_PAY: u8 = 49;
+
+/// The boxes of output VAT: on sales (10 to 12), on purchases with
+/// reverse charge (30 to 32) and on imports (60 to 62).
+pub const OUTPUT_VAT: [u8; 9] = [10, 11, 12, 30, 31, 32, 60, 61, 62];
+
+/// The box of the input VAT to deduct.
+pub const INPUT_VAT: u8 = 48;
+
+/// The accounts that are summed into a box.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Rule {
+    pub accounts: Vec<RangeInclusive<u32>>,
+    /// Whether the box is of credits, e.g. sales and output VAT, which
+    /// are negative in the books but positive in the declaration.
+    pub credit: bool,
+}
+
+/// The rules of the boxes, by number.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Rules {
+    boxes: BTreeMap<u8, Rule>,
+    settlement: Vec<RangeInclusive<u32>>,
+}
+
+impl Rules {
+    /// No boxes at all, and no settlement accounts.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            boxes: BTreeMap::new(),
+            settlement: Vec::new(),
+        }
+    }
+
+    /// The accounts of the BAS chart of accounts for the common boxes.
+    #[must_use]
+    pub fn bas() -> Self {
+        let boxes: [(u8, &[RangeInclusive<u32>], bool); 20] = [
+            // sales
+            (5, &[3000..=3003, 3010..=3099], true),
+            (6, &[3200..=3299], true),
+            (8, &[3400..=3499], true),
+            // output VAT on sales
+            (10, &[2610..=2613, 2616..=2619], true),
+            (11, &[2620..=2623, 2626..=2629], true),
+            (12, &[2630..=2633, 2636..=2639], true),
+            // purchases with reverse charge
+            (20, &[4515..=4517], false),
+            (21, &[4535..=4537], false),
+            (22, &[4531..=4533], false),
+            (23, &[4545..=4547], false),
+            (24, &[4425..=4427], false),
+            // output VAT on purchases
+            (30, &[2614..=2614], true),
+            (31, &[2624..=2624], true),
+            (32, &[2634..=2634], true),
+            // sales exempt from VAT
+            (35, &[3108..=3108], true),
+            (36, &[3105..=3105], true),
+            (39, &[3308..=3308], true),
+            (41, &[3231..=3231], true),
+            (
+                42,
+                &[3004..=3004, 3100..=3104, 3106..=3107, 3300..=3307],
+                true,
+            ),
+            // input VAT
+            (INPUT_VAT, &[2640..=2649], false),
+        ];
+
+        let mut rules = Self::new();
+        rules.set_settlement(vec![2650..=2650]);
+        for (no, accounts, credit) in boxes {
+            rules.insert(
+                no,
+                Rule {
+                    accounts: accounts.to_vec(),
+                    credit,
+                },
+            );
+        }
+        rules
+    }
+
+    /// Set the rule of box `no`, replacing any previous one.
+    pub fn insert(&mut self, no: u8, rule: Rule) {
+        self.boxes.insert(no, rule);
+    }
+
+    #[must_use]
+    pub fn get(&self, no: u8) -> Option<&Rule> {
+        self.boxes.get(&no)
+    }
+
+    /// The settlement accounts, which the VAT of a period is moved to
+    /// when it is declared. Vouchers with transactions on them settle an
+    /// earlier period, and aren't summed into any box.
+    #[must_use]
+    pub fn settlement(&self) -> &[RangeInclusive<u32>] {
+        &self.settlement
+    }
+
+    /// Replace the settlement accounts, see [`Rules::settlement`].
+    pub fn set_settlement(&mut self, accounts: Vec<RangeInclusive<u32>>) {
+        self.settlement = accounts;
+    }
+
+    /// Whether `ver` settles the VAT of an earlier period, see
+    /// [`Rules::settlement`]. Its removed transactions are looked at too
+    /// if `include_removed`, see [`Ver::counted`].
+    #[must_use]
+    pub fn is_settlement(&self, ver: &Ver, include_removed: bool) -> bool {
+        ver.counted(include_removed).any(|trans| {
+            self.settlement
+                .iter()
+                .any(|range| trans.account.is_in(range))
+        })
+    }
+
+    /// The boxes that `account` is summed into.
+    pub fn boxes_of(&self, account: impl Into<AccountNo>) -> impl Iterator<Item = u8> + '_ {
+        let account = account.into();
+        self.boxes
+            .iter()
+            .filter(move |(_, rule)| rule.accounts.iter().any(|range| account.is_in(range)))
+            .map(|(&no, _)| no)
+    }
+}
+
+/// See [`Rules::bas`].
+impl Default for Rules {
+    fn default() -> Self {
+        Self::bas()
+    }
+}
+
+/// The boxes of a VAT declaration. Amounts are neither rounded nor
+/// truncated to whole kronor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Report {
+    pub period: RangeInclusive<Date>,
+    /// The amounts of the boxes with any transactions, and
+    /// [`VAT_TO_PAY`], by number.
+    pub boxes: BTreeMap<u8, Decimal>,
+}
+
+impl Report {
+    /// The report of the transactions within `period` with the
+    /// [BAS rules](Rules::bas).
+    #[must_use]
+    pub fn compute(doc: &Document, period: RangeInclusive<Date>) -> Self {
+        Self::compute_with(doc, period, &Rules::bas())
+    }
+
+    /// The report of the transactions within `period`, including
+    /// corrections, dated by the [date policy](Document::date_policy) of
+    /// `doc`. The settlement vouchers are left out, see
+    /// [`Rules::settlement`].
+    #[must_use]
+    pub fn compute_with(doc: &Document, period: RangeInclusive<Date>, rules: &Rules) -> Self {
+        let policy = doc.date_policy();
+        let removed = doc.include_removed();
+        let mut boxes = BTreeMap::<u8, Decimal>::new();
+
+        for ver in doc
+            .vouchers()
+            .filter(|ver| !rules.is_settlement(ver, removed))
+        {
+            for trans in ver.counted(removed) {
+                if !period.contains(&policy.date(ver, trans)) {
+                    continue;
+                }
+                for no in rules.boxes_of(trans.account) {
+                    let amount = if rules.boxes[&no].credit {
+                        -trans.amount
+                    } else {
+                        trans.amount
+                    };
+                    *boxes.entry(no).or_default() += amount;
+                }
+            }
+        }
+
+        let output = OUTPUT_VAT
+            .iter()
+            .filter_map(|no| boxes.get(no))
+            .sum::<Decimal>();
+        let input = boxes.get(&INPUT_VAT).copied().unwrap_or_default();
+        boxes.insert(VAT_TO_PAY, output - input);
+
+        Self { period, boxes }
+    }
+
+    /// The amount of box `no`, which is zero for the boxes without
+    /// transactions.
+    #[must_use]
+    pub fn get(&self, no: u8) -> Decimal {
+        self.boxes.get(&no).copied().unwrap_or_default()
+    }
+
+    /// The output VAT less the input VAT (box 49).
+    #[must_use]
+    pub fn vat_to_pay(&self) -> Decimal {
+        self.get(VAT_TO_PAY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+    use time::macros::date;
+
+    use super::*;
+
+    #[test]
+    fn boxes() {
+        let doc = Document::from_reader(
+            &b"#VER A 1 20230110
+{
+    #TRANS 1510 {} 1120.00
+    #TRANS 3002 {} -1000.00
+    #TRANS 2621 {} -120.00
+}
+#VER A 2 20230120
+{
+    #TRANS 4515 {} 2000.00
+    #TRANS 2614 {} -500.00
+    #BTRANS 2645 {} 500.00
+    #RTRANS 2645 {} 400.00
+    #TRANS 2645 {} 400.00
+    #TRANS 2440 {} -1900.00
+    #TRANS 2660 {} 10.00
+    #TRANS 1930 {} -10.00
+}
+#VER A 3 20230131 \"VAT settlement\"
+{
+    #TRANS 2621 {} 120.00
+    #TRANS 2614 {} 500.00
+    #TRANS 2645 {} -400.00
+    #TRANS 2650 {} -220.00
+}
+#VER A 4 20230201
+{
+    #TRANS 3001 {} -100.00
+    #TRANS 2611 {} -25.00
+    #TRANS 1930 {} 125.00
+}
+"[..],
+        )
+        .unwrap();
+
+        let report = Report::compute(&doc, date!(2023 - 01 - 01)..=date!(2023 - 01 - 31));
+        assert_eq!(
+            report.boxes,
+            BTreeMap::from([
+                (5, dec!(1000.00)),
+                (11, dec!(120.00)),
+                (20, dec!(2000.00)),
+                (30, dec!(500.00)),
+                (48, dec!(400.00)),
+                (49, dec!(220.00)),
+            ])
+        );
+        assert_eq!(report.get(10), dec!(0));
+
+        let mut rules = Rules::new();
+        rules.insert(
+            10,
+            Rule {
+                accounts: vec![2600..=2699],
+                credit: true,
+            },
+        );
+        let report =
+            Report::compute_with(&doc, date!(2023 - 02 - 01)..=date!(2023 - 02 - 28), &rules);
+        assert_eq!(report.get(10), dec!(25.00));
+        assert_eq!(report.vat_to_pay(), dec!(25.00));
+    }
+}
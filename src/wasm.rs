@@ -0,0 +1,27 @@
+//! Bindings for JavaScript, e.g. to parse files that are uploaded to a
+//! web page without sending them to a server.
+//!
+//! ```js
+//! import { parse_sie4 } from "sie4";
+//!
+//! const bytes = new Uint8Array(await file.arrayBuffer());
+//! const { items } = parse_sie4(bytes);
+//! ```
+
+use wasm_bindgen::prelude::*;
+
+use crate::Document;
+
+/// Parse a complete file into the JSON representation of its
+/// [`Document`], as an object with the items in order.
+///
+/// # Errors
+///
+/// Throws the first error encountered by the
+/// [`SliceReader`](crate::SliceReader).
+#[wasm_bindgen]
+pub fn parse_sie4(bytes: &[u8]) -> Result<JsValue, JsError> {
+    let doc = Document::from_slice(bytes)?;
+    let json = serde_json::to_string(&doc)?;
+    js_sys::JSON::parse(&json).map_err(|_| JsError::new("invalid JSON"))
+}
@@ -0,0 +1,879 @@
+//! Writing items in the SIE4 format.
+//!
+//! ```
+//! use sie4::{writer::{Writer, WriterProfile}, Item, SliceReader};
+//!
+//! let data = b"#FLAGGA 0\r\n#KONTO 1930 \"F\x94retagskonto\"\r\n";
+//! let mut writer = Writer::with_profile(Vec::new(), WriterProfile::SpecStrict);
+//! for item in SliceReader::new(data) {
+//!     writer.write_item(&item.unwrap().into_owned()).unwrap();
+//! }
+//! assert_eq!(writer.into_inner(), data);
+//! ```
+
+use std::{fmt, io::Write};
+
+use codepage_437::{ToCp437, CP437_CONTROL};
+use iso_currency::Currency;
+use rust_decimal::{Decimal, RoundingStrategy};
+use time::Date;
+
+use crate::{
+    amount::Amount,
+    company::OrgNr,
+    custom::{Custom, Value},
+    item::{
+        AccountKind, AccountNo, ChartAccountsType, FormatType, Group, ItemType, Ksumma, List,
+        Object, Period, SubEntries, TransLine, TypeNo, Ver,
+    },
+    parsers::{is_line_break, is_whitespace, DATE_FORMAT},
+    sni::SniCode,
+    Item,
+};
+
+/// When to put text fields in quotes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quote {
+    /// Quote all text fields, even numeric-looking ones like the
+    /// organization number.
+    Always,
+    /// Only quote text that would otherwise be read differently, e.g.
+    /// because it contains spaces or is empty.
+    WhenNeeded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+        }
+    }
+}
+
+/// How many decimals amounts are written with, see [`AmountFormat`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Decimals {
+    /// As many as the amount has, e.g. as many as it was read with.
+    #[default]
+    Unchanged,
+    /// Always two, e.g. `100.00` and `0.50`.
+    Two,
+    /// At most two, without trailing zeros, e.g. `100` and `0.5`.
+    Minimal,
+}
+
+/// How amounts are written, for importers that reject amounts with more
+/// than two decimals or a sign on zero. Quantities are written as they
+/// are.
+///
+/// ```
+/// use rust_decimal::RoundingStrategy;
+/// use rust_decimal_macros::dec;
+/// use sie4::writer::{AmountFormat, Decimals};
+///
+/// let format = AmountFormat {
+///     decimals: Decimals::Two,
+///     rounding: RoundingStrategy::ToZero,
+///     plus_sign: true,
+/// };
+/// assert_eq!(format.format(dec!(10.125)), "+10.12");
+/// assert_eq!(format.format(dec!(-0.001)), "0.00");
+/// assert_eq!(AmountFormat::new().format(dec!(-0.000)), "0.000");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AmountFormat {
+    pub decimals: Decimals,
+    /// How amounts with more than two decimals are rounded, unless the
+    /// decimals are [`Unchanged`](Decimals::Unchanged).
+    pub rounding: RoundingStrategy,
+    /// Write `+` before positive amounts. Zero is written without a
+    /// sign either way.
+    pub plus_sign: bool,
+}
+
+impl AmountFormat {
+    /// Amounts as they are, rounded half away from zero if the decimals
+    /// are changed.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            decimals: Decimals::Unchanged,
+            rounding: RoundingStrategy::MidpointAwayFromZero,
+            plus_sign: false,
+        }
+    }
+
+    #[must_use]
+    pub fn format(&self, amount: Decimal) -> String {
+        let mut amount = match self.decimals {
+            Decimals::Unchanged => amount,
+            Decimals::Two | Decimals::Minimal => amount.round_dp_with_strategy(2, self.rounding),
+        };
+        match self.decimals {
+            Decimals::Unchanged => (),
+            Decimals::Two => amount.rescale(2),
+            Decimals::Minimal => amount = amount.normalize(),
+        }
+        if amount.is_zero() {
+            amount.set_sign_positive(true);
+        }
+
+        if self.plus_sign && amount.is_sign_positive() && !amount.is_zero() {
+            format!("+{amount}")
+        } else {
+            amount.to_string()
+        }
+    }
+}
+
+/// See [`AmountFormat::new`].
+impl Default for AmountFormat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Which items may follow each other, see [`Style::order`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Order {
+    /// In ascending order by [`Group`], as the specification requires
+    /// and the [`Reader`](crate::Reader) expects. Writing an item of a
+    /// lower group than the previous one returns [`Error::OutOfOrder`].
+    #[default]
+    ByGroup,
+    /// In the order they are written.
+    AsWritten,
+}
+
+/// Formatting options. All combinations produce files that follow the
+/// specification, except for [`Order::AsWritten`] with items out of
+/// order; they only differ in ways that importers may be picky about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    pub quote: Quote,
+    pub line_ending: LineEnding,
+    /// Written before each sub-entry, e.g. each `#TRANS` of a `#VER`.
+    pub indent: &'static str,
+    /// Write empty (`""`) optional fields up to the last field of each
+    /// item, instead of leaving out the trailing ones.
+    pub pad_fields: bool,
+    /// Write an empty line before the first item of each [`Group`],
+    /// except the first one written.
+    pub blank_lines: bool,
+    pub order: Order,
+    pub amounts: AmountFormat,
+}
+
+/// Predefined [`Style`]s. Programs that are picky about other details
+/// can be written for with [`Custom`](Self::Custom).
+///
+/// There are no presets for particular programs, such as Fortnox, Visma
+/// or BL Administration. Their importers don't document what they accept
+/// beyond the specification, which [`SpecStrict`](Self::SpecStrict)
+/// follows, and a preset named after a program would claim that its
+/// output had been checked against that program's importer. A style
+/// that a program turns out to need, e.g. sub-entries indented by a tab,
+/// is a [`Custom`](Self::Custom) one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WriterProfile {
+    /// Quoted text, CR LF line endings, no indentation, no padding and
+    /// no blank lines, with items in group order.
+    #[default]
+    SpecStrict,
+    /// Quotes only where needed, LF line endings and sub-entries indented
+    /// by four spaces, for reading rather than importing.
+    Readable,
+    Custom(Style),
+}
+
+impl WriterProfile {
+    #[must_use]
+    pub const fn style(self) -> Style {
+        match self {
+            Self::SpecStrict => Style {
+                quote: Quote::Always,
+                line_ending: LineEnding::CrLf,
+                indent: "",
+                pad_fields: false,
+                blank_lines: false,
+                order: Order::ByGroup,
+                amounts: AmountFormat::new(),
+            },
+            Self::Readable => Style {
+                quote: Quote::WhenNeeded,
+                line_ending: LineEnding::Lf,
+                indent: "    ",
+                pad_fields: false,
+                blank_lines: false,
+                order: Order::ByGroup,
+                amounts: AmountFormat::new(),
+            },
+            Self::Custom(style) => style,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(std::io::Error),
+    /// The text contains line breaks or characters that aren't in
+    /// code page 437.
+    #[error("{0:?} cannot be represented in SIE4")]
+    Unrepresentable(String),
+    /// The item labeled `label` is of a lower group than the item
+    /// labeled `after`, the first of its group to be written. See
+    /// [`Order::ByGroup`].
+    #[error("`#{label}` must be written before `#{after}`")]
+    OutOfOrder {
+        label: &'static str,
+        after: &'static str,
+    },
+}
+
+/// Writes items to `W`, encoded in code page 437.
+///
+/// Like the [`Reader`](crate::Reader), the writer requires items to be
+/// in ascending order by [`Group`] unless told otherwise with
+/// [`Style::order`].
+pub struct Writer<W: Write> {
+    inner: W,
+    style: Style,
+    group: Group,
+    /// The label of the first item of `group`, if any item has been
+    /// written.
+    first: Option<&'static str>,
+}
+
+impl<W: Write> Writer<W> {
+    /// A writer using [`WriterProfile::SpecStrict`].
+    pub fn new(inner: W) -> Self {
+        Self::with_profile(inner, WriterProfile::SpecStrict)
+    }
+
+    pub fn with_profile(inner: W, profile: WriterProfile) -> Self {
+        Self {
+            inner,
+            style: profile.style(),
+            group: Group::Flag,
+            first: None,
+        }
+    }
+
+    /// Write an item, including its sub-entries. Nothing is written if an
+    /// error is returned, unless it's an [`Error::Io`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails, if the item is out of order or
+    /// if it contains text that can't be written (see
+    /// [`Error::Unrepresentable`]).
+    pub fn write_item(&mut self, item: &Item) -> Result<(), Error> {
+        self.write(item.label(), item.group(), item)
+    }
+
+    /// Write a verification with all of its transactions, without first
+    /// turning it into an [`Item`]. See [`Writer::write_item`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Writer::write_item`].
+    pub fn write_ver(&mut self, ver: &Ver) -> Result<(), Error> {
+        self.write(Ver::LABEL, Ver::GROUP, ver)
+    }
+
+    fn write(
+        &mut self,
+        label: &'static str,
+        group: Group,
+        item: &dyn WriteFields,
+    ) -> Result<(), Error> {
+        // the whole item is encoded before anything is written
+        let mut buf = Vec::new();
+        for line in lines(&self.style, label, item)? {
+            self.encode_line(&mut buf, &line)?;
+        }
+
+        // see `Ksumma` on why it's exempt
+        if label != Ksumma::LABEL {
+            match self.first {
+                Some(after) if self.style.order == Order::ByGroup && self.group > group => {
+                    return Err(Error::OutOfOrder { label, after });
+                }
+                Some(_) if self.group == group => (),
+                first => {
+                    if first.is_some() && self.style.blank_lines {
+                        buf.splice(0..0, self.style.line_ending.as_str().bytes());
+                    }
+                    self.group = group;
+                    self.first = Some(label);
+                }
+            }
+        }
+
+        self.inner.write_all(&buf).map_err(Error::Io)
+    }
+
+    fn encode_line(&self, buf: &mut Vec<u8>, line: &str) -> Result<(), Error> {
+        // text fields have already been checked
+        let bytes = line
+            .to_cp437(&CP437_CONTROL)
+            .map_err(|_| Error::Unrepresentable(line.to_owned()))?;
+
+        buf.extend_from_slice(&bytes);
+        buf.extend_from_slice(self.style.line_ending.as_str().as_bytes());
+        Ok(())
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the underlying writer can't be flushed.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush().map_err(Error::Io)
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// The lines of `item`, without line endings: the label and fields,
+/// followed by the sub-entries in braces if it has any.
+fn lines(style: &Style, label: &str, item: &dyn WriteFields) -> Result<Vec<String>, Error> {
+    let mut line = Line::new(style);
+    item.write_fields(&mut line)?;
+    let (fields, sub_entries) = line.finish();
+
+    let mut lines = vec![format!("#{label}{fields}")];
+    if let Some(sub_entries) = sub_entries {
+        lines.push("{".to_owned());
+        lines.extend(
+            sub_entries
+                .iter()
+                .map(|entry| format!("{}{entry}", style.indent)),
+        );
+        lines.push("}".to_owned());
+    }
+    Ok(lines)
+}
+
+/// Format `item` as [`WriterProfile::SpecStrict`] would write it, but
+/// with `\n` between the lines and none after the last. Fails if the
+/// item can't be written, see [`Error::Unrepresentable`].
+pub(crate) fn display(label: &str, item: &dyn WriteFields, f: &mut fmt::Formatter) -> fmt::Result {
+    let lines = lines(&WriterProfile::SpecStrict.style(), label, item).map_err(|_| fmt::Error)?;
+    f.write_str(&lines.join("\n"))
+}
+
+/// The fields of an item being written.
+pub(crate) struct Line<'a> {
+    style: &'a Style,
+    buf: String,
+    /// Empty optional fields not yet written, since they can be left out
+    /// if no other fields follow.
+    empty: usize,
+    sub_entries: Option<Vec<String>>,
+}
+
+impl<'a> Line<'a> {
+    fn new(style: &'a Style) -> Self {
+        Self {
+            style,
+            buf: String::new(),
+            empty: 0,
+            sub_entries: None,
+        }
+    }
+
+    fn flush_empty(&mut self) {
+        for _ in 0..self.empty {
+            self.buf.push_str(" \"\"");
+        }
+        self.empty = 0;
+    }
+
+    fn raw(&mut self, s: &str) {
+        self.flush_empty();
+        self.buf.push(' ');
+        self.buf.push_str(s);
+    }
+
+    fn text(&mut self, s: &str) -> Result<(), Error> {
+        if s.contains(|c: char| is_line_break_char(c)) || s.to_cp437(&CP437_CONTROL).is_err() {
+            return Err(Error::Unrepresentable(s.to_owned()));
+        }
+
+        let needs_quotes = s.is_empty()
+            || s.contains(|c: char| {
+                c.is_ascii() && is_whitespace(c as u8) || matches!(c, '"' | '{' | '}' | '#')
+            });
+
+        if self.style.quote == Quote::Always || needs_quotes {
+            let escaped = s.replace('\\', "\\\\").replace('"', "\\\"");
+            self.raw(&format!("\"{escaped}\""));
+        } else {
+            self.raw(s);
+        }
+
+        Ok(())
+    }
+
+    fn empty(&mut self) {
+        self.empty += 1;
+    }
+
+    /// The fields, each preceded by a space, and the sub-entries.
+    fn finish(mut self) -> (String, Option<Vec<String>>) {
+        if self.style.pad_fields {
+            self.flush_empty();
+        }
+
+        (self.buf, self.sub_entries)
+    }
+}
+
+fn is_line_break_char(c: char) -> bool {
+    c.is_ascii() && is_line_break(c as u8)
+}
+
+pub(crate) trait WriteField {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error>;
+}
+
+/// Implemented for all items, and [`Item`], by `item_impl!`.
+pub(crate) trait WriteFields {
+    fn write_fields(&self, line: &mut Line) -> Result<(), Error>;
+}
+
+impl WriteField for String {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+        line.text(self)
+    }
+}
+
+impl WriteField for bool {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+        line.raw(if *self { "1" } else { "0" });
+        Ok(())
+    }
+}
+
+macro_rules! write_display_impl {
+    ($($ty:ty),*) => {
+        $(
+            impl WriteField for $ty {
+                fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+                    line.raw(&self.to_string());
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+write_display_impl!(i32, i64, u32, AccountNo, Decimal, Period);
+
+impl WriteField for Amount {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+        line.raw(&line.style.amounts.format(self.0));
+        Ok(())
+    }
+}
+
+impl WriteField for Date {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+        line.raw(&self.format(DATE_FORMAT).expect("date is formattable"));
+        Ok(())
+    }
+}
+
+impl WriteField for Currency {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+        line.raw(self.code());
+        Ok(())
+    }
+}
+
+impl WriteField for SniCode {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+        line.raw(&self.to_string());
+        Ok(())
+    }
+}
+
+impl WriteField for OrgNr {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+        line.text(&self.to_string())
+    }
+}
+
+impl WriteField for FormatType {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+        line.raw(match self {
+            Self::PC8 => "PC8",
+        });
+        Ok(())
+    }
+}
+
+impl WriteField for TypeNo {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+        line.raw(match self {
+            Self::SIE4 => "4",
+        });
+        Ok(())
+    }
+}
+
+impl WriteField for ChartAccountsType {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+        line.raw(match self {
+            Self::Bas95 => "BAS95",
+            Self::Bas96 => "BAS96",
+            Self::EuBas97 => "EUBAS97",
+            Self::Ne2007 => "NE2007",
+        });
+        Ok(())
+    }
+}
+
+impl WriteField for AccountKind {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+        line.raw(match self {
+            Self::Asset => "T",
+            Self::Liability => "S",
+            Self::Cost => "K",
+            Self::Income => "I",
+        });
+        Ok(())
+    }
+}
+
+impl<T: WriteField> WriteField for Option<T> {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+        if let Some(value) = self {
+            value.write_field(line)
+        } else {
+            line.empty();
+            Ok(())
+        }
+    }
+}
+
+impl<T: WriteField> WriteField for List<T> {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+        let mut list = Line::new(line.style);
+        for value in &self.0 {
+            value.write_field(&mut list)?;
+        }
+        let (values, _) = list.finish();
+        line.raw(&format!("{{{}}}", values.trim_start()));
+        Ok(())
+    }
+}
+
+impl WriteField for Value {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+        match self {
+            Self::Text(text) => line.text(text),
+            Self::List(values) => {
+                let mut list = Line::new(line.style);
+                for value in values {
+                    list.text(value)?;
+                }
+                let (values, _) = list.finish();
+                line.raw(&format!("{{{}}}", values.trim_start()));
+                Ok(())
+            }
+        }
+    }
+}
+
+impl WriteFields for Custom {
+    fn write_fields(&self, line: &mut Line) -> Result<(), Error> {
+        for value in &self.fields {
+            value.write_field(line)?;
+        }
+        Ok(())
+    }
+}
+
+/// The dimensions of object lists are written as text, as in the
+/// examples of the specification (`{"1" "456"}`).
+impl WriteField for (u32, String) {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+        line.text(&self.0.to_string())?;
+        self.1.write_field(line)
+    }
+}
+
+impl WriteField for Object {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+        let mut object = Line::new(line.style);
+        self.dimension.write_field(&mut object)?;
+        self.object.write_field(&mut object)?;
+        let (fields, _) = object.finish();
+        line.raw(&format!("{{{}}}", fields.trim_start()));
+        Ok(())
+    }
+}
+
+/// Entries of [`SubEntries`].
+pub(crate) trait WriteSubEntry {
+    /// Add the lines of the entry to `entries`.
+    fn write_sub_entry(&self, style: &Style, entries: &mut Vec<String>) -> Result<(), Error>;
+}
+
+impl<T: WriteSubEntry> WriteField for SubEntries<T> {
+    fn write_field(&self, line: &mut Line) -> Result<(), Error> {
+        let mut entries = Vec::with_capacity(self.0.len());
+        for entry in &self.0 {
+            entry.write_sub_entry(line.style, &mut entries)?;
+        }
+        line.sub_entries = Some(entries);
+        Ok(())
+    }
+}
+
+impl WriteSubEntry for TransLine {
+    fn write_sub_entry(&self, style: &Style, entries: &mut Vec<String>) -> Result<(), Error> {
+        let mut fields = Line::new(style);
+        self.trans().write_fields(&mut fields)?;
+        let (fields, _) = fields.finish();
+
+        // see `TransLine` on why `#RTRANS` is followed by `#TRANS`
+        match self {
+            Self::Normal(_) => entries.push(format!("#TRANS{fields}")),
+            Self::Added(_) => {
+                entries.push(format!("#RTRANS{fields}"));
+                entries.push(format!("#TRANS{fields}"));
+            }
+            Self::Removed(_) => entries.push(format!("#BTRANS{fields}")),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod roundtrip;
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::{Document, Reader};
+
+    const DATA: &[u8] = b"#FLAGGA 0
+#PROGRAM \"Vi iMproved\" 9.0
+#FORMAT PC8
+#GEN 20230102
+#SIETYP 4
+#FNAMN \"R\x84ksm\x94rg\x86s AB\"
+#ORGNR 556677-8899
+#KPTYP BAS95
+#VALUTA SEK
+#RAR 0 20230101 20231231
+#KONTO 1930 F\x94retagskonto
+#KTYP 1930 T
+#SRU 1930 7281
+#DIM 1 Kostnadsst\x84lle
+#UNDERDIM 2 Kostnadsb\x84rare 1
+#OBJEKT 1 Z1 Stockholm
+#IB 0 1930 15000.00
+#OIB 0 3010 {1 \"Z 1\"} 1000.00
+#OUB 0 3010 {1 Z2} 1000.00 3
+#PBUDGET 0 202301 3010 {} -900.00
+#PSALDO 0 202301 3010 {1 Z2} -1000.00 3
+#VER A 1 20230102 Rent 20230103
+{
+    #TRANS 1930 {} -12000.00
+    #TRANS 5010 {1 \"Z 1\"} 12000.00 \"\" Jan 2
+}
+#VER A 2 20230102 Correction
+{
+    #TRANS 1930 {} -100.00
+    #BTRANS 5010 {} 100.00
+    #RTRANS 5011 {} 100.00
+    #TRANS 5011 {} 100.00
+}
+";
+
+    fn write(profile: WriterProfile) -> Vec<u8> {
+        let mut writer = Writer::with_profile(Vec::new(), profile);
+        for item in Reader::new(DATA) {
+            writer.write_item(&item.unwrap()).unwrap();
+        }
+        writer.into_inner()
+    }
+
+    #[test]
+    fn readable() {
+        assert_eq!(
+            String::from_utf8_lossy(&write(WriterProfile::Readable)),
+            String::from_utf8_lossy(DATA)
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let doc = Document::from_reader(DATA).unwrap();
+
+        let strict = WriterProfile::SpecStrict.style();
+        for profile in [
+            WriterProfile::SpecStrict,
+            WriterProfile::Custom(Style {
+                indent: "\t",
+                ..strict
+            }),
+            WriterProfile::Custom(Style {
+                pad_fields: true,
+                ..strict
+            }),
+            WriterProfile::Custom(Style {
+                blank_lines: true,
+                ..strict
+            }),
+        ] {
+            let written = write(profile);
+            assert_eq!(Document::from_reader(&written[..]).unwrap(), doc);
+            assert_eq!(
+                Reader::new(&written[..])
+                    .strict(true)
+                    .collect::<Result<Document, _>>()
+                    .unwrap(),
+                doc
+            );
+        }
+    }
+
+    #[test]
+    fn styles() {
+        let strict = String::from_utf8_lossy(&write(WriterProfile::SpecStrict)).into_owned();
+        assert!(strict.contains("#ORGNR \"556677-8899\"\r\n"));
+        assert!(strict.contains("\r\n#TRANS 1930 {} -12000.00\r\n"));
+
+        let style = |style| {
+            let written = write(WriterProfile::Custom(style));
+            String::from_utf8_lossy(&written).into_owned()
+        };
+        let strict = WriterProfile::SpecStrict.style();
+
+        let indented = style(Style {
+            indent: "\t",
+            ..strict
+        });
+        assert!(indented.contains("\r\n\t#TRANS 1930 {} -12000.00\r\n"));
+
+        let padded = style(Style {
+            pad_fields: true,
+            ..strict
+        });
+        assert!(padded.contains("#TRANS 1930 {} -12000.00 \"\" \"\" \"\" \"\"\r\n"));
+        assert!(padded.contains("#GEN 20230102 \"\"\r\n"));
+
+        let blank = style(Style {
+            blank_lines: true,
+            ..WriterProfile::Readable.style()
+        });
+        assert!(blank.starts_with("#FLAGGA 0\n\n#PROGRAM "));
+        assert!(blank.contains("#RAR 0 20230101 20231231\n\n#KONTO 1930 "));
+        assert!(blank.contains("#OBJEKT 1 Z1 Stockholm\n\n#IB 0 1930 "));
+        assert_eq!(blank.matches("\n\n").count(), 3);
+    }
+
+    #[test]
+    fn amounts() {
+        let style = Style {
+            amounts: AmountFormat {
+                decimals: Decimals::Minimal,
+                plus_sign: true,
+                ..AmountFormat::new()
+            },
+            ..WriterProfile::Readable.style()
+        };
+        let written = write(WriterProfile::Custom(style));
+        let written = String::from_utf8_lossy(&written);
+        assert!(written.contains("#IB 0 1930 +15000\n"));
+        assert!(written.contains("#TRANS 1930 {} -12000\n"));
+
+        let format = AmountFormat {
+            decimals: Decimals::Two,
+            ..AmountFormat::new()
+        };
+        for (amount, formatted) in [
+            (dec!(1), "1.00"),
+            (dec!(0.125), "0.13"),
+            (dec!(-0.125), "-0.13"),
+            (dec!(-0.001), "0.00"),
+            (dec!(-0.00), "0.00"),
+        ] {
+            assert_eq!(format.format(amount), formatted);
+        }
+        assert_eq!(AmountFormat::new().format(dec!(1.5000)), "1.5000");
+    }
+
+    #[test]
+    fn order() {
+        let doc = Document::from_reader(DATA).unwrap();
+        let ver = doc.vouchers().next().unwrap();
+        let konto = Item::Konto(crate::item::Konto {
+            no: 1910.into(),
+            name: "Kassa".to_owned(),
+        });
+
+        let mut writer = Writer::new(Vec::new());
+        writer.write_ver(ver).unwrap();
+        let written = writer.get_ref().len();
+        assert!(matches!(
+            writer.write_item(&konto),
+            Err(Error::OutOfOrder {
+                label: "KONTO",
+                after: "VER"
+            })
+        ));
+        assert_eq!(writer.get_ref().len(), written);
+        writer
+            .write_item(&Item::Ksumma(crate::item::Ksumma { checksum: None }))
+            .unwrap();
+
+        let mut writer = Writer::with_profile(
+            Vec::new(),
+            WriterProfile::Custom(Style {
+                order: Order::AsWritten,
+                blank_lines: true,
+                ..WriterProfile::Readable.style()
+            }),
+        );
+        writer.write_ver(ver).unwrap();
+        writer.write_item(&konto).unwrap();
+        assert!(String::from_utf8_lossy(writer.get_ref()).ends_with("}\n\n#KONTO 1910 Kassa\n"));
+    }
+
+    #[test]
+    fn unrepresentable() {
+        let mut writer = Writer::new(Vec::new());
+        for name in ["Bank\nAB", "Bank 🏦"] {
+            let item = Item::FNamn(crate::item::FNamn {
+                name: name.to_owned(),
+            });
+            assert!(matches!(
+                writer.write_item(&item),
+                Err(Error::Unrepresentable(text)) if text == name
+            ));
+        }
+    }
+}
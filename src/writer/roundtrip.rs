@@ -0,0 +1,294 @@
+//! Property test: arbitrary items are read back as written.
+
+use iso_currency::Currency;
+use proptest::{collection::vec, option, prelude::*};
+use rust_decimal::Decimal;
+use time::{Date, Month};
+
+use super::{AmountFormat, LineEnding, Order, Quote, Style, Writer, WriterProfile};
+use crate::{
+    company::OrgNr,
+    item::{
+        AccountKind, AccountNo, Adress, BKod, ChartAccountsType, Dim, FNamn, FTyp, Flagga, Fnr,
+        Format, FormatType, Gen, Ib, Konto, KpTyp, Ksumma, Ktyp, List, Object, Objekt, Oib,
+        Omfattn, Orgnr, Oub, PBudget, PSaldo, Period, Program, Prosa, Rar, Res, SieTyp, Sru,
+        SubEntries, Taxar, Trans, TransLine, TypeNo, Ub, UnderDim, Valuta, Ver,
+    },
+    tags::Tags,
+    Item, Reader,
+};
+
+/// Printable text in code page 437, including quotes and backslashes
+/// which are escaped.
+fn text() -> impl Strategy<Value = String> {
+    "[ -~åäöÅÄÖéü]{0,12}"
+}
+
+/// Empty optional text is written as `""`, which is read as `None`.
+fn optional_text() -> impl Strategy<Value = Option<String>> {
+    option::of(text().prop_filter("non-empty", |s| !s.is_empty()))
+}
+
+fn amount() -> impl Strategy<Value = Decimal> {
+    (-10_000_000_000i64..10_000_000_000, 0u32..=4).prop_map(|(n, scale)| Decimal::new(n, scale))
+}
+
+fn date() -> impl Strategy<Value = Date> {
+    (1900..2100, 1..=365u16).prop_map(|(year, day)| Date::from_ordinal_date(year, day).unwrap())
+}
+
+fn period() -> impl Strategy<Value = Period> {
+    (1900..2100, 1..=12u8).prop_map(|(year, month)| Period {
+        year,
+        month: Month::try_from(month).unwrap(),
+    })
+}
+
+/// Account numbers, also with leading zeros and letters.
+fn account() -> impl Strategy<Value = AccountNo> {
+    prop_oneof![
+        any::<u32>().prop_map(AccountNo::from),
+        "[0-9A-Za-z]{1,10}".prop_map(|s| s.parse().unwrap()),
+    ]
+}
+
+fn objects() -> impl Strategy<Value = List<(u32, String)>> {
+    vec((any::<u32>(), text()), 0..3).prop_map(List)
+}
+
+fn object() -> impl Strategy<Value = Object> {
+    (any::<u32>(), text()).prop_map(|(dimension, object)| Object { dimension, object })
+}
+
+fn trans() -> impl Strategy<Value = Trans> {
+    (
+        account(),
+        objects(),
+        amount(),
+        option::of(date()),
+        optional_text(),
+        option::of(amount()),
+        optional_text(),
+    )
+        .prop_map(
+            |(account, objects, amount, date, text, quantity, signature)| Trans {
+                account,
+                objects,
+                amount: amount.into(),
+                date,
+                text,
+                quantity,
+                signature,
+                tags: Tags::default(),
+                foreign: None,
+            },
+        )
+}
+
+fn ver() -> impl Strategy<Value = Ver> {
+    let line = prop_oneof![
+        trans().prop_map(TransLine::Normal),
+        trans().prop_map(TransLine::Added),
+        trans().prop_map(TransLine::Removed),
+    ];
+
+    (
+        text(),
+        any::<u32>(),
+        date(),
+        optional_text(),
+        option::of(date()),
+        optional_text(),
+        vec(line, 0..4),
+    )
+        .prop_map(|(series, no, date, text, reg_date, sign, lines)| Ver {
+            series,
+            no,
+            date,
+            text,
+            reg_date,
+            sign,
+            transactions: SubEntries(lines),
+            tags: Tags::default(),
+        })
+}
+
+/// The balance items, which differ in the fields between the year and
+/// the account.
+macro_rules! balance {
+    ($name:ident $(, $field:ident: $strategy:expr)*) => {
+        (any::<i32>(), $($strategy,)* account(), amount(), option::of(amount()))
+            .prop_map(|(year, $($field,)* account, balance, quantity)| {
+                Item::$name($name {
+                    year,
+                    $($field,)*
+                    account,
+                    balance: balance.into(),
+                    quantity,
+                })
+            })
+    };
+}
+
+fn item() -> impl Strategy<Value = Item> {
+    let currency = prop_oneof![
+        Just(Currency::SEK),
+        Just(Currency::EUR),
+        Just(Currency::USD)
+    ];
+    // items aren't `Clone`, so they can't be `Just` values
+    let chart = (0..4).prop_map(|i| match i {
+        0 => ChartAccountsType::Bas95,
+        1 => ChartAccountsType::Bas96,
+        2 => ChartAccountsType::EuBas97,
+        _ => ChartAccountsType::Ne2007,
+    });
+    let kind = (0..4).prop_map(|i| match i {
+        0 => AccountKind::Asset,
+        1 => AccountKind::Liability,
+        2 => AccountKind::Cost,
+        _ => AccountKind::Income,
+    });
+    let adress = (text(), text(), text(), text()).prop_map(
+        |(contact, distribution_address, postal_address, phone)| Adress {
+            contact,
+            distribution_address,
+            postal_address,
+            phone,
+        },
+    );
+
+    prop_oneof![
+        adress.prop_map(Item::Adress),
+        (0u32..100_000)
+            .prop_filter_map("SNI code", |code| format!("{code:05}").parse().ok())
+            .prop_map(|sni| Item::BKod(BKod { sni })),
+        (any::<u32>(), text()).prop_map(|(no, name)| Item::Dim(Dim { no, name })),
+        any::<bool>().prop_map(|read| Item::Flagga(Flagga { read })),
+        text().prop_map(|name| Item::FNamn(FNamn { name })),
+        text().prop_map(|id| Item::Fnr(Fnr { id })),
+        Just(()).prop_map(|()| Item::Format(Format {
+            format: FormatType::PC8
+        })),
+        text().prop_map(|kind| Item::FTyp(FTyp { kind })),
+        (date(), optional_text()).prop_map(|(date, signature)| Item::Gen(Gen { date, signature })),
+        balance!(Ib),
+        (account(), text()).prop_map(|(no, name)| Item::Konto(Konto { no, name })),
+        option::of(any::<i64>()).prop_map(|checksum| Item::Ksumma(Ksumma { checksum })),
+        chart.prop_map(|typ| Item::KpTyp(KpTyp { typ })),
+        (account(), kind).prop_map(|(account, kind)| Item::Ktyp(Ktyp { account, kind })),
+        (any::<u32>(), text(), text()).prop_map(|(dimension, no, name)| Item::Objekt(Objekt {
+            dimension,
+            no,
+            name
+        })),
+        balance!(Oib, object: object()),
+        date().prop_map(|date| Item::Omfattn(Omfattn { date })),
+        (
+            (
+                0u64..1_000_000_000,
+                option::of(prop_oneof![Just(16u8), 19..21u8])
+            )
+                .prop_map(|(digits, century)| {
+                    let check = (0..10)
+                        .find(|check| format!("{digits:09}{check}").parse::<OrgNr>().is_ok())
+                        .unwrap();
+                    let century = century.map(|c| c.to_string()).unwrap_or_default();
+                    format!("{century}{digits:09}{check}").parse().unwrap()
+                }),
+            option::of(any::<u32>()),
+            option::of(any::<u32>())
+        )
+            .prop_map(|(org_no, forv_no, verk_no)| Item::Orgnr(Orgnr {
+                org_no,
+                forv_no,
+                verk_no
+            })),
+        balance!(Oub, object: object()),
+        balance!(PBudget, period: period(), objects: objects()),
+        (text(), text()).prop_map(|(name, version)| Item::Program(Program { name, version })),
+        text().prop_map(|text| Item::Prosa(Prosa { text })),
+        balance!(PSaldo, period: period(), objects: objects()),
+        (any::<i32>(), date(), date()).prop_map(|(no, start, end)| Item::Rar(Rar {
+            no,
+            start,
+            end
+        })),
+        balance!(Res),
+        Just(()).prop_map(|()| Item::SieTyp(SieTyp { no: TypeNo::SIE4 })),
+        (account(), any::<u32>()).prop_map(|(account, code)| Item::Sru(Sru { account, code })),
+        any::<i32>().prop_map(|year| Item::Taxar(Taxar { year })),
+        trans().prop_map(Item::Trans),
+        balance!(Ub),
+        (any::<u32>(), text(), any::<u32>())
+            .prop_map(|(no, name, parent)| Item::UnderDim(UnderDim { no, name, parent })),
+        currency.prop_map(|currency| Item::Valuta(Valuta { currency })),
+        ver().prop_map(Item::Ver),
+    ]
+}
+
+fn profile() -> impl Strategy<Value = WriterProfile> {
+    let style = (
+        any::<bool>(),
+        any::<bool>(),
+        prop_oneof![Just(""), Just("\t"), Just("    ")],
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+    )
+        .prop_map(
+            |(quote, crlf, indent, pad_fields, blank_lines, by_group)| Style {
+                quote: if quote {
+                    Quote::Always
+                } else {
+                    Quote::WhenNeeded
+                },
+                line_ending: if crlf {
+                    LineEnding::CrLf
+                } else {
+                    LineEnding::Lf
+                },
+                indent,
+                pad_fields,
+                blank_lines,
+                order: if by_group {
+                    Order::ByGroup
+                } else {
+                    Order::AsWritten
+                },
+                amounts: AmountFormat::new(),
+            },
+        );
+
+    prop_oneof![
+        Just(WriterProfile::SpecStrict),
+        Just(WriterProfile::Readable),
+        style.prop_map(WriterProfile::Custom),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn roundtrip(mut items in vec(item(), 0..8), profile in profile()) {
+        items.sort_by_key(Item::group);
+        // the checksummed items that a `#KSUMMA` starts must be ended
+        if items.iter().any(|item| matches!(item, Item::Ksumma(Ksumma { checksum: None }))) {
+            items.push(Item::Ksumma(Ksumma { checksum: Some(0) }));
+        }
+
+        let mut writer = Writer::with_profile(Vec::new(), profile);
+        for item in &items {
+            writer.write_item(item).unwrap();
+        }
+        let data = writer.into_inner();
+
+        let read = Reader::new(&data[..]).collect::<Result<Vec<_>, _>>();
+        prop_assert_eq!(read.unwrap(), items, "{}", String::from_utf8_lossy(&data));
+    }
+
+    #[test]
+    fn display(item in item()) {
+        let text = item.to_string();
+        prop_assert_eq!(text.parse::<Item>().unwrap(), item, "{}", text);
+    }
+}
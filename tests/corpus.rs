@@ -0,0 +1,106 @@
+//! Golden-file tests of the exports in `tests/corpus`.
+//!
+//! Each `<name>.se` is read leniently, as exports of real programs are,
+//! and the items, warnings and errors are compared with `<name>.json`.
+//! To add a file, or to accept changes to how the files are read, run
+//! `UPDATE_CORPUS=1 cargo test --test corpus` and review the diff of the
+//! JSON files.
+
+use std::{fs, path::Path};
+
+use serde_json::json;
+use sie4::{
+    reader::{self, OrderingPolicy},
+    writer::{Order, Style, WriterProfile},
+    Document, Reader, Writer,
+};
+
+/// The expectations of `data`, as written to the JSON files.
+fn read(data: &[u8]) -> (Document, String) {
+    let mut reader = Reader::new(data)
+        .lenient_amounts(true)
+        .lenient_sub_entries(true)
+        .ordering(OrderingPolicy::Warn);
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+    for item in reader.by_ref() {
+        match item {
+            Ok(item) => items.push(item),
+            Err(e) => {
+                errors.push(e.to_string());
+                // the reader can't get past invalid items
+                if matches!(e, reader::Error::Parse(_)) {
+                    break;
+                }
+            }
+        }
+    }
+    let warnings = reader
+        .warnings()
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    let doc = items.into_iter().collect::<Document>();
+    let json = json!({
+        "errors": errors,
+        "warnings": warnings,
+        "document": doc,
+    });
+    let mut json = serde_json::to_string_pretty(&json).unwrap();
+    json.push('\n');
+    (doc, json)
+}
+
+/// Write `doc` and read it back, which must give the same document.
+fn roundtrip(doc: &Document) -> Document {
+    let style = Style {
+        order: Order::AsWritten,
+        ..WriterProfile::SpecStrict.style()
+    };
+    let mut writer = Writer::with_profile(Vec::new(), WriterProfile::Custom(style));
+    for item in doc.items() {
+        writer.write_item(item).unwrap();
+    }
+    Reader::new(&writer.into_inner()[..])
+        .ordering(OrderingPolicy::Warn)
+        .collect::<Result<Document, _>>()
+        .unwrap()
+}
+
+#[test]
+fn corpus() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let update = std::env::var_os("UPDATE_CORPUS").is_some();
+    let mut files = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "se"))
+        .collect::<Vec<_>>();
+    files.sort();
+    assert!(!files.is_empty(), "no files in {}", dir.display());
+
+    let mut failed = Vec::new();
+    for path in files {
+        let (doc, json) = read(&fs::read(&path).unwrap());
+        let golden = path.with_extension("json");
+        if update {
+            fs::write(&golden, &json).unwrap();
+        } else if fs::read_to_string(&golden).ok().as_deref() != Some(&json) {
+            failed.push(path.display().to_string());
+        }
+
+        assert_eq!(
+            roundtrip(&doc),
+            doc,
+            "{} changed when written",
+            path.display()
+        );
+    }
+
+    assert!(
+        failed.is_empty(),
+        "read differently than expected: {failed:?}, \
+         run with UPDATE_CORPUS=1 to update the JSON files"
+    );
+}